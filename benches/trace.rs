@@ -0,0 +1,105 @@
+//! Measures the throughput of tracing large containers.
+//!
+//! Run with `cargo bench --bench trace`. Every benchmark runs a full collection of an arena
+//! holding a single rooted container and reports the median time of a collection.
+
+use std::{collections::HashMap, pin::pin, time::Instant};
+
+use dreck::*;
+
+const ELEMENTS: usize = 1_000_000;
+const SAMPLES: usize = 20;
+
+pub struct Pair<'gc, 'own>(pub u64, pub Option<Gc<'gc, 'own, u32>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Pair<'gc, 'own> {
+    type Gc<'to> = Pair<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker);
+        self.1.trace(marker);
+    }
+}
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+    // Warm up caches and the allocator.
+    f();
+    let mut samples = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    samples.sort();
+    let median = samples[SAMPLES / 2];
+    let per_element = median.as_secs_f64() * 1e9 / ELEMENTS as f64;
+    println!(
+        "{name:<40} time: [{:>10.3?} {:>10.3?} {:>10.3?}] {per_element:.2} ns/element",
+        samples[0],
+        median,
+        samples[SAMPLES - 1],
+    );
+}
+
+fn main() {
+    {
+        dreck!(owner, arena);
+        let values = (0..ELEMENTS)
+            .map(|x| (x as u64, (x % 2 == 0).then(|| arena.add(x as u32))))
+            .collect::<Vec<_>>();
+        let guard = pin!(RootGuard::new());
+        let _values = root!(&arena, guard, arena.add(values));
+        bench("Vec<(u64, Option<Gc<u32>>)>", || arena.collect_full(&owner));
+    }
+
+    {
+        dreck!(owner, arena);
+        let values = (0..ELEMENTS)
+            .map(|x| Pair(x as u64, (x % 2 == 0).then(|| arena.add(x as u32))))
+            .collect::<Vec<_>>();
+        let guard = pin!(RootGuard::new());
+        let _values = root!(&arena, guard, arena.add(values));
+        bench("Vec<Pair(u64, Option<Gc<u32>>)>", || {
+            arena.collect_full(&owner)
+        });
+    }
+
+    {
+        // Leaf containers nested in a traced container, 1000 maps of 1000 entries.
+        dreck!(owner, arena);
+        let values = (0..ELEMENTS / 1000)
+            .map(|x| {
+                if x % 2 == 0 {
+                    Ok(arena.add(x as u32))
+                } else {
+                    Err((0..2000u64).map(|y| (y, y)).collect::<HashMap<_, _>>())
+                }
+            })
+            .collect::<Vec<_>>();
+        let guard = pin!(RootGuard::new());
+        let _values = root!(&arena, guard, arena.add(values));
+        bench("Vec<Result<Gc<u32>, HashMap<u64, u64>>>", || {
+            arena.collect_full(&owner)
+        });
+    }
+
+    {
+        dreck!(owner, arena);
+        let values = (0..ELEMENTS / 10)
+            .map(|x| (x as u64, arena.add(x as u32)))
+            .collect::<HashMap<_, _>>();
+        let guard = pin!(RootGuard::new());
+        let _values = root!(&arena, guard, arena.add(values));
+        bench("HashMap<u64, Gc<u32>> (100k)", || {
+            arena.collect_full(&owner)
+        });
+    }
+}
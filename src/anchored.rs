@@ -0,0 +1,112 @@
+//! Roots owned by long-lived host structures.
+
+use std::{pin::Pin, ptr::NonNull};
+
+use crate::{
+    sys::{GcBox, UnsafeRootGuard},
+    Arena, Gc, Invariant, Trace,
+};
+
+/// A GC pointer which is rooted for as long as the handle exists, for storing GC values in
+/// structures which live outside of the arena.
+///
+/// Unlike a [`RootGuard`](crate::RootGuard) the handle doesn't need to be pinned by the user, so
+/// it can be stored as a field and moved around. `T` is the type of the value with a `'static` gc
+/// lifetime, like `Node<'static, 'own>`, the pointer is rebound to the borrow of the arena when
+/// retrieved with [`Anchored::get`].
+///
+/// Use [`anchored!`](crate::anchored!) to declare a structure made up of anchored pointers.
+pub struct Anchored<'own, T> {
+    guard: Pin<Box<UnsafeRootGuard>>,
+    ptr: NonNull<GcBox<T>>,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own, T: Trace<'own>> Anchored<'own, T> {
+    /// Root the pointer for as long as the handle exists.
+    pub fn new<'gc>(arena: &Arena<'own>, value: Gc<'gc, 'own, T::Gc<'gc>>) -> Self {
+        let mut guard = Box::pin(UnsafeRootGuard::new());
+        let ptr = value.into_gc_box().cast::<GcBox<T>>();
+        unsafe { arena.unsafe_arena().root(guard.as_mut(), ptr) };
+        Anchored {
+            guard,
+            ptr,
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Returns the pointer bound to the borrow of the arena.
+    pub fn get<'gc>(&self, arena: &'gc Arena<'own>) -> Gc<'gc, 'own, T::Gc<'gc>> {
+        let _arena = arena;
+        // The pointer is rooted by the handle. Once unrooted by `set` or by dropping the handle it
+        // still remains valid until the next collection, which requires a mutable borrow.
+        unsafe { Gc::from_gc_box(self.ptr.cast()) }
+    }
+
+    /// Replace the anchored pointer, the previous value is no longer kept alive by the handle.
+    ///
+    /// The new pointer is rooted before the old one is unrooted, so no collection can observe the
+    /// handle without a root.
+    pub fn set<'gc>(&mut self, arena: &Arena<'own>, value: Gc<'gc, 'own, T::Gc<'gc>>) {
+        let ptr = value.into_gc_box().cast::<GcBox<T>>();
+        unsafe { arena.unsafe_arena().root(self.guard.as_mut(), ptr) };
+        self.ptr = ptr;
+    }
+}
+
+/// Declare a structure for use outside of the arena whose fields are [`Anchored`] GC pointers.
+///
+/// The structure must have exactly one lifetime parameter, the owner lifetime. Every field is
+/// declared with the type of the value it points to with a `'static` gc lifetime and becomes an
+/// [`Anchored`] field. A constructor `new` is generated taking the arena and the initial pointer
+/// of every field in declaration order.
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// anchored! {
+///     pub struct Editor<'own> {
+///         pub text: String,
+///         pub cursor: usize,
+///     }
+/// }
+///
+/// dreck!(owner, arena);
+///
+/// let text = arena.add(String::from("hello"));
+/// let cursor = arena.add(5usize);
+/// let mut editor = Editor::new(&arena, text, cursor);
+///
+/// arena.collect_full(&owner);
+/// assert_eq!(editor.text.get(&arena).borrow(&owner), "hello");
+///
+/// editor.cursor.set(&arena, arena.add(0usize));
+/// assert_eq!(*editor.cursor.get(&arena).borrow(&owner), 0);
+/// ```
+#[macro_export]
+macro_rules! anchored {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident<$own:lifetime> {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name<$own> {
+            $($(#[$field_meta])* $field_vis $field: $crate::Anchored<$own, $ty>,)*
+        }
+
+        impl<$own> $name<$own> {
+            /// Create the structure, anchoring the given pointers.
+            #[allow(clippy::too_many_arguments)]
+            $vis fn new<'__gc>(
+                arena: &$crate::Arena<$own>,
+                $($field: $crate::Gc<'__gc, $own, <$ty as $crate::Trace<$own>>::Gc<'__gc>>,)*
+            ) -> Self {
+                $name {
+                    $($field: $crate::Anchored::new(arena, $field),)*
+                }
+            }
+        }
+    };
+}
@@ -1,9 +1,21 @@
-use std::pin::Pin;
+use std::{
+    cell::UnsafeCell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    mem::{ManuallyDrop, MaybeUninit},
+    pin::Pin,
+    ptr::{addr_of_mut, NonNull},
+    time::Duration,
+};
 
 use crate::{
     marker::{Invariant, Owner},
-    sys::{UnsafeArena, UnsafeMarker, UnsafeRootGuard},
-    Gc, Trace,
+    sys::{
+        erased_type_id, GcBox, GcStats, MemoryPressure, PhaseMask, Transition,
+        TransitionSubscription, UnsafeArena, UnsafeMarker, UnsafeRootGuard, UnsafeTrace,
+        WorkRequest,
+    },
+    AllocError, DeferToken, Erasable, Error, Gc, GcAny, GcTarget, LeafTrace, Trace,
 };
 
 /// The marker passed to the [`Trace::trace`] method for marking GC pointers.
@@ -20,6 +32,27 @@ impl<'own, 'a> Marker<'own, 'a> {
         unsafe { self.marker.mark(Gc::into_gc_box(ptr)) }
     }
 
+    /// Mark a type erased GC pointer.
+    pub fn mark_any(self, ptr: GcAny<'_, 'own>) {
+        unsafe { self.marker.mark_erased(ptr.into_gc_box()) }
+    }
+
+    /// Mark a GC pointer to a value of any type, like an unsized value, by the v-table of the
+    /// object.
+    pub fn mark_erased<T: ?Sized>(self, ptr: Gc<'_, 'own, T>) {
+        unsafe { self.marker.mark_erased(Gc::into_gc_box(ptr).cast()) }
+    }
+
+    /// Defer an operation to after the collection cycle, see [`DeferToken`].
+    ///
+    /// Only the tracing done by a collection defers operations, tokens passed to the marker while
+    /// the object is traced for another reason, like walking the heap, are dropped without being
+    /// run.
+    pub fn defer(self, token: DeferToken<'own>) {
+        let (ptr, op) = token.into_raw();
+        unsafe { self.marker.defer(ptr, op) }
+    }
+
     /// Create the marker from the unsafe variant.
     pub unsafe fn from_unsafe(marker: UnsafeMarker<'a>) -> Self {
         Self {
@@ -38,6 +71,52 @@ impl RootGuard {
     pub fn new() -> Self {
         Self(UnsafeRootGuard::new())
     }
+
+    /// Move the pointer rooted by `from` to `to`, unrooting any pointer previously rooted by `to`.
+    ///
+    /// The pointer remains rooted throughout, so `from` can be dropped afterwards without the
+    /// pointer becoming collectable.
+    pub fn transfer(from: Pin<&mut RootGuard>, to: Pin<&mut RootGuard>) {
+        unsafe {
+            UnsafeRootGuard::transfer(
+                from.map_unchecked_mut(|x| &mut x.0),
+                to.map_unchecked_mut(|x| &mut x.0),
+            )
+        }
+    }
+
+    /// Point the guard at a new pointer, returning the pointer bound to the lifetime of the guard
+    /// like [`Arena::root`].
+    ///
+    /// The pointer previously rooted by the guard is no longer rooted and is collectable once it
+    /// is unreachable. The pointer returned for it borrows the guard, so it can't be used after
+    /// this call. This also means the new pointer can't be reached through the old one, use
+    /// [`root!`](crate::root) with the guard for that instead.
+    ///
+    /// # Usage
+    /// ```
+    /// # use std::pin::pin;
+    /// # use dreck::*;
+    /// dreck!(owner, arena);
+    ///
+    /// let mut guard = pin!(RootGuard::new());
+    /// let mut best = guard.as_mut().set(&arena, arena.add(0));
+    /// for i in [3, 7, 2] {
+    ///     let candidate = arena.add(i);
+    ///     if *candidate.borrow(&owner) > *best.borrow(&owner) {
+    ///         best = guard.as_mut().set(&arena, candidate);
+    ///     }
+    ///     arena.collect_full(&owner);
+    /// }
+    /// assert_eq!(*best.borrow(&owner), 7);
+    /// ```
+    pub fn set<'r, 'own, T: GcTarget<'own> + ?Sized>(
+        self: Pin<&'r mut Self>,
+        arena: &Arena<'own>,
+        value: Gc<'_, 'own, T>,
+    ) -> Gc<'r, 'own, T::Gc<'r>> {
+        arena.root(value, self)
+    }
 }
 
 impl Default for RootGuard {
@@ -46,6 +125,8 @@ impl Default for RootGuard {
     }
 }
 
+type TeardownHook<'own> = Box<dyn FnOnce(&mut Owner<'own>, &Arena<'own>)>;
+
 /// The arena for garbage collected pointers.
 /// This struct is in charge allocating, freeing, and rooting garbage collected pointers.
 #[repr(transparent)]
@@ -69,6 +150,182 @@ impl<'own> Arena<'own> {
         }
     }
 
+    /// Allocate a value which refers to itself, like [`Rc::new_cyclic`](std::rc::Rc::new_cyclic).
+    ///
+    /// The closure is handed the pointer to the object before the object is initialized, and
+    /// returns the value to initialize it with. The object only becomes part of the arena once it
+    /// is initialized, so a collection can never observe it uninitialized.
+    ///
+    /// The pointer must not be dereferenced by the closure. The owner is borrowed mutably for the
+    /// duration of the call so the closure can't borrow any object, but it can store the pointer
+    /// in other objects it allocates.
+    ///
+    /// # Panic
+    /// If the closure panics the process is aborted, as the pointer might already have been
+    /// rooted or stored while the object can never be initialized.
+    pub fn add_cyclic<'gc, T, F>(&'gc self, owner: &mut Owner<'own>, f: F) -> Gc<'gc, 'own, T>
+    where
+        T: Trace<'own>,
+        F: FnOnce(Gc<'gc, 'own, T>) -> T,
+    {
+        /// Aborts if dropped, which only happens if the closure panics.
+        struct AbortOnUnwind;
+
+        impl Drop for AbortOnUnwind {
+            fn drop(&mut self) {
+                eprintln!("panicked while initializing an object allocated with `add_cyclic`");
+                std::process::abort();
+            }
+        }
+
+        let _owner = owner;
+        unsafe {
+            let ptr = self.arena.alloc_uninit::<T>();
+            let abort = AbortOnUnwind;
+            let value = f(Gc::from_gc_box(ptr));
+            std::mem::forget(abort);
+            addr_of_mut!((*ptr.as_ptr()).value).write(UnsafeCell::new(ManuallyDrop::new(value)));
+            self.arena.link(ptr);
+            Gc::from_gc_box(ptr)
+        }
+    }
+
+    /// Allocate a value by initializing it in place, without moving it into the object.
+    ///
+    /// [`Arena::add`] constructs the value before copying it into the object, which for large
+    /// values is slow and can overflow the stack. The closure is instead handed the uninitialized
+    /// value of the object and returns it once initialized, for example by writing it field by
+    /// field. The object only becomes part of the arena once the closure returns.
+    ///
+    /// # Panic
+    /// Panics if the closure returns a reference to another value than the one it was handed.
+    /// Whether that happens or the closure panics, the object is freed without dropping the
+    /// value: anything the closure already wrote into it, like a `Vec` or `Rc`, is leaked.
+    pub fn add_with<'gc, T, F>(&'gc self, init: F) -> Gc<'gc, 'own, T>
+    where
+        T: Trace<'own>,
+        F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+    {
+        /// Frees the object if dropped, which happens if initialization doesn't finish.
+        struct DeallocOnUnwind<'a, T: UnsafeTrace> {
+            arena: &'a UnsafeArena,
+            ptr: NonNull<GcBox<T>>,
+        }
+
+        impl<T: UnsafeTrace> Drop for DeallocOnUnwind<'_, T> {
+            fn drop(&mut self) {
+                unsafe { self.arena.dealloc_uninit(self.ptr) }
+            }
+        }
+
+        unsafe {
+            let ptr = self.arena.alloc_uninit::<T>();
+            let guard = DeallocOnUnwind {
+                arena: &self.arena,
+                ptr,
+            };
+            // `UnsafeCell` and `ManuallyDrop` are transparent, so the value has the layout of `T`.
+            let slot = &mut *addr_of_mut!((*ptr.as_ptr()).value).cast::<MaybeUninit<T>>();
+            let slot_ptr = slot.as_mut_ptr();
+            let value: *mut T = init(slot);
+            assert!(
+                value == slot_ptr,
+                "the closure passed to `add_with` returned a reference to another value"
+            );
+            std::mem::forget(guard);
+            self.arena.link(ptr);
+            Gc::from_gc_box(ptr)
+        }
+    }
+
+    /// Allocate a slice containing the items of the iterator.
+    ///
+    /// The items are stored inline in the object, without a separate allocation like a `Vec`
+    /// would need.
+    ///
+    /// # Panic
+    /// Panics if the iterator returns fewer items than its length. Items beyond the length are
+    /// ignored.
+    pub fn add_from_iter<'gc, T, I>(&'gc self, iter: I) -> Gc<'gc, 'own, [T]>
+    where
+        T: Trace<'own>,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        unsafe {
+            let ptr = self.arena.add_slice(iter.into_iter());
+            Gc::from_gc_box(ptr)
+        }
+    }
+
+    /// Allocate a slice containing a copy of the given items, see [`Arena::add_from_iter`].
+    pub fn add_slice_copy<'gc, T>(&'gc self, items: &[T]) -> Gc<'gc, 'own, [T]>
+    where
+        T: Trace<'own> + Copy,
+    {
+        self.add_from_iter(items.iter().copied())
+    }
+
+    /// Allocate a string, copying its bytes inline into the object.
+    ///
+    /// Unlike allocating a `String` this needs only a single allocation.
+    pub fn add_str<'gc>(&'gc self, value: &str) -> Gc<'gc, 'own, str> {
+        unsafe { Gc::from_gc_box(self.arena.add_str(value)) }
+    }
+
+    /// Borrow an immutable leaf object for as long as the arena is borrowed, instead of for as
+    /// long as the owner is borrowed like [`Gc::borrow`].
+    ///
+    /// This is sound because of two facts. First, a `Gc<'gc>` points to an object which is not
+    /// freed during `'gc`, and collecting needs a mutable borrow of the arena, which is
+    /// impossible while the returned reference exists. Second, the owner only guards mutation and
+    /// a [`LeafTrace`] object can't be mutated at all.
+    pub fn get_static<'gc, T>(&'gc self, gc: Gc<'gc, 'own, T>) -> &'gc T
+    where
+        T: LeafTrace + ?Sized,
+    {
+        unsafe { &*gc.into_gc_box().as_ref().value.get() }
+    }
+
+    /// Allocate a value, returning an existing object instead if an equal value was allocated
+    /// with this method before and is still alive.
+    ///
+    /// Objects allocated this way are not kept alive by the arena. Interned values must not be
+    /// mutated, doing so is a logic error which can result in equal values being allocated twice.
+    ///
+    /// Interned objects are looked up by the [`TypeId`](std::any::TypeId) of their type, so `T`
+    /// must implement [`Erasable`].
+    pub fn add_interned<'gc, T>(&'gc self, owner: &Owner<'own>, value: T) -> Gc<'gc, 'own, T>
+    where
+        T: Trace<'own> + Erasable<'own> + Eq + Hash,
+    {
+        // Borrowing the owner ensures no interned value is mutably borrowed during comparison.
+        let _owner = owner;
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        unsafe {
+            let existing =
+                self.arena
+                    .find_interned(erased_type_id::<T::Gc<'static>>(), hash, &|ptr| {
+                        **ptr.cast::<GcBox<T>>().as_ref().value.get() == value
+                    });
+            if let Some(ptr) = existing {
+                return Gc::from_gc_box(ptr.cast());
+            }
+
+            let ptr = self.arena.add(value);
+            self.arena.insert_interned(ptr.cast(), hash);
+            Gc::from_gc_box(ptr)
+        }
+    }
+
+    /// Returns the number of interned objects which have not yet been collected.
+    pub fn interned_count(&self) -> usize {
+        self.arena.interned_count()
+    }
+
     // Takes an immutable reference to owner so you cant move an pointer out a container and then
     // collect and then reference the container.
     pub fn collect(&mut self, owner: &Owner<'own>) {
@@ -87,30 +344,383 @@ impl<'own> Arena<'own> {
         }
     }
 
-    pub fn root<'r, T: Trace<'own>>(
+    /// Release memory in response to memory pressure, returning the number of bytes released.
+    ///
+    /// See [`UnsafeArena::on_memory_pressure`] for what is done at each level.
+    pub fn on_memory_pressure(&mut self, owner: &Owner<'own>, level: MemoryPressure) -> usize {
+        let _owner = owner;
+        unsafe { self.arena.on_memory_pressure(level) }
+    }
+
+    /// Shrink the internal buffers of the collector, returning the number of bytes released.
+    pub fn shrink_caches(&self) -> usize {
+        self.arena.shrink_caches()
+    }
+
+    /// Root a GC pointer to be kept alive for the duration of the guard, returning the pointer
+    /// bound to the lifetime of the guard.
+    ///
+    /// A guard which already roots a pointer is pointed at the new pointer instead, see
+    /// [`RootGuard::set`].
+    pub fn root<'r, T: GcTarget<'own> + ?Sized>(
         &self,
         value: Gc<'_, 'own, T>,
         guard: Pin<&'r mut RootGuard>,
     ) -> Gc<'r, 'own, T::Gc<'r>> {
         unsafe {
-            self.arena
-                .root(std::mem::transmute(guard), Gc::into_gc_box(value));
+            self.arena.root(
+                std::mem::transmute::<Pin<&mut RootGuard>, Pin<&mut UnsafeRootGuard>>(guard),
+                Gc::into_gc_box(value).cast::<GcBox<()>>(),
+            );
 
             value.rebind()
         }
     }
 
-    pub fn rebind_to<'gc, T: Trace<'own>>(&'gc self, value: T) -> T::Gc<'gc> {
+    /// Allocate a value and root it with the guard, returning the pointer bound to the lifetime
+    /// of the guard like [`Arena::root`].
+    ///
+    /// No collection can happen between allocating and rooting. The [`add_rooted!`](crate::add_rooted)
+    /// macro also creates and pins the guard.
+    ///
+    /// # Usage
+    /// ```
+    /// # use std::pin::pin;
+    /// # use dreck::*;
+    /// dreck!(owner, arena);
+    ///
+    /// // Instead of `arena.add`, creating a guard and `root!`.
+    /// let guard = pin!(RootGuard::new());
+    /// let ptr = arena.add_rooted(3, guard);
+    ///
+    /// arena.collect_full(&owner);
+    /// assert_eq!(*ptr.borrow(&owner), 3);
+    /// ```
+    pub fn add_rooted<'r, T: Trace<'own>>(
+        &self,
+        value: T,
+        guard: Pin<&'r mut RootGuard>,
+    ) -> Gc<'r, 'own, T::Gc<'r>> {
+        let value = self.add(value);
+        self.root(value, guard)
+    }
+
+    /// Root an already rooted pointer with a new guard, returning the pointer bound to the
+    /// lifetime of the new guard.
+    ///
+    /// Useful for keeping a pointer, rooted by a guard which is about to be dropped, alive for
+    /// longer. As the pointer is still rooted by the old guard it is never collectable in between.
+    pub fn reroot<'r, T: GcTarget<'own> + ?Sized>(
+        &self,
+        value: Gc<'_, 'own, T>,
+        guard: Pin<&'r mut RootGuard>,
+    ) -> Gc<'r, 'own, T::Gc<'r>> {
+        self.root(value, guard)
+    }
+
+    /// Allocate a value, returning an error instead of panicking if the arena can't currently be
+    /// used to allocate or the allocation fails.
+    ///
+    /// The error contains the value, so it can be recovered with [`AllocError::into_value`].
+    pub fn try_add<'gc, T: Trace<'own>>(
+        &'gc self,
+        value: T,
+    ) -> Result<Gc<'gc, 'own, T>, AllocError<T>> {
+        unsafe {
+            let ptr = self.arena.try_add(value)?;
+            Ok(Gc::from_gc_box(ptr))
+        }
+    }
+
+    /// Allocate a slice containing the items of the iterator, returning an error instead of
+    /// panicking if the arena can't currently be used to allocate or the allocation fails, see
+    /// [`Arena::add_from_iter`].
+    ///
+    /// # Panic
+    /// Panics if the iterator returns fewer items than its length.
+    pub fn try_add_from_iter<'gc, T, I>(&'gc self, iter: I) -> Result<Gc<'gc, 'own, [T]>, Error>
+    where
+        T: Trace<'own>,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        unsafe {
+            let ptr = self.arena.try_add_slice(iter.into_iter())?;
+            Ok(Gc::from_gc_box(ptr))
+        }
+    }
+
+    /// Allocate a string, returning an error instead of panicking if the arena can't currently be
+    /// used to allocate or the allocation fails, see [`Arena::add_str`].
+    pub fn try_add_str<'gc>(&'gc self, value: &str) -> Result<Gc<'gc, 'own, str>, Error> {
+        let ptr = self.arena.try_add_str(value)?;
+        Ok(unsafe { Gc::from_gc_box(ptr) })
+    }
+
+    /// Root a GC pointer, returning an error instead of panicking if the arena can't currently be
+    /// used to root pointers, see [`Arena::root`].
+    pub fn try_root<'r, T: GcTarget<'own> + ?Sized>(
+        &self,
+        value: Gc<'_, 'own, T>,
+        guard: Pin<&'r mut RootGuard>,
+    ) -> Result<Gc<'r, 'own, T::Gc<'r>>, Error> {
+        self.arena.check_root()?;
+        Ok(self.root(value, guard))
+    }
+
+    /// Root an already rooted pointer with a new guard, returning an error instead of panicking
+    /// if the arena can't currently be used to root pointers, see [`Arena::reroot`].
+    pub fn try_reroot<'r, T: GcTarget<'own> + ?Sized>(
+        &self,
+        value: Gc<'_, 'own, T>,
+        guard: Pin<&'r mut RootGuard>,
+    ) -> Result<Gc<'r, 'own, T::Gc<'r>>, Error> {
+        self.try_root(value, guard)
+    }
+
+    /// Returns false if the arena can't currently be used to allocate or root pointers.
+    ///
+    /// This is the case while the arena is being dropped or while it is tracing or dropping
+    /// objects during a collection. Code which might run at those moments, like the drop of a
+    /// value holding a reference to the arena, can use this to avoid panicking.
+    pub fn is_usable(&self) -> bool {
+        self.arena.is_usable()
+    }
+
+    /// Returns the number of currently rooted pointers.
+    pub fn root_count(&self) -> usize {
+        self.arena.root_count()
+    }
+
+    /// Set a soft limit on the number of rooted pointers.
+    ///
+    /// The hook is called with the current number of roots every time the number of roots grows
+    /// past the limit. Useful for detecting leaked root guards.
+    pub fn set_root_limit<F: FnMut(usize) + 'static>(&mut self, limit: usize, hook: F) {
+        self.arena.set_root_limit(limit, Box::new(hook))
+    }
+
+    /// Remove the soft limit on the number of rooted pointers.
+    pub fn clear_root_limit(&mut self) {
+        self.arena.clear_root_limit()
+    }
+
+    /// Enable or disable heap verification, see [`UnsafeArena::set_verify`].
+    pub fn set_verify(&mut self, verify: bool) {
+        self.arena.set_verify(verify)
+    }
+
+    /// Limit the amount of work done by a single call to [`Arena::collect`], see
+    /// [`UnsafeArena::set_max_step_work`].
+    pub fn set_max_step_work(&mut self, limit: Option<usize>) {
+        self.arena.set_max_step_work(limit)
+    }
+
+    /// Limit the number of bytes the objects of the arena may occupy, see
+    /// [`UnsafeArena::set_heap_limit`].
+    pub fn set_heap_limit(&mut self, limit: Option<usize>) {
+        self.arena.set_heap_limit(limit)
+    }
+
+    /// Limit the time spent by a single call to [`Arena::collect`], see
+    /// [`UnsafeArena::set_max_step_time`].
+    pub fn set_max_step_time(&mut self, limit: Option<Duration>) {
+        self.arena.set_max_step_time(limit)
+    }
+
+    /// Subscribe to transitions between the phases of a collection cycle, see
+    /// [`UnsafeArena::subscribe_transitions`].
+    ///
+    /// Objects are only freed between the `Sweep` and `Sleep` transitions of a cycle, so caches
+    /// keyed by object addresses only have to be invalidated on the `Sleep` transition.
+    pub fn subscribe_transitions<F: FnMut(Transition) + 'static>(
+        &mut self,
+        filter: PhaseMask,
+        hook: F,
+    ) -> TransitionSubscription {
+        self.arena.subscribe_transitions(filter, Box::new(hook))
+    }
+
+    /// Remove a subscription, returns false if it was already removed.
+    pub fn unsubscribe_transitions(&mut self, subscription: TransitionSubscription) -> bool {
+        self.arena.unsubscribe_transitions(subscription)
+    }
+
+    /// Set a function which is asked to schedule collection work, see
+    /// [`UnsafeArena::set_work_scheduler`].
+    ///
+    /// Meant for event loops: the scheduler arranges for [`Arena::pump`] to be called again, for
+    /// example from an idle callback, and that call performs a slice of collection work.
+    pub fn set_work_scheduler<F: FnMut(WorkRequest) + 'static>(&mut self, scheduler: F) {
+        self.arena.set_work_scheduler(Box::new(scheduler))
+    }
+
+    /// Remove the work scheduler.
+    pub fn clear_work_scheduler(&mut self) {
+        self.arena.clear_work_scheduler()
+    }
+
+    /// Drive work scheduling, returns true if collection work was performed.
+    ///
+    /// Should be called once every iteration of the event loop. Does nothing unless work was
+    /// requested, see [`UnsafeArena::pump`].
+    pub fn pump(&mut self, owner: &Owner<'own>) -> bool {
+        let _owner = owner;
+        unsafe { self.arena.pump() }
+    }
+
+    /// Register a hook which is run by [`Arena::shutdown`] while all objects are still alive.
+    ///
+    /// Hooks are run in the order they were registered. If the arena is dropped instead of shut
+    /// down there is no owner available, so the hooks are dropped without being run.
+    pub fn on_teardown(&mut self, hook: TeardownHook<'own>) {
+        let hook: Box<dyn FnOnce(&UnsafeArena) + 'own> = Box::new(move |arena| unsafe {
+            // Safe because teardown hooks are only run from `shutdown` which holds a mutable
+            // borrow of the owner for the duration of the hooks.
+            let mut owner = Owner::new();
+            hook(&mut owner, Arena::from_unsafe_ref(arena))
+        });
+        // Safe because the hook is owned by the arena which can't outlive `'own`.
+        let hook = unsafe {
+            std::mem::transmute::<
+                Box<dyn FnOnce(&UnsafeArena) + 'own>,
+                Box<dyn FnOnce(&UnsafeArena) + 'static>,
+            >(hook)
+        };
+        self.arena.on_teardown(hook)
+    }
+
+    /// Run all teardown hooks and then free the arena along with all its objects.
+    pub fn shutdown(self, owner: &mut Owner<'own>) {
+        let _owner = owner;
+        unsafe { self.arena.run_teardown() }
+    }
+
+    /// Returns statistics about the current state of the arena.
+    pub fn stats(&self) -> GcStats {
+        self.arena.stats()
+    }
+
+    /// Check whether the pointer is suspected to be used after every guard which rooted it was
+    /// dropped, returning where the last root was created if so.
+    ///
+    /// Debug builds also perform this check whenever a pointer is borrowed, printing a warning to
+    /// stderr for suspect pointers. Safe code can't use a pointer after its root is dropped, this
+    /// is meant for finding misuse in unsafe code. See [`sys::check_rooted`](crate::sys::check_rooted)
+    /// for when a pointer is suspect and when false positives are reported.
+    #[cfg(feature = "root-provenance")]
+    pub fn debug_check_rooted<T: ?Sized>(&self, gc: Gc<'_, 'own, T>) -> Option<crate::RootSuspect> {
+        crate::sys::check_rooted(gc.into_gc_box().cast())
+    }
+
+    /// Rebind a value to the lifetime of the borrow of the arena, see [`rebind!`](crate::rebind).
+    ///
+    /// The value must belong to this arena: values of other arenas have a different owner
+    /// lifetime and are rejected at compile time.
+    pub fn rebind_value<'gc, T: Trace<'own>>(&'gc self, value: T) -> T::Gc<'gc> {
         unsafe { value.rebind() }
     }
 
+    /// Rebind a GC pointer to the lifetime of the borrow of the arena.
+    ///
+    /// Like [`Arena::rebind_value`] but only for pointers, including pointers to unsized values.
+    pub fn rebind_gc<'gc, T: GcTarget<'own> + ?Sized>(
+        &'gc self,
+        value: Gc<'_, 'own, T>,
+    ) -> Gc<'gc, 'own, T::Gc<'gc>> {
+        unsafe { value.rebind() }
+    }
+
+    /// The same as [`Arena::rebind_value`].
+    pub fn rebind_to<'gc, T: Trace<'own>>(&'gc self, value: T) -> T::Gc<'gc> {
+        self.rebind_value(value)
+    }
+
+    /// Run the operations deferred with [`Marker::defer`] in collection cycles which have
+    /// completed, in the order they were deferred, returning the number of operations run.
+    ///
+    /// # Panic
+    /// Panics if called from a trace implementation or while the arena is being dropped.
+    pub fn run_deferred(&self, owner: &mut Owner<'own>) -> usize {
+        unsafe { self.arena.run_deferred(NonNull::from(owner).cast()) }
+    }
+
+    /// Returns the number of operations deferred with [`Marker::defer`] which have not yet run.
+    pub fn deferred_count(&self) -> usize {
+        self.arena.deferred_count()
+    }
+
+    /// Call the given function with mutable access to every live object of type `T` in the
+    /// arena.
+    ///
+    /// The heap is traced to completion first, finishing the running collection cycle and
+    /// marking the heap like [`Arena::mark_all`], so only objects reachable from a root are
+    /// visited. The write barrier is applied to each visited object so the function is free to
+    /// change the GC pointers the object contains. Objects are visited in no particular order,
+    /// and objects allocated during the walk are not visited. Allocating in the arena from within
+    /// the function will panic.
+    pub fn for_each_mut<T, F>(&mut self, owner: &mut Owner<'own>, mut f: F)
+    where
+        T: Trace<'own> + Erasable<'own>,
+        F: for<'a> FnMut(&'a mut T::Gc<'a>),
+    {
+        let arena = &self.arena;
+        assert!(
+            !arena.is_logging_undo(),
+            "cannot mutate every object of a type during a speculation"
+        );
+        owner.bump_epoch();
+        let type_id = erased_type_id::<T::Gc<'static>>();
+
+        // Only pointers to marked objects can be written to the visited objects, so the sweep
+        // can't free an object which is still referenced. The sweep is also finished when `f`
+        // panics, as allocating fails for as long as the heap is held marked.
+        struct SweepGuard<'a>(&'a UnsafeArena);
+        impl Drop for SweepGuard<'_> {
+            fn drop(&mut self) {
+                unsafe { self.0.finish_sweep() }
+            }
+        }
+
+        unsafe {
+            arena.mark_all();
+            let _guard = SweepGuard(arena);
+            arena.for_each_object(|ptr| {
+                if !arena.is_marked(ptr) || (ptr.as_ref().data_ptr.v_table().type_id)() != type_id {
+                    return;
+                }
+                if T::needs_trace() {
+                    arena.write_barrier_erased(ptr);
+                }
+                let value = ptr
+                    .cast::<GcBox<T>>()
+                    .as_ref()
+                    .value
+                    .get()
+                    .cast::<T::Gc<'_>>();
+                f(&mut *value)
+            });
+        }
+    }
+
     pub fn write_barrier<T: Trace<'own>>(&self, ptr: Gc<'_, 'own, T>) {
+        self.record_undo(ptr);
         if !T::needs_trace() {
             return;
         }
         unsafe { self.arena.write_barrier(Gc::into_gc_box(ptr)) }
     }
 
+    /// Apply the write barrier like [`Arena::write_barrier`], returning an error instead of
+    /// panicking if the object can't be logged by a running speculation.
+    pub fn try_write_barrier<T: Trace<'own>>(&self, ptr: Gc<'_, 'own, T>) -> Result<(), Error> {
+        self.try_record_undo(ptr)?;
+        if T::needs_trace() {
+            unsafe { self.arena.write_barrier(Gc::into_gc_box(ptr)) }
+        }
+        Ok(())
+    }
+
     pub fn into_unsafe_arena(self) -> UnsafeArena {
         self.arena
     }
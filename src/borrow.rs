@@ -0,0 +1,154 @@
+use std::slice;
+
+use crate::{Arena, Gc, GcAny, Owner, Trace};
+
+/// Extension trait for borrowing all pointers in a slice of GC pointers at once.
+pub trait BorrowAllExt<'gc, 'own, T> {
+    /// Returns an iterator over references to the values of all pointers.
+    ///
+    /// As the owner is only borrowed immutably it can still be used to borrow other values while
+    /// iterating.
+    fn borrow_all<'a>(&'a self, owner: &'a Owner<'own>) -> BorrowAll<'a, 'gc, 'own, T>;
+
+    /// Returns a lending iterator over mutable references to the values of all pointers.
+    ///
+    /// The write barrier is applied to each value when it is returned from the iterator.
+    fn borrow_all_mut<'a>(
+        &'a self,
+        owner: &'a mut Owner<'own>,
+        arena: &'a Arena<'own>,
+    ) -> BorrowAllMut<'a, 'gc, 'own, T>;
+}
+
+impl<'gc, 'own, T> BorrowAllExt<'gc, 'own, T> for [Gc<'gc, 'own, T>] {
+    fn borrow_all<'a>(&'a self, owner: &'a Owner<'own>) -> BorrowAll<'a, 'gc, 'own, T> {
+        BorrowAll {
+            iter: self.iter(),
+            owner,
+        }
+    }
+
+    fn borrow_all_mut<'a>(
+        &'a self,
+        owner: &'a mut Owner<'own>,
+        arena: &'a Arena<'own>,
+    ) -> BorrowAllMut<'a, 'gc, 'own, T> {
+        BorrowAllMut {
+            iter: self.iter(),
+            owner,
+            arena,
+        }
+    }
+}
+
+/// Iterator returned by [`BorrowAllExt::borrow_all`].
+pub struct BorrowAll<'a, 'gc, 'own, T> {
+    iter: slice::Iter<'a, Gc<'gc, 'own, T>>,
+    owner: &'a Owner<'own>,
+}
+
+impl<'a, 'gc, 'own, T> Iterator for BorrowAll<'a, 'gc, 'own, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next().map(|x| x.borrow(self.owner))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, 'gc, 'own, T> ExactSizeIterator for BorrowAll<'a, 'gc, 'own, T> {}
+
+/// Lending iterator returned by [`BorrowAllExt::borrow_all_mut`].
+///
+/// Only one mutable reference can be alive at a time so this type can't implement
+/// [`Iterator`], use `while let Some(x) = iter.next()` instead.
+pub struct BorrowAllMut<'a, 'gc, 'own, T> {
+    iter: slice::Iter<'a, Gc<'gc, 'own, T>>,
+    owner: &'a mut Owner<'own>,
+    arena: &'a Arena<'own>,
+}
+
+impl<'a, 'gc, 'own, T: Trace<'own>> BorrowAllMut<'a, 'gc, 'own, T> {
+    /// Returns a mutable reference to the value of the next pointer.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&mut T::Gc<'_>> {
+        let ptr = *self.iter.next()?;
+        Some(ptr.borrow_mut(self.owner, self.arena))
+    }
+
+    /// Returns the number of remaining pointers.
+    pub fn len(&self) -> usize {
+        self.iter.len()
+    }
+
+    /// Returns true if there are no remaining pointers.
+    pub fn is_empty(&self) -> bool {
+        self.iter.len() == 0
+    }
+}
+
+impl<'own> Owner<'own> {
+    /// Borrow the values of two pointers mutably at once, applying the write barrier to both.
+    ///
+    /// # Panic
+    /// Panics if both pointers point to the same object.
+    pub fn borrow_mut_2<'a, A, B>(
+        &'a mut self,
+        a: Gc<'_, 'own, A>,
+        b: Gc<'_, 'own, B>,
+        arena: &Arena<'own>,
+    ) -> (&'a mut A::Gc<'a>, &'a mut B::Gc<'a>)
+    where
+        A: Trace<'own> + 'a,
+        B: Trace<'own> + 'a,
+    {
+        assert!(
+            Gc::into_gc_box(a).cast::<()>() != Gc::into_gc_box(b).cast(),
+            "called `Owner::borrow_mut_2` with two pointers to the same object"
+        );
+        self.bump_epoch();
+        arena.write_barrier(a);
+        arena.write_barrier(b);
+        // The objects are distinct and the owner is borrowed for `'a`, so no other reference to
+        // them can exist.
+        unsafe { (a.value_mut(), b.value_mut()) }
+    }
+
+    /// Borrow the values of three pointers mutably at once, applying the write barrier to all of
+    /// them.
+    ///
+    /// # Panic
+    /// Panics if any two of the pointers point to the same object.
+    pub fn borrow_mut_3<'a, A, B, C>(
+        &'a mut self,
+        a: Gc<'_, 'own, A>,
+        b: Gc<'_, 'own, B>,
+        c: Gc<'_, 'own, C>,
+        arena: &Arena<'own>,
+    ) -> (&'a mut A::Gc<'a>, &'a mut B::Gc<'a>, &'a mut C::Gc<'a>)
+    where
+        A: Trace<'own> + 'a,
+        B: Trace<'own> + 'a,
+        C: Trace<'own> + 'a,
+    {
+        let (a_any, b_any, c_any) = unsafe {
+            (
+                GcAny::from_gc_box(Gc::into_gc_box(a).cast()),
+                GcAny::from_gc_box(Gc::into_gc_box(b).cast()),
+                GcAny::from_gc_box(Gc::into_gc_box(c).cast()),
+            )
+        };
+        assert!(
+            !a_any.ptr_eq(b_any) && !a_any.ptr_eq(c_any) && !b_any.ptr_eq(c_any),
+            "called `Owner::borrow_mut_3` with two pointers to the same object"
+        );
+        self.bump_epoch();
+        arena.write_barrier(a);
+        arena.write_barrier(b);
+        arena.write_barrier(c);
+        unsafe { (a.value_mut(), b.value_mut(), c.value_mut()) }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::{addr_of_mut, NonNull},
+};
+
+use crate::{
+    sys::{GcBox, UnsafeArena, UnsafeTrace},
+    Arena, Gc, Owner, Trace,
+};
+
+/// A placeholder for an object in a [`HeapBuilder`] which is replaced with a GC pointer once the
+/// heap is built.
+pub struct BuilderRef<N> {
+    index: usize,
+    _marker: PhantomData<fn() -> N>,
+}
+
+impl<N> Clone for BuilderRef<N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<N> Copy for BuilderRef<N> {}
+
+/// A trait for plain values which can be turned into GC allocated objects by a [`HeapBuilder`].
+pub trait IntoHeap<'own>: Sized {
+    /// The type of the object in the arena.
+    type Output<'gc>: Trace<'own>;
+
+    /// Convert the value into its arena representation, replacing all placeholder references with
+    /// the pointers from `refs`.
+    fn into_heap<'gc>(self, refs: &HeapRefs<'gc, 'own, Self>) -> Self::Output<'gc>;
+}
+
+/// The GC pointers of all objects in a [`HeapBuilder`], used to resolve [`BuilderRef`]s.
+///
+/// The pointers handed out may point to objects which are not yet initialized and can only be
+/// stored, not dereferenced.
+pub struct HeapRefs<'gc, 'own, N: IntoHeap<'own>> {
+    ptrs: Vec<Gc<'gc, 'own, N::Output<'gc>>>,
+}
+
+impl<'gc, 'own, N: IntoHeap<'own>> HeapRefs<'gc, 'own, N> {
+    /// Returns the pointer for the given placeholder.
+    pub fn get(&self, r: BuilderRef<N>) -> Gc<'gc, 'own, N::Output<'gc>> {
+        self.ptrs[r.index]
+    }
+}
+
+/// A builder for constructing an object graph outside of an arena and then moving it into the
+/// arena all at once.
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// struct Plain(u32, Option<BuilderRef<Plain>>);
+/// struct Node<'gc, 'own>(u32, Option<Gc<'gc, 'own, Node<'gc, 'own>>>);
+///
+/// unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+///     type Gc<'to> = Node<'to, 'own>;
+///
+///     fn needs_trace() -> bool {
+///         true
+///     }
+///
+///     fn trace(&self, marker: Marker<'own, '_>) {
+///         self.1.trace(marker)
+///     }
+/// }
+///
+/// impl<'own> IntoHeap<'own> for Plain {
+///     type Output<'gc> = Node<'gc, 'own>;
+///
+///     fn into_heap<'gc>(self, refs: &HeapRefs<'gc, 'own, Self>) -> Node<'gc, 'own> {
+///         Node(self.0, self.1.map(|x| refs.get(x)))
+///     }
+/// }
+///
+/// dreck!(owner, arena);
+///
+/// let mut builder = HeapBuilder::new();
+/// let a = builder.reserve();
+/// let b = builder.add(Plain(2, Some(a)));
+/// builder.set(a, Plain(1, Some(b)));
+///
+/// let ptrs = builder.build(&mut owner, &arena, &[a]);
+/// let b = ptrs[0].borrow(&owner).1.unwrap();
+/// assert_eq!(b.borrow(&owner).0, 2);
+/// ```
+pub struct HeapBuilder<N> {
+    nodes: Vec<Option<N>>,
+}
+
+impl<N> Default for HeapBuilder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> HeapBuilder<N> {
+    pub fn new() -> Self {
+        HeapBuilder { nodes: Vec::new() }
+    }
+
+    /// Add a value to the builder, returning a placeholder for it.
+    pub fn add(&mut self, node: N) -> BuilderRef<N> {
+        let r = self.reserve();
+        self.set(r, node);
+        r
+    }
+
+    /// Create a placeholder for a value which is set later with [`HeapBuilder::set`]. Used for
+    /// creating cycles.
+    pub fn reserve(&mut self) -> BuilderRef<N> {
+        self.nodes.push(None);
+        BuilderRef {
+            index: self.nodes.len() - 1,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the value of a placeholder.
+    pub fn set(&mut self, r: BuilderRef<N>, node: N) {
+        self.nodes[r.index] = Some(node);
+    }
+
+    /// Returns the number of values in the builder.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if no values were added to the builder.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Move all values into the arena, returning the pointers for the given entry points.
+    ///
+    /// All objects are allocated before any value is converted, so values can freely refer to
+    /// each other. The owner is borrowed for the duration of the build so that no object can be
+    /// accessed before it is initialized.
+    ///
+    /// # Panic
+    /// Will panic if a reserved placeholder was never set. If [`IntoHeap::into_heap`] panics the
+    /// process is aborted, as the pointers handed out might already have been rooted or stored
+    /// while the objects can never be initialized.
+    pub fn build<'gc, 'own>(
+        self,
+        owner: &mut Owner<'own>,
+        arena: &'gc Arena<'own>,
+        entries: &[BuilderRef<N>],
+    ) -> Vec<Gc<'gc, 'own, N::Output<'gc>>>
+    where
+        N: IntoHeap<'own>,
+    {
+        let _owner = owner;
+        let nodes = self
+            .nodes
+            .into_iter()
+            .enumerate()
+            .map(|(idx, x)| {
+                x.unwrap_or_else(|| panic!("builder placeholder {} was never set", idx))
+            })
+            .collect::<Vec<_>>();
+
+        /// Frees the allocated objects if allocating the others fails.
+        struct Pending<'a, T: UnsafeTrace> {
+            arena: &'a UnsafeArena,
+            ptrs: Vec<NonNull<GcBox<T>>>,
+        }
+
+        impl<'a, T: UnsafeTrace> Drop for Pending<'a, T> {
+            fn drop(&mut self) {
+                unsafe {
+                    for ptr in self.ptrs.iter().copied() {
+                        self.arena.dealloc_uninit(ptr);
+                    }
+                }
+            }
+        }
+
+        /// Aborts if dropped, which only happens if a conversion panics.
+        struct AbortOnUnwind;
+
+        impl Drop for AbortOnUnwind {
+            fn drop(&mut self) {
+                eprintln!("panicked while converting a value of a `HeapBuilder`");
+                std::process::abort();
+            }
+        }
+
+        let unsafe_arena = arena.unsafe_arena();
+        let mut pending = Pending {
+            arena: unsafe_arena,
+            ptrs: Vec::with_capacity(nodes.len()),
+        };
+        for _ in 0..nodes.len() {
+            let ptr = unsafe { unsafe_arena.alloc_uninit::<N::Output<'gc>>() };
+            pending.ptrs.push(ptr);
+        }
+        let ptrs = std::mem::take(&mut pending.ptrs);
+
+        let refs = HeapRefs::<'gc, 'own, N> {
+            ptrs: ptrs
+                .iter()
+                .map(|x| unsafe { Gc::from_gc_box(*x) })
+                .collect(),
+        };
+
+        let abort = AbortOnUnwind;
+        for (node, ptr) in nodes.into_iter().zip(ptrs.iter().copied()) {
+            let value = node.into_heap(&refs);
+            unsafe {
+                addr_of_mut!((*ptr.as_ptr()).value)
+                    .write(UnsafeCell::new(ManuallyDrop::new(value)));
+            }
+        }
+        std::mem::forget(abort);
+
+        for ptr in ptrs {
+            unsafe { unsafe_arena.link(ptr) };
+        }
+
+        entries.iter().map(|x| refs.get(*x)).collect()
+    }
+}
@@ -0,0 +1,318 @@
+//! Interior mutability for GC objects.
+
+use std::{
+    cell::{Cell, OnceCell, Ref, RefCell, RefMut},
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{Arena, Error, Gc, Marker, NoGc, Owner, Trace};
+
+/// A mutable memory location for plain data, mutated through a shared reference.
+///
+/// Meant for small fields of GC allocated objects like counters, flags or cached hashes, which
+/// would otherwise require borrowing the owner mutably. The value can't contain GC pointers, as
+/// required by [`NoGc`], so changing it never needs a write barrier.
+pub struct GcCell<T: NoGc>(Cell<T>);
+
+unsafe impl<'own, T: NoGc> Trace<'own> for GcCell<T> {
+    type Gc<'gc> = GcCell<T>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl<T: NoGc> GcCell<T> {
+    pub fn new(value: T) -> Self {
+        GcCell(Cell::new(value))
+    }
+
+    /// Returns a copy of the value.
+    pub fn get(&self) -> T {
+        self.0.get()
+    }
+
+    /// Set the value.
+    pub fn set(&self, value: T) {
+        self.0.set(value)
+    }
+
+    /// Set the value, returning the old value.
+    pub fn replace(&self, value: T) -> T {
+        self.0.replace(value)
+    }
+
+    /// Returns the contained value.
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+impl<T: NoGc + Default> Default for GcCell<T> {
+    fn default() -> Self {
+        GcCell::new(T::default())
+    }
+}
+
+impl<T: NoGc> Clone for GcCell<T> {
+    fn clone(&self) -> Self {
+        GcCell::new(self.get())
+    }
+}
+
+impl<T: NoGc + fmt::Debug> fmt::Debug for GcCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GcCell").field(&self.get()).finish()
+    }
+}
+
+/// A GC object which can be borrowed mutably with only a shared reference to the owner, checked
+/// at runtime like a [`RefCell`].
+///
+/// Meant for code which only has access to `&Owner` but needs to mutate a well-known object, like
+/// a callback. The value is borrowed mutably with [`Gc::borrow_mut_cell`], which applies the write
+/// barrier to the object, and immutably with [`GcRefCell::borrow`] after borrowing the object with
+/// the owner.
+///
+/// # Tracing
+/// A mutable borrow borrows the arena as well, so the collector can't run while it is alive. The
+/// cell is therefore never traced while mutably borrowed through the safe API, and tracing panics
+/// if it is, for example when stepping the collector through [`UnsafeArena`].
+///
+/// [`UnsafeArena`]: crate::sys::UnsafeArena
+pub struct GcRefCell<T>(RefCell<T>);
+
+unsafe impl<'own, T: Trace<'own>> Trace<'own> for GcRefCell<T> {
+    type Gc<'gc> = GcRefCell<T::Gc<'gc>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        T::needs_trace()
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0
+            .try_borrow()
+            .expect("traced a `GcRefCell` which is mutably borrowed")
+            .trace(marker)
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        self.0.try_borrow().ok()?.snapshot().map(GcRefCell::new)
+    }
+}
+
+impl<T> GcRefCell<T> {
+    pub fn new(value: T) -> Self {
+        GcRefCell(RefCell::new(value))
+    }
+
+    /// Borrow the value immutably.
+    ///
+    /// # Panic
+    /// Panics if the value is mutably borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    /// Borrow the value immutably, returning an error if it is mutably borrowed.
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, Error> {
+        self.0.try_borrow().map_err(|_| Error::BorrowConflict)
+    }
+
+    /// Returns the contained value.
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+impl<'gc, 'own, T: Trace<'own>> Gc<'gc, 'own, GcRefCell<T>> {
+    /// Borrow the value of the cell mutably, applying the write barrier to the object.
+    ///
+    /// The borrow is bound to the borrows of the owner and the arena, so no collection can
+    /// happen while it is alive.
+    ///
+    /// # Panic
+    /// Panics if the value is already borrowed.
+    pub fn borrow_mut_cell<'a>(
+        self,
+        owner: &'a Owner<'own>,
+        arena: &'a Arena<'own>,
+    ) -> GcRefMut<'a, T::Gc<'a>>
+    where
+        T: 'a,
+    {
+        self.try_borrow_mut_cell(owner, arena)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Borrow the value of the cell mutably, returning an error if it is already borrowed.
+    pub fn try_borrow_mut_cell<'a>(
+        self,
+        owner: &'a Owner<'own>,
+        arena: &'a Arena<'own>,
+    ) -> Result<GcRefMut<'a, T::Gc<'a>>, Error>
+    where
+        T: 'a,
+    {
+        let cell = self.borrow(owner);
+        // `T::Gc<'a>` only differs from `T` in lifetimes, like in `Gc::borrow_mut`.
+        let cell = unsafe { &*(cell as *const GcRefCell<T>).cast::<GcRefCell<T::Gc<'a>>>() };
+        // The barrier comes first so a speculation can still read the value before it changes.
+        arena.write_barrier(self);
+        let borrow = cell.0.try_borrow_mut().map_err(|_| Error::BorrowConflict)?;
+        owner.bump_epoch();
+        Ok(GcRefMut(borrow))
+    }
+}
+
+/// A mutable borrow of the value of a [`GcRefCell`], created by [`Gc::borrow_mut_cell`].
+pub struct GcRefMut<'a, T>(RefMut<'a, T>);
+
+impl<T> Deref for GcRefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for GcRefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// A cell which can be written once, for fields which are initialized lazily, like caches.
+///
+/// Reading the cell only requires a shared reference to the owner. The cell is set with a shared
+/// reference as well, as no reference to its value can exist before it is set. The cell doesn't
+/// know the object containing it, so instead of applying the write barrier to that object,
+/// setting the cell marks the pointers in the value if the collector is tracing.
+pub struct GcOnceCell<T>(OnceCell<T>);
+
+unsafe impl<'own, T: Trace<'own>> Trace<'own> for GcOnceCell<T> {
+    type Gc<'gc> = GcOnceCell<T::Gc<'gc>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        T::needs_trace()
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        if let Some(x) = self.0.get() {
+            x.trace(marker)
+        }
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        let cell = OnceCell::new();
+        if let Some(x) = self.0.get() {
+            let _ = cell.set(x.snapshot()?);
+        }
+        Some(GcOnceCell(cell))
+    }
+}
+
+impl<T> GcOnceCell<T> {
+    /// Create an empty cell.
+    pub fn new() -> Self {
+        GcOnceCell(OnceCell::new())
+    }
+
+    /// Returns the value of the cell, or `None` if it isn't set yet.
+    pub fn get<'a, 'own>(&'a self, owner: &'a Owner<'own>) -> Option<&'a T> {
+        let _owner = owner;
+        self.0.get()
+    }
+
+    /// Returns the contained value.
+    pub fn into_inner(self) -> Option<T> {
+        self.0.into_inner()
+    }
+}
+
+impl<T> Default for GcOnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for GcOnceCell<T> {
+    fn from(value: T) -> Self {
+        GcOnceCell(OnceCell::from(value))
+    }
+}
+
+impl<T> GcOnceCell<T> {
+    /// Cast the cell to the type with the given gc lifetime.
+    fn rebound<'a, 'own>(&'a self) -> &'a GcOnceCell<T::Gc<'a>>
+    where
+        T: Trace<'own> + 'a,
+    {
+        // `T::Gc<'a>` only differs from `T` in lifetimes, like in `Gc::borrow_mut`.
+        unsafe { &*(self as *const GcOnceCell<T>).cast::<GcOnceCell<T::Gc<'a>>>() }
+    }
+
+    /// Set the value of the cell, returning the value back if the cell is already set.
+    ///
+    /// The value is bound to the borrows of the owner and the arena, like with [`Gc::set`].
+    pub fn set<'a, 'own>(
+        &'a self,
+        owner: &'a Owner<'own>,
+        arena: &'a Arena<'own>,
+        value: T::Gc<'a>,
+    ) -> Result<(), T::Gc<'a>>
+    where
+        T: Trace<'own> + 'a,
+    {
+        let cell = self.rebound();
+        cell.0.set(value)?;
+        owner.bump_epoch();
+        // Safe as the value was a valid pointer for the borrow of the arena.
+        unsafe { arena.unsafe_arena().mark_value(self.0.get().unwrap()) };
+        Ok(())
+    }
+
+    /// Returns the value of the cell, initializing it with the function if it isn't set yet.
+    ///
+    /// # Panic
+    /// Panics if the function initializes the cell itself.
+    pub fn get_or_init<'a, 'own, F>(
+        &'a self,
+        owner: &'a Owner<'own>,
+        arena: &'a Arena<'own>,
+        f: F,
+    ) -> &'a T::Gc<'a>
+    where
+        T: Trace<'own> + 'a,
+        F: FnOnce() -> T::Gc<'a>,
+    {
+        let cell = self.rebound();
+        if let Some(x) = cell.0.get() {
+            return x;
+        }
+        let value = f();
+        assert!(
+            cell.0.set(value).is_ok(),
+            "`GcOnceCell` was initialized by its own initializer"
+        );
+        owner.bump_epoch();
+        unsafe { arena.unsafe_arena().mark_value(self.0.get().unwrap()) };
+        cell.0.get().unwrap()
+    }
+}
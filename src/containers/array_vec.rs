@@ -0,0 +1,133 @@
+use std::{mem::MaybeUninit, ptr, slice};
+
+use crate::{arena::Marker, Trace};
+
+/// A vector with a fixed capacity of `N` elements stored inline.
+///
+/// Unlike [`GcVec`](super::GcVec) this doesn't require any allocation, which makes it useful for
+/// small collections stored as a field of GC allocated objects. Only the initialized elements
+/// are traced and dropped.
+///
+/// The vector doesn't contain any GC allocated parts itself so it needs no write barriers apart
+/// from the one for the containing object applied by [`Gc::borrow_mut`](crate::Gc::borrow_mut).
+pub struct GcArrayVec<T, const N: usize> {
+    len: usize,
+    values: [MaybeUninit<T>; N],
+}
+
+unsafe impl<'own, T: Trace<'own>, const N: usize> Trace<'own> for GcArrayVec<T, N> {
+    type Gc<'gc> = GcArrayVec<T::Gc<'gc>, N>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        T::needs_trace()
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        for v in self.as_slice() {
+            v.trace(marker)
+        }
+    }
+}
+
+impl<T, const N: usize> Default for GcArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> GcArrayVec<T, N> {
+    /// Create a new empty vector.
+    pub fn new() -> Self {
+        GcArrayVec {
+            len: 0,
+            values: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    /// Returns the initialized elements of the vector.
+    pub fn as_slice(&self) -> &[T] {
+        // Safe because the first `len` elements are always initialized.
+        unsafe { slice::from_raw_parts(self.values.as_ptr().cast(), self.len) }
+    }
+
+    /// Returns the initialized elements of the vector.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // Safe because the first `len` elements are always initialized.
+        unsafe { slice::from_raw_parts_mut(self.values.as_mut_ptr().cast(), self.len) }
+    }
+
+    /// Returns the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the vector can't hold any more elements.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the number of elements the vector can hold.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the element at the given index.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// Push a value onto the end of the vector.
+    ///
+    /// # Panics
+    /// Panics if the vector is full.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("pushed onto a full GcArrayVec with capacity {}", N);
+        }
+    }
+
+    /// Push a value onto the end of the vector, returning the value back if the vector is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.values[self.len].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove the last element from the vector.
+    ///
+    /// The returned value is bound to the lifetime of the vector, so it can't outlive the
+    /// borrow through which the vector was accessed unless rooted.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // Safe because the element was initialized and is no longer part of the vector.
+        Some(unsafe { self.values[self.len].assume_init_read() })
+    }
+
+    /// Remove all elements from the vector.
+    pub fn clear(&mut self) {
+        let elements: *mut [T] = self.as_mut_slice();
+        // Set the length first so a panicking drop can't cause a double drop.
+        self.len = 0;
+        unsafe { ptr::drop_in_place(elements) }
+    }
+}
+
+impl<T, const N: usize> Drop for GcArrayVec<T, N> {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
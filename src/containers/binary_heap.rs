@@ -0,0 +1,138 @@
+use std::collections::BinaryHeap;
+
+use super::spine;
+use crate::{arena::Marker, Arena, Gc, Owner, Trace};
+
+/// A priority queue with its elements stored in a separate GC allocated buffer, the spine.
+///
+/// Grows the same way as [`GcVec`](super::GcVec): when a push exceeds the capacity of the spine
+/// a new, larger, spine is allocated and the elements are moved over. All write barriers for the
+/// spine are handled by the queue itself.
+///
+/// When the queue is a field of a GC allocated object it should be accessed through
+/// [`Gc::borrow_mut`] which applies the barrier for the containing object. A queue allocated as
+/// an object of its own can be modified directly through the pointer with [`Gc::push`] and
+/// [`Gc::pop`], which apply all barriers themselves.
+///
+/// The spine is a [`BinaryHeap`], which can't be copied into the undo log of a speculation
+/// without requiring its elements to be ordered. Pushing onto or popping from a queue with a
+/// spine during a speculation therefore panics, see [`Arena::begin_speculation`].
+pub struct GcBinaryHeap<'gc, 'own, T> {
+    spine: Option<Gc<'gc, 'own, BinaryHeap<T>>>,
+}
+
+unsafe impl<'gc, 'own, T: Trace<'own>> Trace<'own> for GcBinaryHeap<'gc, 'own, T> {
+    type Gc<'to> = GcBinaryHeap<'to, 'own, T::Gc<'to>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.spine.trace(marker)
+    }
+}
+
+impl<'gc, 'own, T> Default for GcBinaryHeap<'gc, 'own, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'gc, 'own, T> GcBinaryHeap<'gc, 'own, T> {
+    /// Create a new empty queue, no spine is allocated until the first push.
+    pub fn new() -> Self {
+        GcBinaryHeap { spine: None }
+    }
+
+    fn spine(&self) -> Option<&BinaryHeap<T>> {
+        // Safe because the spine is only ever accessed through the queue.
+        self.spine
+            .map(|x| unsafe { &**x.into_gc_box().as_ref().value.get() })
+    }
+
+    fn spine_mut(&mut self) -> Option<&mut BinaryHeap<T>> {
+        // Safe because the spine is only ever accessed through the queue.
+        self.spine
+            .map(|x| unsafe { &mut **x.into_gc_box().as_ref().value.get() })
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.spine().map(|x| x.len()).unwrap_or(0)
+    }
+
+    /// Returns true if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of elements the current spine can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.spine().map(|x| x.capacity()).unwrap_or(0)
+    }
+
+    /// Returns the greatest element of the queue.
+    pub fn peek(&self) -> Option<&T> {
+        self.spine().and_then(|x| x.peek())
+    }
+
+    /// Returns an iterator over the elements in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.spine().into_iter().flat_map(|x| x.iter())
+    }
+}
+
+impl<'gc, 'own, T: Ord> GcBinaryHeap<'gc, 'own, T> {
+    /// Remove the greatest element from the queue.
+    ///
+    /// The returned value is bound to the lifetime of the queue, so it can't outlive the borrow
+    /// through which the queue was accessed unless rooted.
+    pub fn pop(&mut self) -> Option<T> {
+        self.spine_mut().and_then(|x| x.pop())
+    }
+}
+
+impl<'gc, 'own, T: Trace<'own> + Ord> GcBinaryHeap<'gc, 'own, T> {
+    /// Push a value into the queue, growing the spine if required.
+    pub fn push(&mut self, arena: &'gc Arena<'own>, value: T) {
+        spine::reserve_one(&mut self.spine, arena);
+        self.spine_mut().unwrap().push(value);
+    }
+}
+
+impl<'gc, 'own, 'v, T: Trace<'own> + Ord> Gc<'gc, 'own, GcBinaryHeap<'v, 'own, T>> {
+    /// Push a value into the queue, applying the write barriers to the queue and its spine.
+    ///
+    /// The value is bound to the borrow of the owner, like with [`Gc::set`].
+    pub fn push<'a>(self, owner: &'a mut Owner<'own>, arena: &'a Arena<'own>, value: T::Gc<'a>)
+    where
+        T: 'a,
+        T::Gc<'a>: Trace<'own> + Ord,
+    {
+        // Log the queue for a running speculation, before the barrier of `Gc::borrow_mut` tries
+        // to snapshot it.
+        arena.record_undo_with(self, |x| GcBinaryHeap { spine: x.spine });
+        self.borrow_mut(owner, arena).push(arena, value)
+    }
+
+    /// Remove the greatest element from the queue.
+    ///
+    /// The returned value is no longer reachable through the queue, so it is bound to the borrow
+    /// of the arena and can't be kept across a collection unless rooted.
+    pub fn pop<'a>(self, owner: &mut Owner<'own>, arena: &'a Arena<'own>) -> Option<T::Gc<'a>>
+    where
+        T: 'a,
+        T::Gc<'a>: Ord,
+    {
+        owner.bump_epoch();
+        // Log the spine for a running speculation, as removing elements applies no barrier.
+        if let Some(spine) = self.borrow(owner).spine {
+            arena.record_undo(spine);
+        }
+        unsafe { self.value_mut().pop() }
+    }
+}
@@ -0,0 +1,18 @@
+//! Container types for use inside GC allocated objects which handle write barriers themselves.
+
+mod spine;
+
+mod vec;
+pub use vec::GcVec;
+
+mod array_vec;
+pub use array_vec::GcArrayVec;
+
+mod vec_deque;
+pub use vec_deque::GcVecDeque;
+
+mod binary_heap;
+pub use binary_heap::GcBinaryHeap;
+
+mod persistent_map;
+pub use persistent_map::{GcKeyable, GcPersistentMap};
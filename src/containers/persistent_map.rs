@@ -0,0 +1,470 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use crate::{arena::Marker, Arena, Gc, Owner, Trace};
+
+/// Types which can be used as keys of a [`GcPersistentMap`].
+///
+/// Keys are hashed and compared without access to the owner, so keys which are GC pointers are
+/// compared by identity. Use [`Symbol`](crate::Symbol) for string keys.
+pub trait GcKeyable<'own>: Trace<'own> + Hash + Eq + Clone {}
+
+impl<'own, T: Trace<'own> + Hash + Eq + Clone> GcKeyable<'own> for T {}
+
+/// The number of hash bits consumed by every level of the trie.
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+enum Entry<'gc, 'own, K, V> {
+    Leaf { hash: u64, key: K, value: V },
+    Node(Gc<'gc, 'own, Node<'gc, 'own, K, V>>),
+}
+
+impl<'gc, 'own, K: Clone, V: Clone> Clone for Entry<'gc, 'own, K, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Entry::Leaf { hash, key, value } => Entry::Leaf {
+                hash: *hash,
+                key: key.clone(),
+                value: value.clone(),
+            },
+            Entry::Node(x) => Entry::Node(*x),
+        }
+    }
+}
+
+/// A node of the trie.
+///
+/// Nodes below the last level of hash bits hold only leaves with the same hash, which are
+/// searched linearly, and have an empty bitmap.
+struct Node<'gc, 'own, K, V> {
+    bitmap: u32,
+    entries: Vec<Entry<'gc, 'own, K, V>>,
+}
+
+unsafe impl<'gc, 'own, K: Trace<'own>, V: Trace<'own>> Trace<'own> for Node<'gc, 'own, K, V> {
+    type Gc<'to> = Node<'to, 'own, K::Gc<'to>, V::Gc<'to>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        for entry in self.entries.iter() {
+            match entry {
+                Entry::Leaf { key, value, .. } => {
+                    key.trace(marker);
+                    value.trace(marker);
+                }
+                Entry::Node(x) => x.trace(marker),
+            }
+        }
+    }
+}
+
+/// The result of removing a key from a node.
+enum Removed<'gc, 'own, K, V> {
+    NotFound,
+    Empty,
+    /// A single leaf remains, which is moved into the parent.
+    Single(Entry<'gc, 'own, K, V>),
+    Node(Node<'gc, 'own, K, V>),
+}
+
+/// A persistent hash map, a hash array mapped trie with its nodes allocated as GC objects.
+///
+/// The map is an immutable handle: [`GcPersistentMap::insert`] and [`GcPersistentMap::remove`]
+/// return a new map which shares all unchanged nodes with the old one, copying only the nodes on
+/// the path to the changed key. Copying a handle is cheap, and old versions stay valid for as long
+/// as they are reachable. Nodes no longer reachable from any version are freed by the collector.
+///
+/// Lookups only require the owner and updates only require the arena, so a map stored in a GC
+/// object can be updated in place through [`Gc::borrow_mut`].
+///
+/// # Write barriers
+/// The map never needs a write barrier. A node is never mutated once it is allocated, so the only
+/// pointers ever created are those from a new node to its children. A new node is only reachable
+/// from the map returned by the operation which created it, and storing that map in a GC object
+/// goes through [`Gc::borrow_mut`], which applies the barrier for the containing object. Its
+/// nodes are then traced like any other object reachable from it.
+pub struct GcPersistentMap<'gc, 'own, K, V> {
+    root: Option<Gc<'gc, 'own, Node<'gc, 'own, K, V>>>,
+    len: usize,
+}
+
+impl<'gc, 'own, K, V> Clone for GcPersistentMap<'gc, 'own, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'gc, 'own, K, V> Copy for GcPersistentMap<'gc, 'own, K, V> {}
+
+unsafe impl<'gc, 'own, K: Trace<'own>, V: Trace<'own>> Trace<'own>
+    for GcPersistentMap<'gc, 'own, K, V>
+{
+    type Gc<'to> = GcPersistentMap<'to, 'own, K::Gc<'to>, V::Gc<'to>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.root.trace(marker)
+    }
+}
+
+impl<'gc, 'own, K, V> Default for GcPersistentMap<'gc, 'own, K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'gc, 'own, K, V> GcPersistentMap<'gc, 'own, K, V> {
+    /// Create a new empty map, no node is allocated until the first insert.
+    pub fn new() -> Self {
+        GcPersistentMap { root: None, len: 0 }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the entries of the map, in an unspecified order.
+    pub fn iter<'a>(
+        &self,
+        owner: &'a Owner<'own>,
+    ) -> impl Iterator<Item = (&'a K, &'a V)> + use<'a, 'gc, 'own, K, V>
+    where
+        'gc: 'a,
+        K: 'a,
+        V: 'a,
+    {
+        let mut stack = Vec::new();
+        if let Some(root) = self.root {
+            stack.push(root.borrow(owner).entries.iter());
+        }
+        std::iter::from_fn(move || loop {
+            match stack.last_mut()?.next() {
+                Some(Entry::Leaf { key, value, .. }) => return Some((key, value)),
+                Some(Entry::Node(x)) => stack.push(x.borrow(owner).entries.iter()),
+                None => {
+                    stack.pop();
+                }
+            }
+        })
+    }
+
+    /// Returns the number of GC allocated nodes of the map, including nodes shared with other
+    /// versions of the map.
+    pub fn node_count(&self, owner: &Owner<'own>) -> usize {
+        fn count<'own, K, V>(
+            node: Gc<'_, 'own, Node<'_, 'own, K, V>>,
+            owner: &Owner<'own>,
+        ) -> usize {
+            1 + node
+                .borrow(owner)
+                .entries
+                .iter()
+                .map(|x| match x {
+                    Entry::Leaf { .. } => 0,
+                    Entry::Node(x) => count(*x, owner),
+                })
+                .sum::<usize>()
+        }
+        self.root.map(|x| count(x, owner)).unwrap_or(0)
+    }
+}
+
+/// Returns the node the pointer points to.
+///
+/// Updating a map doesn't require the owner so it can be done on a map accessed through
+/// [`Gc::borrow_mut`]. This is safe because nodes are never mutated once allocated and are only
+/// ever accessed through maps, so no mutable reference to a node can exist.
+fn read_node<'a, 'gc, 'own, K, V>(
+    ptr: &'a Gc<'gc, 'own, Node<'gc, 'own, K, V>>,
+) -> &'a Node<'gc, 'own, K, V> {
+    unsafe { &*ptr.into_gc_box().as_ref().value.get() }
+}
+
+fn hash_key<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the bit of the bitmap for the hash at the given level.
+fn bit(hash: u64, shift: u32) -> u32 {
+    1 << ((hash >> shift) & MASK)
+}
+
+/// Returns the index of the entry for the bit in a node with the given bitmap.
+fn index(bitmap: u32, bit: u32) -> usize {
+    (bitmap & (bit - 1)).count_ones() as usize
+}
+
+impl<'gc, 'own, K: GcKeyable<'own>, V> GcPersistentMap<'gc, 'own, K, V> {
+    /// Returns the value of the key.
+    pub fn get<'a>(&self, owner: &'a Owner<'own>, key: &K) -> Option<&'a V>
+    where
+        'gc: 'a,
+        K: 'a,
+    {
+        let hash = hash_key(key);
+        let mut node = self.root?.borrow(owner);
+        let mut shift = 0;
+        loop {
+            let entry = if shift >= u64::BITS {
+                node.entries.iter().find(|x| match x {
+                    Entry::Leaf { key: k, .. } => k == key,
+                    Entry::Node(_) => false,
+                })?
+            } else {
+                let bit = bit(hash, shift);
+                if node.bitmap & bit == 0 {
+                    return None;
+                }
+                &node.entries[index(node.bitmap, bit)]
+            };
+            match entry {
+                Entry::Leaf {
+                    hash: h,
+                    key: k,
+                    value,
+                } => return (*h == hash && k == key).then_some(value),
+                Entry::Node(x) => node = x.borrow(owner),
+            }
+            shift += BITS;
+        }
+    }
+
+    /// Returns true if the map contains the key.
+    pub fn contains_key(&self, owner: &Owner<'own>, key: &K) -> bool {
+        self.get(owner, key).is_some()
+    }
+}
+
+impl<'gc, 'own, K: GcKeyable<'own>, V: Trace<'own> + Clone> GcPersistentMap<'gc, 'own, K, V> {
+    /// Returns a new map with the key set to the value, replacing any previous value of the key.
+    ///
+    /// The map itself is not changed.
+    pub fn insert(&self, arena: &'gc Arena<'own>, key: K, value: V) -> Self {
+        let hash = hash_key(&key);
+        let leaf = Entry::Leaf { hash, key, value };
+        let Some(root) = self.root else {
+            let node = Node {
+                bitmap: bit(hash, 0),
+                entries: vec![leaf],
+            };
+            return GcPersistentMap {
+                root: Some(arena.add(node)),
+                len: 1,
+            };
+        };
+
+        let (node, added) = Self::insert_node(arena, read_node(&root), 0, leaf);
+        GcPersistentMap {
+            root: Some(arena.add(node)),
+            len: self.len + added as usize,
+        }
+    }
+
+    /// Returns a new map without the key, or `None` if the map doesn't contain the key.
+    ///
+    /// The map itself is not changed.
+    pub fn remove(&self, arena: &'gc Arena<'own>, key: &K) -> Option<Self> {
+        let hash = hash_key(key);
+        let root = match Self::remove_node(arena, read_node(self.root.as_ref()?), 0, hash, key) {
+            Removed::NotFound => return None,
+            Removed::Empty => None,
+            Removed::Single(entry) => {
+                let Entry::Leaf { hash, .. } = entry else {
+                    unreachable!()
+                };
+                Some(arena.add(Node {
+                    bitmap: bit(hash, 0),
+                    entries: vec![entry],
+                }))
+            }
+            Removed::Node(node) => Some(arena.add(node)),
+        };
+        Some(GcPersistentMap {
+            root,
+            len: self.len - 1,
+        })
+    }
+
+    /// Returns a copy of the node with the leaf inserted and whether the key is new.
+    fn insert_node(
+        arena: &'gc Arena<'own>,
+        node: &Node<'gc, 'own, K, V>,
+        shift: u32,
+        leaf: Entry<'gc, 'own, K, V>,
+    ) -> (Node<'gc, 'own, K, V>, bool) {
+        let Entry::Leaf { hash, ref key, .. } = leaf else {
+            unreachable!()
+        };
+        let mut entries = node.entries.clone();
+
+        if shift >= u64::BITS {
+            let existing = entries.iter().position(|x| match x {
+                Entry::Leaf { key: k, .. } => k == key,
+                Entry::Node(_) => false,
+            });
+            let added = existing.is_none();
+            match existing {
+                Some(idx) => entries[idx] = leaf,
+                None => entries.push(leaf),
+            }
+            return (Node { bitmap: 0, entries }, added);
+        }
+
+        let bit = bit(hash, shift);
+        let idx = index(node.bitmap, bit);
+        if node.bitmap & bit == 0 {
+            entries.insert(idx, leaf);
+            let node = Node {
+                bitmap: node.bitmap | bit,
+                entries,
+            };
+            return (node, true);
+        }
+
+        let added = match &node.entries[idx] {
+            Entry::Leaf {
+                hash: h, key: k, ..
+            } if *h == hash && k == key => {
+                entries[idx] = leaf;
+                false
+            }
+            Entry::Leaf { .. } => {
+                let existing = node.entries[idx].clone();
+                let child = Self::pair(arena, shift + BITS, existing, leaf);
+                entries[idx] = Entry::Node(arena.add(child));
+                true
+            }
+            Entry::Node(child) => {
+                let (child, added) = Self::insert_node(arena, read_node(child), shift + BITS, leaf);
+                entries[idx] = Entry::Node(arena.add(child));
+                added
+            }
+        };
+        let node = Node {
+            bitmap: node.bitmap,
+            entries,
+        };
+        (node, added)
+    }
+
+    /// Returns a node containing two leaves with different keys.
+    fn pair(
+        arena: &'gc Arena<'own>,
+        shift: u32,
+        a: Entry<'gc, 'own, K, V>,
+        b: Entry<'gc, 'own, K, V>,
+    ) -> Node<'gc, 'own, K, V> {
+        let (Entry::Leaf { hash: hash_a, .. }, Entry::Leaf { hash: hash_b, .. }) = (&a, &b) else {
+            unreachable!()
+        };
+        if shift >= u64::BITS {
+            return Node {
+                bitmap: 0,
+                entries: vec![a, b],
+            };
+        }
+
+        let (bit_a, bit_b) = (bit(*hash_a, shift), bit(*hash_b, shift));
+        if bit_a == bit_b {
+            let child = Self::pair(arena, shift + BITS, a, b);
+            return Node {
+                bitmap: bit_a,
+                entries: vec![Entry::Node(arena.add(child))],
+            };
+        }
+        let entries = if bit_a < bit_b {
+            vec![a, b]
+        } else {
+            vec![b, a]
+        };
+        Node {
+            bitmap: bit_a | bit_b,
+            entries,
+        }
+    }
+
+    fn remove_node(
+        arena: &'gc Arena<'own>,
+        node: &Node<'gc, 'own, K, V>,
+        shift: u32,
+        hash: u64,
+        key: &K,
+    ) -> Removed<'gc, 'own, K, V> {
+        // Find the changed entry first so nothing is copied if the key isn't in the map.
+        let mut bitmap = node.bitmap;
+        let (idx, replacement) = if shift >= u64::BITS {
+            let Some(idx) = node.entries.iter().position(|x| match x {
+                Entry::Leaf { key: k, .. } => k == key,
+                Entry::Node(_) => false,
+            }) else {
+                return Removed::NotFound;
+            };
+            (idx, None)
+        } else {
+            let bit = bit(hash, shift);
+            if bitmap & bit == 0 {
+                return Removed::NotFound;
+            }
+            let idx = index(bitmap, bit);
+            let replacement = match &node.entries[idx] {
+                Entry::Leaf {
+                    hash: h, key: k, ..
+                } => {
+                    if *h != hash || k != key {
+                        return Removed::NotFound;
+                    }
+                    None
+                }
+                Entry::Node(child) => {
+                    match Self::remove_node(arena, read_node(child), shift + BITS, hash, key) {
+                        Removed::NotFound => return Removed::NotFound,
+                        Removed::Empty => None,
+                        Removed::Single(leaf) => Some(leaf),
+                        Removed::Node(child) => Some(Entry::Node(arena.add(child))),
+                    }
+                }
+            };
+            if replacement.is_none() {
+                bitmap &= !bit;
+            }
+            (idx, replacement)
+        };
+
+        let mut entries = node.entries.clone();
+        match replacement {
+            Some(entry) => entries[idx] = entry,
+            None => {
+                entries.remove(idx);
+            }
+        }
+
+        match entries.as_slice() {
+            [] => Removed::Empty,
+            [Entry::Leaf { .. }] => Removed::Single(entries.pop().unwrap()),
+            _ => Removed::Node(Node { bitmap, entries }),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::{Arena, Gc, Trace};
+
+/// The minimum capacity of a newly allocated spine.
+const MIN_CAPACITY: usize = 4;
+
+/// A buffer with a fixed capacity which can be used as the spine of a container.
+pub(super) trait Spine: Sized {
+    fn with_capacity(capacity: usize) -> Self;
+
+    fn len(&self) -> usize;
+
+    fn capacity(&self) -> usize;
+
+    /// Move all elements of `other` into `self`, leaving `other` empty.
+    fn append(&mut self, other: &mut Self);
+}
+
+impl<T> Spine for Vec<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other)
+    }
+}
+
+impl<T> Spine for VecDeque<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        VecDeque::with_capacity(capacity)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other)
+    }
+}
+
+impl<T: Ord> Spine for BinaryHeap<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        BinaryHeap::with_capacity(capacity)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other)
+    }
+}
+
+/// Make sure the spine can hold one more element and apply the write barrier to it.
+///
+/// A full spine is replaced by a new spine with twice the capacity to which the elements are
+/// moved, the old spine is left for the collector to free.
+pub(super) fn reserve_one<'gc, 'own, S>(
+    spine: &mut Option<Gc<'gc, 'own, S>>,
+    arena: &'gc Arena<'own>,
+) where
+    S: Spine + Trace<'own>,
+{
+    let (len, capacity) = spine
+        .map(|x| {
+            // Safe because the spine is only ever accessed through its container.
+            let spine = unsafe { &**x.into_gc_box().as_ref().value.get() };
+            (spine.len(), spine.capacity())
+        })
+        .unwrap_or((0, 0));
+    let ptr = match *spine {
+        Some(ptr) if len < capacity => ptr,
+        old => {
+            let mut elements = S::with_capacity((capacity * 2).max(MIN_CAPACITY));
+            if let Some(old) = old {
+                // Moving the elements mutates the old spine, which a running speculation has to
+                // restore if the container is rolled back to it.
+                arena.record_undo(old);
+                elements.append(unsafe { &mut **old.into_gc_box().as_ref().value.get() });
+            }
+            let ptr = arena.add(elements);
+            // The containing object might already have been traced, so make sure the new
+            // spine is not freed during the current cycle.
+            unsafe {
+                arena.unsafe_arena().mark_erased(ptr.into_gc_box().cast());
+            }
+            *spine = Some(ptr);
+            ptr
+        }
+    };
+
+    arena.write_barrier(ptr);
+}
@@ -0,0 +1,224 @@
+use super::spine;
+use crate::{arena::Marker, Arena, Gc, Owner, Trace};
+
+/// A growable vector with its elements stored in a separate GC allocated buffer, the spine.
+///
+/// The spine has a fixed capacity. When a push exceeds it a new, larger, spine is allocated, the
+/// elements are moved over and the old spine is left for the collector to free. All write
+/// barriers for the spine are handled by the vector itself.
+///
+/// When the vector is a field of a GC allocated object it should be accessed through
+/// [`Gc::borrow_mut`] which applies the barrier for the containing object. A vector allocated as
+/// an object of its own can be modified directly through the pointer with [`Gc::push`] and
+/// friends, which apply all barriers themselves.
+///
+/// A copy of the vector would share its spine, so the vector doesn't implement
+/// [`Trace::snapshot`] and objects containing one can't be mutated during a speculation, see
+/// [`Arena::begin_speculation`].
+pub struct GcVec<'gc, 'own, T> {
+    spine: Option<Gc<'gc, 'own, Vec<T>>>,
+}
+
+unsafe impl<'gc, 'own, T: Trace<'own>> Trace<'own> for GcVec<'gc, 'own, T> {
+    type Gc<'to> = GcVec<'to, 'own, T::Gc<'to>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.spine.trace(marker)
+    }
+}
+
+impl<'gc, 'own, T> Default for GcVec<'gc, 'own, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'gc, 'own, T> GcVec<'gc, 'own, T> {
+    /// Create a new empty vector, no spine is allocated until the first push.
+    pub fn new() -> Self {
+        GcVec { spine: None }
+    }
+
+    fn spine(&self) -> Option<&Vec<T>> {
+        // Safe because the spine is only ever accessed through the vector.
+        self.spine
+            .map(|x| unsafe { &**x.into_gc_box().as_ref().value.get() })
+    }
+
+    fn spine_mut(&mut self) -> Option<&mut Vec<T>> {
+        // Safe because the spine is only ever accessed through the vector.
+        self.spine
+            .map(|x| unsafe { &mut **x.into_gc_box().as_ref().value.get() })
+    }
+
+    /// Returns the elements of the vector.
+    pub fn as_slice(&self) -> &[T] {
+        self.spine().map(|x| x.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns true if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of elements the current spine can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.spine().map(|x| x.capacity()).unwrap_or(0)
+    }
+
+    /// Returns the element at the given index.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// Remove the last element from the vector.
+    ///
+    /// The returned value is bound to the lifetime of the vector, so it can't outlive the
+    /// borrow through which the vector was accessed unless rooted.
+    pub fn pop(&mut self) -> Option<T> {
+        self.spine_mut().and_then(|x| x.pop())
+    }
+
+    /// Remove the element at the given index, shifting all elements after it to the left.
+    ///
+    /// Like [`GcVec::pop`] the returned value is bound to the lifetime of the vector.
+    ///
+    /// # Panic
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        match self.spine_mut() {
+            Some(spine) if index < len => spine.remove(index),
+            _ => panic!("removal index (is {index}) should be < len (is {len})"),
+        }
+    }
+}
+
+impl<'gc, 'own, T: Trace<'own>> GcVec<'gc, 'own, T> {
+    /// Push a value onto the end of the vector, growing the spine if required.
+    pub fn push(&mut self, arena: &'gc Arena<'own>, value: T) {
+        self.reserve_one(arena);
+        self.spine_mut().unwrap().push(value);
+    }
+
+    /// Insert a value at the given index, shifting all elements after it to the right and growing
+    /// the spine if required.
+    ///
+    /// # Panic
+    /// Panics if the index is larger than the length of the vector.
+    pub fn insert(&mut self, arena: &'gc Arena<'own>, index: usize, value: T) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        self.reserve_one(arena);
+        self.spine_mut().unwrap().insert(index, value);
+    }
+
+    /// Make sure the spine can hold one more element and apply the write barrier to it.
+    fn reserve_one(&mut self, arena: &'gc Arena<'own>) {
+        spine::reserve_one(&mut self.spine, arena)
+    }
+}
+
+impl<'gc, 'own, 'v, T: Trace<'own>> Gc<'gc, 'own, GcVec<'v, 'own, T>> {
+    /// Returns the elements of the vector.
+    pub fn as_slice<'a>(self, owner: &'a Owner<'own>) -> &'a [T]
+    where
+        'v: 'a,
+    {
+        self.borrow(owner).as_slice()
+    }
+
+    /// Push a value onto the end of the vector, applying the write barriers to the vector and
+    /// its spine.
+    ///
+    /// The value is bound to the borrow of the owner, like with [`Gc::set`].
+    pub fn push<'a>(self, owner: &'a mut Owner<'own>, arena: &'a Arena<'own>, value: T::Gc<'a>)
+    where
+        T: 'a,
+        T::Gc<'a>: Trace<'own>,
+    {
+        self.record(arena);
+        self.borrow_mut(owner, arena).push(arena, value)
+    }
+
+    /// Insert a value at the given index, applying the write barriers to the vector and its
+    /// spine.
+    ///
+    /// # Panic
+    /// Panics if the index is larger than the length of the vector.
+    pub fn insert<'a>(
+        self,
+        owner: &'a mut Owner<'own>,
+        arena: &'a Arena<'own>,
+        index: usize,
+        value: T::Gc<'a>,
+    ) where
+        T: 'a,
+        T::Gc<'a>: Trace<'own>,
+    {
+        self.record(arena);
+        self.borrow_mut(owner, arena).insert(arena, index, value)
+    }
+
+    /// Remove the last element from the vector.
+    ///
+    /// The returned value is no longer reachable through the vector, so it is bound to the
+    /// borrow of the arena and can't be kept across a collection unless rooted.
+    pub fn pop<'a>(self, owner: &mut Owner<'own>, arena: &'a Arena<'own>) -> Option<T::Gc<'a>>
+    where
+        T: 'a,
+    {
+        owner.bump_epoch();
+        self.record_spine(owner, arena);
+        // Removing an element stores no pointer, so no barrier is required.
+        unsafe { self.value_mut().pop() }
+    }
+
+    /// Remove the element at the given index, shifting all elements after it to the left.
+    ///
+    /// Like [`Gc::pop`] the returned value is bound to the borrow of the arena.
+    ///
+    /// # Panic
+    /// Panics if the index is out of bounds.
+    pub fn remove<'a>(
+        self,
+        owner: &mut Owner<'own>,
+        arena: &'a Arena<'own>,
+        index: usize,
+    ) -> T::Gc<'a>
+    where
+        T: 'a,
+    {
+        owner.bump_epoch();
+        self.record_spine(owner, arena);
+        unsafe { self.value_mut().remove(index) }
+    }
+
+    /// Log the vector for a running speculation, before the barrier of [`Gc::borrow_mut`] tries
+    /// to snapshot it.
+    fn record(self, arena: &Arena<'own>) {
+        arena.record_undo_with(self, |x| GcVec { spine: x.spine });
+    }
+
+    /// Log the spine for a running speculation, as removing elements applies no barrier.
+    fn record_spine(self, owner: &Owner<'own>, arena: &Arena<'own>) {
+        if let Some(spine) = self.borrow(owner).spine {
+            arena.record_undo(spine);
+        }
+    }
+}
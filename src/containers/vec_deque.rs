@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+
+use super::spine;
+use crate::{arena::Marker, Arena, Gc, Owner, Trace};
+
+/// A double-ended queue with its elements stored in a separate GC allocated buffer, the spine.
+///
+/// Grows the same way as [`GcVec`](super::GcVec): when a push exceeds the capacity of the spine
+/// a new, larger, spine is allocated and the elements are moved over. All write barriers for the
+/// spine are handled by the queue itself.
+///
+/// When the queue is a field of a GC allocated object it should be accessed through
+/// [`Gc::borrow_mut`] which applies the barrier for the containing object. A queue allocated as
+/// an object of its own can be modified directly through the pointer with [`Gc::push_back`] and
+/// friends, which apply all barriers themselves.
+///
+/// Like [`GcVec`](super::GcVec) the queue doesn't implement [`Trace::snapshot`], as a copy would
+/// share its spine.
+pub struct GcVecDeque<'gc, 'own, T> {
+    spine: Option<Gc<'gc, 'own, VecDeque<T>>>,
+}
+
+unsafe impl<'gc, 'own, T: Trace<'own>> Trace<'own> for GcVecDeque<'gc, 'own, T> {
+    type Gc<'to> = GcVecDeque<'to, 'own, T::Gc<'to>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.spine.trace(marker)
+    }
+}
+
+impl<'gc, 'own, T> Default for GcVecDeque<'gc, 'own, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'gc, 'own, T> GcVecDeque<'gc, 'own, T> {
+    /// Create a new empty queue, no spine is allocated until the first push.
+    pub fn new() -> Self {
+        GcVecDeque { spine: None }
+    }
+
+    fn spine(&self) -> Option<&VecDeque<T>> {
+        // Safe because the spine is only ever accessed through the queue.
+        self.spine
+            .map(|x| unsafe { &**x.into_gc_box().as_ref().value.get() })
+    }
+
+    fn spine_mut(&mut self) -> Option<&mut VecDeque<T>> {
+        // Safe because the spine is only ever accessed through the queue.
+        self.spine
+            .map(|x| unsafe { &mut **x.into_gc_box().as_ref().value.get() })
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.spine().map(|x| x.len()).unwrap_or(0)
+    }
+
+    /// Returns true if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of elements the current spine can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.spine().map(|x| x.capacity()).unwrap_or(0)
+    }
+
+    /// Returns the element at the given index, counting from the front.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.spine().and_then(|x| x.get(index))
+    }
+
+    /// Returns the first element of the queue.
+    pub fn front(&self) -> Option<&T> {
+        self.spine().and_then(|x| x.front())
+    }
+
+    /// Returns the last element of the queue.
+    pub fn back(&self) -> Option<&T> {
+        self.spine().and_then(|x| x.back())
+    }
+
+    /// Returns an iterator over the elements from front to back.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.spine().into_iter().flat_map(|x| x.iter())
+    }
+
+    /// Remove the first element from the queue.
+    ///
+    /// The returned value is bound to the lifetime of the queue, so it can't outlive the borrow
+    /// through which the queue was accessed unless rooted.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.spine_mut().and_then(|x| x.pop_front())
+    }
+
+    /// Remove the last element from the queue.
+    ///
+    /// The returned value is bound to the lifetime of the queue, so it can't outlive the borrow
+    /// through which the queue was accessed unless rooted.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.spine_mut().and_then(|x| x.pop_back())
+    }
+}
+
+impl<'gc, 'own, T: Trace<'own>> GcVecDeque<'gc, 'own, T> {
+    /// Push a value onto the front of the queue, growing the spine if required.
+    pub fn push_front(&mut self, arena: &'gc Arena<'own>, value: T) {
+        self.reserve_one(arena);
+        self.spine_mut().unwrap().push_front(value);
+    }
+
+    /// Push a value onto the back of the queue, growing the spine if required.
+    pub fn push_back(&mut self, arena: &'gc Arena<'own>, value: T) {
+        self.reserve_one(arena);
+        self.spine_mut().unwrap().push_back(value);
+    }
+
+    /// Make sure the spine can hold one more element and apply the write barrier to it.
+    fn reserve_one(&mut self, arena: &'gc Arena<'own>) {
+        spine::reserve_one(&mut self.spine, arena)
+    }
+}
+
+impl<'gc, 'own, 'v, T: Trace<'own>> Gc<'gc, 'own, GcVecDeque<'v, 'own, T>> {
+    /// Push a value onto the front of the queue, applying the write barriers to the queue and
+    /// its spine.
+    ///
+    /// The value is bound to the borrow of the owner, like with [`Gc::set`].
+    pub fn push_front<'a>(
+        self,
+        owner: &'a mut Owner<'own>,
+        arena: &'a Arena<'own>,
+        value: T::Gc<'a>,
+    ) where
+        T: 'a,
+        T::Gc<'a>: Trace<'own>,
+    {
+        self.record(arena);
+        self.borrow_mut(owner, arena).push_front(arena, value)
+    }
+
+    /// Push a value onto the back of the queue, applying the write barriers to the queue and its
+    /// spine.
+    ///
+    /// The value is bound to the borrow of the owner, like with [`Gc::set`].
+    pub fn push_back<'a>(self, owner: &'a mut Owner<'own>, arena: &'a Arena<'own>, value: T::Gc<'a>)
+    where
+        T: 'a,
+        T::Gc<'a>: Trace<'own>,
+    {
+        self.record(arena);
+        self.borrow_mut(owner, arena).push_back(arena, value)
+    }
+
+    /// Remove the first element from the queue.
+    ///
+    /// The returned value is no longer reachable through the queue, so it is bound to the borrow
+    /// of the arena and can't be kept across a collection unless rooted.
+    pub fn pop_front<'a>(self, owner: &mut Owner<'own>, arena: &'a Arena<'own>) -> Option<T::Gc<'a>>
+    where
+        T: 'a,
+    {
+        owner.bump_epoch();
+        self.record_spine(owner, arena);
+        // Removing an element stores no pointer, so no barrier is required.
+        unsafe { self.value_mut().pop_front() }
+    }
+
+    /// Remove the last element from the queue.
+    ///
+    /// Like [`Gc::pop_front`] the returned value is bound to the borrow of the arena.
+    pub fn pop_back<'a>(self, owner: &mut Owner<'own>, arena: &'a Arena<'own>) -> Option<T::Gc<'a>>
+    where
+        T: 'a,
+    {
+        owner.bump_epoch();
+        self.record_spine(owner, arena);
+        unsafe { self.value_mut().pop_back() }
+    }
+
+    /// Log the queue for a running speculation, before the barrier of [`Gc::borrow_mut`] tries to
+    /// snapshot it.
+    fn record(self, arena: &Arena<'own>) {
+        arena.record_undo_with(self, |x| GcVecDeque { spine: x.spine });
+    }
+
+    /// Log the spine for a running speculation, as removing elements applies no barrier.
+    fn record_spine(self, owner: &Owner<'own>, arena: &Arena<'own>) {
+        if let Some(spine) = self.borrow(owner).spine {
+            arena.record_undo(spine);
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Formatting of GC values, which requires the owner to borrow them.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug, Display, Write},
+    ptr::NonNull,
+};
+
+use crate::{
+    sys::{self, GcBox},
+    Arena, Gc, Owner,
+};
+
+/// A version of [`Debug`] for types containing GC pointers, which can only be followed with the
+/// owner.
+///
+/// # Usage
+/// ```
+/// # use std::fmt;
+/// # use dreck::*;
+/// struct Node<'gc, 'own> {
+///     value: i32,
+///     children: Vec<Gc<'gc, 'own, i32>>,
+/// }
+///
+/// impl<'gc, 'own> DebugWith<'own> for Node<'gc, 'own> {
+///     fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.debug_struct("Node")
+///             .field("value", &self.value)
+///             .field("children", &self.children.with_owner(owner))
+///             .finish()
+///     }
+/// }
+/// # unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+/// #     type Gc<'to> = Node<'to, 'own>;
+/// #     fn needs_trace() -> bool { true }
+/// #     fn trace(&self, marker: Marker<'own, '_>) { self.children.trace(marker) }
+/// # }
+///
+/// dreck!(owner, arena);
+///
+/// let children = vec![arena.add(1), arena.add(2)];
+/// let node = arena.add(Node { value: 0, children });
+/// assert_eq!(
+///     format!("{:?}", node.with_owner(&owner)),
+///     "Node { value: 0, children: [1, 2] }"
+/// );
+/// ```
+pub trait DebugWith<'own> {
+    /// Format the value, using the owner to borrow the values of contained GC pointers.
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Returns an adapter which implements [`Debug`] for the value.
+    fn with_owner<'a>(&'a self, owner: &'a Owner<'own>) -> WithOwner<'a, 'own, Self> {
+        WithOwner { value: self, owner }
+    }
+}
+
+/// Implements [`Debug`] for a value implementing [`DebugWith`], see [`DebugWith::with_owner`].
+pub struct WithOwner<'a, 'own, T: ?Sized> {
+    value: &'a T,
+    owner: &'a Owner<'own>,
+}
+
+impl<'own, T: DebugWith<'own> + ?Sized> Debug for WithOwner<'_, 'own, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_with(self.owner, f)
+    }
+}
+
+thread_local! {
+    /// The objects which are currently being formatted, to detect cycles.
+    static FORMATTING: RefCell<HashSet<usize>> = RefCell::new(HashSet::new());
+}
+
+/// Removes an object from the set of objects being formatted when dropped.
+struct Formatting(usize);
+
+impl Drop for Formatting {
+    fn drop(&mut self) {
+        FORMATTING.with(|x| x.borrow_mut().remove(&self.0));
+    }
+}
+
+/// Formats the value pointed to, or `Gc(<cycle>)` if the pointer is reached again while formatting
+/// its own value.
+impl<'gc, 'own, T: DebugWith<'own>> DebugWith<'own> for Gc<'gc, 'own, T> {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let addr = self.into_gc_box().as_ptr() as usize;
+        if !FORMATTING.with(|x| x.borrow_mut().insert(addr)) {
+            return write!(f, "Gc(<cycle>)");
+        }
+        let _formatting = Formatting(addr);
+        self.borrow(owner).fmt_with(owner, f)
+    }
+}
+
+macro_rules! impl_debug {
+    ($($name:ty),*$(,)*) => {
+        $(
+            impl<'own> DebugWith<'own> for $name {
+                fn fmt_with(&self, _owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    Debug::fmt(self, f)
+                }
+            }
+        )*
+    };
+}
+
+impl_debug!(
+    (),
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    str,
+    String
+);
+
+impl<'own, T: DebugWith<'own>> DebugWith<'own> for Option<T> {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(x) => f.debug_tuple("Some").field(&x.with_owner(owner)).finish(),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+impl<'own, T: DebugWith<'own> + ?Sized> DebugWith<'own> for Box<T> {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt_with(owner, f)
+    }
+}
+
+impl<'own, T: DebugWith<'own>> DebugWith<'own> for [T] {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|x| x.with_owner(owner)))
+            .finish()
+    }
+}
+
+impl<'own, T: DebugWith<'own>> DebugWith<'own> for Vec<T> {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt_with(owner, f)
+    }
+}
+
+impl<'own, T: DebugWith<'own>, const N: usize> DebugWith<'own> for [T; N] {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt_with(owner, f)
+    }
+}
+
+/// Implements [`Debug`] for the value of a GC pointer, see [`Gc::debug`].
+pub struct GcDebug<'a, T>(&'a T);
+
+impl<T: Debug> Debug for GcDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Implements [`Display`] for the value of a GC pointer, see [`Gc::display`].
+pub struct GcDisplay<'a, T>(&'a T);
+
+impl<T: Display> Display for GcDisplay<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'gc, 'own, T> Gc<'gc, 'own, T> {
+    /// Returns an adapter which formats the value with its [`Debug`] implementation.
+    ///
+    /// For values containing GC pointers themselves use [`DebugWith::with_owner`] instead.
+    pub fn debug<'a>(self, owner: &'a Owner<'own>) -> GcDebug<'a, T>
+    where
+        T: Debug,
+    {
+        GcDebug(self.borrow(owner))
+    }
+
+    /// Returns an adapter which formats the value with its [`Display`] implementation.
+    pub fn display<'a>(self, owner: &'a Owner<'own>) -> GcDisplay<'a, T>
+    where
+        T: Display,
+    {
+        GcDisplay(self.borrow(owner))
+    }
+}
+
+/// Options for [`Arena::debug_heap`].
+#[derive(Clone, Copy, Debug)]
+pub struct DebugOptions<'a> {
+    /// The depth below the roots after which objects are no longer written. Roots have depth 0.
+    pub max_depth: usize,
+    /// Only write objects whose type name contains one of these strings, all objects are written
+    /// if empty. Objects which are left out are still followed.
+    pub only_types: &'a [&'a str],
+    /// The number of objects after which the output is truncated.
+    pub max_nodes: usize,
+}
+
+impl Default for DebugOptions<'_> {
+    fn default() -> Self {
+        DebugOptions {
+            max_depth: 16,
+            only_types: &[],
+            max_nodes: 1000,
+        }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Render all objects reachable from the roots as an indented tree, for inspecting the heap
+    /// during development.
+    ///
+    /// Uses the same format as [`dump_structure`](crate::testing::dump_structure): every object is
+    /// written as `#id type`, followed by `= value` for leaves, and objects which were already
+    /// written are referred to as `*id`. Children left out because of the depth limit are written
+    /// as `...`.
+    pub fn debug_heap(&self, owner: &Owner<'own>, options: DebugOptions) -> String {
+        // The owner is only required to ensure no value is mutably borrowed while formatting.
+        let _owner = owner;
+        let mut ids = HashMap::<NonNull<GcBox<()>>, usize>::new();
+        let mut out = String::new();
+        let mut written = 0;
+
+        // Roots are returned most recent first, so the oldest root ends up on top of the stack.
+        let mut stack = self
+            .unsafe_arena()
+            .roots()
+            .into_iter()
+            .map(|x| (x, 0))
+            .collect::<Vec<_>>();
+        let children = RefCell::new(Vec::new());
+        while let Some((ptr, depth)) = stack.pop() {
+            if written == options.max_nodes {
+                out.push_str("... truncated\n");
+                break;
+            }
+
+            let v_table = unsafe { ptr.as_ref().data_ptr.v_table() };
+            let type_name = (v_table.type_name)();
+            let shown = options.only_types.is_empty()
+                || options.only_types.iter().any(|x| type_name.contains(x));
+            let indent = "  ".repeat(depth);
+
+            if let Some(id) = ids.get(&ptr) {
+                if shown {
+                    let _ = writeln!(out, "{}*{}", indent, id);
+                    written += 1;
+                }
+                continue;
+            }
+            let id = ids.len();
+            ids.insert(ptr, id);
+
+            unsafe { sys::visit_children(ptr, &|child| children.borrow_mut().push(child)) };
+            let mut children = children.borrow_mut();
+
+            let depth = if shown {
+                let _ = write!(out, "{}#{} {}", indent, id, type_name);
+                let mut leaf = String::new();
+                if let Some(Ok(())) = unsafe { (v_table.fmt_leaf)(ptr.as_ptr(), &mut leaf) } {
+                    let _ = write!(out, " = {}", leaf);
+                }
+                out.push('\n');
+                written += 1;
+                depth + 1
+            } else {
+                depth
+            };
+
+            if depth > options.max_depth {
+                if !children.is_empty() {
+                    let _ = writeln!(out, "{}...", "  ".repeat(depth));
+                }
+                children.clear();
+                continue;
+            }
+            stack.extend(children.drain(..).rev().map(|x| (x, depth)));
+        }
+        out
+    }
+}
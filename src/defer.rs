@@ -0,0 +1,170 @@
+//! Work deferred by trace implementations to after a collection cycle, and by host code to the
+//! next safepoint.
+
+use std::{ptr::NonNull, rc::Rc};
+
+use crate::{
+    sys::{DeferredOp, GcBox, HostOp, HostOpQueue, UnsafeArena},
+    Arena, Gc, Invariant, Owner, Trace,
+};
+
+/// A [`DeferredOp`] which might borrow data for `'own`.
+type LocalDeferredOp<'own> = Box<dyn FnOnce(&UnsafeArena, NonNull<()>, NonNull<GcBox<()>>) + 'own>;
+
+/// A [`HostOp`] which uses `'own`.
+type LocalHostOp<'own> =
+    Box<dyn FnOnce(&UnsafeArena, NonNull<()>, Option<NonNull<GcBox<()>>>) + 'own>;
+
+/// An operation a trace implementation defers to after the collection cycle, passed to
+/// [`Marker::defer`](crate::Marker::defer).
+///
+/// Trace implementations can't allocate or otherwise use the arena, work like lazily
+/// materializing the children of a proxy object has to be deferred instead. The operation is run
+/// by [`Arena::run_deferred`] once the cycle has completed, with mutable access to the heap.
+///
+/// The token holds a pointer to an object which is kept alive until the operation has run and
+/// which is passed to the operation. The write barrier is applied to the object after the
+/// operation has run, so it is free to store new pointers in it.
+pub struct DeferToken<'own> {
+    ptr: NonNull<GcBox<()>>,
+    op: DeferredOp,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> DeferToken<'own> {
+    /// Create a token running the function with the object of the pointer.
+    pub fn new<T, F>(ptr: Gc<'_, 'own, T>, f: F) -> Self
+    where
+        T: Trace<'own>,
+        F: for<'gc> FnOnce(&mut Owner<'own>, &'gc Arena<'own>, Gc<'gc, 'own, T::Gc<'gc>>) + 'own,
+    {
+        let op: LocalDeferredOp<'own> = Box::new(move |arena, owner, ptr| unsafe {
+            // Safe because deferred operations are only run from `Arena::run_deferred` which
+            // passes the owner it mutably borrows for the duration of the operations.
+            let owner = owner.cast::<Owner<'own>>().as_mut();
+            let arena = Arena::from_unsafe_ref(arena);
+            let gc = Gc::<T>::from_gc_box(ptr.cast());
+            f(owner, arena, gc.rebind());
+            arena.write_barrier(gc);
+        });
+        // Safe because the operation is owned by the arena which can't outlive `'own`.
+        let op = unsafe { std::mem::transmute::<LocalDeferredOp<'own>, DeferredOp>(op) };
+        DeferToken {
+            ptr: ptr.into_gc_box().cast(),
+            op,
+            _invariant: Invariant::new(),
+        }
+    }
+
+    pub(crate) fn into_raw(self) -> (NonNull<GcBox<()>>, DeferredOp) {
+        (self.ptr, self.op)
+    }
+}
+
+/// A handle to the queue of operations deferred by host code, created by
+/// [`Arena::deferred_ops`].
+///
+/// Host objects like RAII guards often want to change GC state when they are dropped, but might
+/// not have access to the owner or the arena at that point. The handle is cheap to clone and
+/// doesn't borrow the arena, so it can be stored in such objects and used to queue operations
+/// which are run by the next [`Arena::flush_deferred`] or [`Arena::safepoint`].
+///
+/// Operations can't capture GC pointers directly, a pointer passed to
+/// [`DeferredOps::defer_with`] is kept alive by the queue instead and handed to the operation
+/// when it runs. Operations queued after the arena was dropped are dropped without being run.
+#[derive(Clone)]
+pub struct DeferredOps<'own> {
+    queue: Rc<HostOpQueue>,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> DeferredOps<'own> {
+    /// Queue an operation.
+    pub fn defer<F>(&self, op: F)
+    where
+        F: FnOnce(&mut Owner<'own>, &Arena<'own>) + 'own,
+    {
+        let op: LocalHostOp<'own> = Box::new(move |arena, owner, _| unsafe {
+            // Safe because queued operations are only run from `Arena::flush_deferred` which
+            // passes the owner it mutably borrows for the duration of the operations.
+            let owner = owner.cast::<Owner<'own>>().as_mut();
+            op(owner, Arena::from_unsafe_ref(arena))
+        });
+        unsafe { self.queue.push(None, Self::erase(op)) }
+    }
+
+    /// Queue an operation which is called with the object of the pointer, the object is kept
+    /// alive until the operation has run.
+    ///
+    /// The write barrier is applied to the object after the operation has run, so it is free to
+    /// store new pointers in it.
+    pub fn defer_with<T, F>(&self, ptr: Gc<'_, 'own, T>, op: F)
+    where
+        T: Trace<'own>,
+        F: for<'gc> FnOnce(&mut Owner<'own>, &'gc Arena<'own>, Gc<'gc, 'own, T::Gc<'gc>>) + 'own,
+    {
+        let op: LocalHostOp<'own> = Box::new(move |arena, owner, ptr| unsafe {
+            // Safe because queued operations are only run from `Arena::flush_deferred` which
+            // passes the owner it mutably borrows for the duration of the operations.
+            let owner = owner.cast::<Owner<'own>>().as_mut();
+            let arena = Arena::from_unsafe_ref(arena);
+            let gc = Gc::<T>::from_gc_box(ptr.unwrap_unchecked().cast());
+            op(owner, arena, gc.rebind());
+            arena.write_barrier(gc);
+        });
+        unsafe {
+            self.queue
+                .push(Some(ptr.into_gc_box().cast()), Self::erase(op))
+        }
+    }
+
+    /// Returns the number of operations which have not yet run.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no operations are queued.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn erase(op: LocalHostOp<'own>) -> HostOp {
+        // Safe because operations are only run by the arena, which can't outlive `'own`.
+        unsafe { std::mem::transmute::<LocalHostOp<'own>, HostOp>(op) }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Returns a handle to the queue of operations deferred by host code.
+    pub fn deferred_ops(&self) -> DeferredOps<'own> {
+        DeferredOps {
+            queue: self.unsafe_arena().host_ops().clone(),
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Run the operations queued with [`DeferredOps`] in the order they were queued, returning
+    /// the number of operations run.
+    ///
+    /// Operations queued while running an operation are run as well. If an operation panics the
+    /// remaining operations stay queued.
+    ///
+    /// # Panic
+    /// Panics if called from a trace implementation or while the arena is being dropped.
+    pub fn flush_deferred(&self, owner: &mut Owner<'own>) -> usize {
+        unsafe {
+            self.unsafe_arena()
+                .flush_host_ops(NonNull::from(owner).cast())
+        }
+    }
+
+    /// A point where the host is not borrowing any GC objects: runs the operations queued with
+    /// [`DeferredOps`] and then does some collection work like [`Arena::collect`].
+    ///
+    /// Returns the number of queued operations run.
+    pub fn safepoint(&mut self, owner: &mut Owner<'own>) -> usize {
+        let ran = self.flush_deferred(owner);
+        self.collect(owner);
+        ran
+    }
+}
@@ -0,0 +1,503 @@
+//! A small expression language whose environments, closures and lists live in an arena.
+//!
+//! This module serves as an end-to-end test of the public API of this crate and as an example of
+//! how a language runtime can be built on top of it. Values are only ever held by the evaluator
+//! while the arena is borrowed, so the arena can only be collected between evaluations, when all
+//! live values are reachable from the rooted [`Globals`].
+//!
+//! # Usage
+//! ```
+//! # use std::pin::pin;
+//! # use dreck::{*, demo::{self, Globals, Tracker}};
+//! dreck!(owner, arena);
+//!
+//! let tracker = Tracker::default();
+//! let guard = pin!(RootGuard::new());
+//! let globals = root!(&arena, guard, Globals::new(&arena, &tracker));
+//!
+//! demo::run(&mut owner, &arena, globals, "def square = fn(x) => x * x").unwrap();
+//! let value = demo::run(&mut owner, &arena, globals, "[square(2), square(3)]").unwrap();
+//! assert_eq!(value.display(&owner), "[4, 9]");
+//!
+//! arena.collect_full(&owner);
+//! ```
+
+use std::{cell::Cell, fmt, rc::Rc};
+
+use crate::{containers::GcVec, Arena, Gc, Marker, Owner, Trace};
+
+mod parse;
+pub use parse::{parse, BinOp, Expr, Stmt};
+
+/// The maximum depth of nested function calls.
+const MAX_DEPTH: usize = 256;
+
+/// An error produced while parsing or evaluating a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Parse(String),
+    UnknownVariable(String),
+    Type(String),
+    DivideByZero,
+    StackOverflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(x) => write!(f, "parse error: {}", x),
+            Error::UnknownVariable(x) => write!(f, "unknown variable `{}`", x),
+            Error::Type(x) => write!(f, "type error: {}", x),
+            Error::DivideByZero => write!(f, "divide by zero"),
+            Error::StackOverflow => write!(f, "stack overflow"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Counts the number of objects allocated by the interpreter which are still alive.
+#[derive(Clone, Default)]
+pub struct Tracker(Rc<Cell<usize>>);
+
+impl Tracker {
+    /// Returns the number of tracked objects which have not yet been dropped.
+    pub fn live(&self) -> usize {
+        self.0.get()
+    }
+
+    fn track(&self) -> Tracked {
+        self.0.set(self.0.get() + 1);
+        Tracked(self.0.clone())
+    }
+}
+
+struct Tracked(Rc<Cell<usize>>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+/// A built-in function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Builtin {
+    /// `len(list)`, the length of a list.
+    Len,
+    /// `push(list, value)`, append a value to a list, returning the list.
+    Push,
+    /// `get(list, index)`, the value at the index of a list or nil if out of bounds.
+    Get,
+}
+
+impl Builtin {
+    fn lookup(name: &str) -> Option<Self> {
+        match name {
+            "len" => Some(Builtin::Len),
+            "push" => Some(Builtin::Push),
+            "get" => Some(Builtin::Get),
+            _ => None,
+        }
+    }
+}
+
+/// A value of the language.
+pub enum Value<'gc, 'own> {
+    Nil,
+    Int(i64),
+    Bool(bool),
+    Builtin(Builtin),
+    Closure(Gc<'gc, 'own, Closure<'gc, 'own>>),
+    List(Gc<'gc, 'own, List<'gc, 'own>>),
+}
+
+impl<'gc, 'own> Clone for Value<'gc, 'own> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'gc, 'own> Copy for Value<'gc, 'own> {}
+
+unsafe impl<'gc, 'own> Trace<'own> for Value<'gc, 'own> {
+    type Gc<'to> = Value<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        match *self {
+            Value::Closure(x) => marker.mark(x),
+            Value::List(x) => marker.mark(x),
+            _ => {}
+        }
+    }
+}
+
+impl<'gc, 'own> Value<'gc, 'own> {
+    fn type_name(self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Int(_) => "int",
+            Value::Bool(_) => "bool",
+            Value::Builtin(_) | Value::Closure(_) => "function",
+            Value::List(_) => "list",
+        }
+    }
+
+    /// Render the value as text.
+    pub fn display(self, owner: &Owner<'own>) -> String {
+        match self {
+            Value::Nil => "nil".to_string(),
+            Value::Int(x) => x.to_string(),
+            Value::Bool(x) => x.to_string(),
+            Value::Builtin(x) => format!("<builtin {:?}>", x),
+            Value::Closure(_) => "<fn>".to_string(),
+            Value::List(x) => {
+                let items = x
+                    .borrow(owner)
+                    .items
+                    .as_slice()
+                    .iter()
+                    .map(|x| x.display(owner))
+                    .collect::<Vec<_>>();
+                format!("[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+/// A single binding in an environment.
+pub struct Env<'gc, 'own> {
+    name: Rc<str>,
+    value: Value<'gc, 'own>,
+    parent: Option<Gc<'gc, 'own, Env<'gc, 'own>>>,
+    _tracked: Tracked,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Env<'gc, 'own> {
+    type Gc<'to> = Env<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.value.trace(marker);
+        self.parent.trace(marker);
+    }
+}
+
+/// A function together with the environment it was created in.
+pub struct Closure<'gc, 'own> {
+    params: Rc<[Rc<str>]>,
+    body: Rc<Expr>,
+    env: Option<Gc<'gc, 'own, Env<'gc, 'own>>>,
+    _tracked: Tracked,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Closure<'gc, 'own> {
+    type Gc<'to> = Closure<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.env.trace(marker)
+    }
+}
+
+/// A growable list of values.
+pub struct List<'gc, 'own> {
+    items: GcVec<'gc, 'own, Value<'gc, 'own>>,
+    _tracked: Tracked,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for List<'gc, 'own> {
+    type Gc<'to> = List<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.items.trace(marker)
+    }
+}
+
+/// The global definitions of a program, should be rooted between calls to [`run`].
+pub struct Globals<'gc, 'own> {
+    env: Option<Gc<'gc, 'own, Env<'gc, 'own>>>,
+    tracker: Tracker,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Globals<'gc, 'own> {
+    type Gc<'to> = Globals<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.env.trace(marker)
+    }
+}
+
+impl<'gc, 'own> Globals<'gc, 'own> {
+    /// Allocate an empty set of globals, all objects created by [`run`] are counted by the
+    /// tracker.
+    pub fn new(arena: &'gc Arena<'own>, tracker: &Tracker) -> Gc<'gc, 'own, Self> {
+        arena.add(Globals {
+            env: None,
+            tracker: tracker.clone(),
+        })
+    }
+}
+
+/// Parse and evaluate a program, returning the value of the last statement.
+///
+/// Definitions are added to the globals and are visible to later calls.
+pub fn run<'gc, 'own>(
+    owner: &mut Owner<'own>,
+    arena: &'gc Arena<'own>,
+    globals: Gc<'_, 'own, Globals<'_, 'own>>,
+    src: &str,
+) -> Result<Value<'gc, 'own>, Error> {
+    let stmts = parse(src)?;
+    // No collection can happen while the arena is borrowed, so the globals can be bound to it.
+    let globals = arena.rebind_to(globals);
+    let tracker = globals.borrow(owner).tracker.clone();
+    let mut eval = Eval {
+        owner,
+        arena,
+        tracker,
+        depth: 0,
+    };
+
+    let mut res = Value::Nil;
+    for stmt in stmts {
+        res = match stmt {
+            Stmt::Def(name, expr) => {
+                let env = globals.borrow(eval.owner).env;
+                let env = eval.bind(name, env);
+                globals.borrow_mut(eval.owner, arena).env = Some(arena.rebind_to(env));
+                let value = eval.eval(&expr, Some(env))?;
+                env.borrow_mut(eval.owner, arena).value = arena.rebind_to(value);
+                Value::Nil
+            }
+            Stmt::Expr(expr) => {
+                let env = globals.borrow(eval.owner).env;
+                eval.eval(&expr, env)?
+            }
+        };
+    }
+    Ok(res)
+}
+
+struct Eval<'a, 'gc, 'own> {
+    owner: &'a mut Owner<'own>,
+    arena: &'gc Arena<'own>,
+    tracker: Tracker,
+    depth: usize,
+}
+
+type EnvPtr<'gc, 'own> = Option<Gc<'gc, 'own, Env<'gc, 'own>>>;
+
+impl<'a, 'gc, 'own> Eval<'a, 'gc, 'own> {
+    /// Create a new binding with a nil value.
+    fn bind(&self, name: Rc<str>, parent: EnvPtr<'gc, 'own>) -> Gc<'gc, 'own, Env<'gc, 'own>> {
+        self.arena.add(Env {
+            name,
+            value: Value::Nil,
+            parent,
+            _tracked: self.tracker.track(),
+        })
+    }
+
+    fn lookup(&self, name: &str, mut env: EnvPtr<'gc, 'own>) -> Result<Value<'gc, 'own>, Error> {
+        while let Some(x) = env {
+            let binding = x.borrow(self.owner);
+            if &*binding.name == name {
+                return Ok(binding.value);
+            }
+            env = binding.parent;
+        }
+        Builtin::lookup(name)
+            .map(Value::Builtin)
+            .ok_or_else(|| Error::UnknownVariable(name.to_string()))
+    }
+
+    fn eval(&mut self, expr: &Expr, env: EnvPtr<'gc, 'own>) -> Result<Value<'gc, 'own>, Error> {
+        match expr {
+            Expr::Int(x) => Ok(Value::Int(*x)),
+            Expr::Bool(x) => Ok(Value::Bool(*x)),
+            Expr::Nil => Ok(Value::Nil),
+            Expr::Var(name) => self.lookup(name, env),
+            Expr::List(items) => {
+                let list = self.arena.add(List {
+                    items: GcVec::new(),
+                    _tracked: self.tracker.track(),
+                });
+                for item in items {
+                    let value = self.eval(item, env)?;
+                    self.push(list, value);
+                }
+                Ok(Value::List(list))
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(lhs, env)?;
+                let rhs = self.eval(rhs, env)?;
+                binary(*op, lhs, rhs)
+            }
+            Expr::If(cond, then, otherwise) => match self.eval(cond, env)? {
+                Value::Bool(true) => self.eval(then, env),
+                Value::Bool(false) => self.eval(otherwise, env),
+                x => Err(Error::Type(format!(
+                    "expected bool found {}",
+                    x.type_name()
+                ))),
+            },
+            Expr::Let(name, value, body) => {
+                let binding = self.bind(name.clone(), env);
+                let value = self.eval(value, Some(binding))?;
+                binding.borrow_mut(self.owner, self.arena).value = self.arena.rebind_to(value);
+                self.eval(body, Some(binding))
+            }
+            Expr::Fn(params, body) => Ok(Value::Closure(self.arena.add(Closure {
+                params: params.clone(),
+                body: body.clone(),
+                env,
+                _tracked: self.tracker.track(),
+            }))),
+            Expr::Call(callee, args) => {
+                let callee = self.eval(callee, env)?;
+                let args = args
+                    .iter()
+                    .map(|x| self.eval(x, env))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(callee, &args)
+            }
+        }
+    }
+
+    fn push(&mut self, list: Gc<'gc, 'own, List<'gc, 'own>>, value: Value<'gc, 'own>) {
+        let arena = self.arena;
+        list.borrow_mut(self.owner, arena)
+            .items
+            .push(arena, arena.rebind_to(value));
+    }
+
+    fn call(
+        &mut self,
+        callee: Value<'gc, 'own>,
+        args: &[Value<'gc, 'own>],
+    ) -> Result<Value<'gc, 'own>, Error> {
+        let closure = match callee {
+            Value::Closure(x) => x,
+            Value::Builtin(x) => return self.builtin(x, args),
+            x => {
+                return Err(Error::Type(format!(
+                    "expected function found {}",
+                    x.type_name()
+                )))
+            }
+        };
+        let (params, body, mut env) = {
+            let closure = closure.borrow(self.owner);
+            (closure.params.clone(), closure.body.clone(), closure.env)
+        };
+        if params.len() != args.len() {
+            return Err(Error::Type(format!(
+                "expected {} arguments found {}",
+                params.len(),
+                args.len()
+            )));
+        }
+        for (name, value) in params.iter().zip(args.iter().copied()) {
+            let binding = self.bind(name.clone(), env);
+            binding.borrow_mut(self.owner, self.arena).value = self.arena.rebind_to(value);
+            env = Some(binding);
+        }
+
+        if self.depth == MAX_DEPTH {
+            return Err(Error::StackOverflow);
+        }
+        self.depth += 1;
+        let res = self.eval(&body, env);
+        self.depth -= 1;
+        res
+    }
+
+    fn builtin(
+        &mut self,
+        builtin: Builtin,
+        args: &[Value<'gc, 'own>],
+    ) -> Result<Value<'gc, 'own>, Error> {
+        match (builtin, args) {
+            (Builtin::Len, [Value::List(list)]) => {
+                Ok(Value::Int(list.borrow(self.owner).items.len() as i64))
+            }
+            (Builtin::Push, [Value::List(list), value]) => {
+                self.push(*list, *value);
+                Ok(Value::List(*list))
+            }
+            (Builtin::Get, [Value::List(list), Value::Int(idx)]) => {
+                let items = &list.borrow(self.owner).items;
+                Ok(usize::try_from(*idx)
+                    .ok()
+                    .and_then(|x| items.get(x).copied())
+                    .unwrap_or(Value::Nil))
+            }
+            _ => Err(Error::Type(format!(
+                "invalid arguments for builtin {:?}",
+                builtin
+            ))),
+        }
+    }
+}
+
+fn binary<'gc, 'own>(
+    op: BinOp,
+    lhs: Value<'gc, 'own>,
+    rhs: Value<'gc, 'own>,
+) -> Result<Value<'gc, 'own>, Error> {
+    let (lhs, rhs) = match (op, lhs, rhs) {
+        (BinOp::Equal, Value::Bool(a), Value::Bool(b)) => return Ok(Value::Bool(a == b)),
+        (BinOp::Equal, Value::Nil, Value::Nil) => return Ok(Value::Bool(true)),
+        (_, Value::Int(a), Value::Int(b)) => (a, b),
+        (_, a, b) => {
+            return Err(Error::Type(format!(
+                "invalid operands {} and {} for {:?}",
+                a.type_name(),
+                b.type_name(),
+                op
+            )))
+        }
+    };
+    Ok(match op {
+        BinOp::Add => Value::Int(lhs.wrapping_add(rhs)),
+        BinOp::Sub => Value::Int(lhs.wrapping_sub(rhs)),
+        BinOp::Mul => Value::Int(lhs.wrapping_mul(rhs)),
+        BinOp::Div if rhs == 0 => return Err(Error::DivideByZero),
+        BinOp::Div => Value::Int(lhs.wrapping_div(rhs)),
+        BinOp::Less => Value::Bool(lhs < rhs),
+        BinOp::Equal => Value::Bool(lhs == rhs),
+    })
+}
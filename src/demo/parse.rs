@@ -0,0 +1,285 @@
+//! The syntax of the demo language and its parser.
+
+use std::rc::Rc;
+
+use super::Error;
+
+/// A binary operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Less,
+    Equal,
+}
+
+/// An expression.
+#[derive(Debug)]
+pub enum Expr {
+    Int(i64),
+    Bool(bool),
+    Nil,
+    Var(Rc<str>),
+    List(Vec<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// A binding which is visible in both the value and the body, allowing recursive functions.
+    Let(Rc<str>, Box<Expr>, Box<Expr>),
+    Fn(Rc<[Rc<str>]>, Rc<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+}
+
+/// A top level statement.
+#[derive(Debug)]
+pub enum Stmt {
+    /// Define a global, visible in its own value and all following statements.
+    Def(Rc<str>, Expr),
+    Expr(Expr),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Int(i64),
+    Ident(Rc<str>),
+    Sym(&'static str),
+}
+
+const SYMBOLS: [&str; 14] = [
+    "=>", "==", "(", ")", "[", "]", ",", ";", "=", "+", "-", "*", "/", "<",
+];
+
+const KEYWORDS: [&str; 10] = [
+    "def", "let", "in", "if", "then", "else", "fn", "true", "false", "nil",
+];
+
+fn lex(src: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = src.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c == '#' {
+            rest = rest.split_once('\n').map(|x| x.1).unwrap_or("");
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let value = rest[..end]
+                .parse()
+                .map_err(|_| Error::Parse(format!("integer `{}` is too large", &rest[..end])))?;
+            tokens.push(Token::Int(value));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].into()));
+            rest = &rest[end..];
+        } else if let Some(sym) = SYMBOLS.iter().find(|x| rest.starts_with(**x)) {
+            tokens.push(Token::Sym(sym));
+            rest = &rest[sym.len()..];
+        } else {
+            return Err(Error::Parse(format!("unexpected character `{}`", c)));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Parse a program, a list of statements separated by `;`.
+pub fn parse(src: &str) -> Result<Vec<Stmt>, Error> {
+    let mut parser = Parser {
+        tokens: lex(src)?,
+        pos: 0,
+    };
+    let mut stmts = Vec::new();
+    while parser.peek().is_some() {
+        stmts.push(parser.stmt()?);
+        if parser.peek().is_some() {
+            parser.expect(";")?;
+        }
+    }
+    Ok(stmts)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| Error::Parse("unexpected end of input".to_string()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is(&self, s: &str) -> bool {
+        match self.peek() {
+            Some(Token::Sym(x)) => *x == s,
+            Some(Token::Ident(x)) => &**x == s,
+            _ => false,
+        }
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let res = self.is(s);
+        if res {
+            self.pos += 1;
+        }
+        res
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), Error> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(Error::Parse(format!(
+                "expected `{}` found {:?}",
+                s,
+                self.peek()
+            )))
+        }
+    }
+
+    fn ident(&mut self) -> Result<Rc<str>, Error> {
+        match self.next()? {
+            Token::Ident(x) if !KEYWORDS.contains(&&*x) => Ok(x),
+            x => Err(Error::Parse(format!("expected identifier found {:?}", x))),
+        }
+    }
+
+    /// Parse a comma separated list of items until the closing symbol.
+    fn list<T>(
+        &mut self,
+        close: &str,
+        mut f: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(f(self)?);
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, Error> {
+        if self.eat("def") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            Ok(Stmt::Def(name, self.expr()?))
+        } else {
+            Ok(Stmt::Expr(self.expr()?))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        if self.eat("let") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            let value = self.expr()?;
+            self.expect("in")?;
+            Ok(Expr::Let(name, Box::new(value), Box::new(self.expr()?)))
+        } else if self.eat("if") {
+            let cond = self.expr()?;
+            self.expect("then")?;
+            let then = self.expr()?;
+            self.expect("else")?;
+            Ok(Expr::If(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(self.expr()?),
+            ))
+        } else if self.eat("fn") {
+            self.expect("(")?;
+            let params = self.list(")", Self::ident)?;
+            self.expect("=>")?;
+            Ok(Expr::Fn(params.into(), Rc::new(self.expr()?)))
+        } else {
+            self.compare()
+        }
+    }
+
+    fn compare(&mut self) -> Result<Expr, Error> {
+        let lhs = self.sum()?;
+        let op = if self.eat("<") {
+            BinOp::Less
+        } else if self.eat("==") {
+            BinOp::Equal
+        } else {
+            return Ok(lhs);
+        };
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = if self.eat("+") {
+                BinOp::Add
+            } else if self.eat("-") {
+                BinOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.call()?;
+        loop {
+            let op = if self.eat("*") {
+                BinOp::Mul
+            } else if self.eat("/") {
+                BinOp::Div
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.call()?));
+        }
+    }
+
+    fn call(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.atom()?;
+        while self.eat("(") {
+            let args = self.list(")", Self::expr)?;
+            expr = Expr::Call(Box::new(expr), args);
+        }
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr, Error> {
+        if self.eat("(") {
+            let expr = self.expr()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        if self.eat("[") {
+            return Ok(Expr::List(self.list("]", Self::expr)?));
+        }
+        match self.next()? {
+            Token::Int(x) => Ok(Expr::Int(x)),
+            Token::Ident(x) => match &*x {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                "nil" => Ok(Expr::Nil),
+                x if KEYWORDS.contains(&x) => {
+                    Err(Error::Parse(format!("unexpected keyword `{}`", x)))
+                }
+                _ => Ok(Expr::Var(x)),
+            },
+            x => Err(Error::Parse(format!("unexpected {:?}", x))),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Capturing GC values for error reports.
+
+use std::{
+    any::Any,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{Arena, GcAny, Owner};
+
+/// A value captured by [`Arena::capture_diagnostic`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticValue {
+    /// The name of the type of the value.
+    pub type_name: &'static str,
+    /// The value as rendered when it was captured.
+    pub rendered: String,
+}
+
+/// Rendered GC values which don't refer to the arena, created by [`Arena::capture_diagnostic`].
+///
+/// The snapshot contains no GC pointers so it can outlive the arena, for example as the payload
+/// of a panic.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiagnosticSnapshot {
+    values: Vec<DiagnosticValue>,
+}
+
+impl DiagnosticSnapshot {
+    /// Returns the captured values in the order they were given.
+    pub fn values(&self) -> &[DiagnosticValue] {
+        &self.values
+    }
+}
+
+impl fmt::Display for DiagnosticSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for value in self.values.iter() {
+            writeln!(f, "{}: {}", value.type_name, value.rendered)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Render GC values into a snapshot which doesn't refer to the arena.
+    ///
+    /// Every value is rendered by the given function, which can use [`GcAny::downcast`] to access
+    /// the value and leave out anything which shouldn't end up in an error report.
+    pub fn capture_diagnostic<F>(
+        &self,
+        owner: &Owner<'own>,
+        values: &[GcAny<'_, 'own>],
+        redactor: F,
+    ) -> DiagnosticSnapshot
+    where
+        F: Fn(GcAny<'_, 'own>, &Owner<'own>) -> String,
+    {
+        DiagnosticSnapshot {
+            values: values
+                .iter()
+                .map(|value| DiagnosticValue {
+                    type_name: value.type_name(),
+                    rendered: redactor(*value, owner),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Call a function with the arena, catching a panic like [`std::panic::catch_unwind`].
+///
+/// Pointers allocated within the function can't be returned from it and the panic payload must be
+/// `'static`, so no GC values escape the unwind except as rendered values, like a
+/// [`DiagnosticSnapshot`]. The arena remains usable after a panic, all pointers rooted within the
+/// function are unrooted by the unwind.
+pub fn catch_unwind_with_heap<'own, R, F>(
+    arena: &mut Arena<'own>,
+    owner: &mut Owner<'own>,
+    f: F,
+) -> Result<R, Box<dyn Any + Send>>
+where
+    F: for<'gc> FnOnce(&mut Owner<'own>, &'gc Arena<'own>) -> R,
+{
+    // The arena is left in a consistent state by a panic, values in it might only be logically
+    // inconsistent, which is the responsibility of the caller.
+    panic::catch_unwind(AssertUnwindSafe(|| f(owner, arena)))
+}
@@ -0,0 +1,180 @@
+//! The error type of the library.
+
+use std::fmt;
+
+use crate::{sys::GcUnavailable, StaleHandle};
+
+/// The error returned by the fallible methods of the library.
+///
+/// The methods which panic instead of returning an error panic with the message of the error
+/// they would have returned.
+///
+/// # Remaining panics
+/// Every situation in which the allocating, rooting and borrowing methods panic during normal
+/// operation has a fallible variant: [`Arena::try_add`](crate::Arena::try_add),
+/// [`Arena::try_add_from_iter`](crate::Arena::try_add_from_iter),
+/// [`Arena::try_add_str`](crate::Arena::try_add_str), [`Arena::try_root`](crate::Arena::try_root),
+/// [`Gc::try_borrow_mut`](crate::Gc::try_borrow_mut) and the `try_` methods of
+/// [`GcRefCell`](crate::GcRefCell). Collecting doesn't panic unless a [`Trace`](crate::Trace) or
+/// [`Drop`] implementation does.
+///
+/// The panics which remain are violations of a contract of the API, which a correct program never
+/// reaches:
+/// - Using a pointer with an arena which didn't allocate it, detected with the `arena-id` feature.
+/// - A write barrier missed by unsafe code, detected in debug builds when tracing finishes.
+/// - [`Gc::borrow_mut_untraced`](crate::Gc::borrow_mut_untraced) on a type which needs tracing.
+/// - [`Gc::swap`](crate::Gc::swap) with two pointers to the same object.
+/// - An iterator passed to [`Arena::add_from_iter`](crate::Arena::add_from_iter) which returns
+///   fewer items than its length.
+/// - Starting a speculation while one is running, or calling
+///   [`Arena::for_each_mut`](crate::Arena::for_each_mut) during one.
+/// - Initializing a [`GcOnceCell`](crate::GcOnceCell) from its own initializer.
+/// - Out of bounds indices for containers, like for their `std` counterparts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The allocator failed to allocate an object.
+    AllocationFailed,
+    /// The arena can't currently be used to allocate or root pointers.
+    ArenaUnavailable {
+        /// Why the arena can't be used.
+        reason: GcUnavailable,
+    },
+    /// A script handle can't be resolved, see [`ScriptHandleRegistry`](crate::ScriptHandleRegistry).
+    StaleHandle(StaleHandle),
+    /// A script handle registry already holds as many handles as it can.
+    HandleQuotaExceeded {
+        /// The maximum number of live handles of the registry.
+        quota: usize,
+    },
+    /// A value is already borrowed in a way which conflicts with the requested borrow.
+    BorrowConflict,
+    /// An object was mutated during a speculation but its type doesn't support snapshots, see
+    /// [`Arena::begin_speculation`](crate::Arena::begin_speculation).
+    SnapshotUnsupported {
+        /// The name of the type of the object.
+        type_name: &'static str,
+    },
+    /// A pointer was used with an arena other than the one which allocated it, detected with the
+    /// `arena-id` feature.
+    WrongArena {
+        /// The id of the arena which allocated the object.
+        object_arena: u64,
+        /// The id of the arena the pointer was used with.
+        arena: u64,
+    },
+    /// Allocating an object would grow the arena past its heap limit, see
+    /// [`Arena::set_heap_limit`](crate::Arena::set_heap_limit).
+    LimitExceeded {
+        /// The maximum number of bytes the objects of the arena may occupy.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AllocationFailed => write!(f, "the allocator failed to allocate an object"),
+            Error::ArenaUnavailable { reason } => match reason {
+                GcUnavailable::Dropping => write!(
+                    f,
+                    "cannot allocate or root while {reason}, code which might run during a \
+                     collection can check `Arena::is_usable` first"
+                ),
+                _ => write!(f, "cannot allocate or root while {reason}"),
+            },
+            Error::StaleHandle(reason) => write!(f, "stale script handle, {reason}"),
+            Error::HandleQuotaExceeded { quota } => write!(
+                f,
+                "the registry already holds its quota of {quota} handles, release handles or \
+                 prune the registry first"
+            ),
+            Error::BorrowConflict => write!(
+                f,
+                "the value of the `GcRefCell` is already borrowed, drop the other borrow first"
+            ),
+            Error::SnapshotUnsupported { type_name } => write!(
+                f,
+                "mutated an object of type `{type_name}` during a speculation, but the type \
+                 doesn't support snapshots, implement `Trace::snapshot` for it"
+            ),
+            Error::WrongArena {
+                object_arena,
+                arena,
+            } => write!(
+                f,
+                "object allocated by arena {object_arena} used with arena {arena}, pointers can \
+                 only be used with the arena which allocated them"
+            ),
+            Error::LimitExceeded { limit } => write!(
+                f,
+                "allocating would exceed the heap limit of {limit} bytes, collect garbage or \
+                 raise the limit first"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The error returned by [`Arena::try_add`](crate::Arena::try_add), which gives back the value
+/// which couldn't be allocated.
+pub struct AllocError<T> {
+    error: Error,
+    value: T,
+}
+
+impl<T> AllocError<T> {
+    pub(crate) fn new(error: Error, value: T) -> Self {
+        AllocError { error, value }
+    }
+
+    /// Returns why the value couldn't be allocated.
+    pub fn error(&self) -> Error {
+        self.error
+    }
+
+    /// Returns the value which couldn't be allocated.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Returns why the value couldn't be allocated together with the value.
+    pub fn into_parts(self) -> (Error, T) {
+        (self.error, self.value)
+    }
+}
+
+impl<T> fmt::Debug for AllocError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllocError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for AllocError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<T> std::error::Error for AllocError<T> {}
+
+impl<T> From<AllocError<T>> for Error {
+    fn from(error: AllocError<T>) -> Self {
+        error.error
+    }
+}
+
+impl From<GcUnavailable> for Error {
+    fn from(reason: GcUnavailable) -> Self {
+        Error::ArenaUnavailable { reason }
+    }
+}
+
+impl From<StaleHandle> for Error {
+    fn from(reason: StaleHandle) -> Self {
+        Error::StaleHandle(reason)
+    }
+}
@@ -0,0 +1,61 @@
+//! Cheap exports of the object graph for external analysis.
+
+use std::{cell::RefCell, io};
+
+use crate::{sys, Arena, Gc, Owner};
+
+impl<'gc, 'own, T> Gc<'gc, 'own, T> {
+    /// Returns the allocation sequence number of the object, which identifies the object for as
+    /// long as it is alive.
+    ///
+    /// Sequence numbers are never reused by an arena. They are kept in a table next to the arena
+    /// instead of in the object header so they don't add to the size of every object.
+    pub fn stable_id(self, arena: &Arena<'own>) -> u64 {
+        arena
+            .unsafe_arena()
+            .stable_id(self.into_gc_box().cast())
+            .expect("pointer was not allocated by this arena")
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Write every edge of the object graph to the writer.
+    ///
+    /// Each edge is written as a pair of little endian `u64`s: the [stable id](Gc::stable_id) of
+    /// the object containing the pointer followed by the stable id of the object pointed to.
+    /// Objects which are unreachable but not yet freed are included. Doesn't allocate per object,
+    /// so it can be used on a live arena.
+    pub fn export_edges<W: io::Write>(&self, owner: &Owner<'own>, w: &mut W) -> io::Result<()> {
+        let _owner = owner;
+        let arena = self.unsafe_arena();
+        let children = RefCell::new(Vec::new());
+        let mut res = Ok(());
+
+        unsafe {
+            arena.for_each_object(|ptr| {
+                if res.is_err() {
+                    return;
+                }
+
+                let Some(from) = arena.stable_id(ptr) else {
+                    return;
+                };
+                sys::visit_children(ptr, &|child| children.borrow_mut().push(child));
+
+                for child in children.borrow_mut().drain(..) {
+                    let Some(to) = arena.stable_id(child) else {
+                        continue;
+                    };
+                    res = w
+                        .write_all(&from.to_le_bytes())
+                        .and_then(|_| w.write_all(&to.to_le_bytes()));
+                    if res.is_err() {
+                        return;
+                    }
+                }
+            })
+        }
+
+        res
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{Gc, Marker, Trace};
+
+/// An object safe version of [`Trace`], implemented for every type which implements [`Trace`].
+///
+/// [`Trace`] itself can't be used as a trait object, so traits whose trait objects are stored in
+/// GC objects use this trait as a supertrait instead. See [`gc_trait!`](crate::gc_trait) for
+/// declaring such traits.
+///
+/// # Safety
+/// Implementations must mark every GC pointer contained in the object, like [`Trace::trace`].
+pub unsafe trait GcDyn<'own> {
+    /// Trace the object marking all GC pointers contained in the implementing object.
+    fn trace_dyn(&self, marker: Marker<'own, '_>);
+}
+
+unsafe impl<'own, T: Trace<'own>> GcDyn<'own> for T {
+    fn trace_dyn(&self, marker: Marker<'own, '_>) {
+        self.trace(marker)
+    }
+}
+
+/// Trait objects of traits declared with [`gc_trait!`](crate::gc_trait), the only types a pointer
+/// can be converted into with [`gc_coerce!`](crate::gc_coerce).
+///
+/// Other unsizing coercions, like from an array to a slice, are not allowed as slice and `str`
+/// objects are assumed to never be mutated, see [`Arena::get_static`](crate::Arena::get_static),
+/// while the array they would be created from can be.
+///
+/// # Safety
+/// Must only be implemented for trait objects.
+pub unsafe trait GcTraitObject {}
+
+/// Types which a [`Gc`] contained in a GC object can point to.
+///
+/// Implemented for every type which implements [`Trace`], for slices of such types, for `str`, and
+/// by
+/// [`gc_trait!`](crate::gc_trait) for the trait objects of the declared trait, so
+/// `Gc<'gc, 'own, dyn Trait<'gc, 'own> + 'gc>` implements [`Trace`].
+///
+/// # Safety
+/// `Gc` must be the same type with a different gc lifetime, like [`Trace::Gc`], and `mark` must
+/// mark the pointer.
+pub unsafe trait GcTarget<'own> {
+    /// The type with a different gc lifetime.
+    type Gc<'gc>: ?Sized;
+
+    /// Mark a pointer to a value of this type.
+    fn mark(ptr: Gc<'_, 'own, Self>, marker: Marker<'own, '_>);
+}
+
+unsafe impl<'own, T: Trace<'own>> GcTarget<'own> for T {
+    type Gc<'gc> = T::Gc<'gc>;
+
+    fn mark(ptr: Gc<'_, 'own, Self>, marker: Marker<'own, '_>) {
+        marker.mark(ptr)
+    }
+}
+
+unsafe impl<'own, T: Trace<'own>> GcTarget<'own> for [T] {
+    type Gc<'gc> = [T::Gc<'gc>];
+
+    fn mark(ptr: Gc<'_, 'own, Self>, marker: Marker<'own, '_>) {
+        marker.mark_erased(ptr)
+    }
+}
+
+unsafe impl<'own> GcTarget<'own> for str {
+    type Gc<'gc> = str;
+
+    fn mark(ptr: Gc<'_, 'own, Self>, marker: Marker<'own, '_>) {
+        marker.mark_erased(ptr)
+    }
+}
@@ -0,0 +1,238 @@
+//! Enums of GC pointers packed into a single word, see [`gc_enum!`](crate::gc_enum).
+
+use std::{mem::align_of, num::NonZeroUsize, ptr::NonNull};
+
+use crate::{sys::GcBox, Gc};
+
+/// A GC pointer with a tag stored in the low bits which are always zero because of the alignment
+/// of objects. Used by [`gc_enum!`](crate::gc_enum).
+#[doc(hidden)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaggedPtr(NonNull<GcBox<()>>);
+
+impl TaggedPtr {
+    /// The number of different tags which can be stored.
+    pub const CAPACITY: usize = align_of::<GcBox<()>>();
+
+    /// Tag the pointer.
+    ///
+    /// # Panic
+    /// Panics if the tag doesn't fit.
+    pub fn new<T: ?Sized>(ptr: Gc<'_, '_, T>, tag: usize) -> Self {
+        assert!(tag < Self::CAPACITY, "tag does not fit in a pointer");
+        let ptr = ptr.into_gc_box().cast::<GcBox<()>>();
+        TaggedPtr(ptr.map_addr(|addr| addr | tag))
+    }
+
+    /// Returns the tag.
+    pub fn tag(self) -> usize {
+        self.0.addr().get() & (Self::CAPACITY - 1)
+    }
+
+    /// Returns the pointer without the tag.
+    ///
+    /// # Safety
+    /// The pointer must have been tagged while it pointed to a `T` and the object must still be
+    /// alive for `'gc`.
+    pub unsafe fn get<'gc, 'own, T>(self) -> Gc<'gc, 'own, T> {
+        // Objects are aligned to at least `CAPACITY` so the address without the tag is not null.
+        let ptr = self
+            .0
+            .map_addr(|addr| NonZeroUsize::new_unchecked(addr.get() & !(Self::CAPACITY - 1)));
+        Gc::from_gc_box(ptr.cast())
+    }
+}
+
+/// Declare an enum of GC pointers together with a version of it packed into a single word.
+///
+/// Every variant contains a single GC pointer, declared with the sized type it points to. The enum is
+/// declared as written, with the payload of every variant wrapped in a [`Gc`]. The packed version
+/// stores which variant is active in the low bits of the pointer, which are always zero as
+/// objects are aligned to at least the size of a pointer. So at most 8 variants are supported on
+/// 64 bit platforms and 4 on 32 bit platforms, more variants fail to compile.
+///
+/// Both types implement [`Trace`](crate::Trace), marking the active variant, and can be converted
+/// into each other with [`From`]. Pointers can be converted into both types with [`From`] and
+/// back out of the packed type with [`TryFrom`], so the pointed to types of all variants must be
+/// distinct. Match on a packed value by unpacking it first.
+///
+/// The lifetimes of the enum must be the gc and owner lifetimes, in that order. Like a manual
+/// [`Trace`](crate::Trace) implementation the gc lifetime must only be used for the gc lifetime of
+/// the pointed to types.
+///
+/// # Usage
+/// ```
+/// # use std::pin::pin;
+/// # use dreck::*;
+/// gc_enum! {
+///     /// A value of a dynamically typed language.
+///     pub enum Value<'gc, 'own> {
+///         Str(String),
+///         Int(i64),
+///         List(Vec<PackedValue<'gc, 'own>>),
+///     }
+///
+///     /// A value packed into a single word.
+///     pub struct PackedValue;
+/// }
+///
+/// dreck!(owner, arena);
+///
+/// let str = PackedValue::from(arena.add(String::from("a")));
+/// let int = PackedValue::from(arena.add(1i64));
+/// let list = PackedValue::from(arena.add(vec![str, int]));
+/// assert_eq!(std::mem::size_of::<Option<PackedValue>>(), std::mem::size_of::<usize>());
+///
+/// let guard = pin!(RootGuard::new());
+/// let list = root!(&arena, guard, arena.add(list));
+/// arena.collect_full(&owner);
+///
+/// let Value::List(items) = list.borrow(&owner).unpack() else {
+///     panic!("not a list");
+/// };
+/// match items.borrow(&owner)[0].unpack() {
+///     Value::Str(x) => assert_eq!(x.borrow(&owner), "a"),
+///     _ => panic!("not a string"),
+/// }
+/// ```
+#[macro_export]
+macro_rules! gc_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident<$gc:lifetime, $own:lifetime> {
+            $($(#[$variant_meta:meta])* $variant:ident($ty:ty)),* $(,)?
+        }
+
+        $(#[$packed_meta:meta])*
+        $packed_vis:vis struct $packed:ident;
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $name<$gc, $own> {
+            $($(#[$variant_meta])* $variant($crate::Gc<$gc, $own, $ty>),)*
+        }
+
+        unsafe impl<$gc, $own> $crate::Trace<$own> for $name<$gc, $own> {
+            type Gc<'__to> = $name<'__to, $own>;
+
+            fn needs_trace() -> bool
+            where
+                Self: Sized,
+            {
+                true
+            }
+
+            fn trace(&self, marker: $crate::Marker<$own, '_>) {
+                match *self {
+                    $($name::$variant(x) => marker.mark(x),)*
+                }
+            }
+        }
+
+        $(
+            impl<$gc, $own> ::std::convert::From<$crate::Gc<$gc, $own, $ty>> for $name<$gc, $own> {
+                fn from(value: $crate::Gc<$gc, $own, $ty>) -> Self {
+                    $name::$variant(value)
+                }
+            }
+        )*
+
+        const _: () = assert!(
+            [$(stringify!($variant)),*].len() <= $crate::TaggedPtr::CAPACITY,
+            "too many variants to pack into a pointer"
+        );
+
+        $(#[$packed_meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        $packed_vis struct $packed<$gc, $own> {
+            ptr: $crate::TaggedPtr,
+            _marker: ::std::marker::PhantomData<$name<$gc, $own>>,
+        }
+
+        impl<$gc, $own> $packed<$gc, $own> {
+            /// Pack the value into a single word.
+            pub fn pack(value: $name<$gc, $own>) -> Self {
+                #[allow(non_camel_case_types, dead_code, clippy::enum_variant_names)]
+                enum Tag {
+                    $($variant,)*
+                }
+
+                let ptr = match value {
+                    $($name::$variant(x) => $crate::TaggedPtr::new(x, Tag::$variant as usize),)*
+                };
+                $packed {
+                    ptr,
+                    _marker: ::std::marker::PhantomData,
+                }
+            }
+
+            /// Returns the unpacked value.
+            pub fn unpack(self) -> $name<$gc, $own> {
+                #[allow(non_camel_case_types, dead_code, clippy::enum_variant_names)]
+                enum Tag {
+                    $($variant,)*
+                }
+
+                let tag = self.ptr.tag();
+                $(
+                    if tag == Tag::$variant as usize {
+                        // The pointer was tagged by `pack` with the variant it was created from.
+                        return $name::$variant(unsafe { self.ptr.get() });
+                    }
+                )*
+                unreachable!()
+            }
+        }
+
+        unsafe impl<$gc, $own> $crate::Trace<$own> for $packed<$gc, $own> {
+            type Gc<'__to> = $packed<'__to, $own>;
+
+            fn needs_trace() -> bool
+            where
+                Self: Sized,
+            {
+                true
+            }
+
+            fn trace(&self, marker: $crate::Marker<$own, '_>) {
+                $crate::Trace::trace(&self.unpack(), marker)
+            }
+        }
+
+        impl<$gc, $own> ::std::convert::From<$name<$gc, $own>> for $packed<$gc, $own> {
+            fn from(value: $name<$gc, $own>) -> Self {
+                $packed::pack(value)
+            }
+        }
+
+        impl<$gc, $own> ::std::convert::From<$packed<$gc, $own>> for $name<$gc, $own> {
+            fn from(value: $packed<$gc, $own>) -> Self {
+                value.unpack()
+            }
+        }
+
+        $(
+            impl<$gc, $own> ::std::convert::From<$crate::Gc<$gc, $own, $ty>>
+                for $packed<$gc, $own>
+            {
+                fn from(value: $crate::Gc<$gc, $own, $ty>) -> Self {
+                    $packed::pack($name::$variant(value))
+                }
+            }
+
+            impl<$gc, $own> ::std::convert::TryFrom<$packed<$gc, $own>>
+                for $crate::Gc<$gc, $own, $ty>
+            {
+                type Error = $packed<$gc, $own>;
+
+                fn try_from(value: $packed<$gc, $own>) -> Result<Self, Self::Error> {
+                    match value.unpack() {
+                        $name::$variant(x) => Ok(x),
+                        #[allow(unreachable_patterns)]
+                        _ => Err(value),
+                    }
+                }
+            }
+        )*
+    };
+}
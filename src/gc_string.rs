@@ -0,0 +1,87 @@
+//! Mutable strings.
+
+use crate::{Arena, Gc, Marker, Owner, Trace};
+
+/// A mutable string, created by [`Arena::add_string`].
+///
+/// A pointer to a `String` object. The string contains no GC pointers, so it is mutated with only
+/// the owner and without a write barrier.
+///
+/// The buffer of the string is not allocated in the arena and growing it does not count towards
+/// the size of the arena.
+#[derive(Clone, Copy)]
+pub struct GcString<'gc, 'own>(Gc<'gc, 'own, String>);
+
+impl<'gc, 'own> GcString<'gc, 'own> {
+    /// Returns the contents of the string.
+    pub fn as_str<'a>(self, owner: &'a Owner<'own>) -> &'a str {
+        self.0.borrow(owner)
+    }
+
+    /// Append a string to the end of the string.
+    pub fn push_str(self, owner: &mut Owner<'own>, value: &str) {
+        self.0.borrow_mut_untraced(owner).push_str(value)
+    }
+
+    /// Append a character to the end of the string.
+    pub fn push(self, owner: &mut Owner<'own>, value: char) {
+        self.0.borrow_mut_untraced(owner).push(value)
+    }
+
+    /// Remove all contents of the string, keeping its buffer.
+    pub fn clear(self, owner: &mut Owner<'own>) {
+        self.0.borrow_mut_untraced(owner).clear()
+    }
+
+    /// Returns the length of the string in bytes.
+    pub fn len(self, owner: &Owner<'own>) -> usize {
+        self.0.borrow(owner).len()
+    }
+
+    /// Returns true if the string is empty.
+    pub fn is_empty(self, owner: &Owner<'own>) -> bool {
+        self.0.borrow(owner).is_empty()
+    }
+
+    /// Returns the pointer to the string object, for rooting it.
+    pub fn gc(self) -> Gc<'gc, 'own, String> {
+        self.0
+    }
+
+    /// Returns true if both strings are the same object.
+    pub fn ptr_eq(self, other: GcString<'_, 'own>) -> bool {
+        self.0.ptr_eq(other.0)
+    }
+}
+
+impl<'gc, 'own> From<Gc<'gc, 'own, String>> for GcString<'gc, 'own> {
+    fn from(value: Gc<'gc, 'own, String>) -> Self {
+        GcString(value)
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for GcString<'gc, 'own> {
+    type Gc<'to> = GcString<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Allocate a mutable string with the given contents.
+    pub fn add_string<'gc>(&'gc self, value: &str) -> GcString<'gc, 'own> {
+        GcString(self.add(value.to_owned()))
+    }
+}
@@ -0,0 +1,211 @@
+//! Integer handles to GC objects which can be handed out to untrusted code.
+
+use std::{fmt, ptr::NonNull};
+
+use crate::{
+    sys::{GcBox, Liveness},
+    Arena, Error, GcAny, Invariant,
+};
+
+/// The reason a handle can't be resolved, returned by [`ScriptHandleRegistry::resolve`] in
+/// [`Error::StaleHandle`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StaleHandle {
+    /// The handle was released with [`ScriptHandleRegistry::release`].
+    Released,
+    /// The object of the handle was collected.
+    Collected,
+    /// The handle was never issued by the registry, or its slot has been reused since it became
+    /// stale.
+    Unknown,
+}
+
+impl fmt::Display for StaleHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaleHandle::Released => write!(f, "the handle was released"),
+            StaleHandle::Collected => write!(f, "the object of the handle was collected"),
+            StaleHandle::Unknown => write!(f, "the handle is not known to the registry"),
+        }
+    }
+}
+
+impl std::error::Error for StaleHandle {}
+
+enum SlotState {
+    Occupied {
+        ptr: NonNull<GcBox<()>>,
+        alive: Liveness,
+    },
+    Vacant(StaleHandle),
+}
+
+struct Slot {
+    generation: u32,
+    state: SlotState,
+}
+
+/// A table handing out integer handles to GC objects, for exposing objects to code which can't
+/// hold GC pointers, like user scripts.
+///
+/// A handle packs the index of a slot in its low 32 bits and the generation of the slot in its
+/// high 32 bits. Releasing a handle or collecting its object bumps the generation once the slot
+/// is reused, so stale handles are detected instead of resolving to an other object. 0 is never a
+/// valid handle.
+///
+/// Handles don't keep their object alive, the registry only tracks whether the object was freed.
+/// Handles of freed objects count towards the quota until they are pruned, which happens
+/// automatically when the quota is reached or explicitly with [`ScriptHandleRegistry::prune`].
+pub struct ScriptHandleRegistry<'own> {
+    slots: Vec<Slot>,
+    vacant: Vec<u32>,
+    len: usize,
+    quota: usize,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> ScriptHandleRegistry<'own> {
+    /// Create a registry holding at most `quota` handles at once.
+    pub fn new(quota: usize) -> Self {
+        ScriptHandleRegistry {
+            slots: Vec::new(),
+            vacant: Vec::new(),
+            len: 0,
+            quota,
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Returns a new handle for the object.
+    ///
+    /// Registering the same object twice returns two different handles.
+    pub fn register(&mut self, arena: &Arena<'own>, value: GcAny<'_, 'own>) -> Result<u64, Error> {
+        if self.len >= self.quota {
+            self.prune();
+            if self.len >= self.quota {
+                return Err(Error::HandleQuotaExceeded { quota: self.quota });
+            }
+        }
+
+        let ptr = value.into_gc_box();
+        let state = SlotState::Occupied {
+            ptr,
+            alive: unsafe { arena.unsafe_arena().liveness(ptr) },
+        };
+        self.len += 1;
+
+        if let Some(index) = self.vacant.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.generation = slot.generation.wrapping_add(1);
+            slot.state = state;
+            return Ok(pack(index, slot.generation));
+        }
+
+        let index = u32::try_from(self.slots.len()).expect("too many handle slots");
+        // Generations start at 1 so 0 is never a valid handle.
+        self.slots.push(Slot {
+            generation: 1,
+            state,
+        });
+        Ok(pack(index, 1))
+    }
+
+    /// Returns the object of the handle.
+    pub fn resolve<'gc>(
+        &self,
+        handle: u64,
+        arena: &'gc Arena<'own>,
+    ) -> Result<GcAny<'gc, 'own>, Error> {
+        // Unswept objects might be unreachable, so an object is only known to be alive once the
+        // sweep has finished.
+        unsafe { arena.unsafe_arena().finish_sweep() };
+
+        match &self.slot(handle)?.state {
+            SlotState::Occupied { ptr, alive } => {
+                if alive.is_alive() {
+                    Ok(unsafe { GcAny::from_gc_box(ptr.cast()) })
+                } else {
+                    Err(StaleHandle::Collected.into())
+                }
+            }
+            SlotState::Vacant(reason) => Err((*reason).into()),
+        }
+    }
+
+    /// Invalidate the handle.
+    ///
+    /// Returns an error if the handle was already stale, the slot of a handle whose object was
+    /// collected is still freed.
+    pub fn release(&mut self, handle: u64) -> Result<(), Error> {
+        let slot = self.slot(handle)?;
+        let reason = match &slot.state {
+            SlotState::Occupied { alive, .. } if !alive.is_alive() => StaleHandle::Collected,
+            SlotState::Occupied { .. } => StaleHandle::Released,
+            SlotState::Vacant(reason) => return Err((*reason).into()),
+        };
+        let index = handle as u32;
+        self.vacate(index, reason);
+        if reason == StaleHandle::Collected {
+            Err(reason.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Free the slots of handles whose object was collected, returning the number of slots
+    /// freed.
+    pub fn prune(&mut self) -> usize {
+        let collected = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| {
+                matches!(&slot.state, SlotState::Occupied { alive, .. } if !alive.is_alive())
+            })
+            .map(|(index, _)| index as u32)
+            .collect::<Vec<_>>();
+        for index in collected.iter().copied() {
+            self.vacate(index, StaleHandle::Collected);
+        }
+        collected.len()
+    }
+
+    /// Returns the number of handles, including handles whose object was collected since they
+    /// were last pruned.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the registry holds no handles.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of handles the registry can hold at once.
+    pub fn quota(&self) -> usize {
+        self.quota
+    }
+
+    /// Returns the slot of a handle if the handle belongs to the current generation of the slot.
+    fn slot(&self, handle: u64) -> Result<&Slot, StaleHandle> {
+        let (index, generation) = unpack(handle);
+        match self.slots.get(index as usize) {
+            Some(slot) if slot.generation == generation => Ok(slot),
+            _ => Err(StaleHandle::Unknown),
+        }
+    }
+
+    fn vacate(&mut self, index: u32, reason: StaleHandle) {
+        self.slots[index as usize].state = SlotState::Vacant(reason);
+        self.vacant.push(index);
+        self.len -= 1;
+    }
+}
+
+fn pack(index: u32, generation: u32) -> u64 {
+    (generation as u64) << 32 | index as u64
+}
+
+fn unpack(handle: u64) -> (u32, u32) {
+    (handle as u32, (handle >> 32) as u32)
+}
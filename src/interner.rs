@@ -0,0 +1,143 @@
+//! Interning of strings.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    ptr::NonNull,
+};
+
+use crate::{
+    sys::{GcBox, Liveness},
+    Arena, Gc, Invariant, Marker, Owner, Trace,
+};
+
+/// An interned string, created by [`Interner::intern`].
+///
+/// Symbols of equal strings interned by the same interner are the same object, so symbols are
+/// compared and hashed by identity, which is much cheaper than comparing the strings.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol<'gc, 'own>(Gc<'gc, 'own, str>);
+
+impl<'gc, 'own> Symbol<'gc, 'own> {
+    /// Returns the interned string.
+    pub fn as_str<'a>(self, owner: &'a Owner<'own>) -> &'a str {
+        self.0.borrow(owner)
+    }
+
+    /// Returns the pointer to the string object.
+    pub fn gc(self) -> Gc<'gc, 'own, str> {
+        self.0
+    }
+
+    /// Returns true if both symbols are the same object, which is the case for symbols of equal
+    /// strings interned by the same interner.
+    pub fn ptr_eq(self, other: Symbol<'_, 'own>) -> bool {
+        self.0.ptr_eq(other.0)
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Symbol<'gc, 'own> {
+    type Gc<'to> = Symbol<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+struct Entry {
+    ptr: NonNull<GcBox<str>>,
+    alive: Liveness,
+}
+
+/// A table of interned strings, returning the same [`Symbol`] for equal strings.
+///
+/// The table only holds weak references: a symbol is kept alive by the pointers to it like any
+/// other object, not by the interner. Once a symbol is freed, interning the same string again
+/// allocates a new symbol. Entries of freed symbols are removed when a string with the same hash
+/// is interned, or by [`Interner::purge`].
+pub struct Interner<'own> {
+    buckets: HashMap<u64, Vec<Entry>>,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> Interner<'own> {
+    /// Create an empty interner.
+    pub fn new() -> Self {
+        Interner {
+            buckets: HashMap::new(),
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Returns the symbol for the string, allocating it if no symbol for an equal string is
+    /// alive.
+    pub fn intern<'gc>(&mut self, arena: &'gc Arena<'own>, value: &str) -> Symbol<'gc, 'own> {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let arena = arena.unsafe_arena();
+        unsafe {
+            // Unswept objects might be unreachable, so an entry is only known to be alive once the
+            // sweep has finished.
+            arena.finish_sweep();
+
+            let bucket = self.buckets.entry(hash).or_default();
+            bucket.retain(|x| x.alive.is_alive());
+            // String objects are never mutated so they can be read without the owner.
+            if let Some(entry) = bucket
+                .iter()
+                .find(|x| &**x.ptr.as_ref().value.get() == value)
+            {
+                return Symbol(Gc::from_gc_box(entry.ptr));
+            }
+
+            let ptr = arena.add_str(value);
+            bucket.push(Entry {
+                ptr,
+                alive: arena.liveness(ptr.cast()),
+            });
+            Symbol(Gc::from_gc_box(ptr))
+        }
+    }
+
+    /// Remove the entries of freed symbols, returning the number of entries removed.
+    pub fn purge(&mut self) -> usize {
+        let mut removed = 0;
+        self.buckets.retain(|_, bucket| {
+            let before = bucket.len();
+            bucket.retain(|x| x.alive.is_alive());
+            removed += before - bucket.len();
+            !bucket.is_empty()
+        });
+        removed
+    }
+
+    /// Returns the number of entries, including entries of symbols which were freed since they
+    /// were last purged.
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(|x| x.len()).sum()
+    }
+
+    /// Returns true if the interner has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+impl<'own> Default for Interner<'own> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,47 @@
+//! The memory overhead of the types in this library.
+//!
+//! The overhead per object is considered part of the public API, any change to these values is a
+//! breaking change. All values are checked at compile time.
+
+use std::mem::{offset_of, size_of};
+
+use crate::{
+    sys::{GcBox, UnsafeRootGuard},
+    Gc, RootGuard,
+};
+
+/// The number of bytes in front of the value of every GC allocated object.
+///
+/// The `arena-id` feature adds the id of the arena to the header, the `object-id` feature adds
+/// the id of the object and the `tag-word` feature adds the tag word of the object.
+pub const GC_BOX_HEADER_BYTES: usize = 2 * size_of::<usize>()
+    + if cfg!(feature = "arena-id") {
+        size_of::<u64>()
+    } else {
+        0
+    }
+    + if cfg!(feature = "object-id") {
+        size_of::<u64>()
+    } else {
+        0
+    }
+    + if cfg!(feature = "tag-word") {
+        size_of::<usize>()
+    } else {
+        0
+    };
+
+/// The size of a [`Gc`] pointer.
+pub const GC_PTR_BYTES: usize = size_of::<usize>();
+
+/// The size of an `Option<Gc>`, which is the same as a [`Gc`] pointer.
+pub const OPTION_GC_PTR_BYTES: usize = GC_PTR_BYTES;
+
+/// The size of a [`RootGuard`].
+pub const ROOT_GUARD_BYTES: usize = 4 * size_of::<usize>();
+
+const _: () = assert!(offset_of!(GcBox<u8>, value) == GC_BOX_HEADER_BYTES);
+const _: () = assert!(size_of::<Gc<'static, 'static, u8>>() == GC_PTR_BYTES);
+const _: () = assert!(size_of::<Option<Gc<'static, 'static, u8>>>() == OPTION_GC_PTR_BYTES);
+const _: () = assert!(size_of::<UnsafeRootGuard>() == ROOT_GUARD_BYTES);
+const _: () = assert!(size_of::<RootGuard>() == ROOT_GUARD_BYTES);
@@ -6,16 +6,130 @@ pub use marker::{Invariant, Owner};
 mod arena;
 pub use arena::{Arena, Marker, RootGuard};
 
+mod error;
+pub use error::{AllocError, Error};
+
 mod ptr;
-pub use ptr::Gc;
+#[cfg(feature = "object-id")]
+pub use ptr::ObjectId;
+pub use ptr::{Erasable, Gc, GcAny};
+
+mod borrow;
+pub use borrow::{BorrowAll, BorrowAllExt, BorrowAllMut};
 
 mod trace;
-pub use trace::Trace;
+pub use trace::{LeafTrace, Trace};
+
+mod gc_dyn;
+pub use gc_dyn::{GcDyn, GcTarget, GcTraitObject};
+
+mod sampled;
+pub use sampled::{NoGc, Sampled};
+
+mod cell;
+pub use cell::{GcCell, GcOnceCell, GcRefCell, GcRefMut};
+
+mod versioned;
+pub use versioned::Versioned;
+
+mod sealed;
+pub use sealed::{Seal, SealedGc};
+
+mod debug;
+pub use debug::{DebugOptions, DebugWith, GcDebug, GcDisplay, WithOwner};
+
+mod rooted;
+pub use rooted::Rooted;
+
+mod root_vec;
+pub use root_vec::RootVec;
+
+mod value_root;
+pub use value_root::ValueRootGuard;
+
+mod anchored;
+pub use anchored::Anchored;
+
+mod walk;
+pub use walk::SubgraphCursor;
+
+mod memo;
+pub use memo::{MemoCache, MemoStats};
+
+mod handles;
+pub use handles::{ScriptHandleRegistry, StaleHandle};
+
+mod marked;
+pub use marked::MarkedHeapGuard;
+
+mod interner;
+pub use interner::{Interner, Symbol};
+
+mod observers;
+pub use observers::{ObserverPruner, WeakObserverList};
+
+mod gc_string;
+pub use gc_string::GcString;
+
+mod resource;
+pub use resource::{Resource, ResourceKind};
+
+mod gc_enum;
+#[doc(hidden)]
+pub use gc_enum::TaggedPtr;
+
+mod read_scope;
+pub use read_scope::{ReadScope, ReadSync, ReadToken, SharedGc};
+
+mod speculation;
+pub use speculation::SpeculationGuard;
+
+mod write;
+pub use write::Write;
+#[doc(hidden)]
+pub use write::{ProbePlain, ProbeTraced, ProjectPlainTag, ProjectProbe, ProjectTracedTag};
+
+mod rebind;
+#[doc(hidden)]
+pub use rebind::{ProbeGc, ProbeValue, RebindGcTag, RebindProbe, RebindValueTag, Rebindable};
+
+mod builder;
+pub use builder::{BuilderRef, HeapBuilder, HeapRefs, IntoHeap};
+
+mod migrate;
+pub use migrate::{Migration, Replacer};
+
+mod barrier;
+pub use barrier::BarrierBatch;
+
+mod defer;
+pub use defer::{DeferToken, DeferredOps};
+
+mod diagnostic;
+pub use diagnostic::{catch_unwind_with_heap, DiagnosticSnapshot, DiagnosticValue};
+
+#[cfg(feature = "stable-id")]
+mod export;
+
+pub mod layout;
 
 pub mod sys;
+#[cfg(feature = "root-provenance")]
+pub use sys::RootSuspect;
+pub use sys::{
+    GcStats, GcUnavailable, MemoryPressure, PhaseMask, Transition, TransitionSubscription,
+    WorkRequest,
+};
 
 pub mod scoped;
 
+pub mod containers;
+
+pub mod testing;
+
+#[cfg(feature = "demo")]
+pub mod demo;
+
 /// Create a new safe arena and owner.
 ///
 /// # Usage
@@ -50,6 +164,9 @@ macro_rules! dreck {
 
 /// Rebind a GC pointer back to a arena.
 ///
+/// Pointers are rebound with [`Arena::rebind_gc`], any other value implementing [`Trace`], like a
+/// container of pointers, with [`Arena::rebind_value`]. The value must belong to the arena.
+///
 /// # Usage
 /// ```
 /// # use std::pin::pin;
@@ -70,16 +187,21 @@ macro_rules! dreck {
 #[macro_export]
 macro_rules! rebind {
     ($arena:expr,$value:expr) => {{
-        let value = unsafe {
-            // detach from any existing lifetime
-            $value.rebind()
-        };
-        $crate::Arena::rebind_to($arena, value)
+        #[allow(unused_imports)]
+        use $crate::{ProbeGc as _, ProbeValue as _};
+        let value = $value;
+        // Pointers are rebound with `Arena::rebind_gc`, other values with `Arena::rebind_value`.
+        (&$crate::RebindProbe(&value))
+            .rebind_tag()
+            .rebind($arena, value)
     }};
 }
 
 /// Root a GC pointer to be kept alive for the duration of the given guard.
 ///
+/// The form `root!(&arena, let name = ptr)` creates and pins the guard itself, declaring a
+/// variable holding the rooted pointer. The guard lives until the end of the enclosing scope.
+///
 /// # Usage
 /// ```
 /// # use std::pin::pin;
@@ -90,13 +212,273 @@ macro_rules! rebind {
 /// let guard = pin!(RootGuard::new());
 /// let ptr = root!(&arena,guard,ptr);
 ///
+/// root!(&arena, let other = arena.add(4));
+///
 /// arena.collect(&owner);
 ///
-/// assert_eq!(*ptr.borrow(&owner),3)
+/// assert_eq!(*ptr.borrow(&owner),3);
+/// assert_eq!(*other.borrow(&owner),4)
+/// ```
 #[macro_export]
 macro_rules! root {
+    ($arena:expr, let $name:ident = $value:expr) => {
+        let guard = ::std::pin::pin!($crate::RootGuard::new());
+        let $name = $crate::root!($arena, guard, $value);
+    };
     ($arena:expr,$guard:expr,$value:expr) => {{
-        let value = unsafe { Trace::rebind($value) };
+        let value = unsafe { $crate::Trace::rebind($value) };
         $crate::Arena::root($arena, value, $guard)
     }};
 }
+
+/// Allocate a value and root it with a new guard, declaring a variable holding the pointer.
+///
+/// The guard lives until the end of the enclosing scope, see [`Arena::add_rooted`].
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// dreck!(owner,arena);
+///
+/// add_rooted!(&arena,ptr,3);
+///
+/// arena.collect_full(&owner);
+///
+/// assert_eq!(*ptr.borrow(&owner),3)
+/// ```
+#[macro_export]
+macro_rules! add_rooted {
+    ($arena:expr,$name:ident,$value:expr) => {
+        let guard = ::std::pin::pin!($crate::RootGuard::new());
+        let $name = $crate::Arena::add_rooted($arena, $value, guard);
+    };
+}
+
+/// Root a value which is not allocated in the arena, keeping every GC pointer it contains alive
+/// for the duration of the given guard, see [`Arena::root_value`].
+///
+/// The form `stack_root!(&arena, let name = value)` creates and pins the guard itself, declaring
+/// a variable holding a reference to the rooted value. The guard lives until the end of the
+/// enclosing scope.
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// dreck!(owner,arena);
+///
+/// stack_root!(&arena, let frame = (arena.add(1), vec![arena.add(2)]));
+///
+/// arena.collect_full(&owner);
+///
+/// assert_eq!(*frame.0.borrow(&owner),1);
+/// assert_eq!(*frame.1[0].borrow(&owner),2)
+/// ```
+#[macro_export]
+macro_rules! stack_root {
+    ($arena:expr, let $name:ident = $value:expr) => {
+        let guard = ::std::pin::pin!($crate::ValueRootGuard::new());
+        let $name = $crate::stack_root!($arena, guard, $value);
+    };
+    ($arena:expr,$guard:expr,$value:expr) => {
+        $crate::Arena::root_value($arena, $value, $guard)
+    };
+}
+
+/// Declare a trait whose boxed trait objects can be stored in GC objects.
+///
+/// The trait must have exactly the two lifetime parameters `'gc` and `'own`, in that order, and is
+/// declared with [`GcDyn`] as its supertrait. `Box<dyn Trait<'gc, 'own> + 'gc>` then implements
+/// [`Trace`], as does `Gc<'gc, 'own, dyn Trait<'gc, 'own> + 'gc>` created with [`gc_coerce!`],
+/// mapping the gc lifetime of the trait object when rebound.
+///
+/// The trait is declared unsafe as the lifetime of the trait object is changed when rebinding it,
+/// which would be unsound for a type borrowing anything for a lifetime other than `'gc`.
+/// Implementing the trait is thus only safe for types which contain no lifetimes other than
+/// `'gc` and `'own`, or lifetimes which are `'static`.
+///
+/// # Usage
+/// ```
+/// # use std::pin::pin;
+/// # use dreck::*;
+/// gc_trait! {
+///     pub unsafe trait Getter<'gc, 'own> {
+///         fn get(&self, owner: &Owner<'own>) -> u32;
+///     }
+/// }
+///
+/// struct Field<'gc, 'own>(Gc<'gc, 'own, u32>);
+///
+/// unsafe impl<'gc, 'own> Trace<'own> for Field<'gc, 'own> {
+///     type Gc<'to> = Field<'to, 'own>;
+///
+///     fn needs_trace() -> bool {
+///         true
+///     }
+///
+///     fn trace(&self, marker: Marker<'own, '_>) {
+///         self.0.trace(marker)
+///     }
+/// }
+///
+/// unsafe impl<'gc, 'own> Getter<'gc, 'own> for Field<'gc, 'own> {
+///     fn get(&self, owner: &Owner<'own>) -> u32 {
+///         *self.0.borrow(owner)
+///     }
+/// }
+///
+/// dreck!(owner, arena);
+///
+/// let value = arena.add(3);
+/// let getter: Box<dyn Getter> = Box::new(Field(value));
+/// let getter = arena.add(getter);
+/// let guard = pin!(RootGuard::new());
+/// let getter = root!(&arena, guard, getter);
+///
+/// arena.collect_full(&owner);
+/// assert_eq!(getter.borrow(&owner).get(&owner), 3);
+/// ```
+#[macro_export]
+macro_rules! gc_trait {
+    (
+        $(#[$meta:meta])*
+        $vis:vis unsafe trait $name:ident<$gc:lifetime, $own:lifetime> {
+            $($body:tt)*
+        }
+    ) => {
+        $(#[$meta])*
+        ///
+        /// # Safety
+        /// Trait objects of this trait are rebound to different gc lifetimes, implementing types
+        /// must not borrow anything for a lifetime other than the gc lifetime.
+        // Clippy doesn't see the safety section above as it comes from a macro.
+        #[allow(clippy::missing_safety_doc)]
+        $vis unsafe trait $name<$gc, $own>: $crate::GcDyn<$own> {
+            $($body)*
+        }
+
+        unsafe impl<$gc, $own> $crate::Trace<$own>
+            for ::std::boxed::Box<dyn $name<$gc, $own> + $gc>
+        {
+            // Trait objects only differing in lifetimes have the same layout and vtable, and
+            // implementors of the trait promise to only borrow for the gc lifetime, so rebinding
+            // the box by transmuting it is sound.
+            type Gc<'__to> = ::std::boxed::Box<dyn $name<'__to, $own> + '__to>;
+
+            fn needs_trace() -> bool
+            where
+                Self: Sized,
+            {
+                true
+            }
+
+            fn trace(&self, marker: $crate::Marker<$own, '_>) {
+                $crate::GcDyn::trace_dyn(&**self, marker)
+            }
+        }
+
+        unsafe impl<$gc, $own> $crate::GcTraitObject for dyn $name<$gc, $own> + $gc {}
+
+        unsafe impl<$gc, $own> $crate::GcTarget<$own> for dyn $name<$gc, $own> + $gc {
+            type Gc<'__to> = dyn $name<'__to, $own> + '__to;
+
+            fn mark(ptr: $crate::Gc<'_, $own, Self>, marker: $crate::Marker<$own, '_>) {
+                marker.mark_erased(ptr)
+            }
+        }
+    };
+}
+
+/// Convert a GC pointer into a pointer to an unsized type, like a trait object.
+///
+/// Performs the same unsizing coercions as references do, so the value must implement the trait
+/// of the trait object. Only trait objects of traits declared with [`gc_trait!`] are accepted, see
+/// [`GcTraitObject`], and pointers to them can be stored in GC objects.
+///
+/// # Usage
+/// ```
+/// # use std::pin::pin;
+/// # use dreck::*;
+/// gc_trait! {
+///     pub unsafe trait Named<'gc, 'own> {
+///         fn name(&self) -> String;
+///     }
+/// }
+///
+/// unsafe impl<'gc, 'own> Named<'gc, 'own> for u32 {
+///     fn name(&self) -> String {
+///         format!("number {}", self)
+///     }
+/// }
+///
+/// dreck!(owner, arena);
+///
+/// let named = gc_coerce!(arena.add(3u32) => dyn Named);
+/// let list = arena.add(vec![named]);
+/// let guard = pin!(RootGuard::new());
+/// let list = root!(&arena, guard, list);
+///
+/// arena.collect_full(&owner);
+/// assert_eq!(list.borrow(&owner)[0].borrow(&owner).name(), "number 3");
+/// ```
+#[macro_export]
+macro_rules! gc_coerce {
+    ($value:expr => $ty:ty) => {{
+        let value = $value;
+        // The closure returns the pointer unchanged, only coercing its type.
+        unsafe {
+            $crate::Gc::coerce_with(
+                value,
+                |ptr| -> ::std::ptr::NonNull<$crate::sys::GcBox<$ty>> { ptr },
+            )
+        }
+    }};
+}
+
+/// Project a [`Write`] to a field of its value, see [`Write::project`].
+///
+/// Fields which are plain data, see [`NoGc`], are returned as a plain mutable reference, other
+/// fields as a `&mut Write` of the field. Nested fields are projected with a path like `a.b.0`.
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// pub struct Frame<'gc, 'own> {
+///     pub pc: usize,
+///     pub acc: Option<Gc<'gc, 'own, u32>>,
+/// }
+///
+/// unsafe impl<'gc, 'own> Trace<'own> for Frame<'gc, 'own> {
+///     type Gc<'to> = Frame<'to, 'own>;
+///
+///     fn needs_trace() -> bool {
+///         true
+///     }
+///
+///     fn trace(&self, marker: Marker<'own, '_>) {
+///         self.acc.trace(marker)
+///     }
+/// }
+///
+/// dreck!(owner, arena);
+///
+/// let frame = arena.add(Frame { pc: 0, acc: None });
+/// let value = arena.add(3);
+///
+/// let write = frame.write(&mut owner, &arena);
+/// *project!(write, pc) += 1;
+/// project!(write, acc).set(Some(value));
+///
+/// assert_eq!(frame.borrow(&owner).pc, 1);
+/// ```
+#[macro_export]
+macro_rules! project {
+    ($write:expr, $($field:tt).+) => {{
+        #[allow(unused_imports)]
+        use $crate::{ProbePlain as _, ProbeTraced as _};
+        let field = $crate::Write::project($write, |value| &mut value.$($field).+);
+        // Plain data fields are handed out as `&mut F`, other fields as `&mut Write<F>`.
+        (&$crate::ProjectProbe(&*field))
+            .project_tag()
+            .finish(field)
+    }};
+}
@@ -0,0 +1,72 @@
+//! Holding the heap fully marked for inspection.
+
+use crate::{Arena, GcAny, Owner};
+
+/// The heap held fully marked, created by [`Arena::mark_all`].
+///
+/// Every object reachable from a root is marked and no object has been freed yet, so the guard can
+/// tell live objects from dead ones. The arena and the owner are borrowed for as long as the guard
+/// exists, so nothing can be allocated or mutated.
+///
+/// Dropping the guard finishes the sweep, like [`MarkedHeapGuard::finish_sweep`].
+pub struct MarkedHeapGuard<'a, 'own> {
+    arena: &'a mut Arena<'own>,
+    owner: &'a Owner<'own>,
+}
+
+impl<'a, 'own> MarkedHeapGuard<'a, 'own> {
+    /// Returns true if the object is reachable from a root.
+    pub fn is_live(&self, value: GcAny<'_, 'own>) -> bool {
+        unsafe { self.arena.unsafe_arena().is_marked(value.into_gc_box()) }
+    }
+
+    /// Call the function for every object which is not reachable from a root and will be freed by
+    /// the sweep.
+    pub fn for_each_dead<F>(&self, mut f: F)
+    where
+        F: for<'b> FnMut(GcAny<'b, 'own>),
+    {
+        let arena = self.arena.unsafe_arena();
+        unsafe {
+            arena.for_each_object(|ptr| {
+                if !arena.is_marked(ptr) {
+                    f(GcAny::from_gc_box(ptr))
+                }
+            })
+        }
+    }
+
+    /// Returns the owner the heap was marked with, for reading dead objects.
+    pub fn owner(&self) -> &'a Owner<'own> {
+        self.owner
+    }
+
+    /// Free the dead objects, completing the collection cycle.
+    pub fn finish_sweep(self) {
+        // Dropping the guard finishes the sweep.
+    }
+
+    /// Unmark all objects without freeing anything, the dead objects are freed by a later
+    /// collection.
+    pub fn abandon(self) {
+        // The arena is no longer sweeping afterwards, so dropping the guard does nothing.
+        unsafe { self.arena.unsafe_arena().abandon_sweep() };
+    }
+}
+
+impl Drop for MarkedHeapGuard<'_, '_> {
+    fn drop(&mut self) {
+        unsafe { self.arena.unsafe_arena().finish_sweep() }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Run a full collection up to the sweep, holding the heap fully marked so live objects can
+    /// be told apart from dead ones before anything is freed.
+    ///
+    /// Any running cycle is finished first.
+    pub fn mark_all<'a>(&'a mut self, owner: &'a Owner<'own>) -> MarkedHeapGuard<'a, 'own> {
+        unsafe { self.unsafe_arena().mark_all() };
+        MarkedHeapGuard { arena: self, owner }
+    }
+}
@@ -1,6 +1,6 @@
-//! A set of zero-sized marker types.
+//! A set of marker types.
 
-use std::marker::PhantomData;
+use std::{cell::Cell, marker::PhantomData};
 
 /// A marker struct which marks a lifetime as invariant.
 ///
@@ -14,6 +14,7 @@ impl<'inv> Invariant<'inv> {
         Invariant(PhantomData)
     }
 
+    /// Create a marker with the lifetime of the given reference.
     pub fn new_ref<T>(_v: &'inv T) -> Self {
         Invariant(PhantomData)
     }
@@ -35,14 +36,18 @@ impl<'co> Covariant<'co> {
 
 /// An struct which acts as the owner of garbage collected values.
 ///
-/// Altough this struct is zero-sized it acts as if all GC values are contained within this object
-/// allowing zero-cost, statically verified, safe mutable access to shared GC pointers.
+/// Altough this struct only holds a counter it acts as if all GC values are contained within this
+/// object allowing zero-cost, statically verified, safe mutable access to shared GC pointers.
 ///
 /// Using this owner to borrow a GC allocated value mutably also borrows the owner mutably for the
 /// same lifetime, thus disallowing any GC pointer for being borrowed immutably. For the use of
 /// this object see [`Gc::borrow`](`crate::Gc::borrow`) and [`Gc::borrow_mut`](`crate::Gc::borrow_mut`).
+///
+/// The counter is the mutation epoch returned by [`Owner::epoch`].
+// The owner must not be send or sync as a GC pointer borrowed from another thread could then be
+// freed by a collection on the thread which owns the arena.
 #[derive(Debug)]
-pub struct Owner<'own>(Invariant<'own>);
+pub struct Owner<'own>(Invariant<'own>, Cell<u64>, PhantomData<*mut ()>);
 
 impl<'own> Owner<'own> {
     /// Create a new owner.
@@ -52,7 +57,7 @@ impl<'own> Owner<'own> {
     ///
     /// Instead use the safe macros to create an owner.
     pub unsafe fn new() -> Self {
-        Owner(Invariant::new())
+        Owner(Invariant::new(), Cell::new(0), PhantomData)
     }
 
     /// Create a new owner.
@@ -62,6 +67,27 @@ impl<'own> Owner<'own> {
     ///
     /// Instead use the safe macros to create an owner.
     pub unsafe fn from_invariant(inv: Invariant<'own>) -> Self {
-        Owner(inv)
+        Owner(inv, Cell::new(0), PhantomData)
+    }
+
+    /// Returns the mutation epoch of the owner.
+    ///
+    /// The epoch starts at zero and is increased by every function which mutates GC objects
+    /// through the owner: the [`Gc::borrow_mut`](`crate::Gc::borrow_mut`) family, the mutators
+    /// of the GC containers and cells, and functions like
+    /// [`Arena::for_each_mut`](`crate::Arena::for_each_mut`). Borrowing immutably leaves it
+    /// unchanged, so data derived from GC objects can be cached along with the epoch and is up to
+    /// date as long as the epoch is the same.
+    ///
+    /// Changes which don't involve the owner, like [`GcCell::set`](`crate::GcCell::set`), don't
+    /// increase the epoch. Use [`Versioned`](`crate::Versioned`) to track changes of a single
+    /// value.
+    pub fn epoch(&self) -> u64 {
+        self.1.get()
+    }
+
+    /// Record a mutation through the owner.
+    pub(crate) fn bump_epoch(&self) {
+        self.1.set(self.1.get() + 1);
     }
 }
@@ -0,0 +1,171 @@
+//! Memoization of functions from GC keys to GC values.
+
+use std::{collections::HashMap, fmt, marker::PhantomData, pin::Pin, ptr::NonNull};
+
+use crate::{
+    sys::{GcBox, Liveness, UnsafeRootGuard},
+    Arena, Gc, Invariant, Owner, Trace,
+};
+
+/// Counters of a [`MemoCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MemoStats {
+    /// The number of lookups which found a cached value.
+    pub hits: u64,
+    /// The number of lookups which had to compute the value.
+    pub misses: u64,
+    /// The number of entries removed to stay within the capacity.
+    pub evicted: u64,
+    /// The number of entries removed because their key was freed.
+    pub cleared: u64,
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits: {}, misses: {}, evicted: {}, cleared: {}",
+            self.hits, self.misses, self.evicted, self.cleared
+        )
+    }
+}
+
+struct Entry<V> {
+    key: Liveness,
+    value: NonNull<GcBox<V>>,
+    last_used: u64,
+    _guard: Pin<Box<UnsafeRootGuard>>,
+}
+
+/// A cache of values computed from GC keys.
+///
+/// Keys are not kept alive by the cache, entries of keys which are freed are removed by the next
+/// call to [`MemoCache::get_or_insert_with`] or [`MemoCache::purge`]. Values are rooted for as
+/// long as they are in the cache. A value which refers to its own key thus keeps the key alive
+/// for as long as the entry exists.
+///
+/// When the cache is full the least recently used entry is evicted.
+///
+/// Like with [`Anchored`](crate::Anchored), `K` and `V` are the types with a `'static` gc
+/// lifetime, like `Node<'static, 'own>`.
+pub struct MemoCache<'own, K, V> {
+    entries: HashMap<NonNull<GcBox<()>>, Entry<V>>,
+    capacity: usize,
+    tick: u64,
+    stats: MemoStats,
+    _invariant: Invariant<'own>,
+    _marker: PhantomData<fn(K)>,
+}
+
+impl<'own, K: Trace<'own>, V: Trace<'own>> MemoCache<'own, K, V> {
+    /// Create a cache holding at most `capacity` entries.
+    ///
+    /// # Panic
+    /// Panics if the capacity is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "memo cache capacity must be at least 1");
+        MemoCache {
+            entries: HashMap::new(),
+            capacity,
+            tick: 0,
+            stats: MemoStats::default(),
+            _invariant: Invariant::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the cached value for the key, or computes it with the given function and caches
+    /// it.
+    pub fn get_or_insert_with<'gc, F>(
+        &mut self,
+        owner: &mut Owner<'own>,
+        arena: &'gc Arena<'own>,
+        key: Gc<'_, 'own, K::Gc<'_>>,
+        f: F,
+    ) -> Gc<'gc, 'own, V::Gc<'gc>>
+    where
+        F: FnOnce(&mut Owner<'own>) -> Gc<'gc, 'own, V::Gc<'gc>>,
+    {
+        // Entries of freed keys must be removed first as a new key might reuse their address.
+        self.purge();
+        self.tick += 1;
+
+        let key = key.into_gc_box().cast::<GcBox<()>>();
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.stats.hits += 1;
+            entry.last_used = self.tick;
+            // Safe as the value is rooted and can only be unrooted by a mutable borrow of the
+            // cache, after which the pointer remains valid until the next collection.
+            return unsafe { Gc::from_gc_box(entry.value.cast()) };
+        }
+
+        self.stats.misses += 1;
+        let value = f(owner);
+        if self.entries.len() == self.capacity {
+            self.evict();
+        }
+
+        let arena = arena.unsafe_arena();
+        let ptr = value.into_gc_box().cast::<GcBox<V>>();
+        let mut guard = Box::pin(UnsafeRootGuard::new());
+        let entry = unsafe {
+            arena.root(guard.as_mut(), ptr);
+            Entry {
+                key: arena.liveness(key),
+                value: ptr,
+                last_used: self.tick,
+                _guard: guard,
+            }
+        };
+        self.entries.insert(key, entry);
+        value
+    }
+
+    /// Remove the entries whose key has been freed, returning the number of entries removed.
+    pub fn purge(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| entry.key.is_alive());
+        let cleared = before - self.entries.len();
+        self.stats.cleared += cleared as u64;
+        cleared
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the number of entries, including entries whose key was freed since the last purge.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the maximum number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the counters of the cache.
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    /// Remove the least recently used entry.
+    fn evict(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| *key);
+        if let Some(oldest) = oldest {
+            self.entries.remove(&oldest);
+            self.stats.evicted += 1;
+        }
+    }
+}
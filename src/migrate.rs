@@ -0,0 +1,171 @@
+use std::{collections::HashMap, marker::PhantomData, pin::Pin, ptr::NonNull};
+
+use crate::{
+    marker::Covariant,
+    sys::{embed::TraceFn, erased_type_id, GcBox, UnsafeRootGuard},
+    Arena, Erasable, Gc, Invariant, Owner, Trace,
+};
+
+/// Maps old objects to their replacements.
+type Replaced = HashMap<NonNull<GcBox<()>>, NonNull<GcBox<()>>>;
+
+/// A migration of all objects of type `Old` to objects of type `New` started by
+/// [`Arena::migrate`].
+///
+/// All replacement objects are kept alive for as long as the migration exists.
+pub struct Migration<'own, Old, New> {
+    replaced: Replaced,
+    guard: Pin<Box<UnsafeRootGuard>>,
+    _invariant: Invariant<'own>,
+    _marker: PhantomData<fn(Old) -> New>,
+}
+
+impl<'own, Old, New: Trace<'own>> Migration<'own, Old, New> {
+    /// Rewrite the references to old objects held by all objects of type `T`.
+    ///
+    /// The function is called for every object of type `T` like [`Arena::for_each_mut`], together
+    /// with a [`Replacer`] for looking up the replacements of old objects.
+    pub fn rewrite<T, F>(&self, arena: &mut Arena<'own>, owner: &mut Owner<'own>, mut f: F)
+    where
+        T: Trace<'own> + Erasable<'own>,
+        F: for<'a> FnMut(&'a mut T::Gc<'a>, Replacer<'a, 'own, New>),
+    {
+        let replaced = &self.replaced;
+        arena.for_each_mut::<T, _>(owner, |value| {
+            f(
+                value,
+                Replacer {
+                    replaced: NonNull::from(replaced),
+                    _gc_marker: Covariant::new(),
+                    _invariant: Invariant::new(),
+                    _marker: PhantomData,
+                },
+            )
+        })
+    }
+
+    /// Returns the number of migrated objects.
+    pub fn len(&self) -> usize {
+        self.replaced.len()
+    }
+
+    /// Returns true if no objects were migrated.
+    pub fn is_empty(&self) -> bool {
+        self.replaced.is_empty()
+    }
+}
+
+/// Looks up the replacements of migrated objects, see [`Migration::rewrite`].
+pub struct Replacer<'a, 'own, New> {
+    // Not a reference as the lifetime `'a` can outlive the migration. The pointers handed out can
+    // then only be stored in the object being rewritten, which keeps the replacement alive.
+    replaced: NonNull<Replaced>,
+    _gc_marker: Covariant<'a>,
+    _invariant: Invariant<'own>,
+    _marker: PhantomData<fn() -> New>,
+}
+
+impl<'a, 'own, New> Clone for Replacer<'a, 'own, New> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, 'own, New> Copy for Replacer<'a, 'own, New> {}
+
+impl<'a, 'own, New: Trace<'own>> Replacer<'a, 'own, New> {
+    /// Returns the replacement of the given object, or `None` if it was not migrated.
+    pub fn get<T>(self, old: Gc<'_, 'own, T>) -> Option<Gc<'a, 'own, New::Gc<'a>>> {
+        // Safe because the replacer is only handed out while the migration is alive.
+        let replaced = unsafe { self.replaced.as_ref() };
+        let new = replaced.get(&old.into_gc_box().cast())?;
+        // Safe because the replacement is rooted by the migration.
+        Some(unsafe { Gc::from_gc_box(new.cast()) })
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Start replacing every live object of type `Old` with an object of type `New`.
+    ///
+    /// Runs a full collection and then allocates a replacement for every remaining `Old` object
+    /// using the given function. References to the old objects are not changed, they should be
+    /// rewritten with [`Migration::rewrite`] for every type holding them. Afterwards
+    /// [`Arena::finish_migration`] frees the old objects.
+    ///
+    /// References between old objects can't be rewritten by the function as the replacements
+    /// don't exist yet, they have to be rewritten afterwards like any other reference.
+    ///
+    /// Old objects are found by the [`TypeId`](std::any::TypeId) of their type, so `Old` must
+    /// implement [`Erasable`].
+    pub fn migrate<Old, New, F>(
+        &mut self,
+        owner: &mut Owner<'own>,
+        mut f: F,
+    ) -> Migration<'own, Old, New>
+    where
+        Old: Trace<'own> + Erasable<'own>,
+        New: Trace<'own>,
+        F: FnMut(&Old) -> New,
+    {
+        owner.bump_epoch();
+        let arena = self.unsafe_arena();
+        let mut olds = Vec::new();
+        unsafe {
+            arena.collect_full();
+            arena.for_each_erased(erased_type_id::<Old::Gc<'static>>(), |ptr| olds.push(ptr));
+        }
+
+        // No collection can run until the replacements are rooted below.
+        let replaced = olds
+            .into_iter()
+            .map(|old| unsafe {
+                let value = f(&*old.cast::<GcBox<Old>>().as_ref().value.get());
+                (old, arena.add(value).cast::<GcBox<()>>())
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut guard = Box::pin(UnsafeRootGuard::new());
+        unsafe {
+            // The replacement objects are kept alive until the migration is finished.
+            let replacements = replaced.values().copied().collect::<Vec<_>>();
+            let replacements = arena.add(TraceFn::erased(replacements));
+            arena.root(guard.as_mut(), replacements);
+        }
+
+        Migration {
+            replaced,
+            guard,
+            _invariant: Invariant::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Finish a migration, freeing all old objects which are no longer referenced.
+    ///
+    /// Returns the number of old objects which are still alive because a reference to them was
+    /// not rewritten.
+    pub fn finish_migration<Old, New>(
+        &mut self,
+        owner: &Owner<'own>,
+        migration: Migration<'own, Old, New>,
+    ) -> usize
+    where
+        Old: Trace<'own> + Erasable<'own>,
+    {
+        let Migration {
+            replaced, guard, ..
+        } = migration;
+        drop(guard);
+
+        self.collect_full(owner);
+        let mut remaining = 0;
+        unsafe {
+            self.unsafe_arena()
+                .for_each_erased(erased_type_id::<Old::Gc<'static>>(), |ptr| {
+                    if replaced.contains_key(&ptr) {
+                        remaining += 1;
+                    }
+                });
+        }
+        remaining
+    }
+}
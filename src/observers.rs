@@ -0,0 +1,146 @@
+//! Lists of observers which don't keep their observers alive.
+
+use std::{cell::RefCell, marker::PhantomData, ptr::NonNull, rc::Rc};
+
+use crate::{
+    sys::{GcBox, Liveness},
+    Arena, Gc, Invariant, Marker, Trace,
+};
+
+struct Entry {
+    ptr: NonNull<GcBox<()>>,
+    alive: Liveness,
+}
+
+/// A list of observers, like event listeners, which only holds weak references to them.
+///
+/// An observer is kept alive by the pointers to it like any other object, not by the list. Once
+/// an observer is freed it no longer shows up when iterating, and its entry is removed by the
+/// next call to [`WeakObserverList::for_each_alive`] or [`WeakObserverList::prune`]. An
+/// [`ObserverPruner`] of the list called from a hook subscribed to the `Sleep` transition with
+/// [`Arena::subscribe_transitions`] removes entries as soon as their observer is freed.
+///
+/// Observers are visited in the order they were subscribed in. The list can also be a field of a
+/// GC object, its [`Trace`] implementation doesn't trace the observers.
+///
+/// Like with [`Anchored`](crate::Anchored), `T` is the type with a `'static` gc lifetime, like
+/// `Listener<'static, 'own>`.
+pub struct WeakObserverList<'own, T> {
+    entries: Rc<RefCell<Vec<Entry>>>,
+    _invariant: Invariant<'own>,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<'own, T: Trace<'own>> WeakObserverList<'own, T> {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        WeakObserverList {
+            entries: Rc::new(RefCell::new(Vec::new())),
+            _invariant: Invariant::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Add an observer to the end of the list.
+    ///
+    /// Subscribing the same observer twice visits it twice.
+    pub fn subscribe(&mut self, arena: &Arena<'own>, observer: Gc<'_, 'own, T::Gc<'_>>) {
+        let ptr = observer.into_gc_box().cast::<GcBox<()>>();
+        self.entries.borrow_mut().push(Entry {
+            ptr,
+            alive: unsafe { arena.unsafe_arena().liveness(ptr) },
+        });
+    }
+
+    /// Remove the first entry of the observer, returns false if the observer wasn't subscribed.
+    pub fn unsubscribe(&mut self, observer: Gc<'_, 'own, T::Gc<'_>>) -> bool {
+        let ptr = observer.into_gc_box().cast::<GcBox<()>>();
+        // Entries of freed observers might have the same address as a new object.
+        let mut entries = self.entries.borrow_mut();
+        match entries
+            .iter()
+            .position(|x| x.ptr == ptr && x.alive.is_alive())
+        {
+            Some(index) => {
+                entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Call the function with every observer which is still alive, in the order they were
+    /// subscribed in, removing the entries of freed observers.
+    pub fn for_each_alive<'gc, F>(&mut self, arena: &'gc Arena<'own>, mut f: F)
+    where
+        F: FnMut(Gc<'gc, 'own, T::Gc<'gc>>),
+    {
+        // Unswept objects might be unreachable, so an observer is only known to be alive once the
+        // sweep has finished.
+        unsafe { arena.unsafe_arena().finish_sweep() };
+        self.prune();
+        // The entries are not borrowed while calling the function, so it may run a hook which
+        // prunes the list.
+        let mut index = 0;
+        while let Some(ptr) = self.entries.borrow().get(index).map(|x| x.ptr) {
+            f(unsafe { Gc::from_gc_box(ptr.cast()) });
+            index += 1;
+        }
+    }
+
+    /// Remove the entries of freed observers, returning the number of entries removed.
+    pub fn prune(&mut self) -> usize {
+        self.pruner().prune()
+    }
+
+    /// Returns a handle which prunes the list, for pruning from places which can't borrow the
+    /// list like a transition hook.
+    pub fn pruner(&self) -> ObserverPruner {
+        ObserverPruner(self.entries.clone())
+    }
+
+    /// Returns the number of entries, including entries of observers which were freed since the
+    /// list was last pruned.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Returns true if the list has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+}
+
+/// Removes the entries of freed observers from a [`WeakObserverList`], created by
+/// [`WeakObserverList::pruner`].
+#[derive(Clone)]
+pub struct ObserverPruner(Rc<RefCell<Vec<Entry>>>);
+
+impl ObserverPruner {
+    /// Remove the entries of freed observers, returning the number of entries removed.
+    pub fn prune(&self) -> usize {
+        let mut entries = self.0.borrow_mut();
+        let before = entries.len();
+        entries.retain(|x| x.alive.is_alive());
+        before - entries.len()
+    }
+}
+
+impl<'own, T: Trace<'own>> Default for WeakObserverList<'own, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<'own, T: Trace<'own>> Trace<'own> for WeakObserverList<'own, T> {
+    type Gc<'to> = WeakObserverList<'own, T>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
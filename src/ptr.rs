@@ -1,25 +1,68 @@
-use std::{mem::ManuallyDrop, ptr::NonNull};
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem::ManuallyDrop,
+    ptr::NonNull,
+};
 
-use crate::{arena::Marker, marker::Covariant, sys::GcBox, Arena, Invariant, Owner, Trace};
+use crate::{
+    arena::Marker,
+    marker::Covariant,
+    sys::{erased_type_id, GcBox},
+    Arena, Error, GcTarget, GcTraitObject, Invariant, Owner, Trace,
+};
 
 /// A safe pointer to a GC allocated value.
+///
+/// The value can be unsized, like a trait object created with [`gc_coerce!`](crate::gc_coerce).
 #[repr(transparent)]
-pub struct Gc<'gc, 'own, T> {
+pub struct Gc<'gc, 'own, T: ?Sized> {
     ptr: NonNull<GcBox<T>>,
     _gc_marker: Covariant<'gc>,
     _cell_marker: Invariant<'own>,
 }
 
-impl<'gc, 'own, T> Clone for Gc<'gc, 'own, T> {
+impl<'gc, 'own, T: ?Sized> Clone for Gc<'gc, 'own, T> {
     fn clone(&self) -> Self {
         *self
     }
 }
-impl<'gc, 'own, T> Copy for Gc<'gc, 'own, T> {}
+impl<'gc, 'own, T: ?Sized> Copy for Gc<'gc, 'own, T> {}
 
-unsafe impl<'gc, 'own, T: Trace<'own>> Trace<'own> for Gc<'gc, 'own, T> {
+/// Pointers are compared by identity: two pointers are equal if they point to the same object, not
+/// if the values they point to are equal. Objects are never moved, so the comparison between two
+/// pointers doesn't change while the objects are alive.
+impl<'gc, 'own, T: ?Sized> PartialEq for Gc<'gc, 'own, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(*other)
+    }
+}
+impl<'gc, 'own, T: ?Sized> Eq for Gc<'gc, 'own, T> {}
+
+/// Hashes the address of the object, consistent with the identity based [`PartialEq`].
+impl<'gc, 'own, T: ?Sized> Hash for Gc<'gc, 'own, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.cast::<u8>().hash(state)
+    }
+}
+
+/// Orders pointers by the address of the object, which is arbitrary but stable while the objects
+/// are alive.
+impl<'gc, 'own, T: ?Sized> PartialOrd for Gc<'gc, 'own, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'gc, 'own, T: ?Sized> Ord for Gc<'gc, 'own, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cast::<u8>().cmp(&other.ptr.cast::<u8>())
+    }
+}
+
+unsafe impl<'gc, 'own, T: GcTarget<'own> + ?Sized> Trace<'own> for Gc<'gc, 'own, T> {
     type Gc<'a> = Gc<'a, 'own, T::Gc<'a>>;
 
+    #[inline]
     fn needs_trace() -> bool
     where
         Self: Sized,
@@ -27,12 +70,17 @@ unsafe impl<'gc, 'own, T: Trace<'own>> Trace<'own> for Gc<'gc, 'own, T> {
         true
     }
 
+    #[inline]
     fn trace(&self, marker: Marker<'own, '_>) {
-        marker.mark(*self);
+        T::mark(*self, marker);
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(*self)
     }
 }
 
-impl<'gc, 'own, T> Gc<'gc, 'own, T> {
+impl<'gc, 'own, T: ?Sized> Gc<'gc, 'own, T> {
     pub unsafe fn from_gc_box(ptr: NonNull<GcBox<T>>) -> Self {
         Gc {
             ptr,
@@ -45,12 +93,143 @@ impl<'gc, 'own, T> Gc<'gc, 'own, T> {
         self.ptr
     }
 
+    /// Erase the type of the pointer, see [`GcAny`].
+    pub fn erase(self) -> GcAny<'gc, 'own>
+    where
+        T: Erasable<'own>,
+    {
+        self.into()
+    }
+
+    /// Returns the number of bytes of the allocation of the object, including its header.
+    ///
+    /// The sizes of all objects in an arena add up to [`GcStats::total_allocated`](crate::GcStats).
+    pub fn allocation_size(self) -> usize {
+        unsafe {
+            let ptr = self.ptr.cast::<GcBox<()>>();
+            ptr.as_ref().data_ptr.v_table().allocation(ptr).0.size()
+        }
+    }
+
+    /// Returns the id of the object, which is unique for the lifetime of the arena which allocated
+    /// it.
+    ///
+    /// Ids are assigned in allocation order and never reused, not even after the object is freed.
+    /// Unlike the address of the object, the id is stable across collections and can be logged
+    /// to follow an object through a program.
+    #[cfg(feature = "object-id")]
+    pub fn object_id(self) -> ObjectId {
+        unsafe { ObjectId(self.ptr.as_ref().object_id) }
+    }
+
+    /// Returns the tag word of the object, a word of metadata stored in the header of the object
+    /// which embedders can use freely, like for a shape pointer or a cached hash.
+    ///
+    /// The word is 0 when the object is allocated.
+    #[cfg(feature = "tag-word")]
+    pub fn tag_word(self, owner: &Owner<'own>) -> usize {
+        let _owner = owner;
+        unsafe { self.ptr.as_ref().tag_word.get() }
+    }
+
+    /// Set the tag word of the object, see [`Gc::tag_word`].
+    ///
+    /// The word is never traced, so it must not hold a GC pointer, not even one converted to an
+    /// integer: the object it points to could be freed while the word still refers to it. With
+    /// the `arena-id` feature debug builds panic if the word is the address of an object in the
+    /// arena, which checks every object of the arena.
+    ///
+    /// Setting the word doesn't need a write barrier, and isn't undone when a speculation is
+    /// rolled back.
+    #[cfg(feature = "tag-word")]
+    pub fn set_tag_word(self, owner: &mut Owner<'own>, arena: &Arena<'own>, word: usize) {
+        owner.bump_epoch();
+        #[cfg(all(feature = "arena-id", debug_assertions))]
+        assert!(
+            !arena.unsafe_arena().is_object_address(word),
+            "the tag word of an object was set to the address of an object, tag words are not \
+             traced and must not hold GC pointers"
+        );
+        let _arena = arena;
+        unsafe { self.ptr.as_ref().tag_word.set(word) }
+    }
+
+    /// Returns true if both pointers point to the same object.
+    pub fn ptr_eq(self, other: Gc<'_, 'own, T>) -> bool {
+        self.ptr.cast::<u8>() == other.ptr.cast::<u8>()
+    }
+
     /// Borrow the contained value.
     pub fn borrow<'a>(self, owner: &'a Owner<'own>) -> &'a T {
         let _owner = owner;
+        #[cfg(all(feature = "root-provenance", debug_assertions))]
+        crate::sys::warn_unrooted(self.ptr.cast());
 
         unsafe { &(*self.ptr.as_ref().value.get()) }
     }
+
+    /// Change the type of the pointer with an unsizing coercion, use
+    /// [`gc_coerce!`](crate::gc_coerce) instead.
+    ///
+    /// # Safety
+    /// The function must return the pointer it is given, only changing its type with a coercion.
+    #[doc(hidden)]
+    pub unsafe fn coerce_with<U: GcTraitObject + ?Sized, F>(self, f: F) -> Gc<'gc, 'own, U>
+    where
+        F: FnOnce(NonNull<GcBox<T>>) -> NonNull<GcBox<U>>,
+    {
+        Gc::from_gc_box(f(self.ptr))
+    }
+}
+
+impl<'gc, 'own, T> Gc<'gc, 'own, [T]> {
+    /// Returns the number of items in the slice.
+    ///
+    /// Doesn't need the owner as the length of a slice object can't change.
+    pub fn len(self) -> usize {
+        (self.ptr.as_ptr() as *mut [T]).len()
+    }
+
+    /// Returns true if the slice contains no items.
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'gc, 'own> Gc<'gc, 'own, str> {
+    /// Returns the length of the string in bytes.
+    ///
+    /// Doesn't need the owner as the length of a string object can't change.
+    pub fn len(self) -> usize {
+        (self.ptr.as_ptr() as *mut [u8]).len()
+    }
+
+    /// Returns true if the string has a length of zero bytes.
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'gc, 'own, T> Gc<'gc, 'own, T> {
+    /// Convert the pointer into an untyped raw pointer, for storing it in places which can't hold
+    /// a [`Gc`], like user data slots of a C API.
+    ///
+    /// The raw pointer does not keep the object alive. It is only valid for as long as the object
+    /// is rooted or otherwise reachable from a root. Turn it back into a pointer with
+    /// [`Gc::from_raw`].
+    pub fn into_raw(self) -> NonNull<()> {
+        self.ptr.cast()
+    }
+
+    /// Create a pointer from a raw pointer returned by [`Gc::into_raw`].
+    ///
+    /// # Safety
+    /// The raw pointer must have been returned by [`Gc::into_raw`] on a pointer to a value of
+    /// type `T`, allocated by an arena with the owner `'own`. The object must still be alive and
+    /// must be kept alive, by a root or by being reachable from one, for as long as `'gc`.
+    pub unsafe fn from_raw(ptr: NonNull<()>) -> Self {
+        Gc::from_gc_box(ptr.cast())
+    }
 }
 
 impl<'gc, 'own, T: Trace<'own>> Gc<'gc, 'own, T> {
@@ -59,47 +238,292 @@ impl<'gc, 'own, T: Trace<'own>> Gc<'gc, 'own, T> {
         owner: &'a mut Owner<'own>,
         arena: &Arena<'own>,
     ) -> &'a mut T::Gc<'a> {
-        let _owner = owner;
+        owner.bump_epoch();
         arena.write_barrier(self);
-        unsafe {
-            let ptr = self
-                .ptr
-                .as_ref()
-                .value
-                .get()
-                .cast::<ManuallyDrop<T::Gc<'a>>>();
-
-            &mut (*ptr)
-        }
+        unsafe { self.value_mut() }
+    }
+
+    /// Borrow the value mutably like [`Gc::borrow_mut`], returning an error instead of panicking
+    /// if the object can't be logged by a running speculation.
+    pub fn try_borrow_mut<'a>(
+        self,
+        owner: &'a mut Owner<'own>,
+        arena: &Arena<'own>,
+    ) -> Result<&'a mut T::Gc<'a>, Error> {
+        arena.try_write_barrier(self)?;
+        owner.bump_epoch();
+        Ok(unsafe { self.value_mut() })
+    }
+
+    /// Overwrite the value, applying the write barrier if the type needs tracing.
+    ///
+    /// The value is bound to the borrow of the owner, like the reference returned by
+    /// [`Gc::borrow_mut`].
+    pub fn set<'a>(self, owner: &'a mut Owner<'own>, arena: &Arena<'own>, value: T::Gc<'a>)
+    where
+        T: 'a,
+    {
+        self.replace(owner, arena, value);
+    }
+
+    /// Overwrite the value, applying the write barrier if the type needs tracing, and return the
+    /// old value.
+    ///
+    /// The old value is no longer reachable through this pointer, so it is bound to the borrow of
+    /// the owner and can't be kept across a collection unless the pointers in it are rooted.
+    pub fn replace<'a>(
+        self,
+        owner: &'a mut Owner<'own>,
+        arena: &Arena<'own>,
+        value: T::Gc<'a>,
+    ) -> T::Gc<'a>
+    where
+        T: 'a,
+    {
+        owner.bump_epoch();
+        arena.write_barrier(self);
+        unsafe { std::mem::replace(self.value_mut(), value) }
+    }
+
+    /// Swap the values of two pointers, applying the write barrier to both if the type needs
+    /// tracing.
+    ///
+    /// # Panic
+    /// Panics if both pointers point to the same object.
+    pub fn swap(a: Self, b: Gc<'_, 'own, T>, owner: &mut Owner<'own>, arena: &Arena<'own>) {
+        assert!(
+            !a.ptr_eq(b),
+            "called `Gc::swap` with two pointers to the same object"
+        );
+        owner.bump_epoch();
+        arena.write_barrier(a);
+        arena.write_barrier(b);
+        unsafe { std::mem::swap(a.value_mut(), b.value_mut()) }
     }
 
     pub fn borrow_mut_untraced<'a>(self, owner: &'a mut Owner<'own>) -> &'a mut T::Gc<'a> {
-        let _owner = owner;
         assert!(
             !T::needs_trace(),
             "called `borrow_mut_untraced` on a pointer to a type which needs tracing"
         );
-        unsafe {
-            let ptr = self
-                .ptr
-                .as_ref()
-                .value
-                .get()
-                .cast::<ManuallyDrop<T::Gc<'a>>>();
-
-            &mut (*ptr)
-        }
+        owner.bump_epoch();
+        unsafe { self.value_mut() }
     }
 
     pub unsafe fn borrow_mut_no_barrier<'a>(self, owner: &'a mut Owner<'own>) -> &'a mut T::Gc<'a> {
+        owner.bump_epoch();
+        self.value_mut()
+    }
+
+    /// Returns the value rebound to the lifetime `'a`.
+    ///
+    /// # Safety
+    /// Caller must ensure the value is not borrowed elsewhere for `'a`.
+    pub(crate) unsafe fn value_mut<'a>(self) -> &'a mut T::Gc<'a> {
+        #[cfg(all(feature = "root-provenance", debug_assertions))]
+        crate::sys::warn_unrooted(self.ptr.cast());
+        let ptr: *mut ManuallyDrop<T> = self.ptr.as_ref().value.get();
+        // `ManuallyDrop` is transparent and `T::Gc<'a>` only differs from `T` in lifetimes.
+        let ptr = ptr.cast::<T::Gc<'a>>();
+        &mut *ptr
+    }
+}
+
+/// A GC pointer to a value of any type.
+///
+/// Created with [`Gc::erase`] and turned back into a typed pointer with [`GcAny::downcast`].
+/// Implements [`Trace`] so values of different types can be stored together in GC objects.
+///
+/// Only pointers to types implementing [`Erasable`] can be erased. The type of the value is
+/// identified by its [`TypeId`](std::any::TypeId), which doesn't distinguish lifetimes, so types
+/// which only differ in their gc lifetime, like `Node<'a, 'own>` and `Node<'b, 'own>`, are the
+/// same type for downcasting. This allows downcasting to types which are not `'static`, which is
+/// required for any type containing GC pointers.
+#[derive(Clone, Copy)]
+pub struct GcAny<'gc, 'own> {
+    ptr: NonNull<GcBox<()>>,
+    _gc_marker: Covariant<'gc>,
+    _cell_marker: Invariant<'own>,
+}
+
+impl<'gc, 'own> GcAny<'gc, 'own> {
+    /// Returns the name of the type of the value.
+    pub fn type_name(self) -> &'static str {
+        unsafe { (self.ptr.as_ref().data_ptr.v_table().type_name)() }
+    }
+
+    /// Returns the value formatted as a string if it is a leaf, see [`Trace::fmt_leaf`].
+    pub fn fmt_leaf(self, owner: &Owner<'own>) -> Option<String> {
         let _owner = owner;
-        let ptr = self
-            .ptr
-            .as_ref()
-            .value
-            .get()
-            .cast::<ManuallyDrop<T::Gc<'a>>>();
-
-        &mut (*ptr)
+        let mut out = String::new();
+        let v_table = unsafe { self.ptr.as_ref().data_ptr.v_table() };
+        match unsafe { (v_table.fmt_leaf)(self.ptr.as_ptr(), &mut out) } {
+            Some(Ok(())) => Some(out),
+            _ => None,
+        }
+    }
+
+    /// Returns the pointer as a pointer to `T` if it points to a value of type `T`.
+    pub fn downcast<T: Trace<'own> + Erasable<'own>>(self) -> Option<Gc<'gc, 'own, T::Gc<'gc>>> {
+        let v_table = unsafe { self.ptr.as_ref().data_ptr.v_table() };
+        // Type ids don't distinguish lifetimes, but `Erasable` types can only differ in the gc
+        // lifetime, which the returned pointer is bound to.
+        if (v_table.type_id)() == erased_type_id::<T::Gc<'static>>() {
+            Some(unsafe { Gc::from_gc_box(self.ptr.cast()) })
+        } else {
+            None
+        }
+    }
+
+    /// Create an erased pointer from a pointer to an object of any type.
+    ///
+    /// # Safety
+    /// The pointer must point to an object which is alive for `'gc`.
+    pub unsafe fn from_gc_box(ptr: NonNull<GcBox<()>>) -> Self {
+        GcAny {
+            ptr,
+            _gc_marker: Covariant::new(),
+            _cell_marker: Invariant::new(),
+        }
+    }
+
+    pub fn into_gc_box(self) -> NonNull<GcBox<()>> {
+        self.ptr
+    }
+
+    /// Returns the id of the object, see [`Gc::object_id`].
+    #[cfg(feature = "object-id")]
+    pub fn object_id(self) -> ObjectId {
+        unsafe { ObjectId(self.ptr.as_ref().object_id) }
+    }
+
+    /// Returns true if both pointers point to the same object.
+    pub fn ptr_eq(self, other: GcAny<'_, 'own>) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for GcAny<'gc, 'own> {
+    type Gc<'a> = GcAny<'a, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark_any(*self);
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+impl<'gc, 'own, T: Erasable<'own> + ?Sized> From<Gc<'gc, 'own, T>> for GcAny<'gc, 'own> {
+    fn from(value: Gc<'gc, 'own, T>) -> Self {
+        unsafe { GcAny::from_gc_box(value.ptr.cast()) }
+    }
+}
+
+/// Types which can be identified by their [`TypeId`](std::any::TypeId) after a pointer to them
+/// is erased into a [`GcAny`].
+///
+/// # Safety
+/// The type must not contain lifetimes other than its gc lifetime, `'own` and `'static`, and every
+/// type which only differs from it in lifetimes must implement the trait as well. Type ids don't
+/// distinguish lifetimes, so a pointer to a type which borrows data, like `&'a String`, could
+/// otherwise be downcast to the same type borrowing the data for longer.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be identified after its type is erased",
+    note = "implement `Erasable` for types without lifetimes other than their gc lifetime and `'own`"
+)]
+pub unsafe trait Erasable<'own> {}
+
+macro_rules! impl_erasable {
+    ($($name:ty),*$(,)*) => {
+        $(
+            unsafe impl<'own> Erasable<'own> for $name {}
+        )*
+    };
+}
+
+impl_erasable!(
+    (),
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    str,
+    String
+);
+
+macro_rules! impl_erasable_tuple {
+    ($($name:ident),*) => {
+        unsafe impl<'own, $($name: Erasable<'own>),*> Erasable<'own> for ($($name,)*) {}
+    };
+}
+
+impl_erasable_tuple!(A);
+impl_erasable_tuple!(A, B);
+impl_erasable_tuple!(A, B, C);
+impl_erasable_tuple!(A, B, C, D);
+impl_erasable_tuple!(A, B, C, D, E);
+impl_erasable_tuple!(A, B, C, D, E, F);
+impl_erasable_tuple!(A, B, C, D, E, F, G);
+impl_erasable_tuple!(A, B, C, D, E, F, G, H);
+
+unsafe impl<'gc, 'own, T: Erasable<'own> + ?Sized> Erasable<'own> for Gc<'gc, 'own, T> {}
+unsafe impl<'gc, 'own> Erasable<'own> for GcAny<'gc, 'own> {}
+unsafe impl<'own, T: Erasable<'own>> Erasable<'own> for [T] {}
+unsafe impl<'own, T: Erasable<'own>, const N: usize> Erasable<'own> for [T; N] {}
+unsafe impl<'own, T: Erasable<'own>> Erasable<'own> for Option<T> {}
+unsafe impl<'own, T: Erasable<'own>, E: Erasable<'own>> Erasable<'own> for Result<T, E> {}
+unsafe impl<'own, T: Erasable<'own>> Erasable<'own> for Vec<T> {}
+unsafe impl<'own, T: Erasable<'own>> Erasable<'own> for std::collections::VecDeque<T> {}
+unsafe impl<'own, T: Erasable<'own> + ?Sized> Erasable<'own> for Box<T> {}
+unsafe impl<'own, K: Erasable<'own>, V: Erasable<'own>> Erasable<'own>
+    for std::collections::HashMap<K, V>
+{
+}
+unsafe impl<'own, K: Erasable<'own>, V: Erasable<'own>> Erasable<'own>
+    for std::collections::BTreeMap<K, V>
+{
+}
+
+/// The id of an object, unique for the lifetime of the arena which allocated it, returned by
+/// [`Gc::object_id`].
+///
+/// Ids are ordered by allocation, an object allocated later has a larger id.
+#[cfg(feature = "object-id")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(u64);
+
+#[cfg(feature = "object-id")]
+impl ObjectId {
+    /// Returns the id as an integer.
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(feature = "object-id")]
+impl std::fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
     }
 }
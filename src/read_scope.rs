@@ -0,0 +1,163 @@
+//! Reading the heap from several threads at once.
+
+use std::{marker::PhantomData, thread};
+
+use crate::{Arena, Gc, GcTarget, Invariant, Owner, Trace};
+
+/// Types which can be read from several threads at once while the heap can't be mutated, see
+/// [`Arena::scope_read`].
+///
+/// # Safety
+/// The type must be [`Sync`] apart from the GC pointers it contains, and those pointers may only
+/// point to types implementing this trait. Types with interior mutability which doesn't require
+/// the owner, like [`GcCell`](crate::GcCell), must not implement it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be read from several threads at once",
+    note = "implement `ReadSync` for types which are `Sync` apart from the GC pointers they contain"
+)]
+pub unsafe trait ReadSync<'own> {}
+
+macro_rules! impl_read_sync {
+    ($($name:ty),*$(,)*) => {
+        $(
+            unsafe impl<'own> ReadSync<'own> for $name {}
+        )*
+    };
+}
+
+impl_read_sync!(
+    (),
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    str,
+    String
+);
+
+unsafe impl<'gc, 'own, T: ReadSync<'own> + ?Sized> ReadSync<'own> for Gc<'gc, 'own, T> {}
+unsafe impl<'own, T: ReadSync<'own>> ReadSync<'own> for [T] {}
+unsafe impl<'own, T: ReadSync<'own>, const N: usize> ReadSync<'own> for [T; N] {}
+unsafe impl<'own, T: ReadSync<'own>> ReadSync<'own> for Option<T> {}
+unsafe impl<'own, T: ReadSync<'own>> ReadSync<'own> for Vec<T> {}
+unsafe impl<'own, T: ReadSync<'own> + ?Sized> ReadSync<'own> for Box<T> {}
+unsafe impl<'own, A: ReadSync<'own>, B: ReadSync<'own>> ReadSync<'own> for (A, B) {}
+unsafe impl<'own, A: ReadSync<'own>, B: ReadSync<'own>, C: ReadSync<'own>> ReadSync<'own>
+    for (A, B, C)
+{
+}
+
+/// A scope for spawning threads which read the heap, created by [`Arena::scope_read`].
+#[derive(Clone, Copy)]
+pub struct ReadScope<'s, 'env, 'own> {
+    scope: &'s thread::Scope<'s, 'env>,
+    // Also implies `'own: 's`.
+    _invariant: PhantomData<&'s Invariant<'own>>,
+}
+
+impl<'s, 'env, 'own> ReadScope<'s, 'env, 'own> {
+    /// Spawn a scoped thread which is given a token for reading the heap.
+    ///
+    /// Pointers are passed to the thread as [`SharedGc`]. All threads are joined before
+    /// [`Arena::scope_read`] returns.
+    pub fn spawn<T, F>(self, f: F) -> thread::ScopedJoinHandle<'s, T>
+    where
+        F: FnOnce(ReadToken<'s, 'own>) -> T + Send + 's,
+        T: Send + 's,
+    {
+        self.scope.spawn(move || f(self.token()))
+    }
+
+    /// Returns a token for reading the heap on the current thread.
+    pub fn token(self) -> ReadToken<'s, 'own> {
+        ReadToken {
+            _invariant: PhantomData,
+        }
+    }
+}
+
+/// Grants shared access to [`ReadSync`] objects for the duration of a [`ReadScope`].
+///
+/// Unlike the owner a token can be sent to and shared between threads.
+#[derive(Clone, Copy)]
+pub struct ReadToken<'s, 'own> {
+    _invariant: PhantomData<&'s Invariant<'own>>,
+}
+
+impl<'s, 'own> ReadToken<'s, 'own> {
+    /// Borrow the value of a pointer for the duration of the scope.
+    pub fn borrow<T>(self, gc: Gc<'_, 'own, T>) -> &'s T::Gc<'s>
+    where
+        T: GcTarget<'own> + ReadSync<'own> + ?Sized,
+    {
+        // No object can be mutated or freed while the scope is alive, and the value can be read
+        // from several threads at once as it implements `ReadSync`.
+        unsafe {
+            let gc: Gc<'s, 'own, T::Gc<'s>> = gc.rebind();
+            &*(*gc.into_gc_box().as_ptr()).value.get()
+        }
+    }
+
+    /// Wrap a pointer so it can be sent to another thread of the scope.
+    pub fn share<T>(self, gc: Gc<'_, 'own, T>) -> SharedGc<'s, 'own, T::Gc<'s>>
+    where
+        T: GcTarget<'own> + ReadSync<'own> + ?Sized,
+    {
+        SharedGc(unsafe { gc.rebind() })
+    }
+}
+
+/// A GC pointer which can be sent to other threads of a [`ReadScope`], created by
+/// [`ReadToken::share`].
+pub struct SharedGc<'s, 'own, T: ?Sized>(Gc<'s, 'own, T>);
+
+impl<T: ?Sized> Clone for SharedGc<'_, '_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for SharedGc<'_, '_, T> {}
+
+unsafe impl<'own, T: ReadSync<'own> + ?Sized> Send for SharedGc<'_, 'own, T> {}
+unsafe impl<'own, T: ReadSync<'own> + ?Sized> Sync for SharedGc<'_, 'own, T> {}
+
+impl<'s, 'own, T: ?Sized> SharedGc<'s, 'own, T> {
+    /// Returns the pointer, for reading it with a [`ReadToken`].
+    pub fn get(self) -> Gc<'s, 'own, T> {
+        self.0
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Read the heap from several threads at once.
+    ///
+    /// The function is given a [`ReadScope`] for spawning threads, which read objects with a
+    /// [`ReadToken`]. The arena is borrowed mutably and the owner can only be borrowed immutably
+    /// for the duration of the call, so no object is mutated, allocated or collected while the
+    /// threads run. Only [`ReadSync`] objects can be read by the threads.
+    pub fn scope_read<'env, R, F>(&mut self, owner: &Owner<'own>, f: F) -> R
+    where
+        'own: 'env,
+        F: for<'s> FnOnce(ReadScope<'s, 'env, 'own>) -> R,
+    {
+        let _owner = owner;
+        thread::scope(|scope| {
+            f(ReadScope {
+                scope,
+                _invariant: PhantomData,
+            })
+        })
+    }
+}
@@ -0,0 +1,83 @@
+//! Dispatch of [`rebind!`](crate::rebind) to [`Arena::rebind_gc`] for GC pointers and to
+//! [`Arena::rebind_value`] for other values.
+//!
+//! The dispatch relies on method resolution preferring methods which don't need an extra
+//! reference to the receiver: [`ProbeGc`] is implemented for the probe of a pointer itself while
+//! [`ProbeValue`] is implemented for a reference to any probe, so it is only picked if the value
+//! is not a pointer.
+
+use crate::{Arena, Gc, GcTarget, Trace};
+
+/// Borrows the value passed to `rebind!` to find the method to rebind it with.
+#[doc(hidden)]
+pub struct RebindProbe<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub struct RebindGcTag;
+
+#[doc(hidden)]
+pub struct RebindValueTag;
+
+#[doc(hidden)]
+pub trait ProbeGc {
+    fn rebind_tag(&self) -> RebindGcTag {
+        RebindGcTag
+    }
+}
+
+impl<T: ?Sized> ProbeGc for RebindProbe<'_, Gc<'_, '_, T>> {}
+
+#[doc(hidden)]
+pub trait ProbeValue {
+    fn rebind_tag(&self) -> RebindValueTag {
+        RebindValueTag
+    }
+}
+
+impl<T> ProbeValue for &RebindProbe<'_, T> {}
+
+/// Values which can be rebound to an arena with [`rebind!`](crate::rebind).
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be rebound to an arena",
+    label = "`{Self}` does not implement `Trace`",
+    note = "`rebind!` only accepts GC pointers and values implementing `Trace`"
+)]
+#[doc(hidden)]
+pub trait Rebindable<'own> {
+    type Rebound<'gc>
+    where
+        'own: 'gc;
+
+    fn rebind_to<'gc>(self, arena: &'gc Arena<'own>) -> Self::Rebound<'gc>;
+}
+
+impl<'own, T: Trace<'own>> Rebindable<'own> for T {
+    type Rebound<'gc>
+        = T::Gc<'gc>
+    where
+        'own: 'gc;
+
+    fn rebind_to<'gc>(self, arena: &'gc Arena<'own>) -> T::Gc<'gc> {
+        arena.rebind_value(self)
+    }
+}
+
+impl RebindGcTag {
+    pub fn rebind<'gc, 'own, T: GcTarget<'own> + ?Sized>(
+        self,
+        arena: &'gc Arena<'own>,
+        value: Gc<'_, 'own, T>,
+    ) -> Gc<'gc, 'own, T::Gc<'gc>> {
+        arena.rebind_gc(value)
+    }
+}
+
+impl RebindValueTag {
+    pub fn rebind<'gc, 'own, T: Rebindable<'own>>(
+        self,
+        arena: &'gc Arena<'own>,
+        value: T,
+    ) -> T::Rebound<'gc> {
+        value.rebind_to(arena)
+    }
+}
@@ -0,0 +1,114 @@
+//! Host resources held by GC objects, tracked by their arena.
+
+use std::{
+    collections::BTreeMap,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use crate::{sys::ResourceLedger, Arena, Marker, Trace};
+
+/// A kind of host resource, like a file descriptor or a GPU buffer, which can be held by a
+/// [`Resource`].
+pub trait ResourceKind {
+    /// The name of the kind, reported by [`Arena::open_resources`].
+    const KIND: &'static str;
+}
+
+/// A host resource held by a GC object, recorded in the ledger of its arena while it is open.
+///
+/// A resource is open until it is closed with [`Resource::close`] or dropped, including when the
+/// collector frees the object containing it. [`Arena::open_resources`] reports the open
+/// resources, and [`Arena::set_audit_resources`] turns resources which are only dropped when the
+/// arena is dropped into a panic.
+pub struct Resource<T: ResourceKind> {
+    value: Option<T>,
+    id: u64,
+    ledger: Rc<ResourceLedger>,
+}
+
+unsafe impl<'own, T: ResourceKind + 'static> Trace<'own> for Resource<T> {
+    type Gc<'gc> = Resource<T>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+impl<T: ResourceKind> Resource<T> {
+    /// Open a resource, recording it in the ledger of the arena.
+    pub fn new(arena: &Arena<'_>, value: T) -> Self {
+        let ledger = arena.unsafe_arena().resources().clone();
+        Resource {
+            value: Some(value),
+            id: ledger.open(T::KIND),
+            ledger,
+        }
+    }
+
+    /// Close the resource, returning the value so it can be released.
+    ///
+    /// Returns `None` if the resource was already closed.
+    pub fn close(&mut self) -> Option<T> {
+        let value = self.value.take()?;
+        self.ledger.close(self.id);
+        Some(value)
+    }
+
+    /// Returns true if the resource was closed.
+    pub fn is_closed(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Returns the value of the resource, or `None` if it was closed.
+    pub fn get(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+}
+
+impl<T: ResourceKind> Deref for Resource<T> {
+    type Target = T;
+
+    /// # Panic
+    /// Panics if the resource was closed.
+    fn deref(&self) -> &T {
+        self.value.as_ref().expect("resource was already closed")
+    }
+}
+
+impl<T: ResourceKind> DerefMut for Resource<T> {
+    /// # Panic
+    /// Panics if the resource was closed.
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("resource was already closed")
+    }
+}
+
+impl<T: ResourceKind> Drop for Resource<T> {
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            self.ledger.close(self.id);
+        }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Returns the number of open resources of every kind.
+    ///
+    /// Resources held by unreachable objects are open until the objects are freed.
+    pub fn open_resources(&self) -> BTreeMap<&'static str, usize> {
+        self.unsafe_arena().resources().count_by_kind()
+    }
+
+    /// Enable or disable the resource audit, see [`UnsafeArena::set_audit_resources`].
+    ///
+    /// [`UnsafeArena::set_audit_resources`]: crate::sys::UnsafeArena::set_audit_resources
+    pub fn set_audit_resources(&mut self, audit: bool) {
+        self.unsafe_arena().set_audit_resources(audit)
+    }
+}
@@ -0,0 +1,155 @@
+//! Rooting a growable set of pointers with a single guard.
+
+use std::{
+    cell::{Cell, RefCell, UnsafeCell},
+    pin::Pin,
+    ptr::NonNull,
+};
+
+use crate::{sys::GcBox, Arena, Gc, GcAny, GcTarget, Invariant, Marker, RootGuard, Trace};
+
+/// The object rooted by a [`RootVec`], holding the pointers pushed to it.
+struct Slots<'own>(RefCell<Vec<GcAny<'static, 'own>>>);
+
+unsafe impl<'own> Trace<'own> for Slots<'own> {
+    type Gc<'to> = Slots<'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        for ptr in self.0.borrow().iter() {
+            marker.mark_any(*ptr);
+        }
+    }
+}
+
+/// A root guard which roots any number of pointers, for keeping temporaries alive whose number
+/// isn't known up front, like the intermediate values of evaluating an expression.
+///
+/// Like [`RootGuard`] it has to be pinned before use. Pointers pushed with [`RootVec::push`] are
+/// bound to the lifetime of the pin borrow and stay rooted until the vector is cleared, truncated
+/// or dropped, which requires that no pushed pointer is still in use.
+///
+/// The pointers are stored in a single GC object which is rooted by the guard of the vector, so
+/// a root scan only visits one root no matter how many pointers were pushed.
+///
+/// # Usage
+/// ```
+/// # use std::pin::pin;
+/// # use dreck::*;
+/// dreck!(owner, arena);
+///
+/// let mut roots = pin!(RootVec::new());
+/// for statement in 0..3 {
+///     let mut sum = 0;
+///     for i in 0..10 {
+///         let temp = roots.as_ref().push(&arena, arena.add(statement * i));
+///         arena.collect_full(&owner);
+///         sum += *temp.borrow(&owner);
+///     }
+///     assert_eq!(sum, statement * 45);
+///     roots.as_mut().clear();
+/// }
+/// ```
+pub struct RootVec<'own> {
+    guard: UnsafeCell<RootGuard>,
+    slots: Cell<Option<NonNull<GcBox<Slots<'own>>>>>,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> RootVec<'own> {
+    /// Create an empty vector.
+    pub fn new() -> Self {
+        RootVec {
+            guard: UnsafeCell::new(RootGuard::new()),
+            slots: Cell::new(None),
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Root a pointer, returning the pointer bound to the lifetime of the vector borrow.
+    pub fn push<'r, T: GcTarget<'own> + ?Sized>(
+        self: Pin<&'r Self>,
+        arena: &Arena<'own>,
+        value: Gc<'_, 'own, T>,
+    ) -> Gc<'r, 'own, T::Gc<'r>> {
+        let slots = self.slots(arena);
+        unsafe {
+            // Roots are only scanned when a cycle starts and the slots might already have been
+            // traced, so a pointer pushed during tracing must be marked here.
+            arena
+                .unsafe_arena()
+                .mark_erased(Gc::into_gc_box(value).cast());
+            slots
+                .borrow_mut()
+                .push(GcAny::from_gc_box(Gc::into_gc_box(value).cast()));
+            value.rebind()
+        }
+    }
+
+    /// Returns the number of rooted pointers.
+    pub fn len(&self) -> usize {
+        self.with_slots(|x| x.len())
+    }
+
+    /// Returns true if no pointers are rooted.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Unroot all pointers, keeping the allocated slots for reuse.
+    pub fn clear(self: Pin<&mut Self>) {
+        self.truncate(0)
+    }
+
+    /// Unroot all pointers except the first `len` pushed.
+    pub fn truncate(self: Pin<&mut Self>, len: usize) {
+        self.with_slots(|x| x.truncate(len))
+    }
+
+    fn with_slots<R>(&self, f: impl FnOnce(&mut Vec<GcAny<'static, 'own>>) -> R) -> R {
+        match self.slots.get() {
+            Some(slots) => unsafe { f(&mut Self::slots_ref(slots).borrow_mut()) },
+            None => f(&mut Vec::new()),
+        }
+    }
+
+    /// Returns the rooted slots, allocating and rooting the slots object on first use.
+    fn slots(self: Pin<&Self>, arena: &Arena<'own>) -> &RefCell<Vec<GcAny<'static, 'own>>> {
+        let ptr = match self.slots.get() {
+            Some(ptr) => ptr,
+            None => unsafe {
+                // Safe because the guard is only borrowed here, once, while it isn't rooting
+                // anything.
+                let guard = Pin::new_unchecked(&mut *self.guard.get());
+                let slots = arena.root(arena.add(Slots(RefCell::new(Vec::new()))), guard);
+                let ptr = Gc::into_gc_box(slots);
+                self.slots.set(Some(ptr));
+                ptr
+            },
+        };
+        unsafe { Self::slots_ref(ptr) }
+    }
+
+    /// Returns the pointers held by the slots object.
+    ///
+    /// # Safety
+    /// The pointer must be the slots object rooted by a vector which lives for `'a`.
+    unsafe fn slots_ref<'a>(
+        ptr: NonNull<GcBox<Slots<'own>>>,
+    ) -> &'a RefCell<Vec<GcAny<'static, 'own>>> {
+        // The slots object is never handed out, so the value can't be borrowed through the owner.
+        &(&*ptr.as_ref().value.get()).0
+    }
+}
+
+impl<'own> Default for RootVec<'own> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,101 @@
+//! GC pointers bundled with the owner.
+
+use std::{
+    fmt::{self, Debug, Display},
+    ops::Deref,
+};
+
+use crate::{Gc, Owner};
+
+/// A GC pointer bundled with a borrow of the owner, which can be used like a reference to the
+/// value.
+///
+/// Created with [`Gc::with`]. Dereferences to the value pointed to and implements [`Debug`],
+/// [`Display`], [`PartialEq`] and [`AsRef`] by delegating to it, so GC values can be passed to
+/// code which only understands ordinary references.
+///
+/// Despite the name the pointer is not rooted in the arena. It is kept alive because the arena is
+/// borrowed for `'a` and the owner borrow prevents the value from being mutated, just like with
+/// [`Gc::borrow`].
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// dreck!(owner, arena);
+///
+/// let name = arena.add(String::from("dreck"));
+/// assert_eq!(format!("hello {}", name.with(&owner)), "hello dreck");
+/// assert_eq!(name.with(&owner).len(), 5);
+/// ```
+pub struct Rooted<'a, 'own, T> {
+    owner: &'a Owner<'own>,
+    ptr: Gc<'a, 'own, T>,
+}
+
+impl<'a, 'own, T> Clone for Rooted<'a, 'own, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, 'own, T> Copy for Rooted<'a, 'own, T> {}
+
+impl<'a, 'own, T> Rooted<'a, 'own, T> {
+    /// Returns the pointer.
+    pub fn gc(self) -> Gc<'a, 'own, T> {
+        self.ptr
+    }
+
+    /// Returns the owner.
+    pub fn owner(self) -> &'a Owner<'own> {
+        self.owner
+    }
+
+    /// Returns a reference to the value which lives as long as the bundle.
+    pub fn get(self) -> &'a T {
+        self.ptr.borrow(self.owner)
+    }
+}
+
+impl<'a, 'own, T> Deref for Rooted<'a, 'own, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<'a, 'own, T: Debug> Debug for Rooted<'a, 'own, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<'a, 'own, T: Display> Display for Rooted<'a, 'own, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+/// Compares the values pointed to, unlike [`Gc`] which is compared by identity.
+impl<'a, 'b, 'own, T: PartialEq<U>, U> PartialEq<Rooted<'b, 'own, U>> for Rooted<'a, 'own, T> {
+    fn eq(&self, other: &Rooted<'b, 'own, U>) -> bool {
+        self.get() == other.get()
+    }
+}
+impl<'a, 'own, T: Eq> Eq for Rooted<'a, 'own, T> {}
+
+impl<'a, 'own, T: AsRef<U>, U: ?Sized> AsRef<U> for Rooted<'a, 'own, T> {
+    fn as_ref(&self) -> &U {
+        self.get().as_ref()
+    }
+}
+
+impl<'gc, 'own, T> Gc<'gc, 'own, T> {
+    /// Bundle the pointer with the owner, see [`Rooted`].
+    pub fn with<'a>(self, owner: &'a Owner<'own>) -> Rooted<'a, 'own, T>
+    where
+        'gc: 'a,
+    {
+        Rooted { owner, ptr: self }
+    }
+}
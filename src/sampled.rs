@@ -0,0 +1,126 @@
+//! A field wrapper which can be read from other threads without the owner.
+
+use std::{
+    cell::UnsafeCell,
+    hint,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{fence, AtomicUsize, Ordering},
+};
+
+use crate::{Invariant, Marker, Owner, Trace};
+
+/// Plain data types which never contain GC pointers.
+///
+/// # Safety
+/// Implementing types must not contain any GC pointers or other references into an arena.
+pub unsafe trait NoGc: Copy + Send {}
+
+macro_rules! impl_no_gc {
+    ($($name:ty),*$(,)*) => {
+        $(
+            unsafe impl NoGc for $name {}
+        )*
+    };
+}
+
+impl_no_gc!(
+    (),
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char
+);
+
+unsafe impl<T: NoGc, const N: usize> NoGc for [T; N] {}
+unsafe impl<T: NoGc> NoGc for Option<T> {}
+unsafe impl<A: NoGc, B: NoGc> NoGc for (A, B) {}
+unsafe impl<A: NoGc, B: NoGc, C: NoGc> NoGc for (A, B, C) {}
+unsafe impl<A: NoGc, B: NoGc, C: NoGc, D: NoGc> NoGc for (A, B, C, D) {}
+
+/// A value which can be sampled from any thread without access to the owner.
+///
+/// Meant for observing small plain data fields of GC allocated objects, like counters, from for
+/// example a profiler thread. The value is protected by a sequence lock: writes, which can only
+/// happen on the thread with the owner, increment a version before and after changing the value,
+/// and readers retry until they have read the value without a write happening in between.
+///
+/// Writing only requires a shared reference to the owner. An owner is neither send nor sync, so
+/// there can only ever be a single thread writing, while a mutable reference would prevent
+/// the value from being borrowed for sampling in the first place.
+pub struct Sampled<'own, T> {
+    version: AtomicUsize,
+    value: UnsafeCell<T>,
+    _invariant: Invariant<'own>,
+}
+
+unsafe impl<'own, T: NoGc> Sync for Sampled<'own, T> {}
+
+unsafe impl<'own, T: NoGc> Trace<'own> for Sampled<'own, T> {
+    type Gc<'gc> = Sampled<'own, T>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+impl<'own, T: NoGc> Sampled<'own, T> {
+    pub fn new(value: T) -> Self {
+        Sampled {
+            version: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Change the value.
+    pub fn write(&self, owner: &Owner<'own>, value: T) {
+        owner.bump_epoch();
+        let version = self.version.load(Ordering::Relaxed);
+        // An odd version signals a write in progress to readers.
+        self.version
+            .store(version.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        unsafe { ptr::write_volatile(self.value.get(), value) };
+        self.version
+            .store(version.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Returns a copy of the value, can be called from any thread.
+    pub fn sample(&self) -> T {
+        loop {
+            let before = self.version.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            // The read might race with a write, in which case the version will have changed and
+            // the possibly torn value is discarded. It is read as uninitialized memory as a torn
+            // value might not be a valid `T`.
+            let value = unsafe { ptr::read_volatile(self.value.get().cast::<MaybeUninit<T>>()) };
+            fence(Ordering::Acquire);
+            if self.version.load(Ordering::Relaxed) == before {
+                // No write happened during the read, so the value is the one last written.
+                return unsafe { value.assume_init() };
+            }
+            hint::spin_loop();
+        }
+    }
+}
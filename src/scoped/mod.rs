@@ -1,15 +1,63 @@
 //! A safe arena implemention which roots all created gc pointers until the end of a specific scope.
 
-use std::{pin::pin, ptr::NonNull};
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    pin::pin,
+    ptr::NonNull,
+};
 
 use crate::{
-    sys::{GcBox, UnsafeArena, UnsafeRootGuard, UnsafeTrace},
-    Invariant, Owner, Trace,
+    sys::{
+        embed::{BrandedArena, RootList},
+        erased_type_id, GcBox, UnsafeArena, UnsafeRootGuard,
+    },
+    Arena, Erasable, GcAny, Invariant, Marker, Owner, Trace,
 };
 
-struct ScopedGuards(Vec<NonNull<GcBox<()>>>);
+#[repr(transparent)]
+pub struct Gc<'own, T> {
+    ptr: NonNull<GcBox<T>>,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own, T> Clone for Gc<'own, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'own, T> Copy for Gc<'own, T> {}
+
+/// Pointers are compared by identity, see the implementation for [`crate::Gc`].
+impl<'own, T> PartialEq for Gc<'own, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+impl<'own, T> Eq for Gc<'own, T> {}
+
+/// Hashes the address of the object, consistent with the identity based [`PartialEq`].
+impl<'own, T> Hash for Gc<'own, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state)
+    }
+}
+
+/// Orders pointers by the address of the object.
+impl<'own, T> PartialOrd for Gc<'own, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'own, T> Ord for Gc<'own, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.as_ptr().cmp(&other.ptr.as_ptr())
+    }
+}
+
+unsafe impl<'own, T: Trace<'own>> Trace<'own> for Gc<'own, T> {
+    type Gc<'gc> = Gc<'own, T>;
 
-unsafe impl UnsafeTrace for ScopedGuards {
     fn needs_trace() -> bool
     where
         Self: Sized,
@@ -17,20 +65,18 @@ unsafe impl UnsafeTrace for ScopedGuards {
         true
     }
 
-    fn trace(&self, marker: crate::sys::UnsafeMarker) {
-        for v in self.0.iter().copied() {
-            unsafe {
-                marker.mark_erased(v);
-            }
-        }
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(unsafe { crate::Gc::from_gc_box(self.ptr) })
     }
 }
 
-#[derive(Clone, Copy)]
-#[repr(transparent)]
-pub struct Gc<'own, T> {
-    ptr: NonNull<GcBox<T>>,
-    _invariant: Invariant<'own>,
+unsafe impl<'own, T: Erasable<'own>> Erasable<'own> for Gc<'own, T> {}
+
+impl<'own, T> Gc<'own, T> {
+    /// Returns true if both pointers point to the same object.
+    pub fn ptr_eq(self, other: Gc<'own, T>) -> bool {
+        self.ptr == other.ptr
+    }
 }
 
 impl<'own, T: Trace<'own>> Gc<'own, T> {
@@ -40,14 +86,15 @@ impl<'own, T: Trace<'own>> Gc<'own, T> {
     }
 
     pub fn borrow_mut<'a>(self, owner: &'a mut Owner<'own>, arena: &ArenaScope<'own>) -> &'a mut T {
-        let _owner = owner;
-        unsafe { arena.arena.arena.write_barrier(self.ptr) }
+        owner.bump_epoch();
+        unsafe { arena.branded().write_barrier(self.ptr) }
         unsafe { &mut (*self.ptr.as_ref().value.get()) }
     }
 }
 
 pub struct ScopedArena {
-    roots: GcBox<ScopedGuards>,
+    roots: RootList,
+    persisted: RootList,
     arena: UnsafeArena,
 }
 
@@ -59,32 +106,62 @@ pub struct ArenaScope<'own> {
 
 impl<'own> ArenaScope<'own> {
     pub fn add<T: Trace<'own>>(&self, value: T) -> Gc<'own, T> {
+        let ptr = self.branded().add(value);
+        unsafe { self.arena.roots.push(&self.arena.arena, ptr.cast()) };
+        Gc {
+            ptr,
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Keep the object alive after the scope ends.
+    ///
+    /// Persisted objects can be retrieved in later scopes with [`ArenaScope::persisted`] and are
+    /// carried over by [`ScopedArena::into_arena`].
+    pub fn persist<T: Trace<'own>>(&self, value: Gc<'own, T>) {
         unsafe {
-            let ptr = self.arena.arena.add(value);
-            (*self.arena.roots.value.get()).0.push(ptr.cast());
-            Gc {
-                ptr,
-                _invariant: Invariant::new(),
-            }
+            self.arena
+                .persisted
+                .push(&self.arena.arena, value.ptr.cast())
         }
     }
 
+    /// Returns all persisted objects of type `T`, in the order they were persisted.
+    ///
+    /// Objects are selected by the [`TypeId`](std::any::TypeId) of their type, so `T` must
+    /// implement [`Erasable`].
+    pub fn persisted<T: Trace<'own> + Erasable<'own>>(&self) -> Vec<Gc<'own, T>> {
+        let type_id = erased_type_id::<T::Gc<'static>>();
+        self.arena
+            .persisted
+            .pointers()
+            .into_iter()
+            .filter(|ptr| (unsafe { ptr.as_ref().data_ptr.v_table() }.type_id)() == type_id)
+            .map(|ptr| Gc {
+                ptr: ptr.cast(),
+                _invariant: Invariant::new(),
+            })
+            .collect()
+    }
+
     pub fn collect(&self) {
         unsafe { self.arena.arena.collect() }
     }
     pub fn collect_full(&self) {
-        unsafe { self.arena.arena.collect() }
+        unsafe { self.arena.arena.collect_full() }
+    }
+
+    fn branded(&self) -> &BrandedArena<'own> {
+        unsafe { BrandedArena::from_ref(&self.arena.arena) }
     }
 }
 
 impl ScopedArena {
     pub fn new() -> Self {
-        unsafe {
-            let roots = GcBox::new(ScopedGuards(Vec::new()));
-            ScopedArena {
-                roots,
-                arena: UnsafeArena::new(),
-            }
+        ScopedArena {
+            roots: RootList::new(),
+            persisted: RootList::new(),
+            arena: unsafe { UnsafeArena::new() },
         }
     }
 
@@ -93,23 +170,69 @@ impl ScopedArena {
         f: F,
     ) -> R {
         let guard = pin!(UnsafeRootGuard::new());
-        let len = unsafe { (*self.roots.value.get()).0.len() };
-        let roots = NonNull::from(&self.roots);
-
+        let persisted_guard = pin!(UnsafeRootGuard::new());
         unsafe {
-            self.arena.root(guard, roots);
+            self.roots.root(&self.arena, guard);
+            self.persisted.root(&self.arena, persisted_guard);
         }
 
         let scope: &ArenaScope = unsafe { std::mem::transmute(&*self) };
         let mut owner = unsafe { Owner::new() };
 
-        let res = f(&mut owner, scope);
+        // Removes the roots added in the scope, also when `f` panics.
+        let _scope = self.roots.scope();
 
-        unsafe {
-            (*self.roots.value.get()).0.drain(..len);
-        }
+        f(&mut owner, scope)
+    }
+
+    /// Convert into a full [`Arena`], calling the function with the arena, its owner and the
+    /// persisted objects.
+    ///
+    /// The persisted objects are rooted until the function returns, the arena is dropped
+    /// afterwards. A full arena is converted back with [`Arena::into_scoped`].
+    pub fn into_arena<R, F>(self, f: F) -> R
+    where
+        F: for<'own> FnOnce(&mut Owner<'own>, &mut Arena<'own>, &[GcAny<'_, 'own>]) -> R,
+    {
+        let ScopedArena {
+            roots,
+            persisted,
+            arena,
+        } = self;
+        drop(roots);
+        let mut arena = unsafe { Arena::from_unsafe(arena) };
+        // The list must not move while rooted, and the guard must be dropped before the arena.
+        let persisted = persisted;
+        let guard = pin!(UnsafeRootGuard::new());
+        unsafe { persisted.root(arena.unsafe_arena(), guard) };
 
-        res
+        let carried = persisted
+            .pointers()
+            .into_iter()
+            .map(|ptr| unsafe { GcAny::from_gc_box(ptr.cast()) })
+            .collect::<Vec<_>>();
+        let mut owner = unsafe { Owner::new() };
+        f(&mut owner, &mut arena, &carried)
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Convert into a [`ScopedArena`], persisting the given objects.
+    ///
+    /// The owner is consumed, so no pointer of this arena can be dereferenced afterwards. The
+    /// persisted objects are available in the scopes of the new arena through
+    /// [`ArenaScope::persisted`].
+    pub fn into_scoped(self, owner: Owner<'own>, keep: &[GcAny<'_, 'own>]) -> ScopedArena {
+        let _owner = owner;
+        let scoped = ScopedArena {
+            roots: RootList::new(),
+            persisted: RootList::new(),
+            arena: self.into_unsafe_arena(),
+        };
+        for value in keep {
+            unsafe { scoped.persisted.push(&scoped.arena, value.into_gc_box()) };
+        }
+        scoped
     }
 }
 
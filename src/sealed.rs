@@ -0,0 +1,179 @@
+//! Read-only GC pointers.
+
+use std::{mem, pin::Pin};
+
+use crate::{Arena, Gc, Marker, Owner, RootGuard, Trace};
+
+/// A GC pointer which can only be used to read the value it points to.
+///
+/// Created from a [`Gc`] with [`Gc::seal`], there is no way to turn a sealed pointer back into a
+/// normal pointer. Borrowing the value returns its [sealed](Seal) version, in which all GC
+/// pointers it contains are sealed as well, so nothing reachable from a sealed pointer can be
+/// mutated, even by code which has the owner.
+///
+/// Values with interior mutability, like a [`Cell`](std::cell::Cell), can still be changed
+/// through a shared reference and thus through a sealed pointer.
+#[repr(transparent)]
+pub struct SealedGc<'gc, 'own, T>(Gc<'gc, 'own, T>);
+
+impl<'gc, 'own, T> Clone for SealedGc<'gc, 'own, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'gc, 'own, T> Copy for SealedGc<'gc, 'own, T> {}
+
+unsafe impl<'gc, 'own, T: Trace<'own>> Trace<'own> for SealedGc<'gc, 'own, T> {
+    type Gc<'a> = SealedGc<'a, 'own, T::Gc<'a>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.0);
+    }
+}
+
+impl<'gc, 'own, T> SealedGc<'gc, 'own, T> {
+    /// Borrow the sealed version of the contained value.
+    pub fn borrow<'a>(self, owner: &'a Owner<'own>) -> &'a T::Sealed
+    where
+        T: Seal<'own>,
+    {
+        const {
+            assert!(mem::size_of::<T>() == mem::size_of::<T::Sealed>());
+            assert!(mem::align_of::<T>() == mem::align_of::<T::Sealed>());
+        }
+        let value: *const T = self.0.borrow(owner);
+        unsafe { &*value.cast::<T::Sealed>() }
+    }
+}
+
+impl<'gc, 'own, T> From<Gc<'gc, 'own, T>> for SealedGc<'gc, 'own, T> {
+    fn from(value: Gc<'gc, 'own, T>) -> Self {
+        SealedGc(value)
+    }
+}
+
+impl<'gc, 'own, T> Gc<'gc, 'own, T> {
+    /// Turn the pointer into a pointer which can only be used to read the value.
+    pub fn seal(self) -> SealedGc<'gc, 'own, T> {
+        SealedGc(self)
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Root a sealed GC pointer to be kept alive for the duration of the given guard, see
+    /// [`Arena::root`].
+    pub fn root_sealed<'r, T: Trace<'own>>(
+        &self,
+        value: SealedGc<'_, 'own, T>,
+        guard: Pin<&'r mut RootGuard>,
+    ) -> SealedGc<'r, 'own, T::Gc<'r>> {
+        SealedGc(self.root(value.0, guard))
+    }
+}
+
+/// A type which has a sealed version, in which all contained GC pointers are replaced by
+/// [`SealedGc`] pointers.
+///
+/// # Safety
+/// `Sealed` must have the same layout as the implementing type, only differing in GC pointers
+/// being replaced by their sealed version. Types with fields should use `#[repr(C)]` for both the
+/// implementing type and its sealed version to guarantee this. The sealed version must not provide
+/// any way to mutate the GC pointers it contains.
+///
+/// # Usage
+/// ```
+/// # use std::pin::pin;
+/// # use dreck::*;
+/// #[repr(C)]
+/// pub struct Node<'gc, 'own> {
+///     value: u32,
+///     next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+/// }
+///
+/// #[repr(C)]
+/// pub struct SealedNode<'gc, 'own> {
+///     pub value: u32,
+///     pub next: Option<SealedGc<'gc, 'own, Node<'gc, 'own>>>,
+/// }
+///
+/// unsafe impl<'gc, 'own> Seal<'own> for Node<'gc, 'own> {
+///     type Sealed = SealedNode<'gc, 'own>;
+/// }
+/// # unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+/// #     type Gc<'to> = Node<'to, 'own>;
+/// #     fn needs_trace() -> bool { true }
+/// #     fn trace(&self, marker: Marker<'own, '_>) { self.next.trace(marker) }
+/// # }
+///
+/// dreck!(owner, arena);
+///
+/// let next = arena.add(Node { value: 2, next: None });
+/// let node = arena.add(Node { value: 1, next: Some(next) }).seal();
+///
+/// let next = node.borrow(&owner).next.unwrap();
+/// assert_eq!(next.borrow(&owner).value, 2);
+/// ```
+pub unsafe trait Seal<'own> {
+    type Sealed;
+}
+
+macro_rules! impl_seal_primitive {
+    ($($name:ty),*$(,)*) => {
+        $(
+            unsafe impl<'own> Seal<'own> for $name {
+                type Sealed = $name;
+            }
+        )*
+    };
+}
+
+impl_seal_primitive!(
+    (),
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    String
+);
+
+unsafe impl<'gc, 'own, T> Seal<'own> for Gc<'gc, 'own, T> {
+    type Sealed = SealedGc<'gc, 'own, T>;
+}
+
+unsafe impl<'gc, 'own, T> Seal<'own> for SealedGc<'gc, 'own, T> {
+    type Sealed = SealedGc<'gc, 'own, T>;
+}
+
+// Both pointers are transparent wrappers around a non-null pointer, so the null pointer
+// optimization guarantees the options have the same layout. Other generic types, like `Vec<T>`,
+// give no such guarantee for different parameters.
+unsafe impl<'gc, 'own, T> Seal<'own> for Option<Gc<'gc, 'own, T>> {
+    type Sealed = Option<SealedGc<'gc, 'own, T>>;
+}
+
+unsafe impl<'gc, 'own, T> Seal<'own> for Option<SealedGc<'gc, 'own, T>> {
+    type Sealed = Option<SealedGc<'gc, 'own, T>>;
+}
+
+unsafe impl<'own, T: Seal<'own>, const N: usize> Seal<'own> for [T; N] {
+    type Sealed = [T::Sealed; N];
+}
@@ -0,0 +1,145 @@
+//! Undoing mutations of the heap.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{Arena, Error, Gc, Owner, Trace};
+
+/// A running speculation, created by [`Arena::begin_speculation`].
+///
+/// Dereferences to the arena, which should be used through the guard for the duration of the
+/// speculation. Dropping the guard keeps all mutations, like [`SpeculationGuard::commit`].
+pub struct SpeculationGuard<'a, 'own> {
+    arena: &'a mut Arena<'own>,
+}
+
+impl<'a, 'own> SpeculationGuard<'a, 'own> {
+    /// Returns the number of objects whose old value is logged.
+    pub fn logged(&self) -> usize {
+        self.arena.unsafe_arena().undo_log_len()
+    }
+
+    /// Undo all logged mutations, restoring every mutated object to the value it had when the
+    /// speculation began.
+    ///
+    /// Objects allocated during the speculation are no longer reachable from the objects which
+    /// existed before it, so unless rooted they are freed by the next collection.
+    pub fn rollback(self, owner: &mut Owner<'own>) {
+        owner.bump_epoch();
+        // The owner is borrowed mutably so no reference to a logged object is alive.
+        unsafe { self.arena.unsafe_arena().rollback_undo_log() }
+    }
+
+    /// Keep all mutations, discarding the log.
+    pub fn commit(self) {
+        // Dropping the guard discards the log.
+    }
+}
+
+impl<'own> Deref for SpeculationGuard<'_, 'own> {
+    type Target = Arena<'own>;
+
+    fn deref(&self) -> &Arena<'own> {
+        self.arena
+    }
+}
+
+impl<'own> DerefMut for SpeculationGuard<'_, 'own> {
+    fn deref_mut(&mut self) -> &mut Arena<'own> {
+        self.arena
+    }
+}
+
+impl Drop for SpeculationGuard<'_, '_> {
+    fn drop(&mut self) {
+        let arena = self.arena.unsafe_arena();
+        // Already stopped by a rollback.
+        if arena.is_logging_undo() {
+            arena.discard_undo_log();
+        }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Start a speculation, during which mutations of objects can be undone with
+    /// [`SpeculationGuard::rollback`].
+    ///
+    /// The first time an object is mutated through [`Gc::borrow_mut`] and friends, which all
+    /// apply the write barrier with [`Arena::write_barrier`], its value is copied with
+    /// [`Trace::snapshot`] into an undo log. Mutating an object whose type doesn't support
+    /// snapshots panics, [`Gc::try_borrow_mut`] returns [`Error::SnapshotUnsupported`] instead.
+    /// Objects allocated during the speculation are never logged.
+    ///
+    /// Mutations which bypass the barrier are invisible to the log and are not undone: mutations
+    /// through [`Gc::borrow_mut_untraced`], [`Gc::borrow_mut_no_barrier`] and
+    /// [`GcCell`](crate::GcCell), and setting a [`GcOnceCell`](crate::GcOnceCell).
+    ///
+    /// The containers don't support snapshots, as a copy would share its spine with the
+    /// original, so objects with a container field can't be mutated during a speculation. A
+    /// [`GcVec`](crate::containers::GcVec) or [`GcVecDeque`](crate::containers::GcVecDeque)
+    /// allocated as an object of its own can be mutated through the methods on `Gc<GcVec>` and
+    /// `Gc<GcVecDeque>`, which log the container and its spine, also when a push moves the
+    /// elements to a new spine. The spine of a
+    /// [`GcBinaryHeap`](crate::containers::GcBinaryHeap) doesn't support snapshots.
+    ///
+    /// # Panic
+    /// Panics if a speculation is already running.
+    pub fn begin_speculation(&mut self) -> SpeculationGuard<'_, 'own> {
+        self.unsafe_arena().begin_undo_log();
+        SpeculationGuard { arena: self }
+    }
+
+    /// Log the value of an object which is about to be mutated, if a speculation is running and
+    /// the object isn't logged yet.
+    pub(crate) fn record_undo<T: Trace<'own>>(&self, ptr: Gc<'_, 'own, T>) {
+        if let Err(error) = self.try_record_undo(ptr) {
+            panic!("{error}")
+        }
+    }
+
+    /// Log the value of an object like [`Arena::record_undo`], returning an error if the value
+    /// can't be logged.
+    pub(crate) fn try_record_undo<T: Trace<'own>>(
+        &self,
+        ptr: Gc<'_, 'own, T>,
+    ) -> Result<(), Error> {
+        self.try_record_undo_with(ptr, T::snapshot)
+    }
+
+    /// Log the value of an object like [`Arena::record_undo`], using the given function instead
+    /// of [`Trace::snapshot`] to copy the value.
+    ///
+    /// Used for the containers, whose copies would share the spine with the original and so
+    /// can't be handed out by [`Trace::snapshot`]. A copy in the undo log is only ever moved back
+    /// into the object it was taken from.
+    pub(crate) fn record_undo_with<T, F>(&self, ptr: Gc<'_, 'own, T>, f: F)
+    where
+        T: Trace<'own>,
+        F: FnOnce(&T) -> T,
+    {
+        if let Err(error) = self.try_record_undo_with(ptr, |x| Some(f(x))) {
+            panic!("{error}")
+        }
+    }
+
+    fn try_record_undo_with<T, F>(&self, ptr: Gc<'_, 'own, T>, f: F) -> Result<(), Error>
+    where
+        T: Trace<'own>,
+        F: FnOnce(&T) -> Option<T>,
+    {
+        let arena = self.unsafe_arena();
+        let ptr = ptr.into_gc_box();
+        if !arena.needs_undo_entry(ptr) {
+            return Ok(());
+        }
+        // The object is about to be mutated, so no mutable reference to its value is alive yet.
+        let value = unsafe { &**ptr.as_ref().value.get() };
+        let snapshot = f(value).ok_or(Error::SnapshotUnsupported {
+            type_name: std::any::type_name::<T>(),
+        })?;
+        unsafe {
+            let snapshot = arena.try_add(snapshot)?;
+            arena.log_undo(ptr, snapshot);
+        }
+        Ok(())
+    }
+}
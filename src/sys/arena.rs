@@ -1,25 +1,58 @@
 use std::{
     alloc::Layout,
+    any::TypeId,
     cell::{Cell, RefCell, UnsafeCell},
-    mem::{ManuallyDrop, MaybeUninit},
+    collections::{HashMap, HashSet},
+    fmt,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::BitOr,
     pin::Pin,
-    ptr::{addr_of_mut, NonNull},
+    ptr::{self, addr_of_mut, NonNull},
+    rc::Rc,
+    time::{Duration, Instant},
 };
 
-use super::{GcBox, GcDataPtr, Status, UnsafeTrace};
+#[cfg(feature = "arena-id")]
+use std::{
+    num::NonZeroU64,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+#[cfg(feature = "root-provenance")]
+use super::provenance;
+use super::{
+    GcBox, GcDataPtr, GcVTable, HostOpQueue, ResourceLedger, Status, UndoLog, UnsafeTrace,
+};
+use crate::{AllocError, Error};
+
+#[derive(Clone, Copy)]
+enum MarkerKind<'a> {
+    Arena(&'a UnsafeArena),
+    Visitor(&'a dyn Fn(NonNull<GcBox<()>>)),
+}
 
 /// The object for marking GC pointers used while tracing objects.
 #[derive(Clone, Copy)]
-#[repr(transparent)]
-pub struct UnsafeMarker<'a>(&'a UnsafeArena);
+pub struct UnsafeMarker<'a>(MarkerKind<'a>);
 
 impl<'a> UnsafeMarker<'a> {
+    /// Create a marker which calls the given function for every pointer marked instead of
+    /// marking it in an arena.
+    pub fn from_visitor(f: &'a dyn Fn(NonNull<GcBox<()>>)) -> Self {
+        UnsafeMarker(MarkerKind::Visitor(f))
+    }
+
     /// Mark a GC pointer as alive.
     ///
     /// # Safety
     /// Caller must ensure that the pointer is a valid, alive, GC object allocated by the same arena
     /// that initiated the tracing with this marker.
     pub unsafe fn mark<T: UnsafeTrace>(self, ptr: NonNull<GcBox<T>>) {
+        let arena = match self.0 {
+            MarkerKind::Arena(x) => x,
+            MarkerKind::Visitor(f) => return f(ptr.cast()),
+        };
+        arena.check_arena_id(ptr);
         if ptr.as_ref().data_ptr.status() != Status::Untraced {
             return;
         }
@@ -27,7 +60,28 @@ impl<'a> UnsafeMarker<'a> {
         //println!("marking: {:?}", ptr.as_ptr());
 
         if T::needs_trace() {
-            self.0.grays.borrow_mut().push(ptr.cast::<GcBox<()>>());
+            arena.grays.push(ptr.cast::<GcBox<()>>());
+        }
+    }
+
+    /// Queue an operation to be run by [`UnsafeArena::run_deferred`] once the current collection
+    /// cycle has completed, passing it the given object.
+    ///
+    /// The object is marked and kept alive by the queue until the operation has run. Operations
+    /// deferred while tracing with a marker created by [`UnsafeMarker::from_visitor`] are dropped
+    /// without being run.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC object allocated by the same arena
+    /// that initiated the tracing with this marker.
+    pub unsafe fn defer(self, ptr: NonNull<GcBox<()>>, op: DeferredOp) {
+        if let MarkerKind::Arena(arena) = self.0 {
+            self.mark_erased(ptr);
+            arena.deferred.borrow_mut().push(Deferred {
+                ptr,
+                cycle: arena.cycle.get(),
+                op,
+            });
         }
     }
 
@@ -37,16 +91,33 @@ impl<'a> UnsafeMarker<'a> {
     /// Caller must ensure that the pointer is a valid, alive, GC object allocated by the same arena
     /// that initiated the tracing with this marker.
     pub unsafe fn mark_erased(self, ptr: NonNull<GcBox<()>>) {
+        let arena = match self.0 {
+            MarkerKind::Arena(x) => x,
+            MarkerKind::Visitor(f) => return f(ptr),
+        };
+        arena.check_arena_id(ptr);
         if ptr.as_ref().data_ptr.status() != Status::Untraced {
             return;
         }
         ptr.as_ref().data_ptr.set_status(Status::Marked);
         //println!("marking: {:?}", ptr.as_ptr());
 
-        self.0.grays.borrow_mut().push(ptr.cast::<GcBox<()>>());
+        if (ptr.as_ref().data_ptr.v_table().needs_trace)() {
+            arena.grays.push(ptr);
+        }
     }
 }
 
+/// Call the given function for every GC pointer directly contained in the given object.
+///
+/// # Safety
+/// Caller must ensure that the pointer is a valid, alive, GC pointer and that the object is not
+/// mutably borrowed.
+pub unsafe fn visit_children(ptr: NonNull<GcBox<()>>, f: &dyn Fn(NonNull<GcBox<()>>)) {
+    let v_table = ptr.as_ref().data_ptr.v_table();
+    (v_table.trace)(ptr.as_ptr(), UnsafeMarker::from_visitor(f));
+}
+
 /// A link of an intrusive list.
 #[repr(C)]
 pub struct ListLink<T> {
@@ -70,6 +141,25 @@ impl<T> ListLink<T> {
         }
     }
 
+    /// Returns true if this link is part of a list.
+    fn is_linked(&self) -> bool {
+        self.prev.get().is_some()
+    }
+
+    /// Remove this link from the list it is part of.
+    unsafe fn unlink(&self) {
+        let prev = self.prev.get();
+        let next = self.next.get();
+
+        if let Some(next) = next {
+            next.as_ref().prev.set(prev);
+        }
+        if let Some(prev) = prev {
+            prev.as_ref().next.set(next);
+        }
+        self.clear();
+    }
+
     /// Remove pointers to the next and previous links
     unsafe fn clear(&self) {
         self.next.set(None);
@@ -84,25 +174,18 @@ impl<T> ListLink<T> {
 
 impl<T> Drop for ListLink<T> {
     fn drop(&mut self) {
-        let prev = self.prev.get();
-        let next = self.next.get();
-
-        if let Some(next) = next {
-            unsafe {
-                next.as_ref().prev.set(prev);
-            }
-        }
-        if let Some(prev) = prev {
-            unsafe {
-                prev.as_ref().next.set(next);
-            }
-        }
+        unsafe { self.unlink() }
     }
 }
 
+struct RootValue {
+    ptr: NonNull<GcBox<()>>,
+    count: NonNull<Cell<usize>>,
+}
+
 /// A guard keeping a pointer alive for the duration of guards lifetime.
 #[repr(transparent)]
-pub struct UnsafeRootGuard(ListLink<NonNull<GcBox<()>>>);
+pub struct UnsafeRootGuard(ListLink<RootValue>);
 
 impl UnsafeRootGuard {
     pub fn new() -> Self {
@@ -112,6 +195,44 @@ impl UnsafeRootGuard {
             value: MaybeUninit::uninit(),
         })
     }
+
+    /// Move the pointer rooted by `from` to `to`, unrooting any pointer previously rooted by `to`.
+    ///
+    /// The pointer is linked into the root list by `to` before it is unlinked by `from` so it
+    /// remains rooted throughout.
+    pub fn transfer(from: Pin<&mut Self>, to: Pin<&mut Self>) {
+        unsafe {
+            if to.0.is_linked() {
+                let value = to.0.value.assume_init_ref();
+                let count = value.count.as_ref();
+                count.set(count.get() - 1);
+                #[cfg(feature = "root-provenance")]
+                provenance::unrooted(to.guard_addr(), value.ptr);
+                to.0.unlink();
+            }
+            if !from.0.is_linked() {
+                return;
+            }
+
+            let value = from.0.value.assume_init_ref();
+            #[cfg(feature = "root-provenance")]
+            provenance::moved(from.guard_addr(), to.guard_addr(), value.ptr);
+            let to = to.get_unchecked_mut();
+            to.0.value.write(RootValue {
+                ptr: value.ptr,
+                count: value.count,
+            });
+            Pin::new_unchecked(&to.0).link(Pin::new_unchecked(&from.0));
+            // The root count doesn't change as the root moved to the other guard.
+            from.0.unlink();
+        }
+    }
+
+    /// The address of the guard, identifying it in the root provenance table.
+    #[cfg(feature = "root-provenance")]
+    fn guard_addr(&self) -> usize {
+        self as *const Self as usize
+    }
 }
 
 impl Default for UnsafeRootGuard {
@@ -120,7 +241,176 @@ impl Default for UnsafeRootGuard {
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+impl Drop for UnsafeRootGuard {
+    fn drop(&mut self) {
+        if self.0.is_linked() {
+            unsafe {
+                let value = self.0.value.assume_init_ref();
+                let count = value.count.as_ref();
+                count.set(count.get() - 1);
+                #[cfg(feature = "root-provenance")]
+                provenance::unrooted(self.guard_addr(), value.ptr);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arena-id")]
+static NEXT_ARENA_ID: AtomicU64 = AtomicU64::new(1);
+
+type RootLimitHook = Box<dyn FnMut(usize)>;
+type TransitionHook = Box<dyn FnMut(Transition)>;
+type WorkScheduler = Box<dyn FnMut(WorkRequest)>;
+type TeardownHook = Box<dyn FnOnce(&UnsafeArena)>;
+/// An operation queued with [`UnsafeMarker::defer`], called with the arena, the owner pointer
+/// passed to [`UnsafeArena::run_deferred`] and the object it was queued with.
+pub type DeferredOp = Box<dyn FnOnce(&UnsafeArena, NonNull<()>, NonNull<GcBox<()>>)>;
+
+/// An operation queued with [`UnsafeMarker::defer`] and the collection cycle it was queued in.
+struct Deferred {
+    ptr: NonNull<GcBox<()>>,
+    cycle: u64,
+    op: DeferredOp,
+}
+
+/// A table of interned objects, objects in the table are not kept alive by it.
+#[derive(Default)]
+struct InternTable {
+    buckets: HashMap<(TypeId, u64), Vec<NonNull<GcBox<()>>>>,
+    keys: HashMap<NonNull<GcBox<()>>, (TypeId, u64)>,
+}
+
+/// Tracks whether an object is still alive, created by [`UnsafeArena::liveness`].
+///
+/// Doesn't keep the object alive. Once the object is freed the address can be reused by a new
+/// object, the liveness of the new object is tracked separately.
+#[derive(Clone, Debug)]
+pub struct Liveness(Rc<Cell<bool>>);
+
+impl Liveness {
+    /// Returns false if the object has been freed.
+    pub fn is_alive(&self) -> bool {
+        self.0.get()
+    }
+}
+
+/// The liveness flags of objects whose liveness is tracked, cleared when the object is freed.
+type LivenessTable = HashMap<NonNull<GcBox<()>>, Rc<Cell<bool>>>;
+
+/// The allocation sequence numbers of all objects in an arena.
+#[cfg(feature = "stable-id")]
+#[derive(Default)]
+struct StableIds {
+    next: u64,
+    ids: HashMap<NonNull<GcBox<()>>, u64>,
+}
+
+/// Statistics about the state of an arena.
+///
+/// Collecting and formatting the statistics never allocates, so they can be logged from
+/// allocation sensitive code.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GcStats {
+    /// The total number of bytes allocated for GC objects.
+    pub total_allocated: usize,
+    /// The number of currently rooted pointers.
+    pub root_count: usize,
+    /// The phase the collector is in.
+    pub phase: Phase,
+    /// The number of bytes held by the internal buffers of the collector.
+    pub cache_bytes: usize,
+    /// The number of pointers the gray stacks of the collector can hold without reallocating.
+    pub gray_capacity: usize,
+    /// The work done by the last call to [`UnsafeArena::collect`], counted towards the limit set
+    /// with [`UnsafeArena::set_max_step_work`].
+    pub collect_work: usize,
+    /// The work the last call to [`UnsafeArena::collect`] left undone because it reached a step
+    /// limit, carried over to the next call. Zero once the cycle is finished.
+    pub deferred_work: usize,
+    /// The number of write barriers applied to objects since the arena was created, not counting
+    /// barriers on types which don't need tracing.
+    pub write_barriers: usize,
+}
+
+impl fmt::Display for GcStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "phase: {}, allocated: {} bytes, roots: {}, cache: {} bytes, gray capacity: {}, \
+             work: {} done {} deferred, write barriers: {}",
+            self.phase,
+            self.total_allocated,
+            self.root_count,
+            self.cache_bytes,
+            self.gray_capacity,
+            self.collect_work,
+            self.deferred_work,
+            self.write_barriers
+        )
+    }
+}
+
+/// The reason an arena can't currently be used to allocate or root pointers.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum GcUnavailable {
+    /// The arena is iterating over its objects, see [`UnsafeArena::for_each_object`].
+    Walking,
+    /// The arena is running the trace implementation of an object.
+    Tracing,
+    /// The arena is being dropped or is dropping objects during a collection.
+    Dropping,
+    /// The heap is held fully marked, see [`UnsafeArena::mark_all`].
+    Marked,
+}
+
+impl fmt::Display for GcUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GcUnavailable::Walking => write!(f, "the arena is iterating over its objects"),
+            GcUnavailable::Tracing => write!(f, "the arena is tracing"),
+            GcUnavailable::Dropping => {
+                write!(f, "the arena is being dropped or is dropping objects")
+            }
+            GcUnavailable::Marked => write!(f, "the heap is held fully marked"),
+        }
+    }
+}
+
+impl std::error::Error for GcUnavailable {}
+
+/// A request for a slice of collection work, passed to the scheduler set with
+/// [`UnsafeArena::set_work_scheduler`].
+#[derive(Clone, Copy, Debug)]
+pub struct WorkRequest {
+    /// The amount of work the collector is behind on, in the same unit as the allocation debt.
+    pub work: usize,
+    /// The phase the collector was in when the work was requested.
+    pub phase: Phase,
+}
+
+/// The state of the work scheduling of an arena.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum WorkState {
+    /// No work was requested.
+    Idle,
+    /// Work was requested but the scheduler was not yet told.
+    Pending,
+    /// The scheduler was told, the next pump performs the work.
+    Scheduled,
+}
+
+/// How urgently an arena should release memory, see [`UnsafeArena::on_memory_pressure`].
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum MemoryPressure {
+    /// Finish the current collection cycle.
+    Normal,
+    /// Run a full collection and shrink all internal buffers.
+    Critical,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Phase {
     Sleep,
     Wake,
@@ -128,6 +418,161 @@ pub enum Phase {
     Sweep,
 }
 
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Phase::Sleep => "sleep",
+            Phase::Wake => "wake",
+            Phase::Trace => "trace",
+            Phase::Sweep => "sweep",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A set of phases, used to select the transitions a subscriber is notified of, see
+/// [`UnsafeArena::subscribe_transitions`].
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct PhaseMask(u8);
+
+impl PhaseMask {
+    /// No phases.
+    pub const NONE: PhaseMask = PhaseMask(0);
+    /// The transition from scanning roots to tracing.
+    pub const TRACE: PhaseMask = PhaseMask(1 << 0);
+    /// The transition from tracing to sweeping.
+    pub const SWEEP: PhaseMask = PhaseMask(1 << 1);
+    /// The transition from sweeping to sleeping, which ends a cycle.
+    pub const SLEEP: PhaseMask = PhaseMask(1 << 2);
+    /// All transitions.
+    pub const ALL: PhaseMask = PhaseMask(0b111);
+
+    /// Returns true if the transition into the given phase is part of the set.
+    pub fn contains(self, phase: Phase) -> bool {
+        let bit = match phase {
+            Phase::Trace => Self::TRACE,
+            Phase::Sweep => Self::SWEEP,
+            Phase::Sleep => Self::SLEEP,
+            Phase::Wake => return false,
+        };
+        self.0 & bit.0 != 0
+    }
+}
+
+impl BitOr for PhaseMask {
+    type Output = PhaseMask;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        PhaseMask(self.0 | rhs.0)
+    }
+}
+
+/// A transition between two phases of a collection cycle.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Transition {
+    /// The phase the collector left.
+    pub from: Phase,
+    /// The phase the collector entered.
+    pub to: Phase,
+    /// The number of the collection cycle, counting from 1 for the first cycle of the arena.
+    pub cycle: u64,
+}
+
+/// Identifies a subscription created by [`UnsafeArena::subscribe_transitions`].
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct TransitionSubscription(u64);
+
+/// The subscribers to phase transitions of an arena.
+#[derive(Default)]
+struct TransitionSubscribers {
+    next: u64,
+    hooks: Vec<(TransitionSubscription, PhaseMask, TransitionHook)>,
+}
+
+/// A stack of objects which still have to be traced.
+///
+/// Pushed to by every mark and write barrier, so it avoids the borrow flag of a `RefCell`. The
+/// stack is only ever accessed within its own methods, which don't run any user code, so no two
+/// references to the vector can exist at the same time.
+struct GrayStack(UnsafeCell<Vec<NonNull<GcBox<()>>>>);
+
+impl GrayStack {
+    fn new() -> Self {
+        GrayStack(UnsafeCell::new(Vec::new()))
+    }
+
+    #[inline(always)]
+    fn push(&self, ptr: NonNull<GcBox<()>>) {
+        unsafe { (*self.0.get()).push(ptr) }
+    }
+
+    fn pop(&self) -> Option<NonNull<GcBox<()>>> {
+        unsafe { (*self.0.get()).pop() }
+    }
+
+    fn len(&self) -> usize {
+        unsafe { (*self.0.get()).len() }
+    }
+
+    fn capacity(&self) -> usize {
+        unsafe { (*self.0.get()).capacity() }
+    }
+
+    fn clear(&self) {
+        unsafe { (*self.0.get()).clear() }
+    }
+
+    fn shrink_to(&self, capacity: usize) {
+        unsafe { (*self.0.get()).shrink_to(capacity) }
+    }
+}
+
+/// Aborts the current collection cycle when dropped, see [`UnsafeArena::abort_cycle`].
+struct AbortCycle<'a>(&'a UnsafeArena);
+
+impl Drop for AbortCycle<'_> {
+    fn drop(&mut self) {
+        unsafe { self.0.abort_cycle() }
+    }
+}
+
+/// Marks an arena as unusable for as long as it is alive, see [`UnsafeArena::is_usable`].
+struct Unusable<'a> {
+    usable: &'a Cell<bool>,
+    prev: bool,
+}
+
+impl<'a> Unusable<'a> {
+    fn new(usable: &'a Cell<bool>) -> Self {
+        Unusable {
+            prev: usable.replace(false),
+            usable,
+        }
+    }
+}
+
+impl Drop for Unusable<'_> {
+    fn drop(&mut self) {
+        self.usable.set(self.prev);
+    }
+}
+
+/// Marks an arena as running a trace implementation for as long as it is alive.
+struct Tracing<'a>(&'a Cell<bool>);
+
+impl<'a> Tracing<'a> {
+    fn new(tracing: &'a Cell<bool>) -> Self {
+        tracing.set(true);
+        Tracing(tracing)
+    }
+}
+
+impl Drop for Tracing<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
 /// The arena for garbage collected pointers.
 /// This struct is in charge allocating, freeing, and rooting garbage collected pointers.
 ///
@@ -135,28 +580,68 @@ pub enum Phase {
 /// as unsafe. The safe arena's implement a safe API on top of this arena. During normal use prefer
 /// the safe implementations over this one.
 pub struct UnsafeArena {
-    roots: Box<ListLink<()>>,
+    roots: Box<ListLink<Cell<usize>>>,
+    /// Linked into the root list while roots are being scanned, right after the last scanned root.
+    root_cursor: Box<ListLink<()>>,
+    root_limit: Cell<Option<usize>>,
+    root_limit_hook: Cell<Option<RootLimitHook>>,
+    work_scheduler: Cell<Option<WorkScheduler>>,
+    work_state: Cell<WorkState>,
+    transitions: RefCell<TransitionSubscribers>,
+    teardown: RefCell<Vec<TeardownHook>>,
+    deferred: RefCell<Vec<Deferred>>,
+    host_ops: Rc<HostOpQueue>,
+    resources: Rc<ResourceLedger>,
+    audit_resources: Cell<bool>,
+    pub(super) undo: RefCell<Option<Box<UndoLog>>>,
 
-    grays: RefCell<Vec<NonNull<GcBox<()>>>>,
-    grays_again: RefCell<Vec<NonNull<GcBox<()>>>>,
+    grays: GrayStack,
+    grays_again: GrayStack,
+    gray_peak: Cell<usize>,
 
     all: Cell<Option<NonNull<GcBox<()>>>>,
 
     sweep: Cell<Option<NonNull<GcBox<()>>>>,
     sweep_prev: Cell<Option<NonNull<GcBox<()>>>>,
+    condemned: Cell<Option<NonNull<GcBox<()>>>>,
 
     total_allocated: Cell<usize>,
     remembered_size: Cell<usize>,
     wakeup_total: Cell<usize>,
     allocation_debt: Cell<f64>,
+    max_step_work: Cell<Option<usize>>,
+    heap_limit: Cell<Option<usize>>,
+    max_step_time: Cell<Option<Duration>>,
+    collect_work: Cell<usize>,
+    deferred_work: Cell<usize>,
+    write_barriers: Cell<usize>,
 
+    interned: RefCell<InternTable>,
+    liveness: RefCell<LivenessTable>,
+    #[cfg(feature = "stable-id")]
+    stable_ids: RefCell<StableIds>,
+
+    #[cfg(feature = "arena-id")]
+    id: NonZeroU64,
+    #[cfg(feature = "object-id")]
+    next_object_id: Cell<u64>,
     phase: Cell<Phase>,
+    cycle: Cell<u64>,
+    verify: Cell<bool>,
+    walking: Cell<bool>,
+    marked: Cell<bool>,
+    tracing: Cell<bool>,
+    traced_type: Cell<Option<fn() -> &'static str>>,
+    usable: Cell<bool>,
 }
 
 impl UnsafeArena {
     const PAUSE_FACTOR: f64 = 0.5;
     const TIMING_FACTOR: f64 = 1.5;
     const MIN_SLEEP: usize = 4096;
+    const MIN_GRAYS: usize = 64;
+    /// The number of steps between checks of the time limit of [`UnsafeArena::collect`].
+    const TIME_CHECK_INTERVAL: u32 = 32;
 
     /// Create a new unsafe arena.
     ///
@@ -170,21 +655,62 @@ impl UnsafeArena {
             roots: Box::new(ListLink {
                 next: Cell::new(None),
                 prev: Cell::new(None),
-                value: MaybeUninit::uninit(),
+                value: MaybeUninit::new(Cell::new(0)),
+            }),
+            root_cursor: Box::new(ListLink {
+                next: Cell::new(None),
+                prev: Cell::new(None),
+                value: MaybeUninit::new(()),
             }),
+            root_limit: Cell::new(None),
+            root_limit_hook: Cell::new(None),
+            work_scheduler: Cell::new(None),
+            work_state: Cell::new(WorkState::Idle),
+            transitions: RefCell::new(TransitionSubscribers::default()),
+            teardown: RefCell::new(Vec::new()),
+            deferred: RefCell::new(Vec::new()),
+            host_ops: Rc::new(HostOpQueue::default()),
+            resources: Rc::new(ResourceLedger::default()),
+            audit_resources: Cell::new(false),
+            undo: RefCell::new(None),
 
-            grays: RefCell::new(Vec::new()),
-            grays_again: RefCell::new(Vec::new()),
+            grays: GrayStack::new(),
+            grays_again: GrayStack::new(),
+            gray_peak: Cell::new(0),
 
             sweep: Cell::new(None),
             sweep_prev: Cell::new(None),
+            condemned: Cell::new(None),
 
             total_allocated: Cell::new(0),
             remembered_size: Cell::new(0),
             wakeup_total: Cell::new(Self::MIN_SLEEP),
             allocation_debt: Cell::new(0.0),
+            max_step_work: Cell::new(None),
+            heap_limit: Cell::new(None),
+            max_step_time: Cell::new(None),
+            collect_work: Cell::new(0),
+            write_barriers: Cell::new(0),
+            deferred_work: Cell::new(0),
 
+            interned: RefCell::new(InternTable::default()),
+            liveness: RefCell::new(HashMap::new()),
+            #[cfg(feature = "stable-id")]
+            stable_ids: RefCell::new(StableIds::default()),
+
+            #[cfg(feature = "arena-id")]
+            id: NonZeroU64::new(NEXT_ARENA_ID.fetch_add(1, Ordering::Relaxed))
+                .expect("ran out of arena ids"),
+            #[cfg(feature = "object-id")]
+            next_object_id: Cell::new(1),
             phase: Cell::new(Phase::Sweep),
+            cycle: Cell::new(0),
+            verify: Cell::new(false),
+            walking: Cell::new(false),
+            marked: Cell::new(false),
+            tracing: Cell::new(false),
+            traced_type: Cell::new(None),
+            usable: Cell::new(true),
         }
     }
 
@@ -196,20 +722,278 @@ impl UnsafeArena {
     /// root during any previous garbage collection cycles..
     ///
     /// # Panic
-    /// Will panic if the allocation of a pointer fails.
+    /// Will panic if the allocation of a pointer fails or if called while the arena is tracing.
     pub unsafe fn add<T: UnsafeTrace>(&self, value: T) -> NonNull<GcBox<T>> {
+        self.assert_allocate();
+        self.add_unchecked(value)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Allocate a new GC object, returning an error instead of panicking if the arena can't be
+    /// used or the allocation fails. The error contains the value so it can be recovered.
+    ///
+    /// # Safety
+    /// See [`UnsafeArena::add`].
+    pub unsafe fn try_add<T: UnsafeTrace>(
+        &self,
+        value: T,
+    ) -> Result<NonNull<GcBox<T>>, AllocError<T>> {
+        if let Err(reason) = self.check_allocate() {
+            return Err(AllocError::new(reason.into(), value));
+        }
+        self.add_unchecked(value)
+    }
+
+    /// Allocate a new GC object without checking if the arena can be used.
+    unsafe fn add_unchecked<T: UnsafeTrace>(
+        &self,
+        value: T,
+    ) -> Result<NonNull<GcBox<T>>, AllocError<T>> {
+        let ptr = match self.alloc_box::<T>() {
+            Ok(x) => x,
+            Err(error) => return Err(AllocError::new(error, value)),
+        };
+        addr_of_mut!((*ptr.as_ptr()).value).write(UnsafeCell::new(ManuallyDrop::new(value)));
+        self.link(ptr);
+        Ok(ptr)
+    }
+
+    /// Allocate a new GC object without initializing its value.
+    ///
+    /// The object is not yet part of the arena and will not be collected or traced until it is
+    /// passed to [`UnsafeArena::link`].
+    ///
+    /// # Safety
+    /// The value of the object must be initialized before the object is linked. An object which is
+    /// never linked must be freed with [`UnsafeArena::dealloc_uninit`].
+    ///
+    /// # Panic
+    /// Will panic if the allocation of a pointer fails or if called while the arena is tracing.
+    pub unsafe fn alloc_uninit<T: UnsafeTrace>(&self) -> NonNull<GcBox<T>> {
+        self.assert_allocate();
+        self.alloc_box().unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Allocate a new GC object without initializing its value, returning an error instead of
+    /// panicking if the arena can't be used or the allocation fails.
+    ///
+    /// # Safety
+    /// See [`UnsafeArena::alloc_uninit`].
+    pub unsafe fn try_alloc_uninit<T: UnsafeTrace>(&self) -> Result<NonNull<GcBox<T>>, Error> {
+        self.check_allocate()?;
+        self.alloc_box()
+    }
+
+    /// Allocate the memory of an object and initialize its header.
+    unsafe fn alloc_box<T: UnsafeTrace>(&self) -> Result<NonNull<GcBox<T>>, Error> {
         let layout = Layout::new::<GcBox<T>>();
+        self.check_heap_limit(layout.size())?;
         let ptr = std::alloc::alloc(layout).cast::<GcBox<T>>();
         //println!("allocated: {:?}", ptr);
-        let ptr = NonNull::new(ptr).expect("allocation failed");
-        let next = self.all.replace(Some(ptr.cast::<GcBox<()>>()));
+        let ptr = NonNull::new(ptr).ok_or(Error::AllocationFailed)?;
 
         let data_ptr = GcDataPtr::new::<T>();
         //println!("v_table: {:?}", data_ptr.v_table() as *const _);
 
-        addr_of_mut!((*ptr.as_ptr()).next).write(Cell::new(next));
+        addr_of_mut!((*ptr.as_ptr()).next).write(Cell::new(None));
         addr_of_mut!((*ptr.as_ptr()).data_ptr).write(data_ptr);
-        addr_of_mut!((*ptr.as_ptr()).value).write(UnsafeCell::new(ManuallyDrop::new(value)));
+        #[cfg(feature = "arena-id")]
+        addr_of_mut!((*ptr.as_ptr()).arena_id).write(Some(self.id));
+        // Assigned on allocation instead of when linking, as pointers to objects which are not
+        // yet linked can be handed out, see `Arena::add_cyclic`.
+        #[cfg(feature = "object-id")]
+        addr_of_mut!((*ptr.as_ptr()).object_id).write(self.take_object_id());
+        #[cfg(feature = "tag-word")]
+        addr_of_mut!((*ptr.as_ptr()).tag_word).write(Cell::new(0));
+        Ok(ptr)
+    }
+
+    /// Returns the id for a new object.
+    #[cfg(feature = "object-id")]
+    fn take_object_id(&self) -> u64 {
+        let id = self.next_object_id.get();
+        self.next_object_id.set(id + 1);
+        id
+    }
+
+    /// Allocate a new GC object containing a slice with the items of the iterator.
+    ///
+    /// The items are stored in the same allocation as the header of the object.
+    ///
+    /// # Safety
+    /// The items must only contain valid, alive, GC pointers allocated by this arena.
+    ///
+    /// # Panic
+    /// Will panic if the allocation of a pointer fails, if called while the arena is tracing or
+    /// if the iterator returns fewer items than its length.
+    pub unsafe fn add_slice<T, I>(&self, iter: I) -> NonNull<GcBox<[T]>>
+    where
+        T: UnsafeTrace,
+        I: ExactSizeIterator<Item = T>,
+    {
+        self.assert_allocate();
+        self.add_slice_with_v_table(iter, GcVTable::get_slice::<T>())
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Allocate a new GC object containing a slice with the items of the iterator, returning an
+    /// error instead of panicking if the arena can't be used or the allocation fails.
+    ///
+    /// # Safety
+    /// See [`UnsafeArena::add_slice`].
+    ///
+    /// # Panic
+    /// Will panic if the iterator returns fewer items than its length.
+    pub unsafe fn try_add_slice<T, I>(&self, iter: I) -> Result<NonNull<GcBox<[T]>>, Error>
+    where
+        T: UnsafeTrace,
+        I: ExactSizeIterator<Item = T>,
+    {
+        self.check_allocate()?;
+        self.add_slice_with_v_table(iter, GcVTable::get_slice::<T>())
+    }
+
+    /// Allocate a new GC object containing a copy of the string.
+    ///
+    /// The bytes are stored in the same allocation as the header of the object.
+    ///
+    /// # Panic
+    /// Will panic if the allocation of a pointer fails or if called while the arena is tracing.
+    pub fn add_str(&self, value: &str) -> NonNull<GcBox<str>> {
+        self.assert_allocate();
+        unsafe { self.add_str_unchecked(value) }.unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Allocate a new GC object containing a copy of the string, returning an error instead of
+    /// panicking if the arena can't be used or the allocation fails.
+    pub fn try_add_str(&self, value: &str) -> Result<NonNull<GcBox<str>>, Error> {
+        self.check_allocate()?;
+        unsafe { self.add_str_unchecked(value) }
+    }
+
+    /// Allocate a string object without checking if the arena can be used.
+    unsafe fn add_str_unchecked(&self, value: &str) -> Result<NonNull<GcBox<str>>, Error> {
+        let ptr = self.add_slice_with_v_table(value.bytes(), GcVTable::get_str())?;
+        Ok(NonNull::new_unchecked(ptr.as_ptr() as *mut GcBox<str>))
+    }
+
+    /// Allocate a slice object with the given v-table, which must be a slice v-table for `T` or a
+    /// v-table with the same layout, trace and drop functions.
+    ///
+    /// Doesn't check if the arena can be used, callers must do so first.
+    unsafe fn add_slice_with_v_table<T, I>(
+        &self,
+        iter: I,
+        v_table: &'static GcVTable,
+    ) -> Result<NonNull<GcBox<[T]>>, Error>
+    where
+        T: UnsafeTrace,
+        I: ExactSizeIterator<Item = T>,
+    {
+        /// Drops the written items and frees the allocation if the iterator panics.
+        struct Partial<T> {
+            base: *mut u8,
+            layout: Layout,
+            items: *mut T,
+            written: usize,
+        }
+
+        impl<T> Drop for Partial<T> {
+            fn drop(&mut self) {
+                unsafe {
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.items, self.written));
+                    std::alloc::dealloc(self.base, self.layout);
+                }
+            }
+        }
+
+        let len = iter.len();
+        let (layout, offset) = super::slice_layout::<T>(len);
+        self.check_heap_limit(layout.size())?;
+        let base = std::alloc::alloc(layout);
+        if base.is_null() {
+            return Err(Error::AllocationFailed);
+        }
+        let ptr = base.add(offset).cast::<GcBox<[T; 0]>>();
+        ptr.cast::<usize>().sub(1).write(len);
+
+        let items = addr_of_mut!((*ptr).value).cast::<T>();
+        let mut partial = Partial {
+            base,
+            layout,
+            items,
+            written: 0,
+        };
+        for item in iter.take(len) {
+            items.add(partial.written).write(item);
+            partial.written += 1;
+        }
+        assert_eq!(
+            partial.written, len,
+            "iterator returned fewer items than its length"
+        );
+        mem::forget(partial);
+
+        addr_of_mut!((*ptr).next).write(Cell::new(None));
+        addr_of_mut!((*ptr).data_ptr).write(GcDataPtr::from_v_table(v_table));
+        #[cfg(feature = "arena-id")]
+        addr_of_mut!((*ptr).arena_id).write(Some(self.id));
+        #[cfg(feature = "object-id")]
+        addr_of_mut!((*ptr).object_id).write(self.take_object_id());
+        #[cfg(feature = "tag-word")]
+        addr_of_mut!((*ptr).tag_word).write(Cell::new(0));
+
+        let ptr = NonNull::new_unchecked(ptr.cast::<GcBox<()>>());
+        self.link_erased(ptr, layout);
+        Ok(NonNull::new_unchecked(
+            ptr::slice_from_raw_parts_mut(ptr.as_ptr().cast::<T>(), len) as *mut GcBox<[T]>,
+        ))
+    }
+
+    /// Returns an error if allocating an object of the given size would exceed the heap limit.
+    fn check_heap_limit(&self, size: usize) -> Result<(), Error> {
+        match self.heap_limit.get() {
+            Some(limit) if self.total_allocated.get().saturating_add(size) > limit => {
+                Err(Error::LimitExceeded { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Free an object allocated by [`UnsafeArena::alloc_uninit`] which was never linked.
+    ///
+    /// # Safety
+    /// The object must have been allocated by this arena and not yet been linked. The value of the
+    /// object is not dropped.
+    pub unsafe fn dealloc_uninit<T: UnsafeTrace>(&self, ptr: NonNull<GcBox<T>>) {
+        std::alloc::dealloc(ptr.as_ptr().cast(), Layout::new::<GcBox<T>>());
+    }
+
+    /// Make an object allocated by [`UnsafeArena::alloc_uninit`] part of the arena.
+    ///
+    /// # Safety
+    /// The object must have been allocated by this arena, its value must be initialized and it must
+    /// not already be linked.
+    pub unsafe fn link<T: UnsafeTrace>(&self, ptr: NonNull<GcBox<T>>) {
+        self.link_erased(ptr.cast(), Layout::new::<GcBox<T>>());
+    }
+
+    /// Make an object part of the arena, the layout is the layout of its allocation.
+    unsafe fn link_erased(&self, ptr: NonNull<GcBox<()>>, layout: Layout) {
+        let next = self.all.replace(Some(ptr.cast::<GcBox<()>>()));
+        ptr.as_ref().next.set(next);
+        self.skip_undo(ptr);
+
+        #[cfg(feature = "root-provenance")]
+        provenance::allocated(ptr);
+
+        #[cfg(feature = "stable-id")]
+        {
+            let mut ids = self.stable_ids.borrow_mut();
+            let id = ids.next;
+            ids.next += 1;
+            ids.ids.insert(ptr.cast(), id);
+        }
 
         self.total_allocated
             .set(self.total_allocated.get() + layout.size());
@@ -231,7 +1015,10 @@ impl UnsafeArena {
             self.sweep_prev.set(self.all.get())
         }
 
-        ptr
+        // Only recorded here, the scheduler is called by the next pump as it runs user code.
+        if self.phase.get() != Phase::Sleep && self.work_state.get() == WorkState::Idle {
+            self.work_state.set(WorkState::Pending);
+        }
     }
 
     /// Run a full collection cycle.
@@ -244,120 +1031,1044 @@ impl UnsafeArena {
     /// root. Implementor must ensure that GC pointers that where not rooted or traced before
     /// calling this method are no longer used after calling this method.
     pub unsafe fn collect_full(&self) {
+        // Objects traced in a running cycle are not traced again by a new cycle, so any running
+        // cycle must be finished before starting a new one.
+        while self.phase.get() != Phase::Sleep {
+            self.step();
+        }
         self.phase.set(Phase::Wake);
-        self.allocation_debt.set(f64::INFINITY);
-        self.collect()
+        while self.phase.get() != Phase::Sleep {
+            self.step();
+        }
     }
 
-    /// Allow the arena to collect pointers.
+    /// Run a full collection up to the start of the sweep, leaving every object reachable from a
+    /// root marked without freeing anything.
     ///
-    /// This arena implements partial collection cycles and sleeping between cycles thus this method
-    /// only marks a point where the arena could run garbage collection if nessacry.
+    /// Allocating panics until the sweep is finished with [`UnsafeArena::finish_sweep`] or
+    /// abandoned with [`UnsafeArena::abandon_sweep`]. Use [`UnsafeArena::is_marked`] to find out
+    /// whether an object is reachable.
     ///
     /// # Safety
-    /// This methods could possibly collect all pointers which are not rooted or traced from a
-    /// root. Implementor must ensure that GC pointers that where not rooted or traced before
-    /// calling this method are no longer used after calling this method.
-    pub unsafe fn collect(&self) {
-        //println!("=== Collecting ===");
-        if self.phase.get() == Phase::Sleep {
-            return;
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn mark_all(&self) {
+        while self.phase.get() != Phase::Sleep {
+            self.step();
         }
+        self.phase.set(Phase::Wake);
+        while self.phase.get() != Phase::Sweep {
+            self.step();
+        }
+        self.marked.set(true);
+    }
 
-        let work = self.allocation_debt.get();
+    /// Returns true if the object is marked as reachable by the current cycle.
+    ///
+    /// After [`UnsafeArena::mark_all`] an object is marked if and only if it is reachable from a
+    /// root.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
+    pub unsafe fn is_marked(&self, ptr: NonNull<GcBox<()>>) -> bool {
+        ptr.as_ref().data_ptr.status() != Status::Untraced
+    }
+
+    /// Stop the sweep of the current cycle without freeing any more objects, unmarking all
+    /// objects.
+    ///
+    /// Objects which are unreachable stay allocated until they are freed by a later cycle. Objects
+    /// which were already swept are freed.
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn abandon_sweep(&self) {
+        if self.phase.get() != Phase::Sweep {
+            return;
+        }
+        self.marked.set(false);
+        // Already swept objects are unmarked, the rest still has to be.
+        let mut cur = self.sweep.take();
+        while let Some(ptr) = cur {
+            cur = ptr.as_ref().next.get();
+            ptr.as_ref().data_ptr.set_status(Status::Untraced);
+        }
+        self.free_condemned();
+        self.sweep_prev.set(None);
+        self.phase.set(Phase::Sleep);
+        self.allocation_debt.set(0.0);
+        self.work_state.set(WorkState::Idle);
+        self.notify_transition(Phase::Sweep, Phase::Sleep);
+    }
+
+    /// Allow the arena to collect pointers.
+    ///
+    /// This arena implements partial collection cycles and sleeping between cycles thus this method
+    /// only marks a point where the arena could run garbage collection if nessacry.
+    ///
+    /// # Safety
+    /// This methods could possibly collect all pointers which are not rooted or traced from a
+    /// root. Implementor must ensure that GC pointers that where not rooted or traced before
+    /// calling this method are no longer used after calling this method.
+    pub unsafe fn collect(&self) {
+        //println!("=== Collecting ===");
+        if self.phase.get() == Phase::Sleep {
+            return;
+        }
+
+        let work = self.allocation_debt.get();
         let mut work_done = 0usize;
+        // Unlike the pacing the step limits also count the sweeping of objects as work.
+        let mut limited_work = 0usize;
+        let start = self.max_step_time.get().map(|_| Instant::now());
+        let mut steps = 0u32;
+        self.deferred_work.set(0);
 
         while work > work_done as f64 {
-            match self.phase.get() {
-                Phase::Wake => {
+            if self.step_limit_reached(limited_work, start, steps) {
+                self.deferred_work.set((work - work_done as f64) as usize);
+                break;
+            }
+            limited_work += self.sweep_size();
+            let done = self.step();
+            work_done += done;
+            limited_work += done;
+            steps += 1;
+            if self.phase.get() == Phase::Sleep {
+                break;
+            }
+        }
+        self.collect_work.set(limited_work);
+    }
+
+    /// Limit the amount of work done by a single call to [`UnsafeArena::collect`], in bytes of
+    /// objects traced or swept.
+    ///
+    /// A call stops as soon as its work reaches the limit, so it exceeds the limit by at most the
+    /// size of one object. Work left undone is carried over to the next call and reported by
+    /// [`GcStats::deferred_work`]. [`UnsafeArena::collect_full`] ignores the limit.
+    pub fn set_max_step_work(&self, limit: Option<usize>) {
+        self.max_step_work.set(limit);
+    }
+
+    /// Limit the number of bytes the objects of the arena may occupy, as counted by
+    /// [`GcStats::total_allocated`].
+    ///
+    /// Allocations which would exceed the limit fail with [`Error::LimitExceeded`], or panic for
+    /// the methods without a `try_` prefix. Objects which are no longer reachable count towards
+    /// the limit until they are swept, so a failed allocation can succeed after a collection.
+    pub fn set_heap_limit(&self, limit: Option<usize>) {
+        self.heap_limit.set(limit);
+    }
+
+    /// Returns the limit set with [`UnsafeArena::set_heap_limit`].
+    pub fn heap_limit(&self) -> Option<usize> {
+        self.heap_limit.get()
+    }
+
+    /// Limit the time spent by a single call to [`UnsafeArena::collect`].
+    ///
+    /// The time is only checked every few steps to keep the check cheap, so a call can exceed the
+    /// limit by the time of those steps. Work left undone is carried over like with
+    /// [`UnsafeArena::set_max_step_work`].
+    pub fn set_max_step_time(&self, limit: Option<Duration>) {
+        self.max_step_time.set(limit);
+    }
+
+    /// Returns true if a call to collect which did `work` in `steps` steps since `start` must
+    /// stop. Always allows the first step so every call makes progress.
+    fn step_limit_reached(&self, work: usize, start: Option<Instant>, steps: u32) -> bool {
+        if steps == 0 {
+            return false;
+        }
+        if self.max_step_work.get().is_some_and(|max| work >= max) {
+            return true;
+        }
+        match (start, self.max_step_time.get()) {
+            (Some(start), Some(max)) if steps.is_multiple_of(Self::TIME_CHECK_INTERVAL) => {
+                start.elapsed() >= max
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the size of the object the next step sweeps, if the next step sweeps one.
+    unsafe fn sweep_size(&self) -> usize {
+        match self.sweep.get() {
+            Some(ptr) if self.phase.get() == Phase::Sweep => self.allocation_size(ptr),
+            _ => 0,
+        }
+    }
+
+    /// Returns the phase the collector is currently in.
+    pub fn phase(&self) -> Phase {
+        self.phase.get()
+    }
+
+    /// Trace a single gray object, marking its children.
+    unsafe fn trace_gray(&self, ptr: NonNull<GcBox<()>>) {
+        //println!("tracing: {:?}", ptr.as_ptr());
+        let v_table = ptr.as_ref().data_ptr.v_table();
+        //println!("v table: {:?}", v_table as *const _);
+        let _unusable = Unusable::new(&self.usable);
+        let _tracing = Tracing::new(&self.tracing);
+        let outer = self.traced_type.replace(Some(v_table.type_name));
+        (v_table.trace)(ptr.as_ptr(), UnsafeMarker(MarkerKind::Arena(self)));
+        self.traced_type.set(outer);
+        ptr.as_ref().data_ptr.set_status(Status::Traced);
+    }
+
+    /// Perform a single unit of collection work, returning the amount of work done.
+    ///
+    /// A unit of work is either the scanning of a single root, the tracing of a single object, the
+    /// sweeping of a single object, or a transition between phases. Calling this method while the
+    /// arena is sleeping starts a new collection cycle.
+    ///
+    /// If tracing an object panics the current cycle is aborted, leaving the arena ready to start
+    /// a new cycle.
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn step(&self) -> usize {
+        #[cfg(feature = "root-provenance")]
+        provenance::step(self.provenance_key(), self.cycle.get());
+        match self.phase.get() {
+            Phase::Sleep => {
+                self.phase.set(Phase::Wake);
+                0
+            }
+            Phase::Wake => {
+                // Roots are scanned one at a time, the cursor is a link in the root list so guards
+                // can still be dropped while it points to them.
+                let cursor = Pin::new_unchecked(&*self.root_cursor);
+                if !cursor.is_linked() {
+                    self.cycle.set(self.cycle.get() + 1);
                     self.sweep_prev.set(None);
+                    cursor.link(Pin::new(&*self.roots));
+                    return 0;
+                }
 
-                    let mut cur = self.roots.next();
-                    while let Some(x) = cur {
-                        let root = x.cast::<UnsafeRootGuard>();
-                        let ptr = *root.as_ref().0.value.assume_init_ref();
-                        ptr.as_ref().data_ptr.set_status(Status::Marked);
-                        //println!("marking root: {:?}", ptr.as_ptr());
-                        self.grays.borrow_mut().push(ptr);
-                        cur = root.as_ref().0.next();
+                if let Some(x) = cursor.next() {
+                    let root = x.cast::<UnsafeRootGuard>();
+                    let ptr = root.as_ref().0.value.assume_init_ref().ptr;
+                    ptr.as_ref().data_ptr.set_status(Status::Marked);
+                    //println!("marking root: {:?}", ptr.as_ptr());
+                    // Objects without children only have to be marked.
+                    if (ptr.as_ref().data_ptr.v_table().needs_trace)() {
+                        self.grays.push(ptr);
                     }
 
-                    self.phase.set(Phase::Trace)
+                    cursor.unlink();
+                    cursor.link(Pin::new_unchecked(&root.as_ref().0));
+                    mem::size_of::<UnsafeRootGuard>()
+                } else {
+                    cursor.unlink();
+                    // Objects of deferred operations are kept alive until the operation has run.
+                    for deferred in self.deferred.borrow().iter() {
+                        UnsafeMarker(MarkerKind::Arena(self)).mark_erased(deferred.ptr);
+                    }
+                    self.host_ops.take_dirty();
+                    self.host_ops.for_each_ptr(|ptr| {
+                        UnsafeMarker(MarkerKind::Arena(self)).mark_erased(ptr);
+                    });
+                    self.phase.set(Phase::Trace);
+                    self.notify_transition(Phase::Wake, Phase::Trace);
+                    0
                 }
-                Phase::Trace => {
-                    let ptr = self.grays.borrow_mut().pop();
-                    if let Some(ptr) = ptr {
-                        //println!("tracing: {:?}", ptr.as_ptr());
-                        let v_table = ptr.as_ref().data_ptr.v_table();
-                        //println!("v table: {:?}", v_table as *const _);
-                        work_done += v_table.layout.size();
-                        (v_table.trace)(ptr.as_ptr(), UnsafeMarker(self));
-                        ptr.as_ref().data_ptr.set_status(Status::Traced);
-                    } else if let Some(ptr) = self.grays_again.borrow_mut().pop() {
-                        //println!("tracing: {:?}", ptr.as_ptr());
-                        let v_table = ptr.as_ref().data_ptr.v_table();
-                        (v_table.trace)(ptr.as_ptr(), UnsafeMarker(self));
-                        ptr.as_ref().data_ptr.set_status(Status::Traced);
-                    } else {
-                        self.phase.set(Phase::Sweep);
-                        self.sweep.set(self.all.get());
-                        self.remembered_size.set(0)
+            }
+            Phase::Trace => {
+                // A panicking trace implementation could leave objects marked with unmarked
+                // children, so the cycle is aborted instead of continued.
+                let abort = AbortCycle(self);
+                // Pointers are popped into a local first so that no borrow of the gray stacks is
+                // held while running the trace implementation, which marks into them.
+                let gray = self.pop_gray(&self.grays);
+                let gray_again = match gray {
+                    Some(_) => None,
+                    None => self.pop_gray(&self.grays_again),
+                };
+                let work = if let Some(ptr) = gray {
+                    self.trace_gray(ptr);
+                    self.allocation_size(ptr)
+                } else if let Some(ptr) = gray_again {
+                    self.trace_gray(ptr);
+                    0
+                } else if self.host_ops.take_dirty() {
+                    // Objects queued by the host after the roots were scanned might not be
+                    // reachable from anything which was traced.
+                    self.host_ops.for_each_ptr(|ptr| {
+                        UnsafeMarker(MarkerKind::Arena(self)).mark_erased(ptr);
+                    });
+                    0
+                } else {
+                    if self.verify.get() {
+                        self.verify_heap();
+                        self.check_barriers();
                     }
+                    #[cfg(feature = "root-provenance")]
+                    provenance::traced(self.provenance_key(), self.cycle.get(), |ptr| {
+                        ptr.as_ref().data_ptr.status() != Status::Untraced
+                    });
+                    self.phase.set(Phase::Sweep);
+                    self.sweep.set(self.all.get());
+                    self.remembered_size.set(0);
+                    0
+                };
+                mem::forget(abort);
+                if self.phase.get() == Phase::Sweep {
+                    self.notify_transition(Phase::Trace, Phase::Sweep);
                 }
-                Phase::Sweep => {
-                    if let Some(ptr) = self.sweep.get() {
-                        //println!("sweeping: {:?}", ptr.as_ptr());
-                        self.sweep.set(ptr.as_ref().next.get());
-                        let v_table = ptr.as_ref().data_ptr.v_table();
-                        if ptr.as_ref().data_ptr.status() == Status::Untraced {
-                            //println!("freeing: {:?}", ptr.as_ptr());
-                            if let Some(prev) = self.sweep_prev.get() {
-                                prev.as_ref().next.set(ptr.as_ref().next.get())
-                            } else {
-                                self.all.set(ptr.as_ref().next.get())
-                            }
-                            self.total_allocated
-                                .set(self.total_allocated.get() - v_table.layout.size());
-
-                            (v_table.drop)(ptr.as_ptr());
-                            std::alloc::dealloc(ptr.as_ptr().cast(), v_table.layout);
+                work
+            }
+            Phase::Sweep => {
+                if let Some(ptr) = self.sweep.get() {
+                    //println!("sweeping: {:?}", ptr.as_ptr());
+                    self.sweep.set(ptr.as_ref().next.get());
+                    let size = self.allocation_size(ptr);
+                    if ptr.as_ref().data_ptr.status() == Status::Untraced {
+                        //println!("freeing: {:?}", ptr.as_ptr());
+                        if let Some(prev) = self.sweep_prev.get() {
+                            prev.as_ref().next.set(ptr.as_ref().next.get())
                         } else {
-                            self.remembered_size
-                                .set(self.remembered_size.get() + v_table.layout.size());
-                            ptr.as_ref().data_ptr.set_status(Status::Untraced);
-                            self.sweep_prev.set(Some(ptr))
+                            self.all.set(ptr.as_ref().next.get())
                         }
+                        self.total_allocated.set(self.total_allocated.get() - size);
+
+                        self.remove_interned(ptr);
+                        if let Some(alive) = self.liveness.borrow_mut().remove(&ptr) {
+                            alive.set(false);
+                        }
+                        #[cfg(feature = "stable-id")]
+                        self.stable_ids.borrow_mut().ids.remove(&ptr);
+                        #[cfg(feature = "root-provenance")]
+                        provenance::freed(ptr);
+                        ptr.as_ref().next.set(self.condemned.replace(Some(ptr)));
                     } else {
-                        self.phase.set(Phase::Sleep);
-                        self.allocation_debt.set(0.0);
-                        self.wakeup_total.set(
-                            self.total_allocated.get()
-                                + ((self.remembered_size.get() as f64 * Self::PAUSE_FACTOR)
-                                    .round()
-                                    .min(usize::MAX as f64)
-                                    as usize)
-                                    .max(Self::MIN_SLEEP),
-                        );
-                        return;
+                        self.remembered_size.set(self.remembered_size.get() + size);
+                        ptr.as_ref().data_ptr.set_status(Status::Untraced);
+                        self.sweep_prev.set(Some(ptr))
                     }
+                } else {
+                    self.marked.set(false);
+                    self.free_condemned();
+                    self.shrink_grays();
+                    self.phase.set(Phase::Sleep);
+                    self.allocation_debt.set(0.0);
+                    self.work_state.set(WorkState::Idle);
+                    self.wakeup_total.set(
+                        self.total_allocated.get()
+                            + ((self.remembered_size.get() as f64 * Self::PAUSE_FACTOR)
+                                .round()
+                                .min(usize::MAX as f64) as usize)
+                                .max(Self::MIN_SLEEP),
+                    );
+                    self.notify_transition(Phase::Sweep, Phase::Sleep);
                 }
-                Phase::Sleep => break,
+                0
+            }
+        }
+    }
+
+    /// Notify the subscribers of a phase transition.
+    fn notify_transition(&self, from: Phase, to: Phase) {
+        let mut transitions = self.transitions.borrow_mut();
+        if transitions.hooks.is_empty() {
+            return;
+        }
+        let _unusable = Unusable::new(&self.usable);
+        let transition = Transition {
+            from,
+            to,
+            cycle: self.cycle.get(),
+        };
+        for (_, filter, hook) in transitions.hooks.iter_mut() {
+            if filter.contains(to) {
+                hook(transition);
             }
         }
     }
 
+    /// Pop a pointer from a gray stack, keeping track of the largest size of the stacks this
+    /// cycle.
+    fn pop_gray(&self, grays: &GrayStack) -> Option<NonNull<GcBox<()>>> {
+        self.gray_peak.set(self.gray_peak.get().max(grays.len()));
+        grays.pop()
+    }
+
+    /// Release the memory of the gray stacks if it is much more than the last cycle needed.
+    ///
+    /// Keeps the stacks from holding on to the memory needed for tracing a single large graph
+    /// forever.
+    fn shrink_grays(&self) {
+        let retain = self.gray_peak.replace(0).max(Self::MIN_GRAYS);
+        for grays in [&self.grays, &self.grays_again] {
+            if grays.capacity() > retain * 4 {
+                grays.shrink_to(retain);
+            }
+        }
+    }
+
+    /// Abort the current collection cycle, the next call to [`UnsafeArena::step`] starts a new
+    /// cycle.
+    ///
+    /// Used to return the arena to a consistent state when tracing panics.
+    unsafe fn abort_cycle(&self) {
+        self.grays.clear();
+        self.grays_again.clear();
+        let mut cur = self.all.get();
+        while let Some(ptr) = cur {
+            cur = ptr.as_ref().next.get();
+            ptr.as_ref().data_ptr.set_status(Status::Untraced);
+        }
+        self.phase.set(Phase::Wake);
+    }
+
+    /// Check that no traced object points to an unmarked object, which would be freed while still
+    /// reachable. This happens when an object receives a new pointer during tracing without the
+    /// write barrier being applied.
+    unsafe fn check_barriers(&self) {
+        let _unusable = Unusable::new(&self.usable);
+        let _tracing = Tracing::new(&self.tracing);
+        let mut cur = self.all.get();
+        while let Some(ptr) = cur {
+            cur = ptr.as_ref().next.get();
+            if ptr.as_ref().data_ptr.status() != Status::Traced {
+                continue;
+            }
+            visit_children(ptr, &|child| {
+                assert!(
+                    child.as_ref().data_ptr.status() != Status::Untraced,
+                    "traced object of type `{}` points to an unmarked object, a write barrier was \
+                     missed",
+                    (ptr.as_ref().data_ptr.v_table().type_name)()
+                );
+            });
+        }
+    }
+
+    /// Trace the heap from the roots again, in one go, and check that every reachable object is
+    /// marked. An unmarked object would be freed by the sweep while still reachable.
+    unsafe fn verify_heap(&self) {
+        let _unusable = Unusable::new(&self.usable);
+        let _tracing = Tracing::new(&self.tracing);
+
+        let mut reached = HashSet::new();
+        let mut stack = Vec::new();
+        let mut cur = self.roots.next();
+        while let Some(link) = cur {
+            cur = link.as_ref().next();
+            let root = link.cast::<UnsafeRootGuard>();
+            let ptr = root.as_ref().0.value.assume_init_ref().ptr;
+            if reached.insert(ptr) {
+                stack.push(ptr);
+            }
+        }
+
+        let children = RefCell::new(Vec::new());
+        while let Some(ptr) = stack.pop() {
+            visit_children(ptr, &|child| {
+                if child.as_ref().data_ptr.status() == Status::Untraced {
+                    let v_table = ptr.as_ref().data_ptr.v_table();
+                    let child_v_table = child.as_ref().data_ptr.v_table();
+                    panic!(
+                        "heap verification failed: object of type `{}` at {:?} is reachable from \
+                         object of type `{}` at {:?} but was not marked, a write barrier was \
+                         missed",
+                        (child_v_table.type_name)(),
+                        child.as_ptr(),
+                        (v_table.type_name)(),
+                        ptr.as_ptr(),
+                    );
+                }
+                children.borrow_mut().push(child);
+            });
+            stack.extend(
+                children
+                    .borrow_mut()
+                    .drain(..)
+                    .filter(|x| reached.insert(*x)),
+            );
+        }
+    }
+
+    /// Find an interned object of the type with the given id, see
+    /// [`erased_type_id`](super::erased_type_id), and the given hash for which `eq` returns true.
+    ///
+    /// Finishes the current sweep, if any, so that no unreachable object is returned which is
+    /// about to be freed.
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn find_interned(
+        &self,
+        type_id: TypeId,
+        hash: u64,
+        eq: &dyn Fn(NonNull<GcBox<()>>) -> bool,
+    ) -> Option<NonNull<GcBox<()>>> {
+        self.finish_sweep();
+        let interned = self.interned.borrow();
+        interned
+            .buckets
+            .get(&(type_id, hash))?
+            .iter()
+            .copied()
+            .find(|x| eq(*x))
+    }
+
+    /// Add an object to the interned table under the given hash.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
+    pub unsafe fn insert_interned(&self, ptr: NonNull<GcBox<()>>, hash: u64) {
+        let key = ((ptr.as_ref().data_ptr.v_table().type_id)(), hash);
+        let mut interned = self.interned.borrow_mut();
+        interned.buckets.entry(key).or_default().push(ptr);
+        interned.keys.insert(ptr, key);
+    }
+
+    /// Returns the number of objects in the interned table.
+    pub fn interned_count(&self) -> usize {
+        self.interned.borrow().keys.len()
+    }
+
+    /// Finish sweeping if the arena is sweeping.
+    ///
+    /// While sweeping, objects which are not yet swept might be unreachable and about to be freed.
+    /// Finishing the sweep first makes it safe to hand out pointers to objects which are only
+    /// known to be alive from a weak reference, like a [`Liveness`].
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn finish_sweep(&self) {
+        while self.phase.get() == Phase::Sweep {
+            self.step();
+        }
+    }
+
+    /// Returns the number of bytes of the allocation of the object, including its header.
+    ///
+    /// This is the amount by which [`GcStats::total_allocated`] changes when the object is
+    /// allocated or freed.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer.
+    pub unsafe fn allocation_size(&self, ptr: NonNull<GcBox<()>>) -> usize {
+        ptr.as_ref().data_ptr.v_table().allocation(ptr).0.size()
+    }
+
+    /// Start tracking whether the object is alive.
+    ///
+    /// # Safety
+    /// The pointer must be a valid, alive, GC pointer allocated by this arena.
+    pub unsafe fn liveness(&self, ptr: NonNull<GcBox<()>>) -> Liveness {
+        let mut liveness = self.liveness.borrow_mut();
+        Liveness(
+            liveness
+                .entry(ptr)
+                .or_insert_with(|| Rc::new(Cell::new(true)))
+                .clone(),
+        )
+    }
+
+    /// Returns the allocation sequence number of an object, or `None` if the object is not
+    /// allocated in this arena.
+    ///
+    /// Sequence numbers are never reused within an arena, so they identify an object for as long as
+    /// it is alive, regardless of collections.
+    #[cfg(feature = "stable-id")]
+    pub fn stable_id(&self, ptr: NonNull<GcBox<()>>) -> Option<u64> {
+        self.stable_ids.borrow().ids.get(&ptr).copied()
+    }
+
+    fn remove_interned(&self, ptr: NonNull<GcBox<()>>) {
+        let mut interned = self.interned.borrow_mut();
+        if let Some(key) = interned.keys.remove(&ptr) {
+            let bucket = interned.buckets.get_mut(&key).unwrap();
+            bucket.retain(|x| *x != ptr);
+            if bucket.is_empty() {
+                interned.buckets.remove(&key);
+            }
+        }
+    }
+
+    /// Drop and free all objects found to be unreachable during the sweep.
+    ///
+    /// All objects are dropped before any object is deallocated. This guarantees that during the
+    /// drop of an object the memory of any other object freed in the same cycle is still valid.
+    /// The value of such an other object might already have been dropped though, so a drop
+    /// implementation may only read the parts of it which are not invalidated by its drop, like
+    /// plain data fields.
+    unsafe fn free_condemned(&self) {
+        let condemned = self.condemned.take();
+
+        let unusable = Unusable::new(&self.usable);
+        let mut cur = condemned;
+        while let Some(ptr) = cur {
+            cur = ptr.as_ref().next.get();
+            (ptr.as_ref().data_ptr.v_table().drop)(ptr.as_ptr());
+        }
+        drop(unusable);
+
+        let mut cur = condemned;
+        while let Some(ptr) = cur {
+            cur = ptr.as_ref().next.get();
+            let (layout, offset) = ptr.as_ref().data_ptr.v_table().allocation(ptr);
+            std::alloc::dealloc(ptr.as_ptr().cast::<u8>().sub(offset), layout);
+        }
+    }
+
     /// Root a GC pointer ensuring that it will remain rooted for as long as the lifetime of th
     /// UnsafeRootGuard object,
     ///
     /// # Safety
     /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
     pub unsafe fn root<T>(&self, mut guard: Pin<&mut UnsafeRootGuard>, value: NonNull<GcBox<T>>) {
+        if self.tracing.get() {
+            self.unavailable(GcUnavailable::Tracing);
+        }
+        if cfg!(debug_assertions) && !self.usable.get() {
+            panic!("{}", Error::from(GcUnavailable::Dropping));
+        }
         //println!("rooting: {:?}", value.as_ptr());
-        guard.0.value.as_mut_ptr().write(value.cast::<GcBox<()>>());
-        guard
-            .into_ref()
-            .map_unchecked(|x| &x.0)
-            .link(Pin::new(&self.roots));
+        self.check_arena_id(value);
+        if guard.0.is_linked() {
+            let old = guard.0.value.assume_init_ref();
+            let count = old.count.as_ref();
+            count.set(count.get() - 1);
+            #[cfg(feature = "root-provenance")]
+            provenance::unrooted(guard.guard_addr(), old.ptr);
+            guard.0.unlink();
+        }
+        #[cfg(feature = "root-provenance")]
+        provenance::rooted(self.provenance_key(), guard.guard_addr(), value.cast());
+
+        // Roots are only scanned when a cycle starts so a pointer rooted during tracing must be
+        // marked here.
+        self.mark_erased(value.cast());
+
+        let count = self.roots.value.assume_init_ref();
+        guard.as_mut().get_unchecked_mut().0.value.write(RootValue {
+            ptr: value.cast::<GcBox<()>>(),
+            count: NonNull::from(count),
+        });
+        let link = guard.into_ref().map_unchecked(|x| &x.0);
+        if self.root_cursor.is_linked() {
+            // Roots are being scanned, linking after the cursor ensures this root is scanned as
+            // well.
+            link.link(Pin::new_unchecked(&*self.root_cursor));
+        } else {
+            link.link(Pin::new(&*self.roots));
+        }
+
+        count.set(count.get() + 1);
+        if self.root_limit.get() == Some(count.get() - 1) {
+            if let Some(mut hook) = self.root_limit_hook.take() {
+                hook(count.get());
+                // The hook might have replaced or removed itself.
+                if self.root_limit.get().is_some() {
+                    let new = self.root_limit_hook.take();
+                    self.root_limit_hook.set(Some(new.unwrap_or(hook)));
+                }
+            }
+        }
+    }
+
+    /// Returns false if the arena is being dropped or is running user code as part of a
+    /// collection, like [`Trace`](crate::Trace) or [`Drop`] implementations of GC objects.
+    ///
+    /// Allocating or rooting pointers is not allowed while the arena is unusable and will panic in
+    /// debug builds.
+    pub fn is_usable(&self) -> bool {
+        self.usable.get()
+    }
+
+    /// Returns the unique id of the arena, stored in every object it allocates.
+    #[cfg(feature = "arena-id")]
+    pub fn id(&self) -> NonZeroU64 {
+        self.id
+    }
+
+    /// Check that an object was allocated by this arena, or not allocated by an arena at all.
+    #[inline(always)]
+    unsafe fn check_arena_id<T>(&self, ptr: NonNull<GcBox<T>>) {
+        #[cfg(feature = "arena-id")]
+        if let Some(id) = ptr.as_ref().arena_id {
+            if id != self.id {
+                panic!(
+                    "{}",
+                    Error::WrongArena {
+                        object_arena: id.get(),
+                        arena: self.id.get(),
+                    }
+                );
+            }
+        }
+        #[cfg(not(feature = "arena-id"))]
+        let _ = ptr;
+    }
+
+    /// Panic with the error returned by [`UnsafeArena::check_allocate`], except that allocating in
+    /// an unusable arena only panics in debug builds.
+    #[inline(always)]
+    fn assert_allocate(&self) {
+        if let Err(reason) = self.check_allocate() {
+            if reason != GcUnavailable::Dropping || cfg!(debug_assertions) {
+                self.unavailable(reason);
+            }
+        }
+    }
+
+    /// Panic because the arena can't currently be used, naming the type whose trace
+    /// implementation is running if the arena is tracing.
+    #[cold]
+    fn unavailable(&self, reason: GcUnavailable) -> ! {
+        match self.traced_type.get() {
+            Some(type_name) if reason == GcUnavailable::Tracing => panic!(
+                "{} an object of type `{}`, trace implementations can defer work to after the \
+                 collection with `Marker::defer`",
+                Error::from(reason),
+                type_name()
+            ),
+            _ => panic!("{}", Error::from(reason)),
+        }
+    }
+
+    /// Returns an error if allocating would currently panic.
+    pub fn check_allocate(&self) -> Result<(), GcUnavailable> {
+        if self.walking.get() {
+            Err(GcUnavailable::Walking)
+        } else if self.marked.get() {
+            Err(GcUnavailable::Marked)
+        } else {
+            self.check_root()
+        }
+    }
+
+    /// Returns an error if rooting a pointer is currently not allowed.
+    pub fn check_root(&self) -> Result<(), GcUnavailable> {
+        if self.tracing.get() {
+            Err(GcUnavailable::Tracing)
+        } else if !self.usable.get() {
+            Err(GcUnavailable::Dropping)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The key identifying the arena in the root provenance table, the address of the root count
+    /// which guards also point to.
+    #[cfg(feature = "root-provenance")]
+    fn provenance_key(&self) -> usize {
+        self.roots.value.as_ptr() as usize
+    }
+
+    /// Returns the number of currently rooted pointers.
+    pub fn root_count(&self) -> usize {
+        unsafe { self.roots.value.assume_init_ref().get() }
+    }
+
+    /// Returns all currently rooted pointers, most recently rooted first.
+    pub fn roots(&self) -> Vec<NonNull<GcBox<()>>> {
+        let cursor = NonNull::from(&*self.root_cursor).cast::<ListLink<()>>();
+        let mut roots = Vec::with_capacity(self.root_count());
+        let mut cur = unsafe { self.roots.next() };
+        while let Some(link) = cur {
+            unsafe {
+                cur = link.as_ref().next();
+                // The cursor is part of the list while roots are being scanned.
+                if link != cursor {
+                    let root = link.cast::<UnsafeRootGuard>();
+                    roots.push(root.as_ref().0.value.assume_init_ref().ptr);
+                }
+            }
+        }
+        roots
+    }
+
+    /// Set a soft limit on the number of rooted pointers.
+    ///
+    /// The hook is called with the current number of roots every time the number of roots grows
+    /// past the limit.
+    pub fn set_root_limit(&self, limit: usize, hook: Box<dyn FnMut(usize)>) {
+        self.root_limit.set(Some(limit));
+        self.root_limit_hook.set(Some(hook));
+    }
+
+    /// Remove the soft limit on the number of rooted pointers.
+    pub fn clear_root_limit(&self) {
+        self.root_limit.set(None);
+        self.root_limit_hook.set(None);
+    }
+
+    /// Subscribe to transitions between the phases of a collection cycle.
+    ///
+    /// The function is called right after the collector transitions into one of the phases in the
+    /// filter, from within the call which performed the collection work. The arena can't be used
+    /// to allocate or root pointers while the function runs.
+    pub fn subscribe_transitions(
+        &self,
+        filter: PhaseMask,
+        hook: Box<dyn FnMut(Transition)>,
+    ) -> TransitionSubscription {
+        let mut transitions = self.transitions.borrow_mut();
+        let id = TransitionSubscription(transitions.next);
+        transitions.next += 1;
+        transitions.hooks.push((id, filter, hook));
+        id
+    }
+
+    /// Remove a subscription, returns false if it was already removed.
+    pub fn unsubscribe_transitions(&self, subscription: TransitionSubscription) -> bool {
+        let mut transitions = self.transitions.borrow_mut();
+        let len = transitions.hooks.len();
+        transitions.hooks.retain(|(id, _, _)| *id != subscription);
+        transitions.hooks.len() != len
+    }
+
+    /// Set a function which is asked to schedule collection work.
+    ///
+    /// Once allocations put the arena in debt, the next call to [`UnsafeArena::pump`] passes a
+    /// [`WorkRequest`] to the scheduler, and the call to `pump` after that performs the work. The
+    /// scheduler is only called from `pump`, never while allocating, and is not asked again until
+    /// the requested work is done.
+    pub fn set_work_scheduler(&self, scheduler: Box<dyn FnMut(WorkRequest)>) {
+        self.work_scheduler.set(Some(scheduler));
+    }
+
+    /// Remove the work scheduler.
+    pub fn clear_work_scheduler(&self) {
+        self.work_scheduler.set(None);
+    }
+
+    /// Drive work scheduling, returns true if collection work was performed.
+    ///
+    /// Does nothing unless work was requested. If work was requested since the last call the
+    /// scheduler is told, or if no scheduler is set the work is performed right away. If the
+    /// scheduler was told during the last call the work is performed, unless the collection cycle
+    /// was already finished by other means.
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn pump(&self) -> bool {
+        match self.work_state.get() {
+            WorkState::Idle => false,
+            WorkState::Pending => {
+                let Some(mut scheduler) = self.work_scheduler.take() else {
+                    self.work_state.set(WorkState::Idle);
+                    self.collect();
+                    return true;
+                };
+                self.work_state.set(WorkState::Scheduled);
+                scheduler(WorkRequest {
+                    work: self.allocation_debt.get() as usize,
+                    phase: self.phase.get(),
+                });
+                // The scheduler might have replaced itself.
+                let new = self.work_scheduler.take();
+                self.work_scheduler.set(Some(new.unwrap_or(scheduler)));
+                false
+            }
+            WorkState::Scheduled => {
+                self.work_state.set(WorkState::Idle);
+                self.collect();
+                true
+            }
+        }
+    }
+
+    /// Enable or disable heap verification.
+    ///
+    /// When enabled the arena traces the whole heap from the roots again right before every sweep,
+    /// panicking if an object which is about to be freed is still reachable, and checks that no
+    /// traced object points to an unmarked object. Such objects are the result of a missed write
+    /// barrier, for example when using
+    /// [`Gc::borrow_mut_no_barrier`](crate::Gc::borrow_mut_no_barrier) or a faulty
+    /// [`UnsafeTrace`] implementation. Verification makes every cycle as expensive as a full
+    /// collection and is meant for debugging.
+    pub fn set_verify(&self, verify: bool) {
+        self.verify.set(verify);
+    }
+
+    /// Returns the ledger of the open resources held by objects of the arena.
+    pub fn resources(&self) -> &Rc<ResourceLedger> {
+        &self.resources
+    }
+
+    /// Returns the queue of operations deferred by host code.
+    pub fn host_ops(&self) -> &Rc<HostOpQueue> {
+        &self.host_ops
+    }
+
+    /// Run the operations queued in the [`HostOpQueue`] of the arena in the order they were
+    /// queued, returning the number of operations run.
+    ///
+    /// Operations queued while running an operation are run as well. If an operation panics the
+    /// remaining operations stay queued. The owner pointer is passed to the operations unchanged.
+    ///
+    /// # Safety
+    /// The operations are free to access any object of the arena, caller must ensure that no
+    /// object is borrowed and that the owner pointer is what the queued operations expect.
+    ///
+    /// # Panic
+    /// Panics if the arena can't currently be used to allocate, see
+    /// [`UnsafeArena::check_allocate`].
+    pub unsafe fn flush_host_ops(&self, owner: NonNull<()>) -> usize {
+        self.assert_allocate();
+        let mut ran = 0;
+        while let Some((ptr, op)) = self.host_ops.pop() {
+            op(self, owner, ptr);
+            ran += 1;
+        }
+        ran
+    }
+
+    /// Enable or disable the resource audit.
+    ///
+    /// When enabled, dropping the arena panics if it drops any object holding a resource which is
+    /// still open, after all objects have been freed. Resources should instead be closed
+    /// explicitly, for example from a teardown hook.
+    pub fn set_audit_resources(&self, audit: bool) {
+        self.audit_resources.set(audit);
+    }
+
+    /// Run the operations queued with [`UnsafeMarker::defer`] in collection cycles which have
+    /// completed, in the order they were queued, returning the number of operations run.
+    ///
+    /// Operations queued while running an operation are run as well once their cycle has
+    /// completed. If an operation panics the remaining operations stay queued. The owner pointer
+    /// is passed to the operations unchanged.
+    ///
+    /// # Safety
+    /// The operations are free to access any object of the arena, caller must ensure that no
+    /// object is borrowed and that the owner pointer is what the queued operations expect.
+    ///
+    /// # Panic
+    /// Panics if the arena can't currently be used to allocate, see
+    /// [`UnsafeArena::check_allocate`].
+    pub unsafe fn run_deferred(&self, owner: NonNull<()>) -> usize {
+        self.assert_allocate();
+        let mut ran = 0;
+        loop {
+            let deferred = {
+                let mut deferred = self.deferred.borrow_mut();
+                // Operations are queued in cycle order, so once one isn't ready neither are the
+                // ones after it.
+                match deferred.first() {
+                    Some(x) if self.deferred_ready(x.cycle) => deferred.remove(0),
+                    _ => break,
+                }
+            };
+            (deferred.op)(self, owner, deferred.ptr);
+            ran += 1;
+        }
+        ran
+    }
+
+    /// Returns true if an operation queued in the given cycle can be run.
+    fn deferred_ready(&self, cycle: u64) -> bool {
+        self.cycle.get() > cycle || self.phase.get() == Phase::Sleep
+    }
+
+    /// Returns the number of operations queued with [`UnsafeMarker::defer`] which have not yet
+    /// run.
+    pub fn deferred_count(&self) -> usize {
+        self.deferred.borrow().len()
+    }
+
+    /// Register a hook to be run by [`UnsafeArena::run_teardown`].
+    pub fn on_teardown(&self, hook: TeardownHook) {
+        self.teardown.borrow_mut().push(hook);
+    }
+
+    /// Run all teardown hooks in the order they were registered, including hooks registered by
+    /// other hooks.
+    ///
+    /// Hooks which are never run are dropped with the arena.
+    ///
+    /// # Safety
+    /// The hooks are allowed to access all objects in the arena, so all requirements for
+    /// accessing GC values must be upheld for the duration of this call.
+    pub unsafe fn run_teardown(&self) {
+        loop {
+            let hooks = std::mem::take(&mut *self.teardown.borrow_mut());
+            if hooks.is_empty() {
+                break;
+            }
+            for hook in hooks {
+                hook(self);
+            }
+        }
+    }
+
+    /// Returns statistics about the current state of the arena.
+    pub fn stats(&self) -> GcStats {
+        GcStats {
+            total_allocated: self.total_allocated.get(),
+            root_count: self.root_count(),
+            phase: self.phase.get(),
+            cache_bytes: self.cache_bytes(),
+            gray_capacity: self.grays.capacity() + self.grays_again.capacity(),
+            collect_work: self.collect_work.get(),
+            deferred_work: self.deferred_work.get(),
+            write_barriers: self.write_barriers.get(),
+        }
+    }
+
+    fn cache_bytes(&self) -> usize {
+        let ptr_size = mem::size_of::<NonNull<GcBox<()>>>();
+        let interned = self.interned.borrow();
+        ptr_size * (self.grays.capacity() + self.grays_again.capacity())
+            + mem::size_of::<TeardownHook>() * self.teardown.borrow().capacity()
+            + mem::size_of::<((TypeId, u64), Vec<NonNull<GcBox<()>>>)>()
+                * interned.buckets.capacity()
+            + mem::size_of::<(NonNull<GcBox<()>>, (TypeId, u64))>() * interned.keys.capacity()
+            + interned
+                .buckets
+                .values()
+                .map(|x| ptr_size * x.capacity())
+                .sum::<usize>()
+            + mem::size_of::<(NonNull<GcBox<()>>, Rc<Cell<bool>>)>()
+                * self.liveness.borrow().capacity()
+    }
+
+    /// Shrink the internal buffers of the collector to the memory they currently need, returning
+    /// the number of bytes released.
+    pub fn shrink_caches(&self) -> usize {
+        let before = self.cache_bytes();
+        self.grays.shrink_to(0);
+        self.grays_again.shrink_to(0);
+        self.teardown.borrow_mut().shrink_to_fit();
+        let mut interned = self.interned.borrow_mut();
+        interned.buckets.values_mut().for_each(Vec::shrink_to_fit);
+        interned.buckets.shrink_to_fit();
+        interned.keys.shrink_to_fit();
+        drop(interned);
+        self.liveness.borrow_mut().shrink_to_fit();
+        before - self.cache_bytes()
+    }
+
+    /// Release memory in response to memory pressure, returning the number of bytes released.
+    ///
+    /// On [`MemoryPressure::Normal`] the current collection cycle is finished. On
+    /// [`MemoryPressure::Critical`] a full collection is run, after which the internal buffers of
+    /// the collector are shrunk. Objects are individually allocated with the global allocator so
+    /// the memory of freed objects is returned to the allocator immediately.
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`].
+    pub unsafe fn on_memory_pressure(&self, level: MemoryPressure) -> usize {
+        let before = self.total_allocated.get();
+        match level {
+            MemoryPressure::Normal => {
+                while self.phase.get() != Phase::Sleep {
+                    self.step();
+                }
+                before.saturating_sub(self.total_allocated.get())
+            }
+            MemoryPressure::Critical => {
+                self.collect_full();
+                before.saturating_sub(self.total_allocated.get()) + self.shrink_caches()
+            }
+        }
     }
 
     /// Mark an object as possibly containing new GC pointers. Any time an object that is allocated
@@ -367,27 +2078,194 @@ impl UnsafeArena {
     /// # Safety
     /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
     pub unsafe fn write_barrier<T: UnsafeTrace>(&self, value: NonNull<GcBox<T>>) {
-        if T::needs_trace() {
+        self.check_arena_id(value);
+        if !T::needs_trace() {
             return;
         }
+        self.write_barriers.set(self.write_barriers.get() + 1);
         unsafe {
             if self.phase.get() == Phase::Trace
                 && value.as_ref().data_ptr.status() == Status::Traced
             {
                 value.as_ref().data_ptr.set_status(Status::Marked);
-                self.grays_again
-                    .borrow_mut()
-                    .push(value.cast::<GcBox<()>>());
+                self.grays_again.push(value.cast::<GcBox<()>>());
+            }
+        }
+    }
+    /// Call the given function for every object allocated in this arena of the type with the
+    /// given id, see [`erased_type_id`](super::erased_type_id).
+    ///
+    /// If the arena is in the middle of sweeping, the sweep is finished first so that no object
+    /// handed to the function can refer to an object which was already freed. Objects which are
+    /// unreachable but not yet swept are still visited. Allocating during the walk will panic.
+    ///
+    /// # Safety
+    /// Same as [`UnsafeArena::collect`] as this method might finish the current sweep.
+    pub unsafe fn for_each_erased<F: FnMut(NonNull<GcBox<()>>)>(&self, type_id: TypeId, mut f: F) {
+        while self.phase.get() == Phase::Sweep {
+            self.step();
+        }
+
+        self.for_each_object(|ptr| {
+            if (ptr.as_ref().data_ptr.v_table().type_id)() == type_id {
+                f(ptr);
+            }
+        })
+    }
+
+    /// Returns true if the address is the address of an object allocated in this arena.
+    ///
+    /// Checks every object of the arena, so it is meant for debug checks.
+    pub fn is_object_address(&self, addr: usize) -> bool {
+        let mut cur = self.all.get();
+        while let Some(ptr) = cur {
+            if ptr.as_ptr() as usize == addr {
+                return true;
             }
+            cur = unsafe { ptr.as_ref().next.get() };
+        }
+        false
+    }
+
+    /// Call the given function for every object allocated in this arena.
+    ///
+    /// Objects which are unreachable but not yet freed are visited as well. Allocating during the
+    /// walk will panic.
+    ///
+    /// # Safety
+    /// The function must not free any objects.
+    pub unsafe fn for_each_object<F: FnMut(NonNull<GcBox<()>>)>(&self, mut f: F) {
+        struct WalkGuard<'a>(&'a Cell<bool>);
+        impl Drop for WalkGuard<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        assert!(
+            !self.walking.replace(true),
+            "cannot iterate the arena while already iterating it"
+        );
+        let _guard = WalkGuard(&self.walking);
+
+        let mut cur = self.all.get();
+        while let Some(ptr) = cur {
+            cur = ptr.as_ref().next.get();
+            f(ptr);
+        }
+    }
+
+    /// Mark an object as alive if the arena is currently tracing.
+    ///
+    /// Objects allocated during tracing start out unmarked, so an object which is only referenced
+    /// from an already traced object would be freed. Marking it ensures it will be traced during the
+    /// current cycle.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
+    pub unsafe fn mark_erased(&self, ptr: NonNull<GcBox<()>>) {
+        if self.phase.get() == Phase::Trace {
+            UnsafeMarker(MarkerKind::Arena(self)).mark_erased(ptr);
+        }
+    }
+
+    /// Mark all pointers contained in a value if the arena is currently tracing.
+    ///
+    /// For storing a value into an object without applying the write barrier to the object, when
+    /// the object is not known. Marking the pointers of the value ensures they will be traced
+    /// during the current cycle even if the object was already traced.
+    ///
+    /// # Safety
+    /// Caller must ensure that all pointers in the value are valid, alive, GC pointers allocated
+    /// by this arena.
+    pub unsafe fn mark_value<T: UnsafeTrace>(&self, value: &T) {
+        if self.phase.get() == Phase::Trace && T::needs_trace() {
+            value.trace(UnsafeMarker(MarkerKind::Arena(self)));
+        }
+    }
+
+    /// Mark a type erased object as possibly containing new GC pointers.
+    ///
+    /// Unlike [`UnsafeArena::write_barrier`] this method can't check if the type needs tracing.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
+    pub unsafe fn write_barrier_erased(&self, value: NonNull<GcBox<()>>) {
+        self.check_arena_id(value);
+        self.write_barriers.set(self.write_barriers.get() + 1);
+        if self.phase.get() == Phase::Trace && value.as_ref().data_ptr.status() == Status::Traced {
+            value.as_ref().data_ptr.set_status(Status::Marked);
+            self.grays_again.push(value);
+        }
+    }
+
+    /// Mark a type erased object as possibly containing new GC pointers, deferring the tracing of
+    /// the object until it is passed to [`UnsafeArena::write_barrier_flush`].
+    ///
+    /// Returns true if the object has to be passed to [`UnsafeArena::write_barrier_flush`]. This
+    /// is the case at most once per object until the next flush, so the objects don't have to be
+    /// deduplicated.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena,
+    /// and that the object is flushed before the collector runs again.
+    pub unsafe fn write_barrier_deferred(&self, value: NonNull<GcBox<()>>) -> bool {
+        self.check_arena_id(value);
+        self.write_barriers.set(self.write_barriers.get() + 1);
+        if self.phase.get() == Phase::Trace && value.as_ref().data_ptr.status() == Status::Traced {
+            value.as_ref().data_ptr.set_status(Status::Marked);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Trace all objects deferred with [`UnsafeArena::write_barrier_deferred`] again.
+    ///
+    /// # Safety
+    /// All pointers must be pointers for which [`UnsafeArena::write_barrier_deferred`] returned
+    /// true without the collector having run since.
+    pub unsafe fn write_barrier_flush<I>(&self, values: I)
+    where
+        I: IntoIterator<Item = NonNull<GcBox<()>>>,
+    {
+        // Pushed one by one so the iterator doesn't run while the stack is borrowed.
+        for value in values {
+            self.grays_again.push(value);
         }
     }
 }
 
 impl Drop for UnsafeArena {
     fn drop(&mut self) {
+        self.usable.set(false);
+        // Deferred operations are never run, their objects must not be kept alive.
+        self.deferred.get_mut().clear();
+        self.host_ops.close();
         unsafe {
+            // Detach all guards so guards which outlive the arena don't refer to it.
+            let mut cur = self.roots.next();
+            while let Some(x) = cur {
+                cur = x.as_ref().next();
+                x.as_ref().clear();
+            }
             self.roots.clear();
-            self.collect_full();
+        }
+        let open = self.resources.open_ids();
+        unsafe { self.collect_full() };
+        #[cfg(feature = "root-provenance")]
+        provenance::arena_dropped(self.provenance_key());
+
+        if self.audit_resources.get() && !std::thread::panicking() {
+            // Resources still open after the collection are not held by an object of the arena.
+            let dropped = open
+                .into_iter()
+                .filter(|x| !self.resources.is_open(*x))
+                .count();
+            assert!(
+                dropped == 0,
+                "dropping the arena dropped {dropped} resources which were never closed"
+            );
         }
     }
 }
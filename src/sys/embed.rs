@@ -0,0 +1,253 @@
+//! Building blocks for safe abstractions on top of the unsafe arena.
+//!
+//! These types capture the patterns a safe API built on [`UnsafeArena`] needs: a list of roots kept
+//! alive by a single guard, tracing of type erased payloads, and an arena branded with the owner
+//! lifetime. The [`scoped`](crate::scoped) arena is implemented with them and can serve as an
+//! example.
+
+use std::{mem::ManuallyDrop, pin::Pin, ptr::NonNull};
+
+use crate::{Invariant, Owner, Trace};
+
+use super::{GcBox, UnsafeArena, UnsafeMarker, UnsafeRootGuard, UnsafeTrace};
+
+/// A GC payload which is traced by a function.
+///
+/// Allows tracing a type which does not implement [`Trace`], like a collection of type erased
+/// pointers, without implementing [`UnsafeTrace`] for it.
+pub struct TraceFn<T> {
+    value: T,
+    trace: fn(&T, UnsafeMarker),
+}
+
+unsafe impl<T> UnsafeTrace for TraceFn<T> {
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: UnsafeMarker) {
+        (self.trace)(&self.value, marker)
+    }
+}
+
+impl<T> TraceFn<T> {
+    /// Create a payload which is traced by calling the given function.
+    ///
+    /// # Safety
+    /// The function must mark every GC pointer contained in the value and must only mark valid,
+    /// alive, GC pointers allocated by the arena the payload is allocated in.
+    pub unsafe fn new(value: T, trace: fn(&T, UnsafeMarker)) -> Self {
+        TraceFn { value, trace }
+    }
+
+    /// Returns a reference to the contained value.
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the contained value.
+    ///
+    /// # Safety
+    /// Caller must call the write barrier of the object containing the payload if pointers are
+    /// added to the value, and must only add valid, alive, GC pointers.
+    pub unsafe fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Returns the contained value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: AsRef<[NonNull<GcBox<()>>]>> TraceFn<T> {
+    /// Create a payload which marks all type erased pointers in the value.
+    ///
+    /// # Safety
+    /// All pointers must be valid, alive, GC pointers allocated by the arena the payload is
+    /// allocated in.
+    pub unsafe fn erased(value: T) -> Self {
+        TraceFn::new(value, |value, marker| {
+            for ptr in value.as_ref().iter().copied() {
+                unsafe { marker.mark_erased(ptr) }
+            }
+        })
+    }
+}
+
+type Roots = TraceFn<Vec<NonNull<GcBox<()>>>>;
+
+/// A list of type erased GC pointers which are all rooted by a single guard.
+///
+/// The list itself is not allocated in the arena, it is rooted with [`RootList::root`] and then
+/// keeps all pointers pushed into it alive.
+pub struct RootList {
+    roots: GcBox<Roots>,
+}
+
+impl RootList {
+    /// Create a new empty list.
+    pub fn new() -> Self {
+        RootList {
+            roots: GcBox::new(unsafe { TraceFn::erased(Vec::new()) }),
+        }
+    }
+
+    /// Root the list, keeping all pointers in it alive for as long as the guard is alive.
+    ///
+    /// # Safety
+    /// The list must not be moved or dropped while the guard is alive, and may only be rooted in
+    /// the arena which allocated the pointers pushed into it.
+    pub unsafe fn root(&self, arena: &UnsafeArena, guard: Pin<&mut UnsafeRootGuard>) {
+        arena.root(guard, NonNull::from(&self.roots))
+    }
+
+    /// Add a pointer to the list.
+    ///
+    /// The pointer is marked if the arena is tracing, as the list might already have been traced
+    /// this cycle.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by the given
+    /// arena.
+    pub unsafe fn push(&self, arena: &UnsafeArena, ptr: NonNull<GcBox<()>>) {
+        arena.mark_erased(ptr);
+        self.with_roots(|roots| roots.push(ptr));
+    }
+
+    /// Returns the number of pointers in the list.
+    pub fn len(&self) -> usize {
+        unsafe { (*self.roots.value.get()).get().len() }
+    }
+
+    /// Returns true if the list contains no pointers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a copy of the pointers in the list.
+    pub fn pointers(&self) -> Vec<NonNull<GcBox<()>>> {
+        self.with_roots(|roots| roots.clone())
+    }
+
+    /// Remove all pointers added after the list had the given length.
+    pub fn truncate(&self, len: usize) {
+        self.with_roots(|roots| roots.truncate(len))
+    }
+
+    /// Returns a guard which removes all pointers added after this call when dropped.
+    pub fn scope(&self) -> RootScope<'_> {
+        RootScope {
+            list: self,
+            len: self.len(),
+        }
+    }
+
+    fn with_roots<R>(&self, f: impl FnOnce(&mut Vec<NonNull<GcBox<()>>>) -> R) -> R {
+        // The list is only ever borrowed for the duration of a single method, tracing it doesn't
+        // call any user code which could access it.
+        unsafe { f((*self.roots.value.get()).get_mut()) }
+    }
+}
+
+impl Default for RootList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for RootList {
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(self.roots.value.get_mut()) }
+    }
+}
+
+/// A guard which truncates a [`RootList`] back to its length at creation, see
+/// [`RootList::scope`].
+pub struct RootScope<'a> {
+    list: &'a RootList,
+    len: usize,
+}
+
+impl Drop for RootScope<'_> {
+    fn drop(&mut self) {
+        self.list.truncate(self.len)
+    }
+}
+
+/// An unsafe arena branded with the lifetime of an owner.
+///
+/// Only types implementing [`Trace`] for the owner lifetime can be allocated into a branded arena,
+/// ensuring all objects of the arena belong to the same owner.
+#[repr(transparent)]
+pub struct BrandedArena<'own> {
+    arena: UnsafeArena,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> BrandedArena<'own> {
+    /// Brand an arena with the lifetime of the given owner.
+    ///
+    /// # Safety
+    /// The owner must be the only owner with its lifetime and all objects already allocated in
+    /// the arena must belong to it.
+    pub unsafe fn new(arena: UnsafeArena, _owner: &Owner<'own>) -> Self {
+        BrandedArena {
+            arena,
+            _invariant: Invariant::new(),
+        }
+    }
+
+    /// Brand a reference to an arena with the owner lifetime.
+    ///
+    /// # Safety
+    /// Same as [`BrandedArena::new`].
+    pub unsafe fn from_ref(arena: &UnsafeArena) -> &Self {
+        &*(arena as *const UnsafeArena).cast::<Self>()
+    }
+
+    /// Call the given function with the arena branded with a new unique lifetime and the owner of
+    /// that lifetime.
+    ///
+    /// As the lifetime can't escape the function, pointers allocated in the function can only be
+    /// used within it unless they are rooted by unsafe means.
+    pub fn with<R, F>(arena: &mut UnsafeArena, f: F) -> R
+    where
+        F: for<'a> FnOnce(&mut Owner<'a>, &BrandedArena<'a>) -> R,
+    {
+        unsafe {
+            let mut owner = Owner::new();
+            f(&mut owner, BrandedArena::from_ref(arena))
+        }
+    }
+
+    /// Allocate a value into the arena.
+    ///
+    /// The returned pointer is only valid until the next collection unless it is rooted or traced
+    /// from a root.
+    pub fn add<T: Trace<'own>>(&self, value: T) -> NonNull<GcBox<T>> {
+        unsafe { self.arena.add(value) }
+    }
+
+    /// Signal the arena that the object behind a pointer might have been given new pointers, see
+    /// [`UnsafeArena::write_barrier`].
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by this arena.
+    pub unsafe fn write_barrier<T: Trace<'own>>(&self, ptr: NonNull<GcBox<T>>) {
+        self.arena.write_barrier(ptr)
+    }
+
+    /// Returns the unbranded arena.
+    pub fn unsafe_arena(&self) -> &UnsafeArena {
+        &self.arena
+    }
+
+    /// Returns the unbranded arena.
+    pub fn into_unsafe_arena(self) -> UnsafeArena {
+        self.arena
+    }
+}
@@ -0,0 +1,88 @@
+//! Operations queued by host code which has no access to the arena.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    ptr::NonNull,
+};
+
+use super::{GcBox, UnsafeArena};
+
+/// An operation queued in a [`HostOpQueue`], called with the arena, the owner pointer passed to
+/// [`UnsafeArena::flush_host_ops`] and the object it was queued with.
+pub type HostOp = Box<dyn FnOnce(&UnsafeArena, NonNull<()>, Option<NonNull<GcBox<()>>>)>;
+
+struct Queued {
+    ptr: Option<NonNull<GcBox<()>>>,
+    op: HostOp,
+}
+
+/// The queue of operations deferred by host code, see [`DeferredOps`](crate::DeferredOps).
+///
+/// Shared between the arena and the handles to the queue, so operations can be queued without
+/// access to the arena, for example from a [`Drop`] implementation. The objects of queued
+/// operations are roots of the arena until the operation has run.
+#[derive(Default)]
+pub struct HostOpQueue {
+    ops: RefCell<VecDeque<Queued>>,
+    /// Set when an operation is queued, so objects queued while the arena is tracing are marked
+    /// before the cycle is allowed to sweep.
+    dirty: Cell<bool>,
+    closed: Cell<bool>,
+}
+
+impl HostOpQueue {
+    /// Queue an operation.
+    ///
+    /// Operations queued after the arena was dropped are dropped immediately.
+    ///
+    /// # Safety
+    /// Caller must ensure that the pointer is a valid, alive, GC pointer allocated by the arena of
+    /// the queue.
+    pub unsafe fn push(&self, ptr: Option<NonNull<GcBox<()>>>, op: HostOp) {
+        if self.closed.get() {
+            return;
+        }
+        self.dirty.set(ptr.is_some() || self.dirty.get());
+        self.ops.borrow_mut().push_back(Queued { ptr, op });
+    }
+
+    /// Remove the oldest operation from the queue.
+    ///
+    /// The object of the operation is no longer kept alive by the queue.
+    pub fn pop(&self) -> Option<(Option<NonNull<GcBox<()>>>, HostOp)> {
+        self.ops.borrow_mut().pop_front().map(|x| (x.ptr, x.op))
+    }
+
+    /// Returns the number of queued operations.
+    pub fn len(&self) -> usize {
+        self.ops.borrow().len()
+    }
+
+    /// Returns true if no operations are queued.
+    pub fn is_empty(&self) -> bool {
+        self.ops.borrow().is_empty()
+    }
+
+    /// Returns true if operations with an object were queued since the last call, clearing the
+    /// flag.
+    pub(super) fn take_dirty(&self) -> bool {
+        self.dirty.replace(false)
+    }
+
+    /// Call the function with the object of every queued operation.
+    pub(super) fn for_each_ptr(&self, mut f: impl FnMut(NonNull<GcBox<()>>)) {
+        for ptr in self.ops.borrow().iter().filter_map(|x| x.ptr) {
+            f(ptr)
+        }
+    }
+
+    /// Drop all queued operations without running them and drop any operation queued later.
+    pub(super) fn close(&self) {
+        self.closed.set(true);
+        // Taken out of the queue first so operations which queue others from their drop
+        // implementation don't borrow the queue twice.
+        let ops = std::mem::take(&mut *self.ops.borrow_mut());
+        drop(ops);
+    }
+}
@@ -6,6 +6,26 @@ pub use arena::*;
 mod ptr;
 pub use ptr::*;
 
+pub mod embed;
+
+mod host_ops;
+pub use host_ops::{HostOp, HostOpQueue};
+
+mod resource;
+pub use resource::ResourceLedger;
+
+mod undo;
+pub use undo::UndoLog;
+
+#[cfg(feature = "root-provenance")]
+mod provenance;
+#[cfg(feature = "root-provenance")]
+pub(crate) use provenance::warn_unrooted;
+#[cfg(feature = "root-provenance")]
+pub use provenance::{check_rooted, RootSuspect};
+
+use std::fmt;
+
 use crate::{arena::Marker, Trace};
 
 /// The lifetime erased version of [`Trace`] used in the unsafe API.
@@ -22,6 +42,11 @@ pub unsafe trait UnsafeTrace {
 
     /// Trace the object marking all GC pointers contained in the implementing object.
     fn trace(&self, marker: UnsafeMarker);
+
+    /// Write the value of the object if it is a leaf, see [`Trace::fmt_leaf`].
+    fn fmt_leaf(&self, _w: &mut dyn fmt::Write) -> Option<fmt::Result> {
+        None
+    }
 }
 
 unsafe impl<'own, T: Trace<'own>> UnsafeTrace for T {
@@ -35,4 +60,8 @@ unsafe impl<'own, T: Trace<'own>> UnsafeTrace for T {
     fn trace(&self, marker: UnsafeMarker) {
         <Self as Trace<'own>>::trace(self, unsafe { Marker::from_unsafe(marker) })
     }
+
+    fn fmt_leaf(&self, w: &mut dyn fmt::Write) -> Option<fmt::Result> {
+        <Self as Trace<'own>>::fmt_leaf(self, w)
+    }
 }
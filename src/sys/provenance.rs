@@ -0,0 +1,239 @@
+//! Tracking which guards rooted which objects, for finding pointers used after their root was
+//! dropped.
+//!
+//! The table is kept per thread so pointers can be checked without access to their arena.
+
+use std::{backtrace::Backtrace, cell::RefCell, collections::HashMap, fmt, ptr::NonNull, rc::Rc};
+
+use super::GcBox;
+
+/// A pointer which was used after the last guard rooting it was dropped, see [`check_rooted`].
+#[derive(Clone, Debug)]
+pub struct RootSuspect {
+    /// The address of the guard which rooted the pointer last.
+    pub guard: usize,
+    /// The sequence number of the root, counting all roots created on this thread.
+    pub root_id: u64,
+    /// The backtrace of the code which created the root, only captured if enabled by the
+    /// `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables.
+    pub backtrace: Rc<Backtrace>,
+    /// Whether the object has already been freed.
+    pub freed: bool,
+}
+
+impl fmt::Display for RootSuspect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pointer used after its last root (#{}, guard at {:#x}) was dropped",
+            self.root_id, self.guard
+        )?;
+        if self.freed {
+            write!(f, ", the object has been freed")?;
+        }
+        write!(f, "\nroot created at:\n{}", self.backtrace)
+    }
+}
+
+struct RootRecord {
+    arena: usize,
+    guard: usize,
+    id: u64,
+    backtrace: Rc<Backtrace>,
+}
+
+struct DroppedRoot {
+    record: RootRecord,
+    /// The number of collection steps of the arena when the root was dropped.
+    steps: u64,
+    /// The collection cycle of the arena when the root was dropped.
+    cycle: u64,
+    freed: bool,
+    warned: bool,
+}
+
+#[derive(Default)]
+struct ArenaState {
+    steps: u64,
+    cycle: u64,
+}
+
+#[derive(Default)]
+struct ProvenanceTable {
+    next_id: u64,
+    live: HashMap<usize, Vec<RootRecord>>,
+    dropped: HashMap<usize, DroppedRoot>,
+    arenas: HashMap<usize, ArenaState>,
+}
+
+thread_local! {
+    static TABLE: RefCell<ProvenanceTable> = RefCell::new(ProvenanceTable::default());
+}
+
+fn with_table<R: Default>(f: impl FnOnce(&mut ProvenanceTable) -> R) -> R {
+    // The table might already be destroyed if an arena is dropped during thread teardown.
+    TABLE
+        .try_with(|table| f(&mut table.borrow_mut()))
+        .unwrap_or_default()
+}
+
+/// Record that the guard rooted the object.
+pub(crate) fn rooted(arena: usize, guard: usize, ptr: NonNull<GcBox<()>>) {
+    with_table(|table| {
+        let id = table.next_id;
+        table.next_id += 1;
+        table.dropped.remove(&(ptr.as_ptr() as usize));
+        table
+            .live
+            .entry(ptr.as_ptr() as usize)
+            .or_default()
+            .push(RootRecord {
+                arena,
+                guard,
+                id,
+                backtrace: Rc::new(Backtrace::capture()),
+            });
+    })
+}
+
+/// Record that the guard no longer roots the object.
+pub(crate) fn unrooted(guard: usize, ptr: NonNull<GcBox<()>>) {
+    with_table(|table| {
+        let key = ptr.as_ptr() as usize;
+        let Some(records) = table.live.get_mut(&key) else {
+            return;
+        };
+        let Some(idx) = records.iter().position(|x| x.guard == guard) else {
+            return;
+        };
+        let record = records.swap_remove(idx);
+        if !records.is_empty() {
+            return;
+        }
+        table.live.remove(&key);
+
+        let state = table.arenas.entry(record.arena).or_default();
+        let dropped = DroppedRoot {
+            steps: state.steps,
+            cycle: state.cycle,
+            record,
+            freed: false,
+            warned: false,
+        };
+        table.dropped.insert(key, dropped);
+    })
+}
+
+/// Record that the root of the object moved to a different guard.
+pub(crate) fn moved(from: usize, to: usize, ptr: NonNull<GcBox<()>>) {
+    with_table(|table| {
+        if let Some(records) = table.live.get_mut(&(ptr.as_ptr() as usize)) {
+            if let Some(record) = records.iter_mut().find(|x| x.guard == from) {
+                record.guard = to;
+            }
+        }
+    })
+}
+
+/// Record a step of collection work of the arena.
+pub(crate) fn step(arena: usize, cycle: u64) {
+    with_table(|table| {
+        let state = table.arenas.entry(arena).or_default();
+        state.steps += 1;
+        state.cycle = cycle;
+    })
+}
+
+/// Forget dropped roots of objects which were found to be alive in a cycle which started after
+/// the root was dropped, as the objects are reachable from another root or from the heap.
+pub(crate) fn traced(arena: usize, cycle: u64, is_marked: impl Fn(NonNull<GcBox<()>>) -> bool) {
+    with_table(|table| {
+        table.dropped.retain(|ptr, dropped| {
+            dropped.record.arena != arena
+                || dropped.freed
+                || dropped.cycle >= cycle
+                || !is_marked(NonNull::new(*ptr as *mut GcBox<()>).unwrap())
+        })
+    })
+}
+
+/// Record that a new object was allocated, possibly reusing the address of a freed object.
+pub(crate) fn allocated(ptr: NonNull<GcBox<()>>) {
+    with_table(|table| {
+        table.dropped.remove(&(ptr.as_ptr() as usize));
+    })
+}
+
+/// Record that an object was freed.
+pub(crate) fn freed(ptr: NonNull<GcBox<()>>) {
+    with_table(|table| {
+        if let Some(dropped) = table.dropped.get_mut(&(ptr.as_ptr() as usize)) {
+            dropped.freed = true;
+        }
+    })
+}
+
+/// Forget everything recorded for the arena.
+pub(crate) fn arena_dropped(arena: usize) {
+    with_table(|table| {
+        table.arenas.remove(&arena);
+        table.dropped.retain(|_, x| x.record.arena != arena);
+        table.live.retain(|_, records| {
+            records.retain(|x| x.arena != arena);
+            !records.is_empty()
+        });
+        // Release the memory of the table once no arena uses it anymore, so dropping every
+        // arena releases all memory.
+        if table.arenas.is_empty() && table.live.is_empty() && table.dropped.is_empty() {
+            table.arenas.shrink_to_fit();
+            table.live.shrink_to_fit();
+            table.dropped.shrink_to_fit();
+        }
+    })
+}
+
+/// Check whether the pointer is suspected to be used after the last guard rooting it was dropped.
+///
+/// A pointer is suspect if every guard which rooted it was dropped, the collector did work since,
+/// and no collection cycle which started after the last root was dropped found the object to be
+/// alive. Pointers which were never rooted are never suspect.
+///
+/// This can report false positives: an object which is only reachable from the heap is suspect
+/// until a cycle finishes tracing, and pointers which were safely rebound by unsafe code are
+/// reported as well. The tool is meant to point at suspects, not to prove misuse.
+pub fn check_rooted(ptr: NonNull<GcBox<()>>) -> Option<RootSuspect> {
+    with_table(|table| {
+        let dropped = table.dropped.get(&(ptr.as_ptr() as usize))?;
+        let steps = table
+            .arenas
+            .get(&dropped.record.arena)
+            .map(|x| x.steps)
+            .unwrap_or_default();
+        if steps == dropped.steps {
+            return None;
+        }
+        Some(RootSuspect {
+            guard: dropped.record.guard,
+            root_id: dropped.record.id,
+            backtrace: dropped.record.backtrace.clone(),
+            freed: dropped.freed,
+        })
+    })
+}
+
+/// Print a warning if the pointer is suspect, see [`check_rooted`]. Warns only once for every
+/// dropped root.
+pub(crate) fn warn_unrooted(ptr: NonNull<GcBox<()>>) {
+    if let Some(suspect) = check_rooted(ptr) {
+        let first = with_table(|table| {
+            table
+                .dropped
+                .get_mut(&(ptr.as_ptr() as usize))
+                .map(|x| !std::mem::replace(&mut x.warned, true))
+                .unwrap_or_default()
+        });
+        if first {
+            eprintln!("dreck warning: {suspect}");
+        }
+    }
+}
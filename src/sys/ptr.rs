@@ -1,22 +1,72 @@
 use std::{
     alloc::Layout,
+    any::TypeId,
     cell::{Cell, UnsafeCell},
-    mem::ManuallyDrop,
-    ptr::NonNull,
+    fmt,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ptr::{self, NonNull},
 };
 
+#[cfg(feature = "arena-id")]
+use std::num::NonZeroU64;
+
 use super::{UnsafeMarker, UnsafeTrace};
 
+/// Returns the layout of the allocation of a dynamically sized object and the offset of the
+/// object within it.
+pub type DynLayoutFn = unsafe fn(*mut GcBox<()>) -> (Layout, usize);
+
 /// A custom v-table for a GC allocated type.
 #[repr(align(16))]
 pub struct GcVTable {
     /// The layout of the type in the GcBox so if this v-table is for type `T` the layout would be
     /// for `GcBox<T>`
     pub layout: Layout,
+    /// Returns whether the type can contain GC pointers and thus needs to be traced, see
+    /// [`UnsafeTrace::needs_trace`].
+    pub needs_trace: fn() -> bool,
     /// The method for tracing the type.
     pub trace: unsafe fn(*mut GcBox<()>, UnsafeMarker),
     /// The method for dropping the type.
     pub drop: unsafe fn(*mut GcBox<()>),
+    /// The method for formatting the type if it is a leaf.
+    pub fmt_leaf: unsafe fn(*mut GcBox<()>, &mut dyn fmt::Write) -> Option<fmt::Result>,
+    /// Returns the name of the type.
+    pub type_name: fn() -> &'static str,
+    /// Returns the id of the type, see [`erased_type_id`].
+    pub type_id: fn() -> TypeId,
+    /// Returns the layout of the allocation of a dynamically sized object and the offset of the
+    /// object within it. `None` for sized types, whose objects are allocated with `layout`.
+    pub dyn_layout: Option<DynLayoutFn>,
+}
+
+/// Returns the [`TypeId`] of a type which doesn't have to be `'static`.
+///
+/// Type ids don't distinguish lifetimes, so types which only differ in lifetimes, like
+/// `&'a String` and `&'static String`, have the same id. A type may only be identified by this id
+/// if it is known to be the same type including lifetimes, see [`Erasable`](crate::Erasable).
+pub fn erased_type_id<T: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
+        }
+    }
+
+    let phantom = PhantomData::<T>;
+    // Lifetimes are erased before the id is computed, so extending the lifetime of the trait
+    // object doesn't change the result.
+    unsafe { mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom) }
+        .type_id()
 }
 
 unsafe fn trace<T: UnsafeTrace>(ptr: *mut GcBox<()>, marker: UnsafeMarker) {
@@ -28,13 +78,112 @@ unsafe fn drop<T: UnsafeTrace>(ptr: *mut GcBox<()>) {
     ManuallyDrop::drop(&mut (*(*ptr.cast::<GcBox<T>>()).value.get()));
 }
 
+unsafe fn fmt_leaf<T: UnsafeTrace>(
+    ptr: *mut GcBox<()>,
+    w: &mut dyn fmt::Write,
+) -> Option<fmt::Result> {
+    (*(*ptr.cast::<GcBox<T>>()).value.get()).fmt_leaf(w)
+}
+
+/// Returns the layout of the allocation of a slice object of the given length and the offset of
+/// the object within it.
+///
+/// The length is stored directly in front of the object.
+pub fn slice_layout<T>(len: usize) -> (Layout, usize) {
+    let header = Layout::new::<GcBox<[T; 0]>>();
+    let items = Layout::array::<T>(len).expect("slice too large");
+    let object = Layout::from_size_align(header.size() + items.size(), header.align())
+        .expect("slice too large")
+        .pad_to_align();
+    Layout::new::<usize>()
+        .extend(object)
+        .expect("slice too large")
+}
+
+/// Returns the length of a slice object.
+///
+/// # Safety
+/// The pointer must point to a slice object allocated with [`slice_layout`].
+pub unsafe fn slice_len(ptr: *mut GcBox<()>) -> usize {
+    ptr.cast::<usize>().sub(1).read()
+}
+
+unsafe fn slice_value<T>(ptr: *mut GcBox<()>) -> *mut [T] {
+    let items = ptr::addr_of_mut!((*ptr.cast::<GcBox<[T; 0]>>()).value).cast::<T>();
+    ptr::slice_from_raw_parts_mut(items, slice_len(ptr))
+}
+
+unsafe fn trace_slice<T: UnsafeTrace>(ptr: *mut GcBox<()>, marker: UnsafeMarker) {
+    for item in (*slice_value::<T>(ptr)).iter() {
+        item.trace(marker);
+    }
+}
+
+unsafe fn drop_slice<T: UnsafeTrace>(ptr: *mut GcBox<()>) {
+    ptr::drop_in_place(slice_value::<T>(ptr));
+}
+
+unsafe fn fmt_leaf_slice(_ptr: *mut GcBox<()>, _w: &mut dyn fmt::Write) -> Option<fmt::Result> {
+    None
+}
+
+unsafe fn fmt_leaf_str(ptr: *mut GcBox<()>, w: &mut dyn fmt::Write) -> Option<fmt::Result> {
+    let bytes = &*slice_value::<u8>(ptr);
+    Some(write!(w, "{:?}", std::str::from_utf8_unchecked(bytes)))
+}
+
+unsafe fn dyn_layout_slice<T>(ptr: *mut GcBox<()>) -> (Layout, usize) {
+    slice_layout::<T>(slice_len(ptr))
+}
+
 impl GcVTable {
     /// Creates a new v-table for this type.
     pub const fn new<T: UnsafeTrace>() -> Self {
         GcVTable {
-            layout: Layout::new::<T>(),
+            layout: Layout::new::<GcBox<T>>(),
+            needs_trace: T::needs_trace,
             trace: trace::<T>,
             drop: drop::<T>,
+            fmt_leaf: fmt_leaf::<T>,
+            type_name: std::any::type_name::<T>,
+            type_id: erased_type_id::<T>,
+            dyn_layout: None,
+        }
+    }
+
+    /// Creates a new v-table for slices of this type.
+    pub const fn new_slice<T: UnsafeTrace>() -> Self {
+        GcVTable {
+            layout: Layout::new::<GcBox<[T; 0]>>(),
+            needs_trace: T::needs_trace,
+            trace: trace_slice::<T>,
+            drop: drop_slice::<T>,
+            fmt_leaf: fmt_leaf_slice,
+            type_name: std::any::type_name::<[T]>,
+            type_id: erased_type_id::<[T]>,
+            dyn_layout: Some(dyn_layout_slice::<T>),
+        }
+    }
+
+    /// Creates a new v-table for strings, which are stored like slices of bytes.
+    pub const fn new_str() -> Self {
+        GcVTable {
+            fmt_leaf: fmt_leaf_str,
+            type_name: std::any::type_name::<str>,
+            type_id: erased_type_id::<str>,
+            ..GcVTable::new_slice::<u8>()
+        }
+    }
+
+    /// Returns the layout of the allocation of the object and the offset of the object within
+    /// it.
+    ///
+    /// # Safety
+    /// The pointer must point to an object with this v-table.
+    pub unsafe fn allocation(&self, ptr: NonNull<GcBox<()>>) -> (Layout, usize) {
+        match self.dyn_layout {
+            Some(x) => x(ptr.as_ptr()),
+            None => (self.layout, 0),
         }
     }
 
@@ -50,6 +199,25 @@ impl GcVTable {
 
         &<T as HasVTable>::V_TABLE
     }
+
+    /// Returns a static reference to the v-table for slices of this type.
+    pub fn get_slice<T: UnsafeTrace>() -> &'static GcVTable {
+        trait HasSliceVTable {
+            const V_TABLE: GcVTable;
+        }
+
+        impl<T: UnsafeTrace> HasSliceVTable for T {
+            const V_TABLE: GcVTable = GcVTable::new_slice::<T>();
+        }
+
+        &<T as HasSliceVTable>::V_TABLE
+    }
+
+    /// Returns a static reference to the v-table for strings.
+    pub fn get_str() -> &'static GcVTable {
+        static V_TABLE: GcVTable = GcVTable::new_str();
+        &V_TABLE
+    }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
@@ -70,7 +238,12 @@ pub struct GcDataPtr(Cell<NonNull<GcVTable>>);
 impl GcDataPtr {
     /// Creates a new data pointer for a specific type.
     pub fn new<T: UnsafeTrace>() -> Self {
-        Self(Cell::new(NonNull::from(GcVTable::get::<T>())))
+        Self::from_v_table(GcVTable::get::<T>())
+    }
+
+    /// Creates a new data pointer with the given v-table.
+    pub fn from_v_table(v_table: &'static GcVTable) -> Self {
+        Self(Cell::new(NonNull::from(v_table)))
     }
 
     fn as_ptr(&self) -> *mut GcVTable {
@@ -103,6 +276,17 @@ pub struct GcBox<T: ?Sized> {
     /// A packed pointer containing both tracing information as well as a pointer to the v table of
     /// the contained object.
     pub data_ptr: GcDataPtr,
+    /// The id of the arena which allocated the object, `None` for objects not allocated by an
+    /// arena.
+    #[cfg(feature = "arena-id")]
+    pub arena_id: Option<NonZeroU64>,
+    /// The id of the object, unique for the lifetime of the arena which allocated it. Assigned
+    /// when the object is allocated, 0 for objects not allocated by an arena.
+    #[cfg(feature = "object-id")]
+    pub object_id: u64,
+    /// A word of metadata for embedders, 0 when the object is allocated.
+    #[cfg(feature = "tag-word")]
+    pub tag_word: Cell<usize>,
     /// the contained object itself.
     pub value: UnsafeCell<ManuallyDrop<T>>,
 }
@@ -112,6 +296,12 @@ impl<T: UnsafeTrace> GcBox<T> {
         Self {
             next: Cell::new(None),
             data_ptr: GcDataPtr::new::<T>(),
+            #[cfg(feature = "arena-id")]
+            arena_id: None,
+            #[cfg(feature = "object-id")]
+            object_id: 0,
+            #[cfg(feature = "tag-word")]
+            tag_word: Cell::new(0),
             value: UnsafeCell::new(ManuallyDrop::new(value)),
         }
     }
@@ -0,0 +1,50 @@
+//! Bookkeeping of the host resources held by the objects of an arena.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+};
+
+/// The ledger of open resources of an arena, see [`Resource`](crate::Resource).
+///
+/// Shared between the arena and its resources, so resources can remove themselves when they are
+/// dropped without access to the arena.
+#[derive(Default)]
+pub struct ResourceLedger {
+    next_id: Cell<u64>,
+    open: RefCell<HashMap<u64, &'static str>>,
+}
+
+impl ResourceLedger {
+    /// Record a newly opened resource of the given kind, returning its id.
+    pub fn open(&self, kind: &'static str) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.open.borrow_mut().insert(id, kind);
+        id
+    }
+
+    /// Record that the resource was closed or dropped.
+    pub fn close(&self, id: u64) {
+        self.open.borrow_mut().remove(&id);
+    }
+
+    /// Returns true if the resource is still open.
+    pub fn is_open(&self, id: u64) -> bool {
+        self.open.borrow().contains_key(&id)
+    }
+
+    /// Returns the ids of all open resources.
+    pub fn open_ids(&self) -> Vec<u64> {
+        self.open.borrow().keys().copied().collect()
+    }
+
+    /// Returns the number of open resources of every kind.
+    pub fn count_by_kind(&self) -> BTreeMap<&'static str, usize> {
+        let mut res = BTreeMap::new();
+        for kind in self.open.borrow().values() {
+            *res.entry(*kind).or_default() += 1;
+        }
+        res
+    }
+}
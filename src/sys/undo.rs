@@ -0,0 +1,158 @@
+//! The undo log of a speculation, see [`SpeculationGuard`](crate::SpeculationGuard).
+
+use std::{collections::HashSet, pin::Pin, ptr::NonNull};
+
+use super::{embed::RootList, GcBox, UnsafeArena, UnsafeRootGuard};
+
+type Restore = unsafe fn(NonNull<GcBox<()>>, NonNull<GcBox<()>>);
+
+struct UndoEntry {
+    target: NonNull<GcBox<()>>,
+    snapshot: NonNull<GcBox<()>>,
+    restore: Restore,
+}
+
+/// Swap the values of two objects of the same type.
+unsafe fn swap_values<T>(a: NonNull<GcBox<()>>, b: NonNull<GcBox<()>>) {
+    let a = a.cast::<GcBox<T>>();
+    let b = b.cast::<GcBox<T>>();
+    std::ptr::swap(a.as_ref().value.get(), b.as_ref().value.get())
+}
+
+/// A log of the values objects had before they were first mutated.
+///
+/// Every logged value is kept in an object of its own, a snapshot. The snapshots and the objects
+/// they belong to are rooted for as long as the log exists, so rolling back never writes to a
+/// freed object or restores a pointer to one.
+pub struct UndoLog {
+    entries: Vec<UndoEntry>,
+    /// Objects which don't have to be logged: objects which are already logged, and objects which
+    /// were allocated while logging, including the snapshots.
+    skip: HashSet<NonNull<GcBox<()>>>,
+    // Declared before the list so the list is unrooted before it is dropped.
+    _guard: Pin<Box<UnsafeRootGuard>>,
+    roots: Box<RootList>,
+}
+
+impl UndoLog {
+    unsafe fn new(arena: &UnsafeArena) -> Self {
+        let mut guard = Box::pin(UnsafeRootGuard::new());
+        let roots = Box::new(RootList::new());
+        roots.root(arena, guard.as_mut());
+        UndoLog {
+            entries: Vec::new(),
+            skip: HashSet::new(),
+            _guard: guard,
+            roots,
+        }
+    }
+
+    /// Returns the number of logged objects.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no object is logged.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl UnsafeArena {
+    /// Start logging the values of objects before they are mutated, so the mutations can be
+    /// undone with [`UnsafeArena::rollback_undo_log`].
+    ///
+    /// # Panic
+    /// Panics if the arena is already logging.
+    pub fn begin_undo_log(&self) {
+        let mut undo = self.undo.borrow_mut();
+        assert!(undo.is_none(), "the arena is already logging undo entries");
+        *undo = Some(Box::new(unsafe { UndoLog::new(self) }));
+    }
+
+    /// Returns true if the arena is logging undo entries.
+    pub fn is_logging_undo(&self) -> bool {
+        self.undo.borrow().is_some()
+    }
+
+    /// Returns the number of objects logged since logging began.
+    pub fn undo_log_len(&self) -> usize {
+        self.undo.borrow().as_ref().map(|x| x.len()).unwrap_or(0)
+    }
+
+    /// Returns true if the object has to be logged with [`UnsafeArena::log_undo`] before it is
+    /// mutated.
+    ///
+    /// Objects only have to be logged once and objects allocated while logging never have to be.
+    pub fn needs_undo_entry<T>(&self, ptr: NonNull<GcBox<T>>) -> bool {
+        self.undo
+            .borrow()
+            .as_ref()
+            .is_some_and(|x| !x.skip.contains(&ptr.cast()))
+    }
+
+    /// Record that the given object is about to be mutated, with a snapshot holding its current
+    /// value.
+    ///
+    /// # Safety
+    /// Both pointers must be valid, alive, GC pointers allocated by this arena, of the same type.
+    /// The snapshot must not be used for anything else afterwards. The arena must be logging and
+    /// the object must not have been logged yet.
+    pub unsafe fn log_undo<T>(&self, target: NonNull<GcBox<T>>, snapshot: NonNull<GcBox<T>>) {
+        let mut undo = self.undo.borrow_mut();
+        let undo = undo
+            .as_mut()
+            .expect("the arena is not logging undo entries");
+        let (target, snapshot) = (target.cast(), snapshot.cast());
+        undo.roots.push(self, target);
+        undo.roots.push(self, snapshot);
+        undo.skip.insert(target);
+        undo.entries.push(UndoEntry {
+            target,
+            snapshot,
+            restore: swap_values::<T>,
+        });
+    }
+
+    /// Record that an object was allocated while logging, so it is never logged.
+    pub(super) fn skip_undo(&self, ptr: NonNull<GcBox<()>>) {
+        if let Some(undo) = self.undo.borrow_mut().as_mut() {
+            undo.skip.insert(ptr);
+        }
+    }
+
+    /// Stop logging, restoring every logged object to the value it had when it was logged.
+    ///
+    /// The values which are replaced are moved into the snapshots, which are freed by a later
+    /// collection.
+    ///
+    /// # Safety
+    /// No references to the values of logged objects may be alive.
+    ///
+    /// # Panic
+    /// Panics if the arena is not logging.
+    pub unsafe fn rollback_undo_log(&self) {
+        let undo = self
+            .undo
+            .borrow_mut()
+            .take()
+            .expect("the arena is not logging undo entries");
+        for entry in undo.entries.iter().rev() {
+            (entry.restore)(entry.target, entry.snapshot);
+            // Both objects now contain pointers they might not have had when they were traced.
+            self.write_barrier_erased(entry.target);
+            self.write_barrier_erased(entry.snapshot);
+        }
+    }
+
+    /// Stop logging, keeping all mutations.
+    ///
+    /// # Panic
+    /// Panics if the arena is not logging.
+    pub fn discard_undo_log(&self) {
+        self.undo
+            .borrow_mut()
+            .take()
+            .expect("the arena is not logging undo entries");
+    }
+}
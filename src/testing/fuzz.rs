@@ -0,0 +1,477 @@
+//! A byte driven interpreter of arena operations for fuzzing the collector.
+//!
+//! Input bytes are decoded into a list of [`Op`]s which are executed against a real arena and
+//! against a simple reference model of the object graph. After every collection the interpreter
+//! asserts that no object reachable in the model was freed, and after every full collection that
+//! the arena contains exactly the objects reachable in the model with the same structure.
+//!
+//! Any input is valid, so the interpreter can be driven directly by a fuzzer:
+//! ```ignore
+//! fuzz_target!(|data: &[u8]| dreck::testing::fuzz::run(data));
+//! ```
+
+use std::{cell::RefCell, collections::HashSet, pin::Pin, rc::Rc};
+
+use crate::{Arena, Gc, Marker, Owner, RootGuard, Trace};
+
+/// The number of different operation codes.
+const OP_CODES: u8 = 11;
+
+/// A single operation of the interpreter.
+///
+/// Operations refer to objects by handle, an index into the list of objects the interpreter still
+/// knows to be alive. Handles and other indices are taken modulo the length of the list they
+/// index, operations which refer to an empty list do nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Allocate a leaf object holding the value.
+    AllocLeaf(u8),
+    /// Allocate a container object without children.
+    AllocContainer,
+    /// Add the object `to` as a child of the object `from` if `from` is a container.
+    Link { from: u8, to: u8 },
+    /// Remove the child at index `slot` from the object `from` if it is a container.
+    Unlink { from: u8, slot: u8 },
+    /// Root an object with a new root guard.
+    Root(u8),
+    /// Drop a root guard.
+    Unroot(u8),
+    /// Perform a number of collection steps.
+    Step(u8),
+    /// Run an incremental collection with the arena's own budget.
+    Collect,
+    /// Run a full collection.
+    CollectFull,
+    /// Enter a scope, all objects allocated within a scope are rooted until the scope is exited.
+    ScopeEnter,
+    /// Exit the innermost scope.
+    ScopeExit,
+}
+
+impl Op {
+    /// Decode a single operation from the start of the bytes, returning the operation and the
+    /// remaining bytes. Returns `None` if the bytes run out before the operation is complete.
+    pub fn decode(data: &[u8]) -> Option<(Op, &[u8])> {
+        let (code, rest) = data.split_first()?;
+        let code = code % OP_CODES;
+        let argc = match code {
+            0 | 4 | 5 | 6 => 1,
+            2 | 3 => 2,
+            _ => 0,
+        };
+        let args = rest.get(..argc)?;
+        let op = match code {
+            0 => Op::AllocLeaf(args[0]),
+            1 => Op::AllocContainer,
+            2 => Op::Link {
+                from: args[0],
+                to: args[1],
+            },
+            3 => Op::Unlink {
+                from: args[0],
+                slot: args[1],
+            },
+            4 => Op::Root(args[0]),
+            5 => Op::Unroot(args[0]),
+            6 => Op::Step(args[0]),
+            7 => Op::Collect,
+            8 => Op::CollectFull,
+            9 => Op::ScopeEnter,
+            _ => Op::ScopeExit,
+        };
+        Some((op, &rest[argc..]))
+    }
+
+    /// Encode the operation, the inverse of [`Op::decode`].
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match *self {
+            Op::AllocLeaf(x) => out.extend_from_slice(&[0, x]),
+            Op::AllocContainer => out.push(1),
+            Op::Link { from, to } => out.extend_from_slice(&[2, from, to]),
+            Op::Unlink { from, slot } => out.extend_from_slice(&[3, from, slot]),
+            Op::Root(x) => out.extend_from_slice(&[4, x]),
+            Op::Unroot(x) => out.extend_from_slice(&[5, x]),
+            Op::Step(x) => out.extend_from_slice(&[6, x]),
+            Op::Collect => out.push(7),
+            Op::CollectFull => out.push(8),
+            Op::ScopeEnter => out.push(9),
+            Op::ScopeExit => out.push(10),
+        }
+    }
+}
+
+/// Decode bytes into a list of operations, ignoring a trailing incomplete operation.
+pub fn decode(mut data: &[u8]) -> Vec<Op> {
+    let mut ops = Vec::new();
+    while let Some((op, rest)) = Op::decode(data) {
+        ops.push(op);
+        data = rest;
+    }
+    ops
+}
+
+/// Encode a list of operations into bytes.
+pub fn encode(ops: &[Op]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for op in ops {
+        op.encode(&mut out);
+    }
+    out
+}
+
+/// Decode and execute the bytes.
+///
+/// # Panic
+/// Panics if the arena diverges from the reference model.
+pub fn run(data: &[u8]) {
+    run_ops(&decode(data))
+}
+
+/// Execute a list of operations.
+///
+/// # Panic
+/// Panics if the arena diverges from the reference model.
+pub fn run_ops(ops: &[Op]) {
+    let dropped = Rc::new(RefCell::new(HashSet::new()));
+    let allocated;
+    {
+        // Safe because this is the only owner with its lifetime.
+        let mut owner = unsafe { Owner::new() };
+        let arena = unsafe { Arena::new(&owner) };
+        let mut machine = Machine::new(&arena, dropped.clone());
+        for op in ops {
+            machine.exec(&mut owner, *op);
+        }
+        machine.exec(&mut owner, Op::CollectFull);
+        allocated = machine.model.nodes.len();
+    }
+    assert_eq!(
+        dropped.borrow().len(),
+        allocated,
+        "not all objects were freed when the arena was dropped"
+    );
+}
+
+type Dropped = Rc<RefCell<HashSet<usize>>>;
+
+struct Leaf {
+    id: usize,
+    value: u8,
+    dropped: Dropped,
+}
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        assert!(self.dropped.borrow_mut().insert(self.id));
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'to> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+struct Container<'gc, 'own> {
+    id: usize,
+    children: Vec<Edge<'gc, 'own>>,
+    dropped: Dropped,
+}
+
+impl Drop for Container<'_, '_> {
+    fn drop(&mut self) {
+        assert!(self.dropped.borrow_mut().insert(self.id));
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.children.trace(marker)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Edge<'gc, 'own> {
+    Leaf(Gc<'gc, 'own, Leaf>),
+    Container(Gc<'gc, 'own, Container<'gc, 'own>>),
+}
+
+impl<'gc, 'own> Edge<'gc, 'own> {
+    fn id(self, owner: &Owner<'own>) -> usize {
+        match self {
+            Edge::Leaf(x) => x.borrow(owner).id,
+            Edge::Container(x) => x.borrow(owner).id,
+        }
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Edge<'gc, 'own> {
+    type Gc<'to> = Edge<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        match *self {
+            Edge::Leaf(x) => marker.mark(x),
+            Edge::Container(x) => marker.mark(x),
+        }
+    }
+}
+
+/// The expected state of an object.
+enum Node {
+    Leaf(u8),
+    Container(Vec<usize>),
+}
+
+/// The reference model of the object graph.
+#[derive(Default)]
+struct Model {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    scopes: Vec<Vec<usize>>,
+}
+
+impl Model {
+    /// Returns for every object whether it is reachable from a root.
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut stack = self
+            .roots
+            .iter()
+            .chain(self.scopes.iter().flatten())
+            .copied()
+            .collect::<Vec<_>>();
+        while let Some(id) = stack.pop() {
+            if std::mem::replace(&mut reachable[id], true) {
+                continue;
+            }
+            if let Node::Container(ref children) = self.nodes[id] {
+                stack.extend_from_slice(children);
+            }
+        }
+        reachable
+    }
+}
+
+struct Machine<'gc, 'own> {
+    arena: &'gc Arena<'own>,
+    dropped: Dropped,
+    model: Model,
+    /// The pointers to all objects which are known to be alive, indexed by id.
+    objects: Vec<Option<Edge<'gc, 'own>>>,
+    /// The ids of all objects which are known to be alive.
+    handles: Vec<usize>,
+    roots: Vec<Pin<Box<RootGuard>>>,
+    scopes: Vec<Vec<Pin<Box<RootGuard>>>>,
+}
+
+impl<'gc, 'own> Machine<'gc, 'own> {
+    fn new(arena: &'gc Arena<'own>, dropped: Dropped) -> Self {
+        Machine {
+            arena,
+            dropped,
+            model: Model::default(),
+            objects: Vec::new(),
+            handles: Vec::new(),
+            roots: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    fn handle(&self, idx: u8) -> Option<usize> {
+        if self.handles.is_empty() {
+            return None;
+        }
+        Some(self.handles[idx as usize % self.handles.len()])
+    }
+
+    fn edge(&self, id: usize) -> Edge<'gc, 'own> {
+        self.objects[id].expect("handle refers to a forgotten object")
+    }
+
+    fn root(&self, id: usize) -> Pin<Box<RootGuard>> {
+        let mut guard = Box::pin(RootGuard::new());
+        match self.edge(id) {
+            Edge::Leaf(x) => {
+                self.arena.root(x, guard.as_mut());
+            }
+            Edge::Container(x) => {
+                self.arena.root(x, guard.as_mut());
+            }
+        }
+        guard
+    }
+
+    fn alloc(&mut self, edge: Edge<'gc, 'own>, node: Node) {
+        let id = self.model.nodes.len();
+        self.model.nodes.push(node);
+        self.objects.push(Some(edge));
+        self.handles.push(id);
+        if !self.scopes.is_empty() {
+            let guard = self.root(id);
+            self.scopes.last_mut().unwrap().push(guard);
+            self.model.scopes.last_mut().unwrap().push(id);
+        }
+    }
+
+    fn exec(&mut self, owner: &mut Owner<'own>, op: Op) {
+        match op {
+            Op::AllocLeaf(value) => {
+                let ptr = self.arena.add(Leaf {
+                    id: self.model.nodes.len(),
+                    value,
+                    dropped: self.dropped.clone(),
+                });
+                self.alloc(Edge::Leaf(ptr), Node::Leaf(value));
+            }
+            Op::AllocContainer => {
+                let ptr = self.arena.add(Container {
+                    id: self.model.nodes.len(),
+                    children: Vec::new(),
+                    dropped: self.dropped.clone(),
+                });
+                self.alloc(Edge::Container(ptr), Node::Container(Vec::new()));
+            }
+            Op::Link { from, to } => {
+                let (Some(from), Some(to)) = (self.handle(from), self.handle(to)) else {
+                    return;
+                };
+                let Edge::Container(ptr) = self.edge(from) else {
+                    return;
+                };
+                let child = self.edge(to);
+                ptr.borrow_mut(owner, self.arena)
+                    .children
+                    .push(unsafe { child.rebind() });
+                let Node::Container(ref mut children) = self.model.nodes[from] else {
+                    unreachable!()
+                };
+                children.push(to);
+            }
+            Op::Unlink { from, slot } => {
+                let Some(from) = self.handle(from) else {
+                    return;
+                };
+                let Node::Container(ref mut children) = self.model.nodes[from] else {
+                    return;
+                };
+                if children.is_empty() {
+                    return;
+                }
+                let slot = slot as usize % children.len();
+                children.remove(slot);
+                let Edge::Container(ptr) = self.edge(from) else {
+                    unreachable!()
+                };
+                ptr.borrow_mut(owner, self.arena).children.remove(slot);
+            }
+            Op::Root(idx) => {
+                let Some(id) = self.handle(idx) else {
+                    return;
+                };
+                let guard = self.root(id);
+                self.roots.push(guard);
+                self.model.roots.push(id);
+            }
+            Op::Unroot(idx) => {
+                if self.roots.is_empty() {
+                    return;
+                }
+                let idx = idx as usize % self.roots.len();
+                self.roots.remove(idx);
+                self.model.roots.remove(idx);
+            }
+            Op::Step(count) => {
+                for _ in 0..=count % 32 {
+                    unsafe { self.arena.unsafe_arena().step() };
+                }
+                self.check(owner, false);
+            }
+            Op::Collect => {
+                unsafe { self.arena.unsafe_arena().collect() };
+                self.check(owner, false);
+            }
+            Op::CollectFull => {
+                unsafe { self.arena.unsafe_arena().collect_full() };
+                self.check(owner, true);
+            }
+            Op::ScopeEnter => {
+                self.scopes.push(Vec::new());
+                self.model.scopes.push(Vec::new());
+            }
+            Op::ScopeExit => {
+                self.scopes.pop();
+                self.model.scopes.pop();
+            }
+        }
+    }
+
+    /// Check the arena against the model after a collection and forget all objects which are no
+    /// longer reachable, as they might have been freed.
+    fn check(&mut self, owner: &Owner<'own>, full: bool) {
+        let reachable = self.model.reachable();
+        {
+            let dropped = self.dropped.borrow();
+            for (id, reachable) in reachable.iter().copied().enumerate() {
+                if reachable {
+                    assert!(!dropped.contains(&id), "reachable object #{} was freed", id);
+                } else if full {
+                    assert!(
+                        dropped.contains(&id),
+                        "unreachable object #{} survived a full collection",
+                        id
+                    );
+                }
+            }
+        }
+
+        self.handles.retain(|x| reachable[*x]);
+        for (object, reachable) in self.objects.iter_mut().zip(reachable.iter().copied()) {
+            if !reachable {
+                *object = None;
+            }
+        }
+
+        if full {
+            for id in self.handles.iter().copied() {
+                match (self.edge(id), &self.model.nodes[id]) {
+                    (Edge::Leaf(ptr), Node::Leaf(value)) => {
+                        assert_eq!(ptr.borrow(owner).value, *value, "leaf #{} changed", id)
+                    }
+                    (Edge::Container(ptr), Node::Container(children)) => {
+                        let found = ptr
+                            .borrow(owner)
+                            .children
+                            .iter()
+                            .map(|x| x.id(owner))
+                            .collect::<Vec<_>>();
+                        assert_eq!(&found, children, "children of container #{} differ", id);
+                    }
+                    _ => panic!("object #{} changed kind", id),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Utilities for testing code which uses garbage collected values.
+
+use std::{cell::RefCell, collections::HashMap, fmt::Write, ptr::NonNull};
+
+use crate::{
+    sys::{self, GcBox},
+    Gc, Owner, Trace,
+};
+
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
+/// Render the structure of the object graph reachable from a pointer as text.
+///
+/// Every object is written on its own line, indented by its depth, as `#id type` followed by
+/// `= value` if the object is a leaf with a representation (see [`Trace::fmt_leaf`]). Ids are
+/// assigned in traversal order and objects which were already written are referred to as `*id`,
+/// so the output contains no addresses and is the same across runs for graphs with the same
+/// shape.
+///
+/// # Usage
+/// ```
+/// # use dreck::*;
+/// dreck!(owner, arena);
+///
+/// let ptr = arena.add(3u32);
+/// let ptr = arena.add(vec![ptr, ptr]);
+/// assert_eq!(
+///     testing::dump_structure(ptr, &owner),
+///     "#0 alloc::vec::Vec<dreck::ptr::Gc<'_, '_, u32>>\n  #1 u32 = 3\n  *1\n"
+/// );
+/// ```
+pub fn dump_structure<'own, T: Trace<'own>>(gc: Gc<'_, 'own, T>, owner: &Owner<'own>) -> String {
+    let _owner = owner;
+    let mut ids = HashMap::new();
+    let mut out = String::new();
+    unsafe { dump_node(gc.into_gc_box().cast(), 0, &mut ids, &mut out) };
+    out
+}
+
+unsafe fn dump_node(
+    ptr: NonNull<GcBox<()>>,
+    depth: usize,
+    ids: &mut HashMap<NonNull<GcBox<()>>, usize>,
+    out: &mut String,
+) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+    if let Some(id) = ids.get(&ptr) {
+        writeln!(out, "*{}", id).unwrap();
+        return;
+    }
+    let id = ids.len();
+    ids.insert(ptr, id);
+
+    let v_table = ptr.as_ref().data_ptr.v_table();
+    write!(out, "#{} {}", id, (v_table.type_name)()).unwrap();
+    let mut leaf = String::new();
+    if let Some(Ok(())) = (v_table.fmt_leaf)(ptr.as_ptr(), &mut leaf) {
+        write!(out, " = {}", leaf).unwrap();
+    }
+    out.push('\n');
+
+    let children = RefCell::new(Vec::new());
+    sys::visit_children(ptr, &|child| children.borrow_mut().push(child));
+    for child in children.into_inner() {
+        dump_node(child, depth + 1, ids, out);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::arena::Marker;
 
 /// A trait for a type which can be GC allocated. It essential that this trait is implemented
@@ -5,6 +7,11 @@ use crate::arena::Marker;
 ///
 /// # Safety
 /// TODO
+///
+/// # Drop
+/// Objects which become unreachable in the same collection cycle are all dropped before any of
+/// them is deallocated. A drop implementation may thus, through unsafe code, read plain data of
+/// the objects it points to, but those objects might already have been dropped themselves.
 pub unsafe trait Trace<'own> {
     /// The type with a different gc lifetime.
     type Gc<'gc>;
@@ -20,6 +27,26 @@ pub unsafe trait Trace<'own> {
     /// Trace the object marking all GC pointers contained in the implementing object.
     fn trace(&self, marker: Marker<'own, '_>);
 
+    /// Write the value of this object if it is a leaf which should be shown in debugging output
+    /// like [`dump_structure`](crate::testing::dump_structure).
+    ///
+    /// Returns `None` if the object has no leaf representation, which is the default.
+    fn fmt_leaf(&self, _w: &mut dyn fmt::Write) -> Option<fmt::Result> {
+        None
+    }
+
+    /// Returns a copy of the value, pointing to the same objects, for restoring the value when a
+    /// speculation is rolled back, see [`Arena::begin_speculation`](crate::Arena::begin_speculation).
+    ///
+    /// Returns `None` if the value can't be copied, which is the default. Mutating an object of
+    /// such a type during a speculation panics.
+    fn snapshot(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     /// An object for changing the Gc lifetime of a gc allocated object.
     /// This is essentially [`std::mem::transmute`] but only for a single lifetime.
     unsafe fn rebind<'gc>(self) -> Self::Gc<'gc>
@@ -32,14 +59,18 @@ pub unsafe trait Trace<'own> {
             b: ManuallyDrop<U>,
         }
 
-        //TODO: compiler error using static assertions?
-        assert_eq!(
-            std::mem::size_of::<Self>(),
-            std::mem::size_of::<Self::Gc<'gc>>(),
-            "type `{}` implements rebind but its `Rebound` ({}) is a different size",
-            std::any::type_name::<Self>(),
-            std::any::type_name::<Self::Gc<'gc>>(),
-        );
+        // Evaluated when the method is instantiated, so a wrong `Gc` type fails the build.
+        const {
+            assert!(
+                std::mem::size_of::<Self>() == std::mem::size_of::<Self::Gc<'gc>>(),
+                "the `Gc` type of a `Trace` implementation has a different size than the type itself"
+            );
+            assert!(
+                std::mem::align_of::<Self>() == std::mem::align_of::<Self::Gc<'gc>>(),
+                "the `Gc` type of a `Trace` implementation has a different alignment than the type \
+                 itself"
+            );
+        }
 
         ManuallyDrop::into_inner(
             (Transmute {
@@ -50,19 +81,42 @@ pub unsafe trait Trace<'own> {
     }
 }
 
+/// Trace a value contained in another value, skipping it entirely if its type can't contain GC
+/// pointers.
+///
+/// `needs_trace` of leaf types returns a constant, so once inlined the check folds away and leaf
+/// fields of compound types, like a `u64` in a tuple, or whole leaf containers cost nothing to
+/// trace.
+#[inline(always)]
+fn trace_element<'own, T: Trace<'own>>(value: &T, marker: Marker<'own, '_>) {
+    if T::needs_trace() {
+        value.trace(marker)
+    }
+}
+
 macro_rules! impl_primitive {
     ($($name:ty),*$(,)*) => {
         $(
             unsafe impl<'own> Trace<'own> for $name {
                 type Gc<'gc> = $name;
 
+                #[inline]
                 fn needs_trace() -> bool
                 where
                     Self: Sized{
                     false
                 }
 
+                #[inline]
                 fn trace(&self,_marker: Marker<'own,'_>){}
+
+                fn fmt_leaf(&self, w: &mut dyn fmt::Write) -> Option<fmt::Result> {
+                    Some(write!(w, "{:?}", self))
+                }
+
+                fn snapshot(&self) -> Option<Self> {
+                    Some(self.clone())
+                }
             }
         )*
     };
@@ -74,16 +128,21 @@ macro_rules! impl_generic{
                 type Gc<'gc> = $name<$($gen::Gc<'gc>,)*>;
 
 
+                #[inline]
                 fn needs_trace() -> bool
                 where
                     Self: Sized{
                     false $(|| $gen::needs_trace())*
                 }
 
+                #[inline]
                 fn trace(&self,marker: Marker<'own,'_>){
+                    if !Self::needs_trace() {
+                        return;
+                    }
                     #[allow(non_snake_case)]
                     for ($($gen,)*) in self.iter(){
-                        $($gen.trace(marker);)*
+                        $(trace_element($gen, marker);)*
                     }
                 }
         }
@@ -92,9 +151,13 @@ macro_rules! impl_generic{
 
 macro_rules! impl_list {
     ($name:ident<$gen:ident>) => {
+        impl_list!($name<$gen>, |_value| None);
+    };
+    ($name:ident<$gen:ident>, |$value:ident| $snapshot:expr) => {
         unsafe impl<'own, $gen: Trace<'own>> Trace<'own> for $name<$gen> {
             type Gc<'gc> = $name<$gen::Gc<'gc>>;
 
+            #[inline]
             fn needs_trace() -> bool
             where
                 Self: Sized,
@@ -102,19 +165,124 @@ macro_rules! impl_list {
                 $gen::needs_trace()
             }
 
+            #[inline]
             fn trace(&self, marker: Marker<'own, '_>) {
+                if !$gen::needs_trace() {
+                    return;
+                }
                 for v in self.iter() {
                     v.trace(marker);
                 }
             }
+
+            fn snapshot(&self) -> Option<Self> {
+                let $value = self;
+                $snapshot
+            }
+        }
+    };
+}
+
+macro_rules! impl_tuple {
+    ($($gen:ident),*) => {
+        unsafe impl<'own, $($gen: Trace<'own>,)*> Trace<'own> for ($($gen,)*) {
+            type Gc<'gc> = ($($gen::Gc<'gc>,)*);
+
+            #[inline]
+            fn needs_trace() -> bool
+            where
+                Self: Sized,
+            {
+                false $(|| $gen::needs_trace())*
+            }
+
+            #[inline]
+            fn trace(&self, marker: Marker<'own, '_>) {
+                #[allow(non_snake_case)]
+                let ($($gen,)*) = self;
+                $(trace_element($gen, marker);)*
+            }
+
+            fn snapshot(&self) -> Option<Self> {
+                #[allow(non_snake_case)]
+                let ($($gen,)*) = self;
+                Some(($($gen.snapshot()?,)*))
+            }
         }
     };
 }
 
 impl_primitive!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, char, bool, String);
 
-impl_list!(Option<T>);
-impl_list!(Vec<T>);
+/// Types of GC objects which contain no GC pointers and can't be mutated after they are
+/// allocated.
+///
+/// A reference to such an object can outlive the borrow of the owner, see
+/// [`Arena::get_static`](crate::Arena::get_static).
+///
+/// # Safety
+/// Safe code must not be able to mutate the value through a [`Gc`](crate::Gc) pointing to it and
+/// the value must not contain GC pointers. No sized type can implement this trait as any GC
+/// object of a sized type can be mutated with [`Gc::borrow_mut`](crate::Gc::borrow_mut) and
+/// friends.
+pub unsafe trait LeafTrace {}
+
+unsafe impl LeafTrace for str {}
+
+macro_rules! impl_leaf_slice {
+    ($($name:ty),*$(,)*) => {
+        $(
+            unsafe impl LeafTrace for [$name] {}
+        )*
+    };
+}
+
+impl_leaf_slice!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, char, bool);
+
+impl_list!(Option<T>, |value| match value {
+    None => Some(None),
+    Some(x) => x.snapshot().map(Some),
+});
+impl_list!(Vec<T>, |value| value.iter().map(|x| x.snapshot()).collect());
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+
+unsafe impl<'own, T: Trace<'own>, const N: usize> Trace<'own> for [T; N] {
+    type Gc<'gc> = [T::Gc<'gc>; N];
+
+    #[inline]
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        T::needs_trace()
+    }
+
+    #[inline]
+    fn trace(&self, marker: Marker<'own, '_>) {
+        if !T::needs_trace() {
+            return;
+        }
+        for v in self.iter() {
+            v.trace(marker);
+        }
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        let values = self
+            .iter()
+            .map(|x| x.snapshot())
+            .collect::<Option<Vec<_>>>()?;
+        values.try_into().ok()
+    }
+}
 
 mod collection {
     use super::*;
@@ -124,7 +292,10 @@ mod collection {
     impl_list!(BTreeSet<K>);
     impl_list!(LinkedList<V>);
     impl_list!(BinaryHeap<V>);
-    impl_list!(VecDeque<V>);
+    impl_list!(VecDeque<V>, |value| value
+        .iter()
+        .map(|x| x.snapshot())
+        .collect());
 
     impl_generic!(HashMap<K,V>);
     impl_generic!(BTreeMap<K,V>);
@@ -133,6 +304,7 @@ mod collection {
 unsafe impl<'own, K: Trace<'own>, V: Trace<'own>> Trace<'own> for Result<K, V> {
     type Gc<'gc> = Result<K::Gc<'gc>, V::Gc<'gc>>;
 
+    #[inline]
     fn needs_trace() -> bool
     where
         Self: Sized,
@@ -140,10 +312,18 @@ unsafe impl<'own, K: Trace<'own>, V: Trace<'own>> Trace<'own> for Result<K, V> {
         K::needs_trace() || V::needs_trace()
     }
 
+    #[inline]
     fn trace(&self, marker: Marker<'own, '_>) {
         match *self {
-            Ok(ref x) => x.trace(marker),
-            Err(ref x) => x.trace(marker),
+            Ok(ref x) => trace_element(x, marker),
+            Err(ref x) => trace_element(x, marker),
+        }
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        match self {
+            Ok(x) => x.snapshot().map(Ok),
+            Err(x) => x.snapshot().map(Err),
         }
     }
 }
@@ -154,6 +334,7 @@ where
 {
     type Gc<'gc> = &'a T::Gc<'gc>;
 
+    #[inline]
     fn needs_trace() -> bool
     where
         Self: Sized,
@@ -161,6 +342,7 @@ where
         T::needs_trace()
     }
 
+    #[inline]
     fn trace(&self, marker: Marker<'own, '_>) {
         (**self).trace(marker)
     }
@@ -172,6 +354,7 @@ where
 {
     type Gc<'gc> = &'a mut T::Gc<'gc>;
 
+    #[inline]
     fn needs_trace() -> bool
     where
         Self: Sized,
@@ -179,6 +362,7 @@ where
         T::needs_trace()
     }
 
+    #[inline]
     fn trace(&self, marker: Marker<'own, '_>) {
         (**self).trace(marker)
     }
@@ -0,0 +1,92 @@
+//! Rooting values which are not allocated in the arena.
+
+use std::{marker::PhantomData, pin::Pin, ptr::NonNull};
+
+use crate::{
+    sys::{GcBox, UnsafeRootGuard},
+    Arena, Invariant, Trace,
+};
+
+/// A root guard which holds a value and keeps every GC pointer in it alive for the duration of
+/// the guards lifetime, see [`Arena::root_value`].
+///
+/// Like with [`Anchored`](crate::Anchored), `T` is the type with a `'static` gc lifetime, like
+/// `Frame<'static, 'own>`. The [`stack_root!`](crate::stack_root) macro creates and pins the
+/// guard without naming the type.
+pub struct ValueRootGuard<'own, T: Trace<'own>> {
+    // Declared first so the value is unrooted before it is dropped.
+    guard: UnsafeRootGuard,
+    value: GcBox<Option<T>>,
+    _invariant: Invariant<'own>,
+    _marker: PhantomData<T>,
+}
+
+impl<'own, T: Trace<'own>> ValueRootGuard<'own, T> {
+    /// Create a guard which doesn't hold a value.
+    pub fn new() -> Self {
+        ValueRootGuard {
+            guard: UnsafeRootGuard::new(),
+            value: GcBox::new(None),
+            _invariant: Invariant::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'own, T: Trace<'own>> Default for ValueRootGuard<'own, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'own, T: Trace<'own>> Drop for ValueRootGuard<'own, T> {
+    fn drop(&mut self) {
+        unsafe { std::mem::ManuallyDrop::drop(self.value.value.get_mut()) }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Move a value which is not allocated in the arena, like the frame of an interpreter, into
+    /// the guard and root every GC pointer it contains, returning a reference to the value bound
+    /// to the lifetime of the guard.
+    ///
+    /// The value is traced by the collector like an object of the arena. It can only be read
+    /// while rooted, as pointers added to it would not be traced. Rooting another value with the
+    /// same guard drops the previous value.
+    ///
+    /// # Usage
+    /// ```
+    /// # use std::pin::pin;
+    /// # use dreck::*;
+    /// dreck!(owner, arena);
+    ///
+    /// let locals = vec![arena.add(1), arena.add(2)];
+    /// let guard = pin!(ValueRootGuard::new());
+    /// let locals = arena.root_value(locals, guard);
+    ///
+    /// arena.collect_full(&owner);
+    /// assert_eq!(*locals[1].borrow(&owner), 2);
+    /// ```
+    pub fn root_value<'r, T: Trace<'own>>(
+        &self,
+        value: T,
+        guard: Pin<&'r mut ValueRootGuard<'own, T::Gc<'static>>>,
+    ) -> &'r T::Gc<'r>
+    where
+        T::Gc<'static>: Trace<'own>,
+    {
+        unsafe {
+            let guard = guard.get_unchecked_mut();
+            **guard.value.value.get_mut() = Some(value.rebind());
+            let value = (*guard.value.value.get()).as_ref().unwrap_unchecked();
+            // The guard might already have been scanned this cycle, so the pointers of the new
+            // value must be marked here.
+            self.unsafe_arena().mark_value(value);
+            self.unsafe_arena().root(
+                Pin::new_unchecked(&mut guard.guard),
+                NonNull::from(&guard.value),
+            );
+            &*(value as *const T::Gc<'static>).cast::<T::Gc<'r>>()
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! A field wrapper which counts the changes of its value.
+
+use std::{
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use crate::{Marker, NoGc, Trace, Write};
+
+/// A value which counts how often it was changed.
+///
+/// Where [`Owner::epoch`](crate::Owner::epoch) changes with every mutation of any object, the
+/// version of a `Versioned` only changes when the value itself is changed through
+/// [`Versioned::get_mut`], [`Versioned::set`] or [`Versioned::replace`]. Data derived from a
+/// single field can thus be cached along with the version of that field.
+///
+/// The value is read through [`Deref`]. Comparing and hashing only looks at the value, so
+/// structs containing a `Versioned` field can derive these traits without the version
+/// influencing the result.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Versioned<T> {
+    value: T,
+    version: u64,
+}
+
+unsafe impl<'own, T: Trace<'own>> Trace<'own> for Versioned<T> {
+    type Gc<'gc> = Versioned<T::Gc<'gc>>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        T::needs_trace()
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.value.trace(marker)
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(Versioned {
+            value: self.value.snapshot()?,
+            version: self.version,
+        })
+    }
+}
+
+unsafe impl<T: NoGc> NoGc for Versioned<T> {}
+
+impl<T> Versioned<T> {
+    /// Create a new value with version zero.
+    pub fn new(value: T) -> Self {
+        Versioned { value, version: 0 }
+    }
+
+    /// Returns the number of times the value was changed.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns a mutable reference to the value, increasing the version.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.version += 1;
+        &mut self.value
+    }
+
+    /// Set the value, increasing the version.
+    pub fn set(&mut self, value: T) {
+        *self.get_mut() = value;
+    }
+
+    /// Set the value, increasing the version, and return the old value.
+    pub fn replace(&mut self, value: T) -> T {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Returns the contained value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Write<Versioned<T>> {
+    /// Project to the value, increasing the version.
+    ///
+    /// Used to change a value which can contain GC pointers, as only values of [`NoGc`] types are
+    /// handed out by [`project!`](crate::project) as plain mutable references.
+    pub fn get_mut(&mut self) -> &mut Write<T> {
+        self.project(Versioned::get_mut)
+    }
+}
+
+impl<T> Deref for Versioned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> From<T> for Versioned<T> {
+    fn from(value: T) -> Self {
+        Versioned::new(value)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Versioned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Versioned<T> {}
+
+impl<T: Hash> Hash for Versioned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
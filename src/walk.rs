@@ -0,0 +1,138 @@
+//! Budgeted, resumable walks of the object graph.
+
+use std::{cell::RefCell, collections::HashSet, ops::ControlFlow, pin::Pin, ptr::NonNull};
+
+use crate::{
+    sys::{self, embed::TraceFn, GcBox, UnsafeMarker, UnsafeRootGuard},
+    Arena, GcAny, Invariant, Owner,
+};
+
+/// The progress of a walk, kept in the arena so the pointers in it stay alive.
+struct WalkState {
+    frontier: Vec<NonNull<GcBox<()>>>,
+    visited: HashSet<NonNull<GcBox<()>>>,
+}
+
+fn trace_state(state: &WalkState, marker: UnsafeMarker) {
+    for ptr in state.frontier.iter().chain(state.visited.iter()).copied() {
+        unsafe { marker.mark_erased(ptr) }
+    }
+}
+
+/// A walk of the subgraph reachable from an object, started by [`Arena::walk_subgraph`].
+///
+/// The objects still to be visited and the objects already visited are kept alive for as long as
+/// the cursor exists, so the walk can be resumed after collections and visits every object
+/// reachable from the start exactly once.
+pub struct SubgraphCursor<'own> {
+    /// Roots the state.
+    _guard: Pin<Box<UnsafeRootGuard>>,
+    state: NonNull<GcBox<TraceFn<WalkState>>>,
+    _invariant: Invariant<'own>,
+}
+
+impl<'own> SubgraphCursor<'own> {
+    /// Continue the walk, visiting at most `budget` objects.
+    ///
+    /// The function is called for every object visited, returning [`ControlFlow::Break`] stops
+    /// the walk after the current object.
+    pub fn resume<F>(&mut self, arena: &Arena<'own>, owner: &Owner<'own>, budget: usize, mut f: F)
+    where
+        F: for<'a> FnMut(GcAny<'a, 'own>) -> ControlFlow<()>,
+    {
+        let _owner = owner;
+        let arena = arena.unsafe_arena();
+        let children = RefCell::new(Vec::new());
+        for _ in 0..budget {
+            // Safe as the state object is rooted and only accessed while the owner is borrowed.
+            let state = unsafe { (*self.state.as_ref().value.get()).get_mut() };
+            let Some(ptr) = state.frontier.pop() else {
+                break;
+            };
+            if !state.visited.insert(ptr) {
+                continue;
+            }
+
+            unsafe { sys::visit_children(ptr, &|child| children.borrow_mut().push(child)) };
+            let mut children = children.borrow_mut();
+            state.frontier.extend(
+                children
+                    .drain(..)
+                    .rev()
+                    .filter(|x| !state.visited.contains(x)),
+            );
+            // Pointers were added to the state, which might already be traced.
+            unsafe { arena.write_barrier(self.state) };
+
+            if f(unsafe { GcAny::from_gc_box(ptr) }).is_break() {
+                break;
+            }
+        }
+    }
+
+    /// Returns true if every object reachable from the start has been visited.
+    pub fn is_done(&self) -> bool {
+        self.state().frontier.is_empty()
+    }
+
+    /// Returns the number of objects visited so far.
+    pub fn visited(&self) -> usize {
+        self.state().visited.len()
+    }
+
+    /// Returns the objects which are known to be reachable but not yet visited.
+    ///
+    /// Objects can occur more than once, or be visited by the time the walk reaches them through
+    /// an other path.
+    pub fn frontier<'gc>(&self, arena: &'gc Arena<'own>) -> Vec<GcAny<'gc, 'own>> {
+        let _arena = arena;
+        self.state()
+            .frontier
+            .iter()
+            .map(|x| unsafe { GcAny::from_gc_box(*x) })
+            .collect()
+    }
+
+    fn state(&self) -> &WalkState {
+        unsafe { (*self.state.as_ref().value.get()).get() }
+    }
+}
+
+impl<'own> Arena<'own> {
+    /// Walk the objects reachable from `start` without running a collection, visiting at most
+    /// `budget` objects.
+    ///
+    /// Objects are visited depth first, the function is called for every object visited and can
+    /// stop the walk early by returning [`ControlFlow::Break`]. The returned cursor continues the
+    /// walk with [`SubgraphCursor::resume`], collections can run in between. The children of an
+    /// object are recorded when it is visited, pointers added to it afterwards are not followed.
+    pub fn walk_subgraph<F>(
+        &self,
+        owner: &Owner<'own>,
+        start: GcAny<'_, 'own>,
+        budget: usize,
+        f: F,
+    ) -> SubgraphCursor<'own>
+    where
+        F: for<'a> FnMut(GcAny<'a, 'own>) -> ControlFlow<()>,
+    {
+        let arena = self.unsafe_arena();
+        let state = WalkState {
+            frontier: vec![start.into_gc_box()],
+            visited: HashSet::new(),
+        };
+        let mut guard = Box::pin(UnsafeRootGuard::new());
+        let state = unsafe {
+            let state = arena.add(TraceFn::new(state, trace_state));
+            arena.root(guard.as_mut(), state);
+            state
+        };
+        let mut cursor = SubgraphCursor {
+            _guard: guard,
+            state,
+            _invariant: Invariant::new(),
+        };
+        cursor.resume(self, owner, budget, f);
+        cursor
+    }
+}
@@ -0,0 +1,129 @@
+//! Proof that the write barrier of an object was applied.
+//!
+//! [`project!`](crate::project) dispatches like [`rebind!`](crate::rebind): [`ProbePlain`] is
+//! implemented for the probe of a plain data field itself while [`ProbeTraced`] is implemented for
+//! a reference to any probe, so plain data fields are handed out as plain mutable references.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{Arena, Gc, NoGc, Owner, Trace};
+
+/// A value inside an object whose write barrier has already been applied, created by
+/// [`Gc::write`].
+///
+/// A `&mut Write<T>` can only be obtained from [`Gc::write`], which applies the barrier, or by
+/// projecting an other `&mut Write` with [`Write::project`] or [`project!`](crate::project). The
+/// owner stays borrowed mutably for as long as the reference is alive, so no collection can happen
+/// before the mutation is done. Functions taking a `&mut Write<T>` can thus mutate the value
+/// without the arena and without applying the barrier again.
+///
+/// Values which can contain GC pointers are changed with [`Write::set`] and [`Write::replace`],
+/// or by projecting further into their fields. Plain data, see [`NoGc`], dereferences mutably.
+#[repr(transparent)]
+pub struct Write<T: ?Sized>(T);
+
+impl<T: ?Sized> Write<T> {
+    /// Wrap a mutable reference to a value.
+    ///
+    /// # Safety
+    /// The value must not be part of a GC object, or the write barrier of the object containing it
+    /// must have been applied while the owner is borrowed for as long as the returned reference is
+    /// alive.
+    pub unsafe fn assume(value: &mut T) -> &mut Write<T> {
+        // `Write` is transparent.
+        &mut *(value as *mut T as *mut Write<T>)
+    }
+
+    /// Project to a part of the value, like a field or an element.
+    pub fn project<F: ?Sized>(&mut self, f: impl FnOnce(&mut T) -> &mut F) -> &mut Write<F> {
+        // Everything reachable mutably from the value is owned by the object, so it is covered by
+        // the barrier as well.
+        unsafe { Write::assume(f(&mut self.0)) }
+    }
+
+    /// Returns a mutable reference to the value.
+    ///
+    /// Prefer projecting, so functions the value is passed to keep the proof that the barrier was
+    /// applied.
+    pub fn unlock(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> Write<T> {
+    /// Overwrite the value.
+    pub fn set(&mut self, value: T) {
+        self.0 = value;
+    }
+
+    /// Overwrite the value, returning the old value.
+    pub fn replace(&mut self, value: T) -> T {
+        std::mem::replace(&mut self.0, value)
+    }
+}
+
+impl<T: ?Sized> Deref for Write<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: NoGc> DerefMut for Write<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'gc, 'own, T: Trace<'own>> Gc<'gc, 'own, T> {
+    /// Borrow the value mutably, applying the write barrier once for all mutations done through
+    /// the returned [`Write`].
+    pub fn write<'a>(
+        self,
+        owner: &'a mut Owner<'own>,
+        arena: &Arena<'own>,
+    ) -> &'a mut Write<T::Gc<'a>> {
+        unsafe { Write::assume(self.borrow_mut(owner, arena)) }
+    }
+}
+
+/// Borrows the field projected by `project!` to find how to hand it out.
+#[doc(hidden)]
+pub struct ProjectProbe<'a, T: ?Sized>(pub &'a Write<T>);
+
+#[doc(hidden)]
+pub struct ProjectPlainTag;
+
+#[doc(hidden)]
+pub struct ProjectTracedTag;
+
+#[doc(hidden)]
+pub trait ProbePlain {
+    fn project_tag(&self) -> ProjectPlainTag {
+        ProjectPlainTag
+    }
+}
+
+impl<T: NoGc> ProbePlain for ProjectProbe<'_, T> {}
+
+#[doc(hidden)]
+pub trait ProbeTraced {
+    fn project_tag(&self) -> ProjectTracedTag {
+        ProjectTracedTag
+    }
+}
+
+impl<T: ?Sized> ProbeTraced for &ProjectProbe<'_, T> {}
+
+impl ProjectPlainTag {
+    pub fn finish<T: NoGc>(self, field: &mut Write<T>) -> &mut T {
+        field
+    }
+}
+
+impl ProjectTracedTag {
+    pub fn finish<T: ?Sized>(self, field: &mut Write<T>) -> &mut Write<T> {
+        field
+    }
+}
@@ -0,0 +1,97 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+pub struct Node<'gc, 'own> {
+    pub name: &'static str,
+    pub next: Gc<'gc, 'own, Node<'gc, 'own>>,
+    pub dropped: Rc<Cell<usize>>,
+}
+
+impl Drop for Node<'_, '_> {
+    fn drop(&mut self) {
+        self.dropped.set(self.dropped.get() + 1);
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.next);
+    }
+}
+
+/// Allocate two nodes pointing at each other, returning the first.
+fn pair<'gc, 'own>(
+    owner: &mut Owner<'own>,
+    arena: &'gc Arena<'own>,
+    dropped: &Rc<Cell<usize>>,
+) -> Gc<'gc, 'own, Node<'gc, 'own>> {
+    arena.add_cyclic(owner, |env| Node {
+        name: "env",
+        next: arena.add(Node {
+            name: "closure",
+            next: env,
+            dropped: dropped.clone(),
+        }),
+        dropped: dropped.clone(),
+    })
+}
+
+#[test]
+fn two_node_cycle() {
+    dreck!(owner, arena);
+    let dropped = Rc::new(Cell::new(0));
+
+    {
+        let env = pair(&mut owner, &arena, &dropped);
+        let guard = pin!(RootGuard::new());
+        let env = root!(&arena, guard, env);
+        arena.collect_full(&owner);
+
+        assert_eq!(dropped.get(), 0);
+        let closure = env.borrow(&owner).next;
+        assert_eq!(closure.borrow(&owner).name, "closure");
+        assert!(closure.borrow(&owner).next.ptr_eq(env));
+        assert_eq!(env.borrow(&owner).name, "env");
+    }
+
+    arena.collect_full(&owner);
+    assert_eq!(dropped.get(), 2);
+}
+
+#[test]
+fn during_trace() {
+    dreck!(owner, arena);
+    let dropped = Rc::new(Cell::new(0));
+
+    let keep = arena.add(0u32);
+    let guard = pin!(RootGuard::new());
+    root!(&arena, guard, keep);
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+    }
+
+    let env = pair(&mut owner, &arena, &dropped);
+    let guard = pin!(RootGuard::new());
+    let env = root!(&arena, guard, env);
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+    arena.collect_full(&owner);
+
+    assert_eq!(dropped.get(), 0);
+    assert_eq!(env.borrow(&owner).next.borrow(&owner).name, "closure");
+}
@@ -0,0 +1,37 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn survives_collection() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let ptr = arena.add_rooted(vec![1u32, 2, 3], guard);
+    assert_eq!(arena.root_count(), 1);
+
+    arena.collect_full(&owner);
+    assert_eq!(*ptr.borrow(&owner), [1, 2, 3]);
+    ptr.borrow_mut(&mut owner, &arena).push(4);
+    arena.collect_full(&owner);
+    assert_eq!(*ptr.borrow(&owner), [1, 2, 3, 4]);
+}
+
+#[test]
+fn macro_roots_until_scope_end() {
+    dreck!(owner, arena);
+
+    {
+        add_rooted!(&arena, inner, 1u32);
+        add_rooted!(&arena, outer, vec![inner]);
+        assert_eq!(arena.root_count(), 2);
+
+        arena.collect_full(&owner);
+        assert_eq!(*outer.borrow(&owner)[0].borrow(&owner), 1);
+        assert!(outer.borrow(&owner)[0].ptr_eq(inner));
+    }
+    assert_eq!(arena.root_count(), 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
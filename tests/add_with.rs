@@ -0,0 +1,120 @@
+use std::{
+    cell::Cell,
+    mem::MaybeUninit,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::pin,
+    rc::Rc,
+};
+
+use dreck::*;
+
+const LEN: usize = 64 * 1024;
+
+/// A value large enough to be expensive to construct on the stack.
+pub struct Chunk {
+    pub code: [u8; LEN],
+    pub len: usize,
+}
+
+unsafe impl<'own> Trace<'own> for Chunk {
+    type Gc<'to> = Chunk;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+/// Initialize a chunk field by field without constructing it on the stack.
+fn init_chunk(slot: &mut MaybeUninit<Chunk>) -> &mut Chunk {
+    let ptr = slot.as_mut_ptr();
+    unsafe {
+        std::ptr::addr_of_mut!((*ptr).code)
+            .cast::<u8>()
+            .write_bytes(7, LEN);
+        std::ptr::addr_of_mut!((*ptr).len).write(LEN);
+        slot.assume_init_mut()
+    }
+}
+
+#[test]
+fn in_place() {
+    dreck!(owner, arena);
+
+    let mut slot_addr = std::ptr::null();
+    let chunk = arena.add_with(|slot: &mut MaybeUninit<Chunk>| {
+        slot_addr = slot.as_ptr();
+        init_chunk(slot)
+    });
+    let guard = pin!(RootGuard::new());
+    let chunk = root!(&arena, guard, chunk);
+    arena.collect_full(&owner);
+
+    let value = chunk.borrow(&owner);
+    // The value was written where it lives in the object, so it was never copied.
+    assert_eq!(value as *const Chunk, slot_addr);
+    assert_eq!(value.len, LEN);
+    assert!(value.code.iter().all(|x| *x == 7));
+}
+
+#[test]
+fn panic_frees_object() {
+    dreck!(owner, arena);
+
+    pub struct Dropped(Rc<Cell<usize>>);
+
+    impl Drop for Dropped {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    unsafe impl<'own> Trace<'own> for Dropped {
+        type Gc<'to> = Dropped;
+
+        fn needs_trace() -> bool
+        where
+            Self: Sized,
+        {
+            false
+        }
+
+        fn trace(&self, _marker: Marker<'own, '_>) {}
+    }
+
+    let dropped = Rc::new(Cell::new(0));
+    let before = arena.stats().total_allocated;
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        arena.add_with(|slot: &mut MaybeUninit<Dropped>| -> &mut Dropped {
+            slot.write(Dropped(dropped.clone()));
+            panic!("failed to initialize");
+        });
+    }));
+    assert!(res.is_err());
+    assert_eq!(arena.stats().total_allocated, before);
+
+    arena.collect_full(&owner);
+    // The value is never dropped, neither on unwinding nor by the collector.
+    assert_eq!(dropped.get(), 0);
+    assert_eq!(Rc::strong_count(&dropped), 2);
+
+    // The arena is still usable after the panic.
+    let value = arena.add(1u32);
+    assert_eq!(*value.borrow(&owner), 1);
+}
+
+#[test]
+#[should_panic(expected = "returned a reference to another value")]
+fn other_reference() {
+    dreck!(owner, arena);
+
+    let other = Box::leak(Box::new(0u32));
+    arena.add_with(|slot: &mut MaybeUninit<u32>| {
+        slot.write(1);
+        other
+    });
+}
@@ -0,0 +1,81 @@
+use std::mem::size_of;
+
+use dreck::{layout::GC_BOX_HEADER_BYTES, *};
+
+/// A type with a larger alignment than the object header.
+#[repr(align(64))]
+pub struct Aligned(pub u8);
+
+unsafe impl<'own> Trace<'own> for Aligned {
+    type Gc<'to> = Aligned;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Large(pub [u64; 32]);
+
+unsafe impl<'own> Trace<'own> for Large {
+    type Gc<'to> = Large;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn sizes_add_up_to_total() {
+    dreck!(owner, arena);
+
+    let before = arena.stats().total_allocated;
+    let sizes = [
+        arena.add(1u8).allocation_size(),
+        arena.add(1u64).allocation_size(),
+        arena.add(Large([0; 32])).allocation_size(),
+        arena.add(String::from("hello")).allocation_size(),
+        arena.add(vec![1u32, 2, 3]).allocation_size(),
+        arena.add(Aligned(0)).allocation_size(),
+        arena.add_slice_copy(&[1u16, 2, 3]).allocation_size(),
+        arena.add_slice_copy::<u64>(&[]).allocation_size(),
+        arena.add_str("a string stored inline").allocation_size(),
+    ];
+    let total: usize = sizes.iter().sum();
+    assert_eq!(arena.stats().total_allocated - before, total);
+
+    assert_eq!(sizes[1], GC_BOX_HEADER_BYTES + size_of::<u64>());
+    assert_eq!(sizes[5] % 64, 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, before);
+}
+
+#[test]
+fn unsafe_arena_agrees() {
+    dreck!(owner, arena);
+
+    let value = arena.add(Large([1; 32]));
+    let str = arena.add_str("erased");
+    unsafe {
+        let arena = arena.unsafe_arena();
+        assert_eq!(
+            arena.allocation_size(value.into_gc_box().cast()),
+            value.allocation_size()
+        );
+        assert_eq!(
+            arena.allocation_size(str.erase().into_gc_box()),
+            str.allocation_size()
+        );
+    }
+    arena.collect_full(&owner);
+}
@@ -0,0 +1,111 @@
+use std::{cell::Cell, rc::Rc};
+
+use dreck::*;
+
+pub struct Document<'gc, 'own> {
+    lines: Vec<Gc<'gc, 'own, String>>,
+    dropped: Rc<Cell<bool>>,
+}
+
+impl Drop for Document<'_, '_> {
+    fn drop(&mut self) {
+        self.dropped.set(true);
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Document<'gc, 'own> {
+    type Gc<'to> = Document<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.lines.trace(marker);
+    }
+}
+
+anchored! {
+    /// A host side structure owning GC state.
+    struct Editor<'own> {
+        document: Document<'static, 'own>,
+        clipboard: Option<Gc<'static, 'own, String>>,
+    }
+}
+
+fn document<'gc, 'own>(
+    arena: &'gc Arena<'own>,
+    lines: &[&str],
+    dropped: &Rc<Cell<bool>>,
+) -> Gc<'gc, 'own, Document<'gc, 'own>> {
+    let lines = lines.iter().map(|x| arena.add(x.to_string())).collect();
+    arena.add(Document {
+        lines,
+        dropped: dropped.clone(),
+    })
+}
+
+#[test]
+fn survives_collections() {
+    dreck!(owner, arena);
+    let dropped = Rc::new(Cell::new(false));
+
+    let doc = document(&arena, &["a", "b"], &dropped);
+    let clip = arena.add(String::from("copied"));
+    let clip = arena.add(Some(clip));
+    let editor = Editor::new(&arena, doc, clip);
+
+    for _ in 0..100 {
+        arena.add(String::from("garbage"));
+        arena.collect(&owner);
+    }
+    arena.collect_full(&owner);
+
+    assert!(!dropped.get());
+    let doc = editor.document.get(&arena).borrow(&owner);
+    assert_eq!(doc.lines.len(), 2);
+    assert_eq!(doc.lines[1].borrow(&owner), "b");
+    let clip = editor.clipboard.get(&arena).borrow(&owner).unwrap();
+    assert_eq!(clip.borrow(&owner), "copied");
+}
+
+#[test]
+fn swap_releases_old() {
+    dreck!(owner, arena);
+    let old_dropped = Rc::new(Cell::new(false));
+    let new_dropped = Rc::new(Cell::new(false));
+
+    let doc = document(&arena, &["old"], &old_dropped);
+    let clip = arena.add(None);
+    let mut editor = Editor::new(&arena, doc, clip);
+    arena.collect_full(&owner);
+
+    let doc = document(&arena, &["new"], &new_dropped);
+    editor.document.set(&arena, doc);
+    assert!(!old_dropped.get());
+
+    arena.collect_full(&owner);
+    assert!(old_dropped.get());
+    assert!(!new_dropped.get());
+    let doc = editor.document.get(&arena).borrow(&owner);
+    assert_eq!(doc.lines[0].borrow(&owner), "new");
+
+    drop(editor);
+    arena.collect_full(&owner);
+    assert!(new_dropped.get());
+}
+
+#[test]
+fn outlives_arena() {
+    let dropped = Rc::new(Cell::new(false));
+    dreck!(_owner, arena);
+    let doc = document(&arena, &["a"], &dropped);
+    let clip = arena.add(None);
+    let editor = Editor::new(&arena, doc, clip);
+    drop(arena);
+    assert!(dropped.get());
+    drop(editor);
+}
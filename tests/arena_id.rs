@@ -0,0 +1,72 @@
+#![cfg(feature = "arena-id")]
+
+use std::{mem::size_of, pin::pin};
+
+use dreck::{
+    layout::GC_BOX_HEADER_BYTES,
+    sys::{embed::TraceFn, GcBox, UnsafeArena, UnsafeRootGuard},
+};
+
+#[test]
+fn distinct_ids() {
+    let a = unsafe { UnsafeArena::new() };
+    let b = unsafe { UnsafeArena::new() };
+    assert_ne!(a.id(), b.id());
+
+    let ptr = unsafe { a.add(1u32) };
+    assert_eq!(unsafe { ptr.as_ref().arena_id }, Some(a.id()));
+    assert_eq!(
+        size_of::<GcBox<u64>>(),
+        GC_BOX_HEADER_BYTES + size_of::<u64>()
+    );
+}
+
+#[test]
+fn same_arena() {
+    let arena = unsafe { UnsafeArena::new() };
+    let guard = pin!(UnsafeRootGuard::new());
+    unsafe {
+        let ptr = arena.add(1u32);
+        arena.root(guard, ptr);
+        arena.write_barrier(ptr);
+        arena.collect_full();
+        assert_eq!(**ptr.as_ref().value.get(), 1);
+    }
+}
+
+#[test]
+#[should_panic(expected = "used with arena")]
+fn root_in_other_arena() {
+    let a = unsafe { UnsafeArena::new() };
+    let b = unsafe { UnsafeArena::new() };
+    let guard = pin!(UnsafeRootGuard::new());
+    unsafe {
+        let ptr = a.add(1u32);
+        b.root(guard, ptr);
+    }
+}
+
+#[test]
+#[should_panic(expected = "used with arena")]
+fn write_barrier_in_other_arena() {
+    let a = unsafe { UnsafeArena::new() };
+    let b = unsafe { UnsafeArena::new() };
+    unsafe {
+        let ptr = a.add(1u32);
+        b.write_barrier(ptr);
+    }
+}
+
+#[test]
+#[should_panic(expected = "used with arena")]
+fn mark_from_other_arena() {
+    let a = unsafe { UnsafeArena::new() };
+    let b = unsafe { UnsafeArena::new() };
+    let guard = pin!(UnsafeRootGuard::new());
+    unsafe {
+        let foreign = a.add(1u32).cast::<GcBox<()>>();
+        let list = b.add(TraceFn::erased(vec![foreign]));
+        b.root(guard, list);
+        b.collect_full();
+    }
+}
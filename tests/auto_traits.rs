@@ -0,0 +1,25 @@
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+use dreck::{
+    containers::GcVec,
+    scoped::{self, ArenaScope, ScopedArena},
+    *,
+};
+
+assert_not_impl_any!(Owner<'static>: Send, Sync);
+assert_not_impl_any!(Arena<'static>: Send, Sync);
+assert_not_impl_any!(Gc<'static, 'static, u32>: Send, Sync);
+assert_not_impl_any!(Marker<'static, 'static>: Send, Sync);
+assert_not_impl_any!(RootGuard: Send, Sync);
+assert_not_impl_any!(scoped::Gc<'static, u32>: Send, Sync);
+assert_not_impl_any!(ArenaScope<'static>: Send, Sync);
+assert_not_impl_any!(ScopedArena: Send, Sync);
+assert_not_impl_any!(GcVec<'static, 'static, u32>: Send, Sync);
+assert_not_impl_any!(BorrowAll<'static, 'static, 'static, u32>: Send, Sync);
+assert_not_impl_any!(BorrowAllMut<'static, 'static, 'static, u32>: Send, Sync);
+assert_not_impl_any!(sys::UnsafeArena: Send, Sync);
+assert_not_impl_any!(sys::UnsafeRootGuard: Send, Sync);
+assert_not_impl_any!(SharedGc<'static, 'static, GcCell<u32>>: Send, Sync);
+
+assert_impl_all!(ReadToken<'static, 'static>: Send, Sync);
+assert_impl_all!(SharedGc<'static, 'static, u32>: Send, Sync);
@@ -0,0 +1,143 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+pub struct Node<'gc, 'own> {
+    edges: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Node<'_, '_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.edges.trace(marker)
+    }
+}
+
+const NODES: usize = 16;
+
+/// Allocate a rooted vector of nodes and step until they are all traced.
+macro_rules! traced_nodes {
+    ($owner:ident, $arena:ident, $drops:expr, $nodes:ident) => {
+        let nodes = (0..NODES)
+            .map(|_| {
+                $arena.add(Node {
+                    edges: Vec::new(),
+                    drops: $drops.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let nodes = $arena.add(nodes);
+        let guard = pin!(RootGuard::new());
+        let $nodes = root!(&$arena, guard, nodes);
+
+        $arena.collect_full(&$owner);
+        unsafe {
+            while $arena.unsafe_arena().phase() != Phase::Trace {
+                $arena.unsafe_arena().step();
+            }
+            // The vector and every node.
+            for _ in 0..=NODES {
+                $arena.unsafe_arena().step();
+            }
+        }
+        assert_eq!($arena.unsafe_arena().phase(), Phase::Trace);
+    };
+}
+
+#[test]
+fn edges_not_lost() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+    traced_nodes!(owner, arena, drops, nodes);
+
+    arena.barrier_batch(|batch| {
+        for i in 0..NODES * 100 {
+            let node = nodes.borrow(&owner)[i % NODES];
+            let new = batch.arena().add(Node {
+                edges: Vec::new(),
+                drops: drops.clone(),
+            });
+            batch.borrow_mut(&mut owner, node).edges.push(new);
+        }
+        assert_eq!(batch.len(), NODES);
+    });
+
+    // Finish the cycle the edges were added in.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+    assert_eq!(drops.get(), 0);
+    for node in nodes.borrow(&owner).iter() {
+        assert_eq!(node.borrow(&owner).edges.len(), 100);
+    }
+}
+
+#[test]
+fn applied_on_panic() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+    traced_nodes!(owner, arena, drops, nodes);
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        arena.barrier_batch(|batch| {
+            let node = nodes.borrow(&owner)[0];
+            let new = batch.arena().add(Node {
+                edges: Vec::new(),
+                drops: drops.clone(),
+            });
+            batch.borrow_mut(&mut owner, node).edges.push(new);
+            panic!("rewrite failed");
+        })
+    }));
+    assert!(res.is_err());
+
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+    assert_eq!(drops.get(), 0);
+    assert_eq!(nodes.borrow(&owner)[0].borrow(&owner).edges.len(), 1);
+}
+
+#[test]
+fn nothing_recorded_outside_tracing() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+    let node = arena.add(Node {
+        edges: Vec::new(),
+        drops: drops.clone(),
+    });
+    let guard = pin!(RootGuard::new());
+    let node = root!(&arena, guard, node);
+    arena.collect_full(&owner);
+
+    arena.barrier_batch(|batch| {
+        let new = batch.arena().add(Node {
+            edges: Vec::new(),
+            drops: drops.clone(),
+        });
+        batch.borrow_mut(&mut owner, node).edges.push(new);
+        assert!(batch.is_empty());
+    });
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+}
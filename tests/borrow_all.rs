@@ -0,0 +1,65 @@
+use dreck::*;
+
+pub struct Item<'gc, 'own> {
+    name: Gc<'gc, 'own, String>,
+    count: u32,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Item<'gc, 'own> {
+    type Gc<'to> = Item<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.name.trace(marker)
+    }
+}
+
+#[test]
+fn shared() {
+    dreck!(owner, arena);
+
+    let items = ["a", "b", "c"]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let name = arena.add(name.to_string());
+            arena.add(Item {
+                name,
+                count: idx as u32,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let iter = items.borrow_all(&owner);
+    assert_eq!(iter.len(), 3);
+    // The owner is still available for reading nested pointers while iterating.
+    let names = iter
+        .map(|item| format!("{}{}", item.name.borrow(&owner), item.count))
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["a0", "b1", "c2"]);
+
+    assert_eq!(items[1..].borrow_all(&owner).count(), 2);
+}
+
+#[test]
+fn mutable() {
+    dreck!(owner, arena);
+
+    let items = (0..4u32).map(|x| arena.add(x)).collect::<Vec<_>>();
+
+    let mut iter = items.borrow_all_mut(&mut owner, &arena);
+    assert_eq!(iter.len(), 4);
+    while let Some(x) = iter.next() {
+        *x *= 2;
+    }
+    assert!(iter.is_empty());
+
+    let values = items.borrow_all(&owner).copied().collect::<Vec<_>>();
+    assert_eq!(values, [0, 2, 4, 6]);
+}
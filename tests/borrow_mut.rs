@@ -0,0 +1,169 @@
+use std::pin::pin;
+
+use dreck::*;
+
+mod common;
+use common::trace_roots;
+
+pub struct Container<'gc, 'own> {
+    value: u32,
+    child: Option<Gc<'gc, 'own, Container<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.child.trace(marker)
+    }
+}
+
+fn container<'gc, 'own>(
+    arena: &'gc Arena<'own>,
+    value: u32,
+) -> Gc<'gc, 'own, Container<'gc, 'own>> {
+    arena.add(Container { value, child: None })
+}
+
+#[test]
+fn borrow_mut_during_trace() {
+    dreck!(owner, arena);
+
+    let parent = container(&arena, 0);
+    let guard = pin!(RootGuard::new());
+    let parent = root!(&arena, guard, parent);
+    trace_roots(&arena);
+
+    let child = container(&arena, 1);
+    let borrow = parent.borrow_mut(&mut owner, &arena);
+    borrow.value = 2;
+    borrow.child = Some(child);
+
+    arena.collect_full(&owner);
+    let parent = parent.borrow(&owner);
+    assert_eq!(parent.value, 2);
+    assert_eq!(parent.child.unwrap().borrow(&owner).value, 1);
+}
+
+#[test]
+fn borrow_mut_untraced() {
+    dreck!(owner, arena);
+
+    let value = arena.add(1u32);
+    let guard = pin!(RootGuard::new());
+    let value = root!(&arena, guard, value);
+
+    *value.borrow_mut_untraced(&mut owner) += 1;
+    arena.collect_full(&owner);
+    assert_eq!(*value.borrow(&owner), 2);
+}
+
+#[test]
+#[should_panic]
+fn borrow_mut_untraced_traced_type() {
+    dreck!(owner, arena);
+
+    let parent = container(&arena, 0);
+    parent.borrow_mut_untraced(&mut owner).value = 1;
+}
+
+#[test]
+fn borrow_mut_no_barrier() {
+    dreck!(owner, arena);
+
+    let parent = container(&arena, 0);
+    let guard = pin!(RootGuard::new());
+    let parent = root!(&arena, guard, parent);
+    trace_roots(&arena);
+
+    let child = container(&arena, 1);
+    let borrow = unsafe { parent.borrow_mut_no_barrier(&mut owner) };
+    borrow.value = 2;
+    borrow.child = Some(child);
+    // Without a barrier the new child has to be marked by hand.
+    arena.write_barrier(parent);
+
+    arena.collect_full(&owner);
+    let parent = parent.borrow(&owner);
+    assert_eq!(parent.value, 2);
+    assert_eq!(parent.child.unwrap().borrow(&owner).value, 1);
+}
+
+#[test]
+fn borrow_mut_2() {
+    dreck!(owner, arena);
+
+    let a = container(&arena, 0);
+    let a_guard = pin!(RootGuard::new());
+    let a = root!(&arena, a_guard, a);
+    let b = container(&arena, 1);
+    let b_guard = pin!(RootGuard::new());
+    let b = root!(&arena, b_guard, b);
+    trace_roots(&arena);
+
+    let (a_mut, b_mut) = owner.borrow_mut_2(a, b, &arena);
+    a_mut.child = Some(container(&arena, 2));
+    b_mut.child = Some(container(&arena, 3));
+    std::mem::swap(&mut a_mut.value, &mut b_mut.value);
+
+    arena.collect_full(&owner);
+    let (a, b) = (a.borrow(&owner), b.borrow(&owner));
+    assert_eq!(a.value, 1);
+    assert_eq!(b.value, 0);
+    assert_eq!(a.child.unwrap().borrow(&owner).value, 2);
+    assert_eq!(b.child.unwrap().borrow(&owner).value, 3);
+}
+
+#[test]
+fn borrow_mut_3() {
+    dreck!(owner, arena);
+
+    let a = container(&arena, 0);
+    let a_guard = pin!(RootGuard::new());
+    let a = root!(&arena, a_guard, a);
+    let b = container(&arena, 1);
+    let b_guard = pin!(RootGuard::new());
+    let b = root!(&arena, b_guard, b);
+    let c = container(&arena, 2);
+    let c_guard = pin!(RootGuard::new());
+    let c = root!(&arena, c_guard, c);
+    trace_roots(&arena);
+
+    let (a_mut, b_mut, c_mut) = owner.borrow_mut_3(a, b, c, &arena);
+    a_mut.child = Some(container(&arena, 3));
+    b_mut.child = Some(container(&arena, 4));
+    c_mut.child = Some(container(&arena, 5));
+    c_mut.value = a_mut.value + b_mut.value;
+
+    arena.collect_full(&owner);
+    for (x, value) in [(a, 3), (b, 4), (c, 5)] {
+        assert_eq!(x.borrow(&owner).child.unwrap().borrow(&owner).value, value);
+    }
+    assert_eq!(c.borrow(&owner).value, 1);
+}
+
+#[test]
+#[should_panic(expected = "two pointers to the same object")]
+fn borrow_mut_2_alias() {
+    dreck!(owner, arena);
+
+    let a = container(&arena, 0);
+    owner.borrow_mut_2(a, a, &arena);
+}
+
+#[test]
+#[should_panic(expected = "two pointers to the same object")]
+fn borrow_mut_3_alias() {
+    dreck!(owner, arena);
+
+    let a = container(&arena, 0);
+    let b = container(&arena, 1);
+    owner.borrow_mut_3(a, b, a, &arena);
+}
@@ -0,0 +1,115 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    value: u32,
+    next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Node<'_, '_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.next.trace(marker)
+    }
+}
+
+pub struct PlainNode {
+    value: u32,
+    next: Option<BuilderRef<PlainNode>>,
+    drops: Rc<Cell<usize>>,
+}
+
+impl<'own> IntoHeap<'own> for PlainNode {
+    type Output<'gc> = Node<'gc, 'own>;
+
+    fn into_heap<'gc>(self, refs: &HeapRefs<'gc, 'own, Self>) -> Node<'gc, 'own> {
+        Node {
+            value: self.value,
+            next: self.next.map(|x| refs.get(x)),
+            drops: self.drops,
+        }
+    }
+}
+
+fn ring(
+    builder: &mut HeapBuilder<PlainNode>,
+    len: u32,
+    drops: &Rc<Cell<usize>>,
+) -> BuilderRef<PlainNode> {
+    let first = builder.reserve();
+    let mut next = first;
+    for value in (1..len).rev() {
+        next = builder.add(PlainNode {
+            value,
+            next: Some(next),
+            drops: drops.clone(),
+        });
+    }
+    builder.set(
+        first,
+        PlainNode {
+            value: 0,
+            next: Some(next),
+            drops: drops.clone(),
+        },
+    );
+    first
+}
+
+#[test]
+fn build_cycle() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut builder = HeapBuilder::new();
+    let kept = ring(&mut builder, 3, &drops);
+    let garbage = ring(&mut builder, 5, &drops);
+    assert_eq!(builder.len(), 8);
+
+    let entries = builder.build(&mut owner, &arena, &[kept, garbage]);
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, entries[0]);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 5);
+
+    let mut cur = kept;
+    let mut values = Vec::new();
+    for _ in 0..4 {
+        values.push(cur.borrow(&owner).value);
+        cur = cur.borrow(&owner).next.unwrap();
+    }
+    assert_eq!(values, [0, 1, 2, 0]);
+}
+
+#[test]
+#[should_panic(expected = "never set")]
+fn unset_placeholder() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut builder = HeapBuilder::new();
+    let a = builder.reserve();
+    builder.add(PlainNode {
+        value: 0,
+        next: Some(a),
+        drops,
+    });
+    builder.build(&mut owner, &arena, &[a]);
+}
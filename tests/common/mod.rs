@@ -0,0 +1,14 @@
+//! Helpers shared between the integration tests.
+
+use dreck::{sys::Phase, Arena};
+
+/// Step the arena until the rooted pointers are traced.
+pub fn trace_roots(arena: &Arena) {
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+}
@@ -2,4 +2,8 @@
 fn compile() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/compile_fail/*.rs");
+    t.pass("tests/compile_pass/*.rs");
+    // Generated by tests/soundness_matrix.rs.
+    t.compile_fail("tests/compile_fail/matrix/*.rs");
+    t.pass("tests/compile_pass/matrix/*.rs");
 }
@@ -0,0 +1,27 @@
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    pub next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.next.trace(marker)
+    }
+}
+
+fn main() {
+    dreck!(owner, arena);
+
+    // The object is not initialized yet, so the closure can't borrow the owner to read it.
+    arena.add_cyclic(&mut owner, |this: Gc<Node>| {
+        let _ = this.borrow(&owner).next;
+        Node { next: Some(this) }
+    });
+}
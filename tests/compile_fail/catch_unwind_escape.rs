@@ -0,0 +1,7 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let escaped = catch_unwind_with_heap(&mut arena, &mut owner, |_, arena| arena.add(3u32));
+    let _ = escaped;
+}
@@ -0,0 +1,8 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let raw = arena.add(3u32).into_raw();
+    let value: Gc<'static, '_, u32> = Gc::from_raw(raw);
+    let _ = value.borrow(&owner);
+}
@@ -0,0 +1,11 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+
+    let text = String::from("text");
+    let erased = arena.add(&text).erase();
+    drop(text);
+    let text: &'static String = *erased.downcast::<&'static String>().unwrap().borrow(&owner);
+    println!("{text}");
+}
@@ -0,0 +1,9 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+
+    // A cell can't hold GC pointers as setting it applies no write barrier.
+    let value = arena.add(1u32);
+    let _cell = GcCell::new(value);
+}
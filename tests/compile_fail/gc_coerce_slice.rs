@@ -0,0 +1,9 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let array = arena.add([1u8, 2, 3, 4]);
+    let slice = arena.get_static(gc_coerce!(array => [u8]));
+    array.borrow_mut(&mut owner, &arena)[0] = 5;
+    assert_eq!(slice[0], 1);
+}
@@ -0,0 +1,13 @@
+use dreck::*;
+
+gc_trait! {
+    pub unsafe trait Named<'gc, 'own> {
+        fn name(&self) -> String;
+    }
+}
+
+fn main() {
+    dreck!(owner, arena);
+    let value = gc_coerce!(arena.add(3u32) => dyn Named);
+    let _ = value.borrow(&owner).name();
+}
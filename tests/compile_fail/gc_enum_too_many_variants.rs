@@ -0,0 +1,19 @@
+use dreck::*;
+
+gc_enum! {
+    enum Value<'gc, 'own> {
+        A(u8),
+        B(u16),
+        C(u32),
+        D(u64),
+        E(i8),
+        F(i16),
+        G(i32),
+        H(i64),
+        I(String),
+    }
+
+    struct PackedValue;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use dreck::*;
+
+gc_trait! {
+    pub unsafe trait Method<'a, 'gc, 'own> {
+        fn get(&self) -> &'a u32;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use dreck::*;
+
+gc_trait! {
+    pub unsafe trait Method<'gc, 'own> {
+        fn call(&self) -> u32;
+    }
+}
+
+pub struct Constant(u32);
+
+unsafe impl<'own> Trace<'own> for Constant {
+    type Gc<'to> = Constant;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+impl<'gc, 'own> Method<'gc, 'own> for Constant {
+    fn call(&self) -> u32 {
+        self.0
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use dreck::*;
+
+gc_trait! {
+    pub trait Method<'gc, 'own> {
+        fn call(&self) -> u32;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use std::pin::pin;
+
+use dreck::{containers::GcVec, *};
+
+fn main() {
+    dreck!(owner, arena);
+
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+    let value = arena.add(1);
+    vec.push(&mut owner, &arena, value);
+
+    // The popped value is no longer reachable, so it can't be kept across a collection.
+    let popped = vec.pop(&mut owner, &arena).unwrap();
+    arena.collect_full(&owner);
+    let _ = popped.borrow(&owner);
+}
@@ -0,0 +1,12 @@
+use std::pin::pin;
+
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let guard = pin!(RootGuard::new());
+    let str = root!(&arena, guard, arena.add_str("rooted"));
+    let value = arena.get_static(str);
+    arena.collect_full(&owner);
+    println!("{value}");
+}
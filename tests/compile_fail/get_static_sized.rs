@@ -0,0 +1,9 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let value = arena.add(1u32);
+    let reference = arena.get_static(value);
+    *value.borrow_mut_untraced(&mut owner) = 2;
+    println!("{reference}");
+}
@@ -0,0 +1,14 @@
+use dreck::*;
+use std::pin::pin;
+
+fn main() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let ptr = root!(&arena, guard, arena.add(1u32));
+    let scoped = arena.into_scoped(owner, &[]);
+
+    // The owner was handed over with the arena, so the pointer can no longer be used.
+    assert_eq!(*ptr.borrow(&owner), 1);
+    drop(scoped);
+}
@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `escape_return` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn escape() -> impl Sized + 'static {
+    dreck!(owner, arena);
+    let h = Anchored::<u32>::new(&arena, arena.add(1u32));
+    h
+}
+
+fn main() {
+    let _ = escape();
+}
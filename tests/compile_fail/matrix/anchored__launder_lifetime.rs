@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `launder_lifetime` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = Anchored::<Gc<u32>>::new(&arena, arena.add(arena.add(1u32)));
+    let inner: Gc<'static, '_, u32> = *h.get(&arena).borrow(&owner);
+    let _ = *inner.borrow(&owner);
+}
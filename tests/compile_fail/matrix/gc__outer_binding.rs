@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `outer_binding` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    let mut outer = Vec::new();
+    {
+        dreck!(owner, arena);
+        let h = arena.add(1u32);
+        outer.push(h);
+    }
+    let _ = outer;
+}
@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `after_collect` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = arena.add(1u32).erase();
+    arena.collect_full(&owner);
+    let _ = *h.downcast::<u32>().unwrap().borrow(&owner);
+}
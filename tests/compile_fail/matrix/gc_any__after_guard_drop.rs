@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `after_guard_drop` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = {
+        root!(&arena, let h = arena.add(1u32));
+        let h = h.erase();
+        h
+    };
+    let _ = *h.downcast::<u32>().unwrap().borrow(&owner);
+}
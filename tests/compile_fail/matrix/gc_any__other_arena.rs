@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `other_arena` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = arena.add(1u32).erase();
+    dreck!(owner2, arena2);
+    let _ = *h.downcast::<u32>().unwrap().borrow(&owner2);
+}
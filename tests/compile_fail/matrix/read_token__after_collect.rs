@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `after_collect` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    root!(&arena, let ptr = arena.add(1u32));
+    arena.scope_read(&owner, |scope| {
+        let h = scope.token();
+        arena.collect_full(&owner);
+        let _ = *h.borrow(ptr);
+    });
+}
@@ -0,0 +1,20 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `escape_return` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn escape() -> impl Sized + 'static {
+    dreck!(owner, arena);
+    root!(&arena, let ptr = arena.add(1u32));
+    arena.scope_read(&owner, |scope| {
+        let h = scope.token();
+        h
+    })
+}
+
+fn main() {
+    let _ = escape();
+}
@@ -0,0 +1,20 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `other_arena` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    root!(&arena, let ptr = arena.add(1u32));
+    arena.scope_read(&owner, |scope| {
+        let h = scope.token();
+        dreck!(owner2, arena2);
+        root!(&arena2, let ptr2 = arena2.add(1u32));
+        arena2.scope_read(&owner2, |scope2| {
+            let _ = *h.borrow(ptr2);
+        });
+    });
+}
@@ -0,0 +1,20 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `outer_binding` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    let mut outer = Vec::new();
+    {
+        dreck!(owner, arena);
+        root!(&arena, let ptr = arena.add(1u32));
+        arena.scope_read(&owner, |scope| {
+            let h = scope.token();
+            outer.push(h);
+        });
+    }
+    let _ = outer;
+}
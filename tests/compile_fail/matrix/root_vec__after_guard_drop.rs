@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `after_guard_drop` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = {
+        let roots = pin!(RootVec::new());
+        let h = roots.as_ref().push(&arena, arena.add(1u32));
+        h
+    };
+    let _ = *h.borrow(&owner);
+}
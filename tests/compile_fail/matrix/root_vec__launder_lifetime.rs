@@ -0,0 +1,15 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `launder_lifetime` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let roots = pin!(RootVec::new());
+    let h = roots.as_ref().push(&arena, arena.add(arena.add(1u32)));
+    let inner: Gc<'static, '_, u32> = *h.borrow(&owner);
+    let _ = *inner.borrow(&owner);
+}
@@ -0,0 +1,15 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `other_arena` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let roots = pin!(RootVec::new());
+    let h = roots.as_ref().push(&arena, arena.add(1u32));
+    dreck!(owner2, arena2);
+    let _ = *h.borrow(&owner2);
+}
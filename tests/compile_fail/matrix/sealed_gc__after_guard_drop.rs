@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `after_guard_drop` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = {
+        let guard = pin!(RootGuard::new());
+        let h = arena.root_sealed(arena.add(1u32).seal(), guard);
+        h
+    };
+    let _ = *h.borrow(&owner);
+}
@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `launder_lifetime` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = arena.add(arena.add(1u32)).seal();
+    let inner: SealedGc<'static, '_, u32> = *h.borrow(&owner);
+    let _ = *inner.borrow(&owner);
+}
@@ -0,0 +1,16 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `after_guard_drop` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = {
+        stack_root!(&arena, let h = vec![arena.add(1u32)]);
+        h
+    };
+    let _ = *h[0].borrow(&owner);
+}
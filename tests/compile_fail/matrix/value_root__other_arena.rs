@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// `other_arena` must not compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    stack_root!(&arena, let h = vec![arena.add(1u32)]);
+    dreck!(owner2, arena2);
+    let _ = *h[0].borrow(&owner2);
+}
@@ -0,0 +1,10 @@
+use dreck::*;
+
+fn shorten<'a, 'b: 'a>(owner: Owner<'b>) -> Owner<'a> {
+    owner
+}
+
+fn main() {
+    dreck!(owner, _arena);
+    let _owner = shorten(owner);
+}
@@ -0,0 +1,15 @@
+use std::pin::pin;
+
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let cell = root!(&arena, guard, arena.add(GcCell::new(0u32)));
+
+    arena.scope_read(&owner, |scope| {
+        let cell = scope.token().share(cell);
+        scope.spawn(move |token| token.borrow(cell.get()).set(1));
+    });
+}
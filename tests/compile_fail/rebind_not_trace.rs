@@ -0,0 +1,9 @@
+use dreck::*;
+
+pub struct Plain(u32);
+
+fn main() {
+    dreck!(_owner, arena);
+
+    let _value = rebind!(&arena, Plain(0));
+}
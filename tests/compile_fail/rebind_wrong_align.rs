@@ -0,0 +1,22 @@
+use dreck::*;
+
+pub struct Bytes([u8; 8]);
+
+unsafe impl<'own> Trace<'own> for Bytes {
+    type Gc<'gc> = u64;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+fn main() {
+    dreck!(_owner, arena);
+
+    let _ptr = arena.rebind_to(Bytes([0; 8]));
+}
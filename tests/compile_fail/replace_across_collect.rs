@@ -0,0 +1,35 @@
+use dreck::*;
+use std::pin::pin;
+
+pub struct Container<'gc, 'own>(Option<Gc<'gc, 'own, u32>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+fn main() {
+    dreck!(owner, arena);
+
+    let ptr = arena.add(Container(Some(arena.add(1))));
+    let guard = pin!(RootGuard::new());
+    let ptr = root!(&arena, guard, ptr);
+
+    // The old value is no longer reachable from `ptr`.
+    let old = ptr.replace(&mut owner, &arena, Container(None));
+    // The old value could be collected here.
+    arena.collect(&owner);
+
+    // The old value is then used.
+    assert_eq!(*old.0.unwrap().borrow(&owner), 1);
+}
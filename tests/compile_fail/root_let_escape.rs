@@ -0,0 +1,14 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+
+    let escaped;
+    {
+        root!(&arena, let rooted = arena.add(1u32));
+        escaped = rooted;
+    }
+    // The guard was dropped at the end of the scope, so the pointer can be collected.
+    arena.collect_full(&owner);
+    assert_eq!(*escaped.borrow(&owner), 1);
+}
@@ -0,0 +1,9 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let value = arena.add(String::from("dreck"));
+    let rooted = value.with(&owner);
+    arena.collect(&owner);
+    println!("{}", rooted);
+}
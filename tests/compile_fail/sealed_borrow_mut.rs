@@ -0,0 +1,7 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let sealed = arena.add(3u32).seal();
+    *sealed.borrow_mut(&mut owner, &arena) = 4;
+}
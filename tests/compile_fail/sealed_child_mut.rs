@@ -0,0 +1,8 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let child = arena.add(3u32);
+    let sealed = arena.add([child]).seal();
+    sealed.borrow(&owner)[0] = arena.add(4u32).seal();
+}
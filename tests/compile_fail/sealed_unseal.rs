@@ -0,0 +1,8 @@
+use dreck::*;
+
+fn main() {
+    dreck!(owner, arena);
+    let sealed = arena.add(3u32).seal();
+    let gc: Gc<u32> = Gc::from(sealed);
+    *gc.borrow_mut(&mut owner, &arena) = 4;
+}
@@ -0,0 +1,33 @@
+use dreck::*;
+use std::pin::pin;
+
+pub struct Container<'gc, 'own>(Option<Gc<'gc, 'own, Container<'gc, 'own>>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+fn main() {
+    dreck!(owner, arena);
+
+    let inner = arena.add(Container(None));
+    let outer = arena.add(Container(None));
+    let guard = pin!(RootGuard::new());
+    let outer = root!(&arena, guard, outer);
+
+    outer.borrow_mut(&mut owner, &arena).0 = Some(rebind!(&arena, inner));
+    arena.collect_full(&owner);
+
+    assert!(outer.borrow(&owner).0.is_some());
+}
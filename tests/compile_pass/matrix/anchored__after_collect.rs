@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// sound by design, anchored pointers are rooted until the handle is dropped
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = Anchored::<u32>::new(&arena, arena.add(1u32));
+    arena.collect_full(&owner);
+    let _ = *h.get(&arena).borrow(&owner);
+}
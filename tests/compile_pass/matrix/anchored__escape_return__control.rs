@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `escape_return` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn escape() -> impl Sized + 'static {
+    dreck!(owner, arena);
+    let h = Anchored::<u32>::new(&arena, arena.add(1u32));
+    *h.get(&arena).borrow(&owner)
+}
+
+fn main() {
+    let _ = escape();
+}
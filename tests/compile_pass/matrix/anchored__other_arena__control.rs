@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `other_arena` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = Anchored::<u32>::new(&arena, arena.add(1u32));
+    dreck!(owner2, arena2);
+    let _ = *h.get(&arena).borrow(&owner);
+}
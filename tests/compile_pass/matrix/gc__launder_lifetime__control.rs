@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `launder_lifetime` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = arena.add(arena.add(1u32));
+    let inner = *h.borrow(&owner);
+    let _ = *inner.borrow(&owner);
+}
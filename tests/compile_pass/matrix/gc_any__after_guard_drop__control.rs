@@ -0,0 +1,16 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `after_guard_drop` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let _ = {
+        root!(&arena, let h = arena.add(1u32));
+        let h = h.erase();
+        *h.downcast::<u32>().unwrap().borrow(&owner)
+    };
+}
@@ -0,0 +1,16 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `after_collect` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    root!(&arena, let ptr = arena.add(1u32));
+    arena.scope_read(&owner, |scope| {
+        let h = scope.token();
+        let _ = *h.borrow(ptr);
+    });
+}
@@ -0,0 +1,20 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `escape_return` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn escape() -> impl Sized + 'static {
+    dreck!(owner, arena);
+    root!(&arena, let ptr = arena.add(1u32));
+    arena.scope_read(&owner, |scope| {
+        let h = scope.token();
+        *h.borrow(ptr)
+    })
+}
+
+fn main() {
+    let _ = escape();
+}
@@ -0,0 +1,20 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `outer_binding` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    let mut outer = Vec::new();
+    {
+        dreck!(owner, arena);
+        root!(&arena, let ptr = arena.add(1u32));
+        arena.scope_read(&owner, |scope| {
+            let h = scope.token();
+            outer.push(*h.borrow(ptr));
+        });
+    }
+    let _ = outer;
+}
@@ -0,0 +1,15 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// sound by design, pointers pushed to a root vector are rooted until it is cleared
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let roots = pin!(RootVec::new());
+    let h = roots.as_ref().push(&arena, arena.add(1u32));
+    arena.collect_full(&owner);
+    let _ = *h.borrow(&owner);
+}
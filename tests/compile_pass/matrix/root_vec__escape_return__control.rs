@@ -0,0 +1,18 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `escape_return` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn escape() -> impl Sized + 'static {
+    dreck!(owner, arena);
+    let roots = pin!(RootVec::new());
+    let h = roots.as_ref().push(&arena, arena.add(1u32));
+    *h.borrow(&owner)
+}
+
+fn main() {
+    let _ = escape();
+}
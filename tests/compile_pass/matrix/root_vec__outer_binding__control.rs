@@ -0,0 +1,18 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `outer_binding` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    let mut outer = Vec::new();
+    {
+        dreck!(owner, arena);
+        let roots = pin!(RootVec::new());
+        let h = roots.as_ref().push(&arena, arena.add(1u32));
+        outer.push(*h.borrow(&owner));
+    }
+    let _ = outer;
+}
@@ -0,0 +1,13 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `after_collect` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    let h = arena.add(1u32).with(&owner);
+    let _ = *h;
+}
@@ -0,0 +1,15 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// sound by design, scoped pointers are rooted until the scope ends
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    ScopedArena::new().with(|owner, arena| {
+        let h = arena.add(1u32);
+        arena.collect_full();
+        let _ = *h.borrow(owner);
+    });
+}
@@ -0,0 +1,16 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `other_arena` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    ScopedArena::new().with(|owner, arena| {
+        let h = arena.add(1u32);
+        ScopedArena::new().with(|owner2, arena2| {
+            let _ = *h.borrow(owner);
+        });
+    });
+}
@@ -0,0 +1,18 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `outer_binding` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    let mut outer = Vec::new();
+    {
+        ScopedArena::new().with(|owner, arena| {
+            let h = arena.add(1u32);
+            outer.push(*h.borrow(owner));
+        });
+    }
+    let _ = outer;
+}
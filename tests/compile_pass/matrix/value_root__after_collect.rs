@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// sound by design, values rooted on the stack are rooted until the guard drops
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    stack_root!(&arena, let h = vec![arena.add(1u32)]);
+    arena.collect_full(&owner);
+    let _ = *h[0].borrow(&owner);
+}
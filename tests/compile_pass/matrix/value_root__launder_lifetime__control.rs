@@ -0,0 +1,14 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `launder_lifetime` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    dreck!(owner, arena);
+    stack_root!(&arena, let h = vec![arena.add(arena.add(1u32))]);
+    let inner = *h[0].borrow(&owner);
+    let _ = *inner.borrow(&owner);
+}
@@ -0,0 +1,17 @@
+// Generated by tests/soundness_matrix.rs, do not edit.
+// the control of `outer_binding` must compile
+#![allow(unused)]
+
+use std::pin::pin;
+
+use dreck::{scoped::ScopedArena, *};
+
+fn main() {
+    let mut outer = Vec::new();
+    {
+        dreck!(owner, arena);
+        stack_root!(&arena, let h = vec![arena.add(1u32)]);
+        outer.push(*h[0].borrow(&owner));
+    }
+    let _ = outer;
+}
@@ -0,0 +1,11 @@
+use dreck::{marker::Invariant, Arena, Owner};
+
+fn main() {
+    let value = ();
+    let invariant = Invariant::new_ref(&value);
+    let owner = unsafe { Owner::from_invariant(invariant) };
+    let arena = unsafe { Arena::new(&owner) };
+
+    let ptr = arena.add(3);
+    assert_eq!(*ptr.borrow(&owner), 3);
+}
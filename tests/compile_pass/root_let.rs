@@ -0,0 +1,37 @@
+use dreck::*;
+
+pub struct Container<'gc, 'own>(Option<Gc<'gc, 'own, Container<'gc, 'own>>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+fn main() {
+    dreck!(owner, arena);
+
+    let guard = ();
+    let inner = arena.add(Container(None));
+    root!(&arena, let outer = arena.add(Container(Some(inner))));
+    root!(&arena, let other = arena.add(Container(None)));
+    arena.collect_full(&owner);
+
+    // The guards of the macro don't shadow variables of the caller.
+    let () = guard;
+    assert!(outer.borrow(&owner).0.is_some());
+    assert!(other.borrow(&owner).0.is_none());
+
+    outer.borrow_mut(&mut owner, &arena).0 = Some(rebind!(&arena, other));
+    arena.collect_full(&owner);
+    assert!(outer.borrow(&owner).0.unwrap().ptr_eq(other));
+}
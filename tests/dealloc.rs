@@ -0,0 +1,127 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    pin::pin,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use dreck::*;
+
+/// An allocator which stores the layout of each allocation in front of it and counts the number
+/// of deallocations with a different layout.
+struct CheckAlloc;
+
+static MISMATCHED: AtomicUsize = AtomicUsize::new(0);
+
+const HEADER: usize = 16;
+
+impl CheckAlloc {
+    fn outer(layout: Layout) -> (Layout, usize) {
+        let offset = layout.align().max(HEADER);
+        let outer = Layout::from_size_align(layout.size() + offset, offset).unwrap();
+        (outer, offset)
+    }
+}
+
+unsafe impl GlobalAlloc for CheckAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (outer, offset) = Self::outer(layout);
+        let ptr = System.alloc(outer);
+        if ptr.is_null() {
+            return ptr;
+        }
+        let ptr = ptr.add(offset);
+        ptr.cast::<usize>().sub(2).write(layout.size());
+        ptr.cast::<usize>().sub(1).write(layout.align());
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let size = ptr.cast::<usize>().sub(2).read();
+        let align = ptr.cast::<usize>().sub(1).read();
+        if size != layout.size() || align != layout.align() {
+            MISMATCHED.fetch_add(1, Ordering::SeqCst);
+        }
+        // Free with the layout used for allocating so a mismatch doesn't corrupt the heap.
+        let (outer, offset) = Self::outer(Layout::from_size_align_unchecked(size, align));
+        System.dealloc(ptr.sub(offset), outer)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CheckAlloc = CheckAlloc;
+
+#[repr(align(64))]
+pub struct Aligned(u8);
+
+unsafe impl<'own> Trace<'own> for Aligned {
+    type Gc<'to> = Aligned;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Large([u64; 1024]);
+
+unsafe impl<'own> Trace<'own> for Large {
+    type Gc<'to> = Large;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn layouts_match() {
+    dreck!(owner, arena);
+
+    for i in 0..16u8 {
+        arena.add(Aligned(i));
+        arena.add(Large([i as u64; 1024]));
+        arena.add(i);
+        arena.add_from_iter((0..i).map(Aligned));
+        arena.add_slice_copy(&[i as u64; 3]);
+        arena.add_str(&"x".repeat(i as usize));
+    }
+    let aligned = arena.add(Aligned(16));
+    assert_eq!(aligned.into_gc_box().as_ptr() as usize % 64, 0);
+    let guard = pin!(RootGuard::new());
+    let aligned = root!(&arena, guard, aligned);
+    let large = arena.add(Large([16; 1024]));
+    let guard = pin!(RootGuard::new());
+    let large = root!(&arena, guard, large);
+
+    arena.collect_full(&owner);
+    assert_eq!(aligned.borrow(&owner).0, 16);
+    assert_eq!(large.borrow(&owner).0[1023], 16);
+    assert_eq!(MISMATCHED.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn total_allocated_returns_to_zero() {
+    dreck!(owner, arena);
+
+    for i in 0..16u8 {
+        arena.add(Aligned(i));
+        arena.add(Large([i as u64; 1024]));
+        arena.add(i);
+        arena.add_from_iter((0..i).map(Aligned));
+        arena.add_slice_copy(&[i as u64; 3]);
+        arena.add_str(&"x".repeat(i as usize));
+    }
+    assert!(arena.stats().total_allocated > 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, 0);
+    assert_eq!(MISMATCHED.load(Ordering::SeqCst), 0);
+}
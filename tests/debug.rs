@@ -0,0 +1,88 @@
+use std::fmt;
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    name: String,
+    values: Vec<Gc<'gc, 'own, i32>>,
+    next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.values.trace(marker);
+        self.next.trace(marker);
+    }
+}
+
+impl<'gc, 'own> DebugWith<'own> for Node<'gc, 'own> {
+    fn fmt_with(&self, owner: &Owner<'own>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("name", &self.name)
+            .field("values", &self.values.with_owner(owner))
+            .field("next", &self.next.with_owner(owner))
+            .finish()
+    }
+}
+
+#[test]
+fn graph() {
+    dreck!(owner, arena);
+
+    let leaf = arena.add(Node {
+        name: "leaf".to_string(),
+        values: vec![arena.add(3)],
+        next: None,
+    });
+    let values = vec![arena.add(1), arena.add(-2)];
+    let root = arena.add(Node {
+        name: "root".to_string(),
+        values,
+        next: Some(leaf),
+    });
+
+    assert_eq!(
+        format!("{:?}", root.with_owner(&owner)),
+        "Node { name: \"root\", values: [1, -2], next: Some(Node { name: \"leaf\", values: [3], \
+         next: None }) }"
+    );
+}
+
+#[test]
+fn self_reference() {
+    dreck!(owner, arena);
+
+    let node = arena.add(Node {
+        name: "self".to_string(),
+        values: Vec::new(),
+        next: None,
+    });
+    node.borrow_mut(&mut owner, &arena).next = Some(unsafe { node.rebind() });
+
+    assert_eq!(
+        format!("{:?}", node.with_owner(&owner)),
+        "Node { name: \"self\", values: [], next: Some(Gc(<cycle>)) }"
+    );
+    // Shared but acyclic pointers are formatted every time.
+    let value = arena.add(7);
+    let values = vec![value, value];
+    assert_eq!(format!("{:?}", values.with_owner(&owner)), "[7, 7]");
+}
+
+#[test]
+fn debug_and_display() {
+    dreck!(owner, arena);
+
+    let text = arena.add("text".to_string());
+    assert_eq!(format!("{:?}", text.debug(&owner)), "\"text\"");
+    assert_eq!(format!("{}", text.display(&owner)), "text");
+}
@@ -0,0 +1,111 @@
+use std::pin::pin;
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    children: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+    value: Option<Gc<'gc, 'own, u32>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.children.trace(marker);
+        self.value.trace(marker);
+    }
+}
+
+const NODE: &str = "debug_heap::Node";
+
+/// Replace the full type name of the node type for readable expectations.
+fn short(out: String) -> String {
+    out.replace(std::any::type_name::<Node>(), "Node")
+}
+
+#[test]
+fn tree() {
+    dreck!(owner, arena);
+
+    let shared = arena.add(3u32);
+    let leaf = arena.add(Node {
+        children: Vec::new(),
+        value: Some(shared),
+    });
+    let root = arena.add(Node {
+        children: vec![leaf, leaf],
+        value: Some(shared),
+    });
+    let guard = pin!(RootGuard::new());
+    let _root = root!(&arena, guard, root);
+    let guard = pin!(RootGuard::new());
+    let _other = root!(&arena, guard, arena.add(7u32));
+
+    arena.collect_full(&owner);
+    let out = short(arena.debug_heap(&owner, DebugOptions::default()));
+    assert_eq!(
+        out,
+        "#0 Node\n  #1 Node\n    #2 u32 = 3\n  *1\n  *2\n#3 u32 = 7\n"
+    );
+}
+
+#[test]
+fn limits() {
+    dreck!(owner, arena);
+
+    let mut list = arena.add(Node {
+        children: Vec::new(),
+        value: None,
+    });
+    for x in 0..100 {
+        let value = arena.add(x);
+        list = arena.add(Node {
+            children: vec![list],
+            value: Some(value),
+        });
+    }
+    let guard = pin!(RootGuard::new());
+    let _list = root!(&arena, guard, list);
+
+    let out = short(arena.debug_heap(
+        &owner,
+        DebugOptions {
+            max_depth: 1,
+            ..Default::default()
+        },
+    ));
+    assert_eq!(out, "#0 Node\n  #1 Node\n    ...\n  #2 u32 = 99\n");
+
+    let out = arena.debug_heap(
+        &owner,
+        DebugOptions {
+            max_depth: usize::MAX,
+            max_nodes: 10,
+            ..Default::default()
+        },
+    );
+    assert_eq!(out.lines().count(), 11);
+    assert!(out.ends_with("... truncated\n"));
+
+    // Only the values, which are all nested in nodes.
+    let out = arena.debug_heap(
+        &owner,
+        DebugOptions {
+            max_depth: usize::MAX,
+            only_types: &["u32"],
+            max_nodes: usize::MAX,
+        },
+    );
+    assert_eq!(out.lines().count(), 100);
+    assert!(!out.contains(NODE));
+    assert!(out
+        .lines()
+        .all(|x| x.starts_with('#') && x.contains("u32 = ")));
+}
@@ -0,0 +1,199 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+type Slot<'gc, 'own> = Gc<'gc, 'own, Option<Gc<'gc, 'own, u32>>>;
+
+/// A proxy which materializes its child the first time it is traced.
+pub struct Proxy<'gc, 'own> {
+    slot: Slot<'gc, 'own>,
+    pending: Cell<bool>,
+    ran: Rc<Cell<usize>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Proxy<'gc, 'own> {
+    type Gc<'to> = Proxy<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.slot);
+        if !self.pending.replace(true) {
+            let ran = self.ran.clone();
+            marker.defer(DeferToken::new(self.slot, move |owner, arena, slot| {
+                ran.set(ran.get() + 1);
+                let child = arena.add(7u32);
+                *slot.borrow_mut(owner, arena) = Some(child);
+            }));
+        }
+    }
+}
+
+fn proxy<'gc, 'own>(
+    arena: &'gc Arena<'own>,
+    ran: &Rc<Cell<usize>>,
+) -> Gc<'gc, 'own, Proxy<'gc, 'own>> {
+    arena.add(Proxy {
+        slot: arena.add(None),
+        pending: Cell::new(false),
+        ran: ran.clone(),
+    })
+}
+
+#[test]
+fn materialize_after_cycle() {
+    dreck!(owner, arena);
+    let ran = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let proxy = root!(&arena, guard, proxy(&arena, &ran));
+    assert_eq!(arena.run_deferred(&mut owner), 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.deferred_count(), 1);
+    assert_eq!(ran.get(), 0);
+    assert_eq!(arena.run_deferred(&mut owner), 1);
+    assert_eq!(ran.get(), 1);
+    assert_eq!(arena.deferred_count(), 0);
+
+    // The materialized child is reachable and survives the next collection.
+    arena.collect_full(&owner);
+    let child = proxy.borrow(&owner).slot.borrow(&owner).unwrap();
+    assert_eq!(*child.borrow(&owner), 7);
+    assert_eq!(arena.run_deferred(&mut owner), 0);
+    assert_eq!(ran.get(), 1);
+}
+
+#[test]
+fn runs_after_sweep() {
+    dreck!(owner, arena);
+    let ran = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    root!(&arena, guard, proxy(&arena, &ran));
+    unsafe {
+        arena.unsafe_arena().finish_sweep();
+        arena.unsafe_arena().step();
+        while arena.unsafe_arena().phase() != Phase::Sweep {
+            arena.unsafe_arena().step();
+        }
+    }
+    // Queued, but the cycle is still sweeping.
+    assert_eq!(arena.deferred_count(), 1);
+    assert_eq!(arena.run_deferred(&mut owner), 0);
+
+    unsafe { arena.unsafe_arena().finish_sweep() };
+    assert_eq!(arena.run_deferred(&mut owner), 1);
+    assert_eq!(ran.get(), 1);
+}
+
+#[test]
+fn object_kept_alive_until_run() {
+    dreck!(owner, arena);
+    let ran = Rc::new(Cell::new(0));
+
+    {
+        let guard = pin!(RootGuard::new());
+        root!(&arena, guard, proxy(&arena, &ran));
+        arena.collect_full(&owner);
+    }
+    let size = arena.stats().total_allocated;
+
+    // The proxy is freed, the slot is kept alive by the queue.
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+    let slot_size = arena.stats().total_allocated;
+    assert!(slot_size > 0 && slot_size < size);
+
+    assert_eq!(arena.run_deferred(&mut owner), 1);
+    assert_eq!(ran.get(), 1);
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
+
+#[test]
+fn dropped_with_arena() {
+    let ran = Rc::new(Cell::new(0));
+    {
+        dreck!(owner, arena);
+        let guard = pin!(RootGuard::new());
+        root!(&arena, guard, proxy(&arena, &ran));
+        arena.collect_full(&owner);
+        assert_eq!(arena.deferred_count(), 1);
+    }
+    // Dropping the arena drops the operation without running it.
+    assert_eq!(ran.get(), 0);
+    assert_eq!(Rc::strong_count(&ran), 1);
+}
+
+/// A value which allocates or roots in the arena it is allocated in while being traced.
+pub struct Allocates<'a, 'gc, 'own> {
+    arena: &'a Arena<'own>,
+    child: Gc<'gc, 'own, u32>,
+    root: bool,
+}
+
+unsafe impl<'a, 'gc, 'own> Trace<'own> for Allocates<'a, 'gc, 'own> {
+    type Gc<'to> = Allocates<'a, 'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.child);
+        if self.root {
+            let guard = pin!(RootGuard::new());
+            self.arena.root(self.child, guard);
+        } else {
+            self.arena.add(0u32);
+        }
+    }
+}
+
+fn trace_allocates(root: bool) {
+    dreck!(_owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let value = arena.add(Allocates {
+        arena: &arena,
+        child: arena.add(1),
+        root,
+    });
+    root!(&arena, guard, value);
+    unsafe { arena.unsafe_arena().collect_full() };
+}
+
+#[test]
+#[should_panic(expected = "while the arena is tracing an object of type `defer::Allocates")]
+fn allocate_names_type() {
+    trace_allocates(false);
+}
+
+#[test]
+#[should_panic(expected = "defer work to after the collection with `Marker::defer`")]
+fn root_names_type() {
+    trace_allocates(true);
+}
+
+#[test]
+fn bumps_owner_epoch() {
+    dreck!(owner, arena);
+    let ran = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let _proxy = root!(&arena, guard, proxy(&arena, &ran));
+    arena.collect_full(&owner);
+
+    let epoch = owner.epoch();
+    assert_eq!(arena.run_deferred(&mut owner), 1);
+    assert!(owner.epoch() > epoch);
+}
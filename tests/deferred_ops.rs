@@ -0,0 +1,142 @@
+use std::{cell::Cell, panic::AssertUnwindSafe, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+/// A host guard which increments a GC counter when it is dropped.
+pub struct Transaction<'gc, 'own> {
+    ops: DeferredOps<'own>,
+    counter: Gc<'gc, 'own, u32>,
+}
+
+impl<'gc, 'own> Drop for Transaction<'gc, 'own> {
+    fn drop(&mut self) {
+        self.ops.defer_with(self.counter, |owner, arena, counter| {
+            *counter.borrow_mut(owner, arena) += 1
+        });
+    }
+}
+
+#[test]
+fn guard_dropped_while_unwinding() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let counter = root!(&arena, guard, arena.add(0u32));
+    let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let _tx = Transaction {
+            ops: arena.deferred_ops(),
+            counter,
+        };
+        panic!("aborted");
+    }));
+    assert!(res.is_err());
+    assert_eq!(*counter.borrow(&owner), 0);
+    assert_eq!(arena.deferred_ops().len(), 1);
+
+    assert_eq!(arena.safepoint(&mut owner), 1);
+    assert_eq!(*counter.borrow(&owner), 1);
+    assert!(arena.deferred_ops().is_empty());
+}
+
+#[test]
+fn queued_pointer_survives_collect() {
+    dreck!(owner, arena);
+    let ops = arena.deferred_ops();
+    let seen = Rc::new(Cell::new(None));
+
+    // The object is only reachable from the queue.
+    ops.defer_with(arena.add(7u32), {
+        let seen = seen.clone();
+        move |owner, _, value| seen.set(Some(*value.borrow(owner)))
+    });
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+    assert!(arena.stats().total_allocated > 0);
+
+    assert_eq!(arena.flush_deferred(&mut owner), 1);
+    assert_eq!(seen.get(), Some(7));
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
+
+#[test]
+fn queued_while_tracing() {
+    dreck!(owner, arena);
+    let ops = arena.deferred_ops();
+
+    unsafe {
+        arena.unsafe_arena().finish_sweep();
+        arena.unsafe_arena().step();
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+    }
+    // The roots have been scanned, the object must still be marked before the cycle sweeps.
+    ops.defer_with(arena.add(3u32), |owner, arena, value| {
+        *value.borrow_mut(owner, arena) += 1;
+        assert_eq!(*value.borrow(owner), 4);
+    });
+    arena.collect_full(&owner);
+    assert!(arena.stats().total_allocated > 0);
+    assert_eq!(arena.flush_deferred(&mut owner), 1);
+}
+
+#[test]
+fn ops_run_in_order() {
+    dreck!(owner, arena);
+    let ops = arena.deferred_ops();
+    let log = Rc::new(Cell::new(Vec::new()));
+
+    for i in 0..3 {
+        let log = log.clone();
+        let nested = ops.clone();
+        ops.defer(move |_, _| {
+            let mut values = log.take();
+            values.push(i);
+            log.set(values);
+            if i == 0 {
+                let log = log.clone();
+                nested.defer(move |_, _| {
+                    let mut values = log.take();
+                    values.push(3);
+                    log.set(values);
+                });
+            }
+        });
+    }
+    assert_eq!(arena.flush_deferred(&mut owner), 4);
+    assert_eq!(log.take(), [0, 1, 2, 3]);
+    assert_eq!(arena.flush_deferred(&mut owner), 0);
+}
+
+#[test]
+fn dropped_with_arena() {
+    let ran = Rc::new(Cell::new(0));
+    {
+        dreck!(_owner, arena);
+        let ops = arena.deferred_ops();
+        let ran = ran.clone();
+        ops.defer_with(arena.add(1u32), move |_, _, _| ran.set(ran.get() + 1));
+    }
+    // Dropping the arena drops the operation without running it.
+    assert_eq!(ran.get(), 0);
+    assert_eq!(Rc::strong_count(&ran), 1);
+}
+
+#[test]
+fn bumps_owner_epoch() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let counter = root!(&arena, guard, arena.add(0u32));
+    arena
+        .deferred_ops()
+        .defer_with(counter, |owner, arena, counter| {
+            *counter.borrow_mut(owner, arena) += 1
+        });
+
+    let epoch = owner.epoch();
+    assert_eq!(arena.flush_deferred(&mut owner), 1);
+    assert_eq!(*counter.borrow(&owner), 1);
+    assert!(owner.epoch() > epoch);
+}
@@ -0,0 +1,111 @@
+use std::pin::pin;
+
+use dreck::{
+    demo::{self, Error, Globals, Tracker},
+    *,
+};
+
+/// Run each program in order in a single arena, collecting fully after each one, and return the
+/// displayed results along with the number of live objects after each collection.
+fn session(programs: &[&str]) -> Vec<(Result<String, Error>, usize)> {
+    dreck!(owner, arena);
+    let tracker = Tracker::default();
+    let guard = pin!(RootGuard::new());
+    let globals = root!(&arena, guard, Globals::new(&arena, &tracker));
+
+    programs
+        .iter()
+        .map(|src| {
+            let res = demo::run(&mut owner, &arena, globals, src).map(|x| x.display(&owner));
+            arena.collect_full(&owner);
+            (res, tracker.live())
+        })
+        .collect()
+}
+
+fn eval(src: &str) -> Result<String, Error> {
+    session(&[src]).pop().unwrap().0
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(eval("1 + 2 * 3"), Ok("7".to_string()));
+    assert_eq!(eval("(1 + 2) * 3 - 4 / 2"), Ok("7".to_string()));
+    assert_eq!(eval("1 < 2"), Ok("true".to_string()));
+    assert_eq!(eval("3 == 4"), Ok("false".to_string()));
+    assert_eq!(eval("1 / 0"), Err(Error::DivideByZero));
+    assert_eq!(
+        eval("1 + true"),
+        Err(Error::Type(
+            "invalid operands int and bool for Add".to_string()
+        ))
+    );
+}
+
+#[test]
+fn closures() {
+    assert_eq!(
+        eval("let add = fn(a) => fn(b) => a + b in let inc = add(1) in inc(41)"),
+        Ok("42".to_string())
+    );
+    assert_eq!(
+        eval("let fact = fn(n) => if n < 2 then 1 else n * fact(n - 1) in fact(10)"),
+        Ok("3628800".to_string())
+    );
+    assert_eq!(eval("x"), Err(Error::UnknownVariable("x".to_string())));
+    assert_eq!(
+        eval("let loop = fn() => loop() in loop()"),
+        Err(Error::StackOverflow)
+    );
+}
+
+#[test]
+fn lists() {
+    assert_eq!(eval("[1, [2, 3], nil]"), Ok("[1, [2, 3], nil]".to_string()));
+    assert_eq!(
+        eval(
+            "let range = fn(l, n) => if len(l) == n then l else range(push(l, len(l)), n) in \
+             range([], 10)"
+        ),
+        Ok("[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]".to_string())
+    );
+    assert_eq!(eval("get([1, 2], 1)"), Ok("2".to_string()));
+    assert_eq!(eval("get([1, 2], 5)"), Ok("nil".to_string()));
+}
+
+#[test]
+fn globals_survive_collection() {
+    let res = session(&[
+        "def counter = [0]",
+        "def bump = fn() => push(counter, len(counter))",
+        "bump(); bump(); counter",
+        "def counter = nil; counter",
+    ]);
+    assert_eq!(res[2].0, Ok("[0, 1, 2]".to_string()));
+    assert_eq!(res[3].0, Ok("nil".to_string()));
+    // The old counter list is still referenced from the older binding.
+    assert_eq!(res[3].1, res[2].1 + 1);
+}
+
+#[test]
+fn garbage_reclaimed_between_evaluations() {
+    let res = session(&[
+        "def keep = [1, 2, 3]",
+        "let make = fn(n) => if n == 0 then [] else push(make(n - 1), [n]) in len(make(100))",
+        "len(keep)",
+    ]);
+    assert_eq!(res[1].0, Ok("100".to_string()));
+    assert_eq!(res[2].0, Ok("3".to_string()));
+    // The binding and the list of the first definition are all that remains.
+    assert_eq!(res[0].1, 2);
+    assert_eq!(res[1].1, 2);
+    assert_eq!(res[2].1, 2);
+}
+
+#[test]
+fn parse_errors() {
+    assert!(matches!(eval("let x = 1"), Err(Error::Parse(_))));
+    assert!(matches!(eval("1 +"), Err(Error::Parse(_))));
+    assert!(matches!(eval("fn(let) => 1"), Err(Error::Parse(_))));
+    assert!(matches!(eval("1 $ 2"), Err(Error::Parse(_))));
+}
@@ -0,0 +1,131 @@
+use std::{cell::Cell, panic, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Object<'gc, 'own> {
+    name: String,
+    value: Gc<'gc, 'own, u32>,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Object<'_, '_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Object<'gc, 'own> {
+    type Gc<'to> = Object<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.value.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for Object<'gc, 'own> {}
+
+fn render<'own>(value: GcAny<'_, 'own>, owner: &Owner<'own>) -> String {
+    if let Some(object) = value.downcast::<Object>() {
+        let object = object.borrow(owner);
+        format!("{} = {}", object.name, object.value.borrow(owner))
+    } else {
+        value
+            .fmt_leaf(owner)
+            .unwrap_or_else(|| "<redacted>".to_string())
+    }
+}
+
+#[test]
+fn capture() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let value = arena.add(3u32);
+    let object = arena.add(Object {
+        name: "x".to_string(),
+        value,
+        drops: drops.clone(),
+    });
+    let text = arena.add("secret".to_string());
+    let snapshot = arena.capture_diagnostic(
+        &owner,
+        &[object.into(), value.into(), text.into()],
+        |value, owner| {
+            if value.downcast::<String>().is_some() {
+                "<redacted>".to_string()
+            } else {
+                render(value, owner)
+            }
+        },
+    );
+
+    assert_eq!(snapshot.values().len(), 3);
+    assert_eq!(snapshot.values()[0].rendered, "x = 3");
+    assert_eq!(snapshot.values()[1].type_name, "u32");
+    assert_eq!(snapshot.values()[1].rendered, "3");
+    assert_eq!(snapshot.values()[2].rendered, "<redacted>");
+    assert_eq!(
+        snapshot.to_string(),
+        format!(
+            "{}: x = 3\nu32: 3\nalloc::string::String: <redacted>\n",
+            std::any::type_name::<Object>()
+        )
+    );
+}
+
+#[test]
+fn downcast() {
+    dreck!(owner, arena);
+
+    let value: GcAny = arena.add(3u32).into();
+    assert!(value.downcast::<u64>().is_none());
+    assert_eq!(*value.downcast::<u32>().unwrap().borrow(&owner), 3);
+    assert!(value.ptr_eq(value.downcast::<u32>().unwrap().into()));
+}
+
+#[test]
+fn panic_payload() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let result = catch_unwind_with_heap(&mut arena, &mut owner, |owner, arena| {
+        let value = arena.add(7u32);
+        let object = arena.add(Object {
+            name: "y".to_string(),
+            value,
+            drops: drops.clone(),
+        });
+        let guard = pin!(RootGuard::new());
+        let object = root!(arena, guard, object);
+
+        let snapshot = arena.capture_diagnostic(owner, &[object.into()], render);
+        panic::panic_any(snapshot);
+    });
+
+    let payload = result.unwrap_err();
+    let snapshot = payload.downcast::<DiagnosticSnapshot>().unwrap();
+    assert_eq!(snapshot.values()[0].rendered, "y = 7");
+
+    // The root guard was dropped by the unwind.
+    assert_eq!(arena.root_count(), 0);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
+
+#[test]
+fn no_panic() {
+    dreck!(owner, arena);
+
+    let result = catch_unwind_with_heap(&mut arena, &mut owner, |owner, arena| {
+        *arena.add(1u32).borrow(owner) + 1
+    });
+    assert_eq!(result.unwrap(), 2);
+}
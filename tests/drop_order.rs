@@ -0,0 +1,96 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    pin::pin,
+    rc::Rc,
+};
+
+use dreck::*;
+
+/// An allocator which overwrites memory when it is freed so reads after free are detected.
+struct PoisonAlloc;
+
+unsafe impl GlobalAlloc for PoisonAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ptr.write_bytes(0xAA, layout.size());
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: PoisonAlloc = PoisonAlloc;
+
+pub struct Parent<'gc, 'own> {
+    child: Option<Gc<'gc, 'own, u64>>,
+    seen: Rc<Cell<Option<u64>>>,
+}
+
+impl Drop for Parent<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(child) = self.child {
+            // Reading a child in drop is only valid because of the sweep drop ordering.
+            let value = unsafe { **child.into_gc_box().as_ref().value.get() };
+            self.seen.set(Some(value));
+        }
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Parent<'gc, 'own> {
+    type Gc<'to> = Parent<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.child.trace(marker)
+    }
+}
+
+#[test]
+fn same_cycle() {
+    dreck!(owner, arena);
+    let seen = Rc::new(Cell::new(None));
+
+    // Allocate the child after the parent so it is swept first.
+    let parent = arena.add(Parent {
+        child: None,
+        seen: seen.clone(),
+    });
+    let child = arena.add(42u64);
+    parent.borrow_mut(&mut owner, &arena).child = Some(unsafe { child.rebind() });
+
+    arena.collect_full(&owner);
+    assert_eq!(seen.get(), Some(42));
+}
+
+#[test]
+fn different_cycle() {
+    dreck!(owner, arena);
+    let seen = Rc::new(Cell::new(None));
+
+    let child = arena.add(42u64);
+    let parent = arena.add(Parent {
+        child: None,
+        seen: seen.clone(),
+    });
+    parent.borrow_mut(&mut owner, &arena).child = Some(unsafe { child.rebind() });
+
+    {
+        let guard = pin!(RootGuard::new());
+        let child = root!(&arena, guard, child);
+
+        arena.collect_full(&owner);
+        assert_eq!(seen.get(), Some(42));
+        assert_eq!(*child.borrow(&owner), 42);
+    }
+
+    arena.collect_full(&owner);
+}
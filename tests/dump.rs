@@ -0,0 +1,106 @@
+use dreck::{testing::dump_structure, *};
+
+pub struct Container<'gc, 'own>(Option<Gc<'gc, 'own, Container<'gc, 'own>>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+pub struct Node<'gc, 'own> {
+    name: Gc<'gc, 'own, String>,
+    children: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.name.trace(marker);
+        self.children.trace(marker);
+    }
+}
+
+#[test]
+fn container_cycle() {
+    dreck!(owner, arena);
+
+    let a = arena.add(Container(None));
+    let b = arena.add(Container(Some(a)));
+    a.borrow_mut(&mut owner, &arena).0 = Some(unsafe { b.rebind() });
+
+    assert_eq!(
+        dump_structure(a, &owner),
+        "#0 dump::Container<'_, '_>\n  #1 dump::Container<'_, '_>\n    *0\n"
+    );
+}
+
+#[test]
+fn diamond() {
+    const EXPECTED: &str = "\
+#0 dump::Node<'_, '_>
+  #1 alloc::string::String = \"top\"
+  #2 dump::Node<'_, '_>
+    #3 alloc::string::String = \"left\"
+    #4 dump::Node<'_, '_>
+      #5 alloc::string::String = \"bottom\"
+  #6 dump::Node<'_, '_>
+    #7 alloc::string::String = \"right\"
+    *4
+";
+
+    let first = {
+        dreck!(owner, arena);
+        let node = |name: &str, children| Node {
+            name: arena.add(name.to_string()),
+            children,
+        };
+
+        let bottom = arena.add(node("bottom", Vec::new()));
+        let left = arena.add(node("left", vec![bottom]));
+        let right = arena.add(node("right", vec![bottom]));
+        let top = arena.add(node("top", vec![left, right]));
+        dump_structure(top, &owner)
+    };
+
+    let second = {
+        dreck!(owner, arena);
+        let node = |name: &str, children| Node {
+            name: arena.add(name.to_string()),
+            children,
+        };
+
+        let right = arena.add(node("right", Vec::new()));
+        let left = arena.add(node("left", Vec::new()));
+        let bottom = arena.add(node("bottom", Vec::new()));
+        let top = arena.add(node("top", vec![left, right]));
+        left.borrow_mut(&mut owner, &arena)
+            .children
+            .push(unsafe { bottom.rebind() });
+        right
+            .borrow_mut(&mut owner, &arena)
+            .children
+            .push(unsafe { bottom.rebind() });
+        dump_structure(top, &owner)
+    };
+
+    assert_eq!(first, EXPECTED);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,137 @@
+use std::{cell::Cell, pin::pin, ptr::NonNull, rc::Rc};
+
+use dreck::{
+    sys::{
+        embed::{BrandedArena, RootList, TraceFn},
+        GcBox, UnsafeArena, UnsafeRootGuard,
+    },
+    *,
+};
+
+/// A value which counts how often values of it are dropped.
+pub struct Counted(Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn root_list_keeps_alive() {
+    let dropped = Rc::new(Cell::new(0));
+    let mut arena = unsafe { UnsafeArena::new() };
+    let roots = RootList::new();
+
+    BrandedArena::with(&mut arena, |_owner, arena| unsafe {
+        let guard = pin!(UnsafeRootGuard::new());
+        roots.root(arena.unsafe_arena(), guard);
+
+        let ptr = arena.add(Counted(dropped.clone()));
+        roots.push(arena.unsafe_arena(), ptr.cast());
+        {
+            let _scope = roots.scope();
+            for _ in 0..3 {
+                let ptr = arena.add(Counted(dropped.clone()));
+                roots.push(arena.unsafe_arena(), ptr.cast());
+            }
+            assert_eq!(roots.len(), 4);
+
+            arena.unsafe_arena().collect_full();
+            assert_eq!(dropped.get(), 0);
+        }
+        assert_eq!(roots.len(), 1);
+
+        arena.unsafe_arena().collect_full();
+        assert_eq!(dropped.get(), 3);
+        assert_eq!(Rc::strong_count(&dropped), 2);
+    });
+
+    drop(arena);
+    assert_eq!(dropped.get(), 4);
+}
+
+#[test]
+fn push_while_tracing() {
+    let dropped = Rc::new(Cell::new(0));
+    let arena = unsafe { UnsafeArena::new() };
+    let roots = RootList::new();
+    let guard = pin!(UnsafeRootGuard::new());
+
+    unsafe {
+        roots.root(&arena, guard);
+        // Wake, then trace the root list.
+        for _ in 0..4 {
+            arena.step();
+        }
+
+        // The list was already traced, the pointer has to be marked when pushed.
+        let ptr = arena.add(Counted(dropped.clone()));
+        roots.push(&arena, ptr.cast());
+
+        arena.collect_full();
+        assert_eq!(dropped.get(), 0);
+    }
+}
+
+/// A pair of erased pointers traced by a function.
+type Pair = TraceFn<(NonNull<GcBox<()>>, Option<NonNull<GcBox<()>>>)>;
+
+#[test]
+fn trace_fn() {
+    let dropped = Rc::new(Cell::new(0));
+    let arena = unsafe { UnsafeArena::new() };
+    let guard = pin!(UnsafeRootGuard::new());
+
+    unsafe {
+        let a = arena.add(Counted(dropped.clone())).cast();
+        let b = arena.add(Counted(dropped.clone())).cast();
+        let pair: Pair = TraceFn::new((a, Some(b)), |(a, b), marker| {
+            marker.mark_erased(*a);
+            if let Some(b) = b {
+                marker.mark_erased(*b);
+            }
+        });
+        let pair = arena.add(pair);
+        arena.root(guard, pair);
+
+        arena.collect_full();
+        assert_eq!(dropped.get(), 0);
+
+        (*pair.as_ref().value.get()).get_mut().1 = None;
+        arena.collect_full();
+        assert_eq!(dropped.get(), 1);
+    }
+}
+
+#[test]
+fn trace_fn_erased() {
+    let dropped = Rc::new(Cell::new(0));
+    let arena = unsafe { UnsafeArena::new() };
+    let guard = pin!(UnsafeRootGuard::new());
+
+    unsafe {
+        let ptrs = (0..4)
+            .map(|_| arena.add(Counted(dropped.clone())).cast())
+            .collect::<Vec<_>>();
+        let ptrs = arena.add(TraceFn::erased(ptrs));
+        arena.root(guard, ptrs);
+
+        arena.collect_full();
+        assert_eq!(dropped.get(), 0);
+        assert_eq!((*ptrs.as_ref().value.get()).get().len(), 4);
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use dreck::{sys::GcUnavailable, *};
+
+thread_local! {
+    static FAIL_ALLOC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// An allocator which fails every allocation of the current thread while `FAIL_ALLOC` is set.
+struct FailingAlloc;
+
+unsafe impl GlobalAlloc for FailingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if FAIL_ALLOC.with(|x| x.get()) {
+            std::ptr::null_mut()
+        } else {
+            System.alloc(layout)
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: FailingAlloc = FailingAlloc;
+
+#[test]
+fn allocation_failed() {
+    dreck!(owner, arena);
+
+    FAIL_ALLOC.with(|x| x.set(true));
+    let result = arena.try_add(1u32).err().map(|e| e.error());
+    FAIL_ALLOC.with(|x| x.set(false));
+    assert_eq!(result, Some(Error::AllocationFailed));
+
+    let value = arena.try_add(2u32).unwrap();
+    assert_eq!(*value.borrow(&owner), 2);
+}
+
+#[test]
+fn arena_unavailable() {
+    dreck!(_owner, arena);
+
+    unsafe { arena.unsafe_arena().mark_all() };
+    assert_eq!(
+        arena.try_add(1u32).err().map(|e| e.error()),
+        Some(Error::ArenaUnavailable {
+            reason: GcUnavailable::Marked
+        })
+    );
+    unsafe { arena.unsafe_arena().finish_sweep() };
+    assert!(arena.try_add(1u32).is_ok());
+}
+
+#[test]
+fn stale_handle() {
+    dreck!(_owner, arena);
+    let mut registry = ScriptHandleRegistry::new(1);
+
+    let handle = registry.register(&arena, arena.add(1u32).into()).unwrap();
+    registry.release(handle).unwrap();
+    assert_eq!(
+        registry.resolve(handle, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Released))
+    );
+    assert_eq!(registry.release(0), Err(Error::from(StaleHandle::Unknown)));
+}
+
+#[test]
+fn handle_quota_exceeded() {
+    dreck!(_owner, arena);
+    let mut registry = ScriptHandleRegistry::new(1);
+
+    let value = arena.add(1u32);
+    registry.register(&arena, value.into()).unwrap();
+    assert_eq!(
+        registry.register(&arena, value.into()),
+        Err(Error::HandleQuotaExceeded { quota: 1 })
+    );
+}
+
+#[test]
+fn borrow_conflict() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(1u32));
+    let borrow = cell.borrow_mut_cell(&owner, &arena);
+    assert_eq!(
+        cell.borrow(&owner).try_borrow().err(),
+        Some(Error::BorrowConflict)
+    );
+    assert_eq!(
+        cell.try_borrow_mut_cell(&owner, &arena).err(),
+        Some(Error::BorrowConflict)
+    );
+    drop(borrow);
+    assert!(cell.try_borrow_mut_cell(&owner, &arena).is_ok());
+}
+
+#[cfg(feature = "arena-id")]
+#[test]
+fn wrong_arena() {
+    use std::{panic, pin::pin};
+
+    use dreck::sys::{UnsafeArena, UnsafeRootGuard};
+
+    let a = unsafe { UnsafeArena::new() };
+    let b = unsafe { UnsafeArena::new() };
+    let ptr = unsafe { a.add(1u32) };
+
+    let error = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let guard = pin!(UnsafeRootGuard::new());
+        unsafe { b.root(guard, ptr) };
+    }))
+    .unwrap_err();
+    let expected = Error::WrongArena {
+        object_arena: a.id().get(),
+        arena: b.id().get(),
+    };
+    assert_eq!(error.downcast_ref::<String>(), Some(&expected.to_string()));
+}
+
+#[test]
+fn panics_with_error_message() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(1u32));
+    let _borrow = cell.borrow_mut_cell(&owner, &arena);
+    let error = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        cell.borrow_mut_cell(&owner, &arena);
+    }))
+    .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<String>(),
+        Some(&Error::BorrowConflict.to_string())
+    );
+}
+
+#[test]
+fn is_std_error() {
+    fn resolve<'own>(
+        registry: &ScriptHandleRegistry<'own>,
+        arena: &Arena<'own>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        registry.resolve(1, arena)?;
+        Ok(())
+    }
+
+    dreck!(_owner, arena);
+    let registry = ScriptHandleRegistry::new(1);
+    let error = resolve(&registry, &arena).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "stale script handle, the handle is not known to the registry"
+    );
+}
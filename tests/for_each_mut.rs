@@ -0,0 +1,159 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+pub struct Leaf(Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Node<'gc, 'own> {
+    visited: usize,
+    a: Option<Gc<'gc, 'own, Leaf>>,
+    b: Option<Gc<'gc, 'own, Leaf>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.a.trace(marker);
+        self.b.trace(marker);
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for Node<'gc, 'own> {}
+
+#[test]
+fn mutate_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut nodes = Vec::new();
+    for _ in 0..16 {
+        let a = arena.add(Leaf(drops.clone()));
+        let b = arena.add(Leaf(drops.clone()));
+        nodes.push(arena.add(Node {
+            visited: 0,
+            a: Some(a),
+            b: None,
+        }));
+        nodes.push(arena.add(Node {
+            visited: 0,
+            a: None,
+            b: Some(b),
+        }));
+    }
+    let nodes = arena.add(nodes);
+    let guard = pin!(RootGuard::new());
+    let nodes = root!(&arena, guard, nodes);
+
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        for _ in 0..8 {
+            arena.unsafe_arena().step();
+        }
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+
+    let mut count = 0;
+    arena.for_each_mut::<Node, _>(&mut owner, |node| {
+        count += 1;
+        node.visited += 1;
+        std::mem::swap(&mut node.a, &mut node.b);
+    });
+    assert_eq!(count, 32);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+
+    for node in nodes.borrow(&owner).iter() {
+        let node = node.borrow(&owner);
+        assert_eq!(node.visited, 1);
+        assert!(node.a.is_some() || node.b.is_some());
+    }
+}
+
+#[test]
+fn only_visits_live_of_type() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    arena.add(Leaf(drops.clone()));
+    arena.add(3u32);
+    let guard = pin!(RootGuard::new());
+    let live = root!(
+        &arena,
+        guard,
+        arena.add(Node {
+            visited: 0,
+            a: None,
+            b: None,
+        })
+    );
+    // Unreachable, so it is not visited even though it hasn't been freed yet.
+    arena.add(Node {
+        visited: 0,
+        a: None,
+        b: None,
+    });
+
+    let mut count = 0;
+    arena.for_each_mut::<Node, _>(&mut owner, |node| {
+        count += 1;
+        node.visited += 1;
+    });
+    assert_eq!(count, 1);
+    assert_eq!(live.borrow(&owner).visited, 1);
+    // The walk completed a collection cycle, freeing the unreachable objects.
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn panic_finishes_sweep() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let _node = root!(
+        &arena,
+        guard,
+        arena.add(Node {
+            visited: 0,
+            a: None,
+            b: None,
+        })
+    );
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        arena.for_each_mut::<Node, _>(&mut owner, |_| panic!("visit"));
+    }));
+    assert!(res.is_err());
+    // The heap is no longer held marked, so allocating works again.
+    assert_ne!(arena.unsafe_arena().phase(), Phase::Sweep);
+    arena.add(1u32);
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "fuzz")]
+
+use dreck::testing::fuzz::{self, Op};
+
+/// A small deterministic generator so the corpus is the same on every run.
+fn xorshift(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn decode_roundtrip() {
+    let ops = [
+        Op::AllocLeaf(3),
+        Op::AllocContainer,
+        Op::Link { from: 1, to: 0 },
+        Op::Unlink { from: 1, slot: 7 },
+        Op::Root(1),
+        Op::Unroot(0),
+        Op::Step(9),
+        Op::Collect,
+        Op::CollectFull,
+        Op::ScopeEnter,
+        Op::ScopeExit,
+    ];
+    let bytes = fuzz::encode(&ops);
+    assert_eq!(fuzz::decode(&bytes), ops);
+    // Trailing incomplete operations are ignored.
+    assert_eq!(fuzz::decode(&bytes[..bytes.len() - 1]), &ops[..10]);
+    assert_eq!(fuzz::decode(&[2, 0]), []);
+}
+
+#[test]
+fn link_during_trace() {
+    fuzz::run_ops(&[
+        Op::AllocContainer,
+        Op::Root(0),
+        // A new arena starts out sweeping, finish that, start a cycle and trace the root.
+        Op::Step(3),
+        Op::AllocLeaf(1),
+        Op::Link { from: 0, to: 1 },
+        Op::Step(31),
+        Op::CollectFull,
+    ]);
+}
+
+#[test]
+fn root_during_trace() {
+    fuzz::run_ops(&[
+        Op::AllocContainer,
+        Op::Root(0),
+        Op::Step(2),
+        Op::AllocLeaf(1),
+        Op::Root(1),
+        Op::Step(31),
+        Op::Step(31),
+        Op::CollectFull,
+    ]);
+}
+
+#[test]
+fn full_collect_during_sweep() {
+    fuzz::run_ops(&[
+        Op::AllocContainer,
+        Op::AllocContainer,
+        Op::AllocLeaf(2),
+        Op::Link { from: 0, to: 1 },
+        Op::Link { from: 1, to: 2 },
+        Op::Root(0),
+        Op::ScopeEnter,
+        Op::AllocLeaf(3),
+        // Run into the sweep phase, then detach everything and collect fully.
+        Op::Step(5),
+        Op::Unlink { from: 0, slot: 0 },
+        Op::ScopeExit,
+        Op::CollectFull,
+    ]);
+}
+
+#[test]
+fn corpus() {
+    for seed in 0..256 {
+        fuzz::run(&xorshift(seed, 1024));
+    }
+}
@@ -0,0 +1,108 @@
+use std::pin::pin;
+
+use dreck::*;
+
+pub struct Pair<'gc, 'own> {
+    name: Gc<'gc, 'own, String>,
+    value: Gc<'gc, 'own, u32>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Pair<'gc, 'own> {
+    type Gc<'to> = Pair<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.name);
+        marker.mark(self.value);
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for Pair<'gc, 'own> {}
+
+/// A type with the same layout and trace behaviour as `u32`.
+pub struct Meters(u32);
+
+unsafe impl<'own> Trace<'own> for Meters {
+    type Gc<'gc> = Meters;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+unsafe impl<'own> Erasable<'own> for Meters {}
+
+#[test]
+fn downcast() {
+    dreck!(owner, arena);
+
+    let number = arena.add(3u32).erase();
+    assert_eq!(number.type_name(), "u32");
+    assert_eq!(*number.downcast::<u32>().unwrap().borrow(&owner), 3);
+    assert!(number.downcast::<u64>().is_none());
+    assert!(number.downcast::<String>().is_none());
+
+    let pair = arena.add(Pair {
+        name: arena.add(String::from("a")),
+        value: arena.add(1),
+    });
+    let erased = pair.erase();
+    assert!(erased.type_name().contains("Pair"));
+    assert!(erased.downcast::<u32>().is_none());
+    let pair = erased.downcast::<Pair>().unwrap();
+    assert_eq!(pair.borrow(&owner).name.borrow(&owner), "a");
+}
+
+#[test]
+fn downcast_same_layout() {
+    dreck!(owner, arena);
+
+    let number = arena.add(3u32).erase();
+    let meters = arena.add(Meters(4)).erase();
+    assert!(number.downcast::<Meters>().is_none());
+    assert!(meters.downcast::<u32>().is_none());
+    assert_eq!(meters.downcast::<Meters>().unwrap().borrow(&owner).0, 4);
+}
+
+#[test]
+fn traced_through_erased() {
+    dreck!(owner, arena);
+
+    let values = vec![
+        arena.add(String::from("one")).erase(),
+        arena.add(2u32).erase(),
+        arena
+            .add(Pair {
+                name: arena.add(String::from("three")),
+                value: arena.add(3),
+            })
+            .erase(),
+    ];
+    let guard = pin!(RootGuard::new());
+    let values = root!(&arena, guard, arena.add(values));
+
+    arena.collect_full(&owner);
+    arena.add(String::from("garbage"));
+    arena.collect_full(&owner);
+
+    let values = values.borrow(&owner);
+    assert_eq!(
+        values[0].downcast::<String>().unwrap().borrow(&owner),
+        "one"
+    );
+    assert_eq!(*values[1].downcast::<u32>().unwrap().borrow(&owner), 2);
+    let pair = values[2].downcast::<Pair>().unwrap().borrow(&owner);
+    assert_eq!(pair.name.borrow(&owner), "three");
+    assert_eq!(*pair.value.borrow(&owner), 3);
+}
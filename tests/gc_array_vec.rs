@@ -0,0 +1,128 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{containers::GcArrayVec, *};
+
+pub struct Leaf(Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Call<'gc, 'own> {
+    args: GcArrayVec<Gc<'gc, 'own, Leaf>, 4>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Call<'gc, 'own> {
+    type Gc<'to> = Call<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.args.trace(marker)
+    }
+}
+
+#[test]
+fn partially_filled() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let call = arena.add(Call {
+        args: GcArrayVec::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let call = root!(&arena, guard, call);
+
+    for _ in 0..2 {
+        let leaf = arena.add(Leaf(drops.clone()));
+        call.borrow_mut(&mut owner, &arena).args.push(leaf);
+    }
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(call.borrow(&owner).args.len(), 2);
+    assert_eq!(call.borrow(&owner).args.capacity(), 4);
+
+    for _ in 0..2 {
+        let leaf = arena.add(Leaf(drops.clone()));
+        call.borrow_mut(&mut owner, &arena).args.push(leaf);
+    }
+    assert!(call.borrow(&owner).args.is_full());
+    let leaf = arena.add(Leaf(drops.clone()));
+    assert!(call
+        .borrow_mut(&mut owner, &arena)
+        .args
+        .try_push(leaf)
+        .is_err());
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(call.borrow(&owner).args.len(), 4);
+}
+
+#[test]
+fn pop_releases() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let call = arena.add(Call {
+        args: GcArrayVec::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let call = root!(&arena, guard, call);
+
+    for _ in 0..3 {
+        let leaf = arena.add(Leaf(drops.clone()));
+        call.borrow_mut(&mut owner, &arena).args.push(leaf);
+    }
+    assert!(call.borrow_mut(&mut owner, &arena).args.pop().is_some());
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+
+    call.borrow_mut(&mut owner, &arena).args.clear();
+    assert!(call.borrow_mut(&mut owner, &arena).args.pop().is_none());
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn drops_initialized_elements() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut values = GcArrayVec::<Leaf, 8>::new();
+    for _ in 0..3 {
+        values.push(Leaf(drops.clone()));
+    }
+    arena.add(values);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+#[should_panic]
+fn push_full() {
+    let mut values = GcArrayVec::<u32, 2>::new();
+    for x in 0..3 {
+        values.push(x);
+    }
+}
@@ -0,0 +1,202 @@
+use std::{cell::Cell, cmp::Ordering, pin::pin, rc::Rc};
+
+use dreck::{containers::GcBinaryHeap, *};
+
+pub struct Task(u32, Rc<Cell<usize>>);
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Task {
+    type Gc<'gc> = Task;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Entry<'gc, 'own> {
+    priority: u32,
+    task: Gc<'gc, 'own, Task>,
+}
+
+impl PartialEq for Entry<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for Entry<'_, '_> {}
+
+impl PartialOrd for Entry<'_, '_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry<'_, '_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Entry<'gc, 'own> {
+    type Gc<'to> = Entry<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.task.trace(marker)
+    }
+}
+
+pub struct Scheduler<'gc, 'own> {
+    queue: GcBinaryHeap<'gc, 'own, Entry<'gc, 'own>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Scheduler<'gc, 'own> {
+    type Gc<'to> = Scheduler<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.queue.trace(marker)
+    }
+}
+
+#[test]
+fn order() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let scheduler = arena.add(Scheduler {
+        queue: GcBinaryHeap::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let scheduler = root!(&arena, guard, scheduler);
+
+    for priority in [3, 1, 4, 1, 5, 9, 2, 6] {
+        let task = arena.add(Task(priority, drops.clone()));
+        scheduler
+            .borrow_mut(&mut owner, &arena)
+            .queue
+            .push(&arena, Entry { priority, task });
+    }
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(scheduler.borrow(&owner).queue.peek().unwrap().priority, 9);
+
+    let mut popped = Vec::new();
+    while let Some(entry) = scheduler.borrow_mut(&mut owner, &arena).queue.pop() {
+        popped.push(entry.priority);
+    }
+    assert_eq!(popped, [9, 6, 5, 4, 3, 2, 1, 1]);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 8);
+}
+
+#[test]
+fn scheduler_workload() {
+    dreck!(owner, arena);
+    arena.set_verify(true);
+    let drops = Rc::new(Cell::new(0));
+
+    let scheduler = arena.add(Scheduler {
+        queue: GcBinaryHeap::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let scheduler = root!(&arena, guard, scheduler);
+
+    let mut created = 0;
+    let mut finished = 0;
+    for round in 0..2000u32 {
+        for x in 0..3 {
+            let priority = (round * 7 + x * 13) % 101;
+            let task = arena.add(Task(priority, drops.clone()));
+            created += 1;
+            scheduler
+                .borrow_mut(&mut owner, &arena)
+                .queue
+                .push(&arena, Entry { priority, task });
+        }
+        // Interleave a few units of collection work with the mutations.
+        unsafe {
+            for _ in 0..4 {
+                arena.unsafe_arena().step();
+            }
+        }
+        for _ in 0..2 {
+            let entry = scheduler.borrow_mut(&mut owner, &arena).queue.pop();
+            let Entry { priority, task } = entry.unwrap();
+            // The popped pointer is bound to the borrow of the owner, rooting rebinds it.
+            let guard = pin!(RootGuard::new());
+            let task = root!(&arena, guard, task);
+            assert_eq!(task.borrow(&owner).0, priority);
+            finished += 1;
+        }
+    }
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), finished);
+    assert_eq!(scheduler.borrow(&owner).queue.len(), created - finished);
+    for entry in scheduler.borrow(&owner).queue.iter() {
+        assert_eq!(entry.task.borrow(&owner).0, entry.priority);
+    }
+}
+
+#[test]
+fn root_popped() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let queue = arena.add(GcBinaryHeap::<Entry>::new());
+    let guard = pin!(RootGuard::new());
+    let queue = root!(&arena, guard, queue);
+    for priority in [2, 7, 1, 8] {
+        let task = arena.add(Task(priority, drops.clone()));
+        queue.push(&mut owner, &arena, Entry { priority, task });
+    }
+
+    let guard = pin!(RootGuard::new());
+    let greatest = queue.pop(&mut owner, &arena).unwrap().task;
+    let greatest = root!(&arena, guard, greatest);
+    assert_eq!(queue.pop(&mut owner, &arena).unwrap().priority, 7);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(greatest.borrow(&owner).0, 8);
+    assert_eq!(queue.borrow(&owner).peek().unwrap().priority, 2);
+}
+
+#[test]
+#[should_panic(expected = "the type doesn't support snapshots")]
+fn no_snapshot() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let queue = arena.add(GcBinaryHeap::<Entry>::new());
+    let guard = pin!(RootGuard::new());
+    let queue = root!(&arena, guard, queue);
+    let task = arena.add(Task(1, drops.clone()));
+    queue.push(&mut owner, &arena, Entry { priority: 1, task });
+
+    let spec = arena.begin_speculation();
+    queue.pop(&mut owner, &spec);
+}
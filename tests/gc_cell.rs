@@ -0,0 +1,73 @@
+use std::pin::pin;
+
+use dreck::*;
+
+pub struct Object<'gc, 'own> {
+    pub name: Gc<'gc, 'own, String>,
+    pub hits: GcCell<u32>,
+    pub hash: GcCell<Option<u64>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Object<'gc, 'own> {
+    type Gc<'to> = Object<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.name.trace(marker);
+        self.hits.trace(marker);
+        self.hash.trace(marker);
+    }
+}
+
+#[test]
+fn field() {
+    dreck!(owner, arena);
+
+    let name = arena.add("object".to_string());
+    let object = arena.add(Object {
+        name,
+        hits: GcCell::new(0),
+        hash: GcCell::default(),
+    });
+    let guard = pin!(RootGuard::new());
+    let object = root!(&arena, guard, object);
+
+    let borrow = object.borrow(&owner);
+    // The name stays borrowed through the owner while the cells are mutated.
+    let name = borrow.name.borrow(&owner);
+    for _ in 0..3 {
+        borrow.hits.set(borrow.hits.get() + 1);
+    }
+    assert_eq!(borrow.hash.replace(Some(name.len() as u64)), None);
+    assert_eq!(name, "object");
+
+    arena.collect_full(&owner);
+    let borrow = object.borrow(&owner);
+    assert_eq!(borrow.hits.get(), 3);
+    assert_eq!(borrow.hash.get(), Some(6));
+    assert_eq!(borrow.name.borrow(&owner), "object");
+}
+
+#[test]
+fn object() {
+    dreck!(owner, arena);
+
+    let counter = arena.add(GcCell::new(1u64));
+    let guard = pin!(RootGuard::new());
+    let counter = root!(&arena, guard, counter);
+
+    let a = counter.borrow(&owner);
+    let b = counter.borrow(&owner);
+    a.set(b.get() + 1);
+    assert_eq!(b.get(), 2);
+
+    arena.collect_full(&owner);
+    assert_eq!(counter.borrow(&owner).get(), 2);
+    assert_eq!(format!("{:?}", counter.borrow(&owner)), "GcCell(2)");
+}
@@ -0,0 +1,117 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+gc_trait! {
+    /// A function implemented by the host.
+    pub unsafe trait NativeFunction<'gc, 'own> {
+        fn call(&self, owner: &Owner<'own>, arg: u32) -> String;
+    }
+}
+
+pub struct Greeter<'gc, 'own> {
+    greeting: Gc<'gc, 'own, String>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Greeter<'gc, 'own> {
+    type Gc<'to> = Greeter<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.greeting);
+    }
+}
+
+unsafe impl<'gc, 'own> NativeFunction<'gc, 'own> for Greeter<'gc, 'own> {
+    fn call(&self, owner: &Owner<'own>, arg: u32) -> String {
+        format!("{} {}", self.greeting.borrow(owner), arg)
+    }
+}
+
+pub struct Doubler {
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Doubler {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Doubler {
+    type Gc<'to> = Doubler;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+unsafe impl<'gc, 'own> NativeFunction<'gc, 'own> for Doubler {
+    fn call(&self, _owner: &Owner<'own>, arg: u32) -> String {
+        (arg * 2).to_string()
+    }
+}
+
+#[test]
+fn vec_of_trait_objects() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let greeter = arena.add(Greeter {
+        greeting: arena.add(String::from("hello")),
+    });
+    let doubler = arena.add(Doubler {
+        drops: drops.clone(),
+    });
+    let functions: Vec<Gc<dyn NativeFunction>> = vec![
+        gc_coerce!(greeter => dyn NativeFunction),
+        gc_coerce!(doubler => dyn NativeFunction),
+    ];
+    assert!(functions[1].ptr_eq(gc_coerce!(doubler => dyn NativeFunction)));
+
+    let guard = pin!(RootGuard::new());
+    let functions = root!(&arena, guard, arena.add(functions));
+
+    arena.add(String::from("garbage"));
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    let results: Vec<_> = functions
+        .borrow(&owner)
+        .iter()
+        .map(|x| x.borrow(&owner).call(&owner, 4))
+        .collect();
+    assert_eq!(results, ["hello 4", "8"]);
+    assert_eq!(drops.get(), 0);
+
+    functions.borrow_mut(&mut owner, &arena).clear();
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn rooted_trait_object() {
+    dreck!(owner, arena);
+
+    let greeter = arena.add(Greeter {
+        greeting: arena.add(String::from("hi")),
+    });
+    let function = gc_coerce!(greeter => dyn NativeFunction);
+    let guard = pin!(RootGuard::new());
+    let function = root!(&arena, guard, arena.add(Some(function)));
+
+    arena.collect_full(&owner);
+    let function = function.borrow(&owner).unwrap();
+    assert_eq!(function.borrow(&owner).call(&owner, 1), "hi 1");
+}
@@ -0,0 +1,168 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Counted {
+    value: u32,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+gc_trait! {
+    /// A method which can be called on an object.
+    pub unsafe trait Method<'gc, 'own> {
+        fn call(&self, owner: &Owner<'own>) -> u32;
+    }
+}
+
+pub struct Constant<'gc, 'own>(Gc<'gc, 'own, Counted>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Constant<'gc, 'own> {
+    type Gc<'to> = Constant<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> Method<'gc, 'own> for Constant<'gc, 'own> {
+    fn call(&self, owner: &Owner<'own>) -> u32 {
+        self.0.borrow(owner).value
+    }
+}
+
+pub struct Sum<'gc, 'own>(Vec<Gc<'gc, 'own, Counted>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Sum<'gc, 'own> {
+    type Gc<'to> = Sum<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> Method<'gc, 'own> for Sum<'gc, 'own> {
+    fn call(&self, owner: &Owner<'own>) -> u32 {
+        self.0.iter().map(|x| x.borrow(owner).value).sum()
+    }
+}
+
+pub struct Object<'gc, 'own> {
+    methods: Vec<Box<dyn Method<'gc, 'own> + 'gc>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Object<'gc, 'own> {
+    type Gc<'to> = Object<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.methods.trace(marker)
+    }
+}
+
+#[test]
+fn trace_through_trait_object() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let counted = |value| {
+        arena.add(Counted {
+            value,
+            drops: drops.clone(),
+        })
+    };
+
+    let object = Object {
+        methods: vec![
+            Box::new(Constant(counted(1))),
+            Box::new(Sum(vec![counted(2), counted(3)])),
+        ],
+    };
+    let object = arena.add(object);
+
+    let guard = pin!(RootGuard::new());
+    let object = root!(&arena, guard, object);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+
+    let res: Vec<u32> = object
+        .borrow(&owner)
+        .methods
+        .iter()
+        .map(|x| x.call(&owner))
+        .collect();
+    assert_eq!(res, [1, 5]);
+
+    object.borrow_mut(&mut owner, &arena).methods.pop();
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 2);
+    assert_eq!(object.borrow(&owner).methods[0].call(&owner), 1);
+}
+
+#[test]
+fn store_added_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let object = arena.add(Object {
+        methods: Vec::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let object = root!(&arena, guard, object);
+
+    // Step into the trace phase so the object is already traced when the method is added.
+    for _ in 0..4 {
+        unsafe { arena.unsafe_arena().step() };
+    }
+
+    let value = arena.add(Counted {
+        value: 7,
+        drops: drops.clone(),
+    });
+    let method: Box<dyn Method> = Box::new(Constant(value));
+    let method = unsafe { method.rebind() };
+    object.borrow_mut(&mut owner, &arena).methods.push(method);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(object.borrow(&owner).methods[0].call(&owner), 7);
+}
@@ -0,0 +1,149 @@
+use std::{cell::Cell, mem::size_of, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Counted(Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+gc_enum! {
+    /// A value of a small dynamic language.
+    pub enum Value<'gc, 'own> {
+        Str(String),
+        Int(i64),
+        Counted(Counted),
+        List(Vec<PackedValue<'gc, 'own>>),
+    }
+
+    /// A packed value.
+    pub struct PackedValue;
+}
+
+gc_enum! {
+    enum Wide<'gc, 'own> {
+        A(u8),
+        B(u16),
+        C(u32),
+        D(u64),
+        E(i8),
+        F(i16),
+        G(i32),
+        H(i64),
+    }
+
+    struct PackedWide;
+}
+
+#[test]
+fn packed_is_a_single_word() {
+    assert_eq!(size_of::<PackedValue>(), size_of::<usize>());
+    assert_eq!(size_of::<Option<PackedValue>>(), size_of::<usize>());
+    assert_eq!(size_of::<PackedWide>(), size_of::<usize>());
+}
+
+#[test]
+fn round_trip() {
+    dreck!(owner, arena);
+
+    let str = arena.add(String::from("hello"));
+    let int = arena.add(42i64);
+    for value in [Value::Str(str), Value::Int(int)] {
+        let packed = PackedValue::pack(value);
+        assert!(packed.unpack() == value);
+        assert!(Value::from(packed) == value);
+    }
+
+    let packed = PackedValue::from(int);
+    let Ok(back) = Gc::<i64>::try_from(packed) else {
+        panic!("expected an integer");
+    };
+    assert_eq!(*back.borrow(&owner), 42);
+    assert!(Gc::<String>::try_from(packed).err() == Some(packed));
+    assert!(packed != PackedValue::from(str));
+}
+
+#[test]
+fn every_tag_round_trips() {
+    dreck!(owner, arena);
+
+    let values = [
+        Wide::A(arena.add(1)),
+        Wide::B(arena.add(2)),
+        Wide::C(arena.add(3)),
+        Wide::D(arena.add(4)),
+        Wide::E(arena.add(5)),
+        Wide::F(arena.add(6)),
+        Wide::G(arena.add(7)),
+        Wide::H(arena.add(8)),
+    ];
+    for (i, value) in values.into_iter().enumerate() {
+        let number = match PackedWide::from(value).unpack() {
+            Wide::A(x) => *x.borrow(&owner) as i64,
+            Wide::B(x) => *x.borrow(&owner) as i64,
+            Wide::C(x) => *x.borrow(&owner) as i64,
+            Wide::D(x) => *x.borrow(&owner) as i64,
+            Wide::E(x) => *x.borrow(&owner) as i64,
+            Wide::F(x) => *x.borrow(&owner) as i64,
+            Wide::G(x) => *x.borrow(&owner) as i64,
+            Wide::H(x) => *x.borrow(&owner),
+        };
+        assert_eq!(number, i as i64 + 1);
+    }
+}
+
+#[test]
+fn traces_active_variant() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let counted = PackedValue::from(arena.add(Counted(drops.clone())));
+    let str = PackedValue::from(arena.add(String::from("a")));
+    let list = root!(&arena, guard, arena.add(vec![counted, str]));
+    arena.add(Counted(drops.clone()));
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+
+    let items = list.borrow(&owner);
+    assert!(matches!(items[0].unpack(), Value::Counted(_)));
+    let Value::Str(str) = items[1].unpack() else {
+        panic!("expected a string");
+    };
+    assert_eq!(str.borrow(&owner), "a");
+
+    list.borrow_mut(&mut owner, &arena).clear();
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn rooted_enum() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let counted = arena.add(Counted(drops.clone()));
+    let value = root!(&arena, guard, arena.add(Value::from(counted)));
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert!(matches!(*value.borrow(&owner), Value::Counted(_)));
+}
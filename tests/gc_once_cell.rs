@@ -0,0 +1,153 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+mod common;
+use common::trace_roots;
+
+pub struct Leaf(pub Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Holder<'gc, 'own> {
+    pub cache: GcOnceCell<Gc<'gc, 'own, Leaf>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Holder<'gc, 'own> {
+    type Gc<'to> = Holder<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.cache.trace(marker)
+    }
+}
+
+fn finish_cycle(arena: &Arena) {
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+}
+
+#[test]
+fn set_after_traced() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let holder = root!(
+        &arena,
+        guard,
+        arena.add(Holder {
+            cache: GcOnceCell::new(),
+        })
+    );
+    arena.collect_full(&owner);
+
+    // The holder is traced while the cell is still empty.
+    trace_roots(&arena);
+    let leaf = arena.add(Leaf(drops.clone()));
+    assert!(holder
+        .borrow(&owner)
+        .cache
+        .set(&owner, &arena, leaf)
+        .is_ok());
+    finish_cycle(&arena);
+    assert_eq!(drops.get(), 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert!(holder.borrow(&owner).cache.get(&owner).is_some());
+}
+
+#[test]
+fn get_or_init_after_traced() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let holder = root!(
+        &arena,
+        guard,
+        arena.add(Holder {
+            cache: GcOnceCell::new(),
+        })
+    );
+    arena.collect_full(&owner);
+
+    trace_roots(&arena);
+    let cache = &holder.borrow(&owner).cache;
+    let mut calls = 0;
+    let first = *cache.get_or_init(&owner, &arena, || {
+        calls += 1;
+        arena.add(Leaf(drops.clone()))
+    });
+    let second = *cache.get_or_init(&owner, &arena, || {
+        calls += 1;
+        arena.add(Leaf(drops.clone()))
+    });
+    assert_eq!(calls, 1);
+    assert!(first.ptr_eq(second));
+    finish_cycle(&arena);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+}
+
+#[test]
+fn set_once() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let holder = arena.add(Holder {
+        cache: GcOnceCell::new(),
+    });
+    let cache = &holder.borrow(&owner).cache;
+    assert!(cache.get(&owner).is_none());
+
+    let a = arena.add(Leaf(drops.clone()));
+    let b = arena.add(Leaf(drops.clone()));
+    assert!(cache.set(&owner, &arena, a).is_ok());
+    let rejected = cache.set(&owner, &arena, b).unwrap_err();
+    assert!(rejected.ptr_eq(b));
+    assert!(cache.get(&owner).unwrap().ptr_eq(a));
+}
+
+#[test]
+#[should_panic(expected = "`GcOnceCell` was initialized by its own initializer")]
+fn reentrant_init() {
+    dreck!(owner, arena);
+
+    let holder = arena.add(Holder {
+        cache: GcOnceCell::new(),
+    });
+    let cache = &holder.borrow(&owner).cache;
+    cache.get_or_init(&owner, &arena, || {
+        let leaf = arena.add(Leaf(Rc::new(Cell::new(0))));
+        assert!(cache.set(&owner, &arena, leaf).is_ok());
+        arena.add(Leaf(Rc::new(Cell::new(0))))
+    });
+}
@@ -0,0 +1,207 @@
+use std::{
+    cell::Cell,
+    hash::{Hash, Hasher},
+    pin::pin,
+    rc::Rc,
+};
+
+use dreck::{containers::GcPersistentMap, *};
+
+pub struct Tracked(pub u32, pub Rc<Cell<usize>>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Tracked {
+    type Gc<'gc> = Tracked;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+/// A key whose hashes all collide.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Collide(pub u32);
+
+impl Hash for Collide {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        0u32.hash(state)
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Collide {
+    type Gc<'gc> = Collide;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+type Map<'gc, 'own> = GcPersistentMap<'gc, 'own, u32, Gc<'gc, 'own, Tracked>>;
+
+fn object_count(arena: &Arena) -> usize {
+    let mut count = 0;
+    unsafe { arena.unsafe_arena().for_each_object(|_| count += 1) };
+    count
+}
+
+#[test]
+fn versions() {
+    dreck!(owner, arena);
+
+    let mut versions = vec![GcPersistentMap::new()];
+    for x in 0..1000u32 {
+        let map = versions.last().unwrap().insert(&arena, x, x as u64);
+        versions.push(map);
+    }
+
+    for (len, map) in versions.iter().enumerate() {
+        assert_eq!(map.len(), len);
+        assert_eq!(map.get(&owner, &(len as u32)), None);
+    }
+    let map = versions[1000];
+    for x in 0..1000u32 {
+        assert_eq!(map.get(&owner, &x), Some(&(x as u64)));
+    }
+    let mut entries = map.iter(&owner).map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(
+        entries,
+        (0..1000).map(|x| (x, x as u64)).collect::<Vec<_>>()
+    );
+
+    let replaced = map.insert(&arena, 10, 0);
+    assert_eq!(replaced.len(), 1000);
+    assert_eq!(replaced.get(&owner, &10), Some(&0));
+    assert_eq!(map.get(&owner, &10), Some(&10));
+
+    assert!(map.remove(&arena, &1000).is_none());
+    let mut removed = map;
+    for x in 0..1000u32 {
+        removed = removed.remove(&arena, &x).unwrap();
+        assert_eq!(removed.len(), 999 - x as usize);
+        assert!(!removed.contains_key(&owner, &x));
+        assert_eq!(removed.contains_key(&owner, &999), x != 999);
+        assert_eq!(map.get(&owner, &x), Some(&(x as u64)));
+    }
+    assert!(removed.is_empty());
+    assert_eq!(removed.node_count(&owner), 0);
+}
+
+#[test]
+fn collisions() {
+    dreck!(owner, arena);
+
+    let mut map = GcPersistentMap::new();
+    for x in 0..50 {
+        map = map.insert(&arena, Collide(x), x);
+    }
+    map = map.insert(&arena, Collide(7), 100);
+    assert_eq!(map.len(), 50);
+    for x in 0..50 {
+        let value = if x == 7 { 100 } else { x };
+        assert_eq!(map.get(&owner, &Collide(x)), Some(&value));
+    }
+    assert_eq!(map.get(&owner, &Collide(50)), None);
+
+    for x in 0..50 {
+        map = map.remove(&arena, &Collide(x)).unwrap();
+        assert_eq!(map.get(&owner, &Collide(x)), None);
+        assert_eq!(map.get(&owner, &Collide(49)).is_some(), x != 49);
+    }
+    assert!(map.is_empty());
+}
+
+#[test]
+fn shared_nodes_retained() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let versions = arena.add(Vec::<Map>::new());
+    let guard = pin!(RootGuard::new());
+    let versions = root!(&arena, guard, versions);
+
+    let values = (0..1000u32)
+        .map(|x| arena.add(Tracked(x, drops.clone())))
+        .collect::<Vec<_>>();
+    let mut map = GcPersistentMap::new();
+    for (x, value) in values.into_iter().enumerate() {
+        map = map.insert(&arena, x as u32, value);
+    }
+    for x in 0..500u32 {
+        map = map.remove(&arena, &x).unwrap();
+    }
+    versions
+        .borrow_mut(&mut owner, &arena)
+        .push(unsafe { map.rebind() });
+
+    // Only the nodes of the latest version and its values survive.
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 500);
+    let nodes = versions.borrow(&owner)[0].node_count(&owner);
+    assert_eq!(object_count(&arena), 1 + nodes + 500);
+    for x in 500..1000u32 {
+        let value = versions.borrow(&owner)[0].get(&owner, &x).unwrap();
+        assert_eq!(value.borrow(&owner).0, x);
+    }
+
+    // A new version shares all nodes not on the path to the new key.
+    let value = arena.add(Tracked(0, drops.clone()));
+    let versions_mut = versions.borrow_mut(&mut owner, &arena);
+    let map = versions_mut[0].insert(&arena, 0, value);
+    versions_mut.push(map);
+
+    arena.collect_full(&owner);
+    let versions = versions.borrow(&owner);
+    let (a, b) = (
+        versions[0].node_count(&owner),
+        versions[1].node_count(&owner),
+    );
+    let nodes = object_count(&arena) - 1 - 501;
+    assert!(nodes > a.max(b));
+    assert!(nodes < a + b);
+    assert_eq!(drops.get(), 500);
+}
+
+#[test]
+fn update_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let versions = arena.add(vec![Map::new()]);
+    let guard = pin!(RootGuard::new());
+    let versions = root!(&arena, guard, versions);
+
+    for x in 0..2000u32 {
+        let value = arena.add(Tracked(x, drops.clone()));
+        let map = &mut versions.borrow_mut(&mut owner, &arena)[0];
+        *map = map.insert(&arena, x, value);
+        if x % 2 == 1 {
+            *map = map.remove(&arena, &(x - 1)).unwrap();
+        }
+        unsafe { arena.unsafe_arena().step() };
+    }
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1000);
+    let map = versions.borrow(&owner)[0];
+    assert_eq!(map.len(), 1000);
+    for x in 0..2000u32 {
+        let value = map.get(&owner, &x).map(|x| x.borrow(&owner).0);
+        assert_eq!(value, (x % 2 == 1).then_some(x));
+    }
+}
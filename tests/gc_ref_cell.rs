@@ -0,0 +1,72 @@
+use std::pin::pin;
+
+use dreck::*;
+
+mod common;
+use common::trace_roots;
+
+#[test]
+fn nested_borrows() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(vec![arena.add(1u32)]));
+
+    let a = cell.borrow(&owner).borrow();
+    let b = cell.borrow(&owner).borrow();
+    assert_eq!(*a[0].borrow(&owner), *b[0].borrow(&owner));
+    assert!(cell.try_borrow_mut_cell(&owner, &arena).is_err());
+    drop((a, b));
+
+    let mut borrow = cell.borrow_mut_cell(&owner, &arena);
+    // The owner is only borrowed immutably, so other objects can still be read.
+    let value = unsafe { borrow[0].rebind() };
+    borrow.push(value);
+    assert!(cell.borrow(&owner).try_borrow().is_err());
+    assert!(cell.try_borrow_mut_cell(&owner, &arena).is_err());
+    drop(borrow);
+
+    assert_eq!(cell.borrow(&owner).borrow().len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "`GcRefCell` is already borrowed")]
+fn conflict() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(1u32));
+    let _a = cell.borrow_mut_cell(&owner, &arena);
+    let _b = cell.borrow_mut_cell(&owner, &arena);
+}
+
+#[test]
+fn mutate_during_trace() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(Vec::<Gc<u32>>::new()));
+    let guard = pin!(RootGuard::new());
+    let cell = root!(&arena, guard, cell);
+    trace_roots(&arena);
+
+    for x in 0..4u32 {
+        let value = arena.add(x);
+        cell.borrow_mut_cell(&owner, &arena).push(value);
+    }
+
+    arena.collect_full(&owner);
+    let values = cell.borrow(&owner).borrow();
+    let values = values.iter().map(|x| *x.borrow(&owner));
+    assert_eq!(values.collect::<Vec<_>>(), [0, 1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "traced a `GcRefCell` which is mutably borrowed")]
+fn trace_while_borrowed() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(vec![arena.add(1u32)]));
+    let guard = pin!(RootGuard::new());
+    let cell = root!(&arena, guard, cell);
+
+    let _borrow = cell.borrow_mut_cell(&owner, &arena);
+    unsafe { arena.unsafe_arena().collect_full() };
+}
@@ -0,0 +1,163 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+pub struct Leaf(pub u32, pub Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Container<'gc, 'own>(pub Vec<Gc<'gc, 'own, Leaf>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Container<'gc, 'own> {
+    type Gc<'to> = Container<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+fn values<'own>(container: &Container<'_, 'own>, owner: &Owner<'own>) -> Vec<u32> {
+    container.0.iter().map(|x| x.borrow(owner).0).collect()
+}
+
+#[test]
+fn set() {
+    dreck!(owner, arena);
+
+    let value = arena.add(1u32);
+    value.set(&mut owner, &arena, 2);
+    assert_eq!(*value.borrow(&owner), 2);
+    assert_eq!(value.replace(&mut owner, &arena, 3), 2);
+    assert_eq!(*value.borrow(&owner), 3);
+}
+
+#[test]
+fn replace_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let leaves = (0..4).map(|x| arena.add(Leaf(x, drops.clone()))).collect();
+    let container = arena.add(Container(leaves));
+    let guard = pin!(RootGuard::new());
+    let container = root!(&arena, guard, container);
+
+    // Trace the container, leaving the collector in the trace phase.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+
+    let leaves = (4..8).map(|x| arena.add(Leaf(x, drops.clone()))).collect();
+    let old = container.replace(&mut owner, &arena, Container(leaves));
+    let old_guard = pin!(RootGuard::new());
+    let old = root!(&arena, old_guard, arena.add(old));
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(values(container.borrow(&owner), &owner), [4, 5, 6, 7]);
+    assert_eq!(values(old.borrow(&owner), &owner), [0, 1, 2, 3]);
+}
+
+#[test]
+fn set_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let container = arena.add(Container(Vec::new()));
+    let guard = pin!(RootGuard::new());
+    let container = root!(&arena, guard, container);
+
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+
+    let leaf = arena.add(Leaf(1, drops.clone()));
+    container.set(&mut owner, &arena, Container(vec![leaf]));
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(container.borrow(&owner).0[0].borrow(&owner).0, 1);
+}
+
+#[test]
+fn swap() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(2u32);
+    Gc::swap(a, b, &mut owner, &arena);
+    assert_eq!(*a.borrow(&owner), 2);
+    assert_eq!(*b.borrow(&owner), 1);
+}
+
+#[test]
+#[should_panic(expected = "two pointers to the same object")]
+fn swap_alias() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    Gc::swap(a, a, &mut owner, &arena);
+}
+
+#[test]
+fn swap_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let leaves = (0..4).map(|x| arena.add(Leaf(x, drops.clone()))).collect();
+    let a = arena.add(Container(leaves));
+    let a_guard = pin!(RootGuard::new());
+    let a = root!(&arena, a_guard, a);
+
+    // Trace the first container, leaving the collector in the trace phase.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+
+    let leaves = (4..8).map(|x| arena.add(Leaf(x, drops.clone()))).collect();
+    let b = arena.add(Container(leaves));
+    let b_guard = pin!(RootGuard::new());
+    let b = root!(&arena, b_guard, b);
+
+    Gc::swap(a, b, &mut owner, &arena);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(values(a.borrow(&owner), &owner), [4, 5, 6, 7]);
+    assert_eq!(values(b.borrow(&owner), &owner), [0, 1, 2, 3]);
+}
@@ -0,0 +1,153 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Counted(Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+/// A type with a larger alignment than the object header.
+#[derive(Clone, Copy)]
+#[repr(align(32))]
+pub struct Aligned(u64);
+
+unsafe impl<'own> Trace<'own> for Aligned {
+    type Gc<'to> = Aligned;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn copy() {
+    dreck!(owner, arena);
+
+    let slice = arena.add_slice_copy(&[1u32, 2, 3]);
+    assert_eq!(slice.borrow(&owner), &[1, 2, 3]);
+    let empty = arena.add_slice_copy::<u64>(&[]);
+    assert!(empty.borrow(&owner).is_empty());
+
+    let wide = arena.add_slice_copy(&[Aligned(u64::MAX), Aligned(7)]);
+    assert_eq!(wide.borrow(&owner)[0].0, u64::MAX);
+    assert_eq!(wide.borrow(&owner)[1].0, 7);
+    assert_eq!(
+        wide.borrow(&owner).as_ptr() as usize % align_of::<Aligned>(),
+        0
+    );
+}
+
+#[test]
+fn traced_items() {
+    dreck!(owner, arena);
+
+    let slice = arena.add_from_iter((0..100).map(|x| arena.add(x)));
+    let guard = pin!(RootGuard::new());
+    let slice = root!(&arena, guard, slice);
+
+    arena.add(5i32);
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    let values: Vec<i32> = slice
+        .borrow(&owner)
+        .iter()
+        .map(|x| *x.borrow(&owner))
+        .collect();
+    assert_eq!(values, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn nested_in_object() {
+    dreck!(owner, arena);
+
+    let slices = vec![
+        arena.add_slice_copy(&[1u8, 2]),
+        arena.add_from_iter([arena.add(3u8)].map(|x| *x.borrow(&owner))),
+    ];
+    let guard = pin!(RootGuard::new());
+    let slices = root!(&arena, guard, arena.add(slices));
+
+    arena.collect_full(&owner);
+    assert_eq!(slices.borrow(&owner)[0].borrow(&owner), &[1, 2]);
+    assert_eq!(slices.borrow(&owner)[1].borrow(&owner), &[3]);
+}
+
+#[test]
+fn drops_items() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    arena.add_from_iter((0..10).map(|_| Counted(drops.clone())));
+    let before = arena.unsafe_arena().stats().total_allocated;
+    assert!(before > 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 10);
+    assert_eq!(arena.unsafe_arena().stats().total_allocated, 0);
+}
+
+/// An iterator which reports more items than it returns.
+struct Lying(usize);
+
+impl Iterator for Lying {
+    type Item = Counted;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0, Some(self.0))
+    }
+}
+
+impl ExactSizeIterator for Lying {}
+
+#[test]
+#[should_panic(expected = "fewer items than its length")]
+fn short_iterator() {
+    dreck!(_owner, arena);
+    arena.add_from_iter(Lying(3));
+}
+
+#[test]
+fn panicking_iterator() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        arena.add_from_iter((0..4).map(|x| {
+            if x == 2 {
+                panic!("iterator panicked");
+            }
+            Counted(drops.clone())
+        }));
+    }));
+    assert!(res.is_err());
+    assert_eq!(drops.get(), 2);
+    arena.collect_full(&owner);
+    assert_eq!(arena.unsafe_arena().stats().total_allocated, 0);
+}
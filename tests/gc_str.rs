@@ -0,0 +1,76 @@
+use std::{collections::HashMap, pin::pin};
+
+use dreck::*;
+
+#[test]
+fn borrow() {
+    dreck!(owner, arena);
+
+    let hello = arena.add_str("hello");
+    assert_eq!(hello.borrow(&owner), "hello");
+    assert_eq!(hello.len(), 5);
+    assert!(!hello.is_empty());
+
+    let empty = arena.add_str("");
+    assert_eq!(empty.borrow(&owner), "");
+    assert!(empty.is_empty());
+
+    let unicode = arena.add_str("gårbage → collected");
+    assert_eq!(unicode.borrow(&owner), "gårbage → collected");
+    assert_eq!(unicode.len(), "gårbage → collected".len());
+
+    assert_eq!(hello.erase().fmt_leaf(&owner).as_deref(), Some("\"hello\""));
+    assert_eq!(hello.erase().type_name(), "str");
+}
+
+#[test]
+fn collect_rooted() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(Vec::<Gc<str>>::new()));
+
+    for i in 0..1000 {
+        let str = arena.add_str(&format!("string number {i}"));
+        if i % 3 == 0 {
+            kept.borrow_mut(&mut owner, &arena).push(str);
+        }
+    }
+
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    let kept = kept.borrow(&owner);
+    assert_eq!(kept.len(), 334);
+    for (i, str) in kept.iter().enumerate() {
+        assert_eq!(str.borrow(&owner), format!("string number {}", i * 3));
+    }
+}
+
+#[test]
+fn root_str() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let str = root!(&arena, guard, arena.add_str("rooted"));
+    arena.add_str("garbage");
+
+    arena.collect_full(&owner);
+    assert_eq!(str.borrow(&owner), "rooted");
+}
+
+#[test]
+fn hash_map_key() {
+    dreck!(owner, arena);
+
+    let a = arena.add_str("same");
+    let b = arena.add_str("same");
+
+    let mut map = HashMap::new();
+    map.insert(a, 1);
+    map.insert(b, 2);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map[&a], 1);
+    assert_eq!(map[&b], 2);
+    assert_eq!(a.borrow(&owner), b.borrow(&owner));
+}
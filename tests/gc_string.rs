@@ -0,0 +1,80 @@
+use std::pin::pin;
+
+use dreck::{sys::Phase, *};
+
+#[test]
+fn grow_across_collections() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let string = arena.add_string("start");
+    let string = GcString::from(root!(&arena, guard, string.gc()));
+
+    let mut expected = String::from("start");
+    for i in 0..1000 {
+        let part = format!(" {i}");
+        string.push_str(&mut owner, &part);
+        expected.push_str(&part);
+        if i % 10 == 0 {
+            unsafe { arena.unsafe_arena().step() };
+        }
+        if i % 100 == 0 {
+            arena.collect_full(&owner);
+        }
+    }
+    string.push(&mut owner, '!');
+    expected.push('!');
+    arena.collect_full(&owner);
+
+    assert_eq!(string.as_str(&owner), expected);
+    assert_eq!(string.len(&owner), expected.len());
+
+    string.clear(&mut owner);
+    assert!(string.is_empty(&owner));
+    assert_eq!(string.as_str(&owner), "");
+}
+
+#[test]
+fn rooting() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let strings = (0..10)
+        .map(|i| arena.add_string(&i.to_string()))
+        .collect::<Vec<_>>();
+    let strings = root!(&arena, guard, arena.add(strings));
+
+    // Mutating a string while its container is being traced needs no barrier.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+    }
+    for string in strings.borrow(&owner).clone() {
+        string.push_str(&mut owner, " pushed");
+    }
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    for (i, string) in strings.borrow(&owner).iter().enumerate() {
+        assert_eq!(string.as_str(&owner), format!("{i} pushed"));
+    }
+
+    let first = strings.borrow(&owner)[0];
+    assert!(first.ptr_eq(strings.borrow(&owner)[0]));
+    assert!(!first.ptr_eq(strings.borrow(&owner)[1]));
+}
+
+#[test]
+fn unrooted_freed() {
+    dreck!(owner, arena);
+
+    let string = arena.add_string("short lived");
+    string.push_str(&mut owner, " string");
+    let size = string.gc().allocation_size();
+    let before = arena.unsafe_arena().stats().total_allocated;
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.unsafe_arena().stats().total_allocated, before - size);
+}
@@ -0,0 +1,216 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{containers::GcVec, sys::Phase, *};
+
+pub struct Leaf(Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Parent<'gc, 'own> {
+    items: GcVec<'gc, 'own, Gc<'gc, 'own, Leaf>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Parent<'gc, 'own> {
+    type Gc<'to> = Parent<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.items.trace(marker)
+    }
+}
+
+#[test]
+fn grow_during_trace() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let parent = arena.add(Parent {
+        items: GcVec::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let parent = root!(&arena, guard, parent);
+
+    for _ in 0..4 {
+        let leaf = arena.add(Leaf(drops.clone()));
+        parent
+            .borrow_mut(&mut owner, &arena)
+            .items
+            .push(&arena, leaf);
+    }
+    assert_eq!(parent.borrow(&owner).items.capacity(), 4);
+
+    // Trace the parent and the spine, leaving the collector in the trace phase.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+        arena.unsafe_arena().step();
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+
+    let leaf = arena.add(Leaf(drops.clone()));
+    parent
+        .borrow_mut(&mut owner, &arena)
+        .items
+        .push(&arena, leaf);
+    assert_eq!(parent.borrow(&owner).items.capacity(), 8);
+
+    // Finish the current cycle.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+    assert_eq!(drops.get(), 0);
+    assert_eq!(parent.borrow(&owner).items.len(), 5);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+}
+
+#[test]
+fn pop_releases() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let parent = arena.add(Parent {
+        items: GcVec::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let parent = root!(&arena, guard, parent);
+
+    for _ in 0..10 {
+        let leaf = arena.add(Leaf(drops.clone()));
+        parent
+            .borrow_mut(&mut owner, &arena)
+            .items
+            .push(&arena, leaf);
+    }
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+
+    for _ in 0..3 {
+        assert!(parent.borrow_mut(&mut owner, &arena).items.pop().is_some());
+    }
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 3);
+    assert_eq!(parent.borrow(&owner).items.len(), 7);
+}
+
+#[test]
+fn push_during_steps() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let vec = arena.add(GcVec::<Gc<Leaf>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+
+    for i in 0..200 {
+        let leaf = arena.add(Leaf(drops.clone()));
+        vec.push(&mut owner, &arena, leaf);
+        if i % 3 == 0 {
+            unsafe { arena.unsafe_arena().step() };
+        }
+    }
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+    assert_eq!(drops.get(), 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(vec.as_slice(&owner).len(), 200);
+}
+
+#[test]
+fn insert_remove() {
+    dreck!(owner, arena);
+
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+
+    for i in 0..4 {
+        let value = arena.add(i);
+        vec.push(&mut owner, &arena, value);
+    }
+    // Inserting into a full spine grows it.
+    let value = arena.add(10);
+    vec.insert(&mut owner, &arena, 1, value);
+    assert_eq!(vec.borrow(&owner).capacity(), 8);
+    let value = arena.add(11);
+    vec.insert(&mut owner, &arena, 5, value);
+
+    arena.collect_full(&owner);
+    let values = vec
+        .as_slice(&owner)
+        .iter()
+        .map(|x| *x.borrow(&owner))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [0, 10, 1, 2, 3, 11]);
+
+    let removed = vec.remove(&mut owner, &arena, 1);
+    assert_eq!(*removed.borrow(&owner), 10);
+    let popped = vec.pop(&mut owner, &arena).unwrap();
+    assert_eq!(*popped.borrow(&owner), 11);
+    assert_eq!(vec.as_slice(&owner).len(), 4);
+}
+
+#[test]
+fn root_popped() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let vec = arena.add(GcVec::<Gc<Leaf>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+    let leaf = arena.add(Leaf(drops.clone()));
+    vec.push(&mut owner, &arena, leaf);
+
+    let guard = pin!(RootGuard::new());
+    let popped = vec.pop(&mut owner, &arena).unwrap();
+    let popped = root!(&arena, guard, popped);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert!(vec.as_slice(&owner).is_empty());
+    let _ = popped.borrow(&owner);
+}
+
+#[test]
+#[should_panic(expected = "insertion index (is 2) should be <= len (is 1)")]
+fn insert_out_of_bounds() {
+    dreck!(owner, arena);
+
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let value = arena.add(0);
+    vec.push(&mut owner, &arena, value);
+    let value = arena.add(1);
+    vec.insert(&mut owner, &arena, 2, value);
+}
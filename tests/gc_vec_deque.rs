@@ -0,0 +1,204 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{containers::GcVecDeque, *};
+
+pub struct Task(usize, Rc<Cell<usize>>);
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Task {
+    type Gc<'gc> = Task;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Scheduler<'gc, 'own> {
+    ready: GcVecDeque<'gc, 'own, Gc<'gc, 'own, Task>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Scheduler<'gc, 'own> {
+    type Gc<'to> = Scheduler<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.ready.trace(marker)
+    }
+}
+
+#[test]
+fn both_ends() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let scheduler = arena.add(Scheduler {
+        ready: GcVecDeque::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let scheduler = root!(&arena, guard, scheduler);
+
+    for x in 0..10 {
+        let task = arena.add(Task(x, drops.clone()));
+        let ready = &mut scheduler.borrow_mut(&mut owner, &arena).ready;
+        if x % 2 == 0 {
+            ready.push_back(&arena, task);
+        } else {
+            ready.push_front(&arena, task);
+        }
+    }
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+
+    let ids = scheduler
+        .borrow(&owner)
+        .ready
+        .iter()
+        .map(|x| x.borrow(&owner).0)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [9, 7, 5, 3, 1, 0, 2, 4, 6, 8]);
+    assert_eq!(
+        scheduler
+            .borrow(&owner)
+            .ready
+            .front()
+            .unwrap()
+            .borrow(&owner)
+            .0,
+        9
+    );
+    assert_eq!(
+        scheduler
+            .borrow(&owner)
+            .ready
+            .back()
+            .unwrap()
+            .borrow(&owner)
+            .0,
+        8
+    );
+
+    let ready = &mut scheduler.borrow_mut(&mut owner, &arena).ready;
+    assert!(ready.pop_front().is_some());
+    assert!(ready.pop_back().is_some());
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 2);
+    assert_eq!(scheduler.borrow(&owner).ready.len(), 8);
+}
+
+#[test]
+fn scheduler_workload() {
+    dreck!(owner, arena);
+    arena.set_verify(true);
+    let drops = Rc::new(Cell::new(0));
+
+    let scheduler = arena.add(Scheduler {
+        ready: GcVecDeque::new(),
+    });
+    let guard = pin!(RootGuard::new());
+    let scheduler = root!(&arena, guard, scheduler);
+
+    let mut next = 0;
+    let mut expected = 0;
+    for _ in 0..2000 {
+        for _ in 0..3 {
+            let task = arena.add(Task(next, drops.clone()));
+            next += 1;
+            scheduler
+                .borrow_mut(&mut owner, &arena)
+                .ready
+                .push_back(&arena, task);
+        }
+        // Interleave a few units of collection work with the mutations.
+        unsafe {
+            for _ in 0..4 {
+                arena.unsafe_arena().step();
+            }
+        }
+        for _ in 0..2 {
+            let task = scheduler.borrow_mut(&mut owner, &arena).ready.pop_front();
+            // The popped pointer is bound to the borrow of the owner, rooting rebinds it.
+            let guard = pin!(RootGuard::new());
+            let task = root!(&arena, guard, task.unwrap());
+            assert_eq!(task.borrow(&owner).0, expected);
+            expected += 1;
+        }
+    }
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), expected);
+    assert_eq!(scheduler.borrow(&owner).ready.len(), next - expected);
+}
+
+#[test]
+fn root_popped() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let queue = arena.add(GcVecDeque::<Gc<Task>>::new());
+    let guard = pin!(RootGuard::new());
+    let queue = root!(&arena, guard, queue);
+    for x in 0..6 {
+        let task = arena.add(Task(x, drops.clone()));
+        queue.push_back(&mut owner, &arena, task);
+    }
+
+    let guard = pin!(RootGuard::new());
+    let front = queue.pop_front(&mut owner, &arena).unwrap();
+    let front = root!(&arena, guard, front);
+    assert_eq!(
+        queue.pop_back(&mut owner, &arena).unwrap().borrow(&owner).0,
+        5
+    );
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(front.borrow(&owner).0, 0);
+    assert_eq!(queue.borrow(&owner).len(), 4);
+}
+
+#[test]
+fn rollback() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let queue = arena.add(GcVecDeque::<Gc<Task>>::new());
+    let guard = pin!(RootGuard::new());
+    let queue = root!(&arena, guard, queue);
+    for x in 0..4 {
+        let task = arena.add(Task(x, drops.clone()));
+        queue.push_back(&mut owner, &arena, task);
+    }
+
+    let spec = arena.begin_speculation();
+    queue.pop_front(&mut owner, &spec);
+    queue.pop_back(&mut owner, &spec);
+    for x in 10..20 {
+        let task = spec.add(Task(x, drops.clone()));
+        queue.push_front(&mut owner, &spec, task);
+    }
+    spec.rollback(&mut owner);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 10);
+    let ids = queue
+        .borrow(&owner)
+        .iter()
+        .map(|x| x.borrow(&owner).0)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [0, 1, 2, 3]);
+}
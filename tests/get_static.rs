@@ -0,0 +1,50 @@
+use std::pin::pin;
+
+use dreck::*;
+
+/// Keeps names borrowed from the arena without borrowing the owner.
+struct Names<'gc> {
+    names: Vec<&'gc str>,
+}
+
+#[test]
+fn outlives_owner_borrow() {
+    dreck!(owner, arena);
+    let mut interner = Interner::new();
+
+    let mut names = Names { names: Vec::new() };
+    for name in ["a", "b", "a"] {
+        let symbol = interner.intern(&arena, name);
+        names.names.push(arena.get_static(symbol.gc()));
+    }
+
+    // The owner can be borrowed mutably while the references are alive.
+    let list = arena.add(vec![1u32]);
+    list.borrow_mut(&mut owner, &arena).push(2);
+
+    assert_eq!(names.names, ["a", "b", "a"]);
+    assert!(std::ptr::eq(names.names[0], names.names[2]));
+    assert_eq!(list.borrow(&owner), &[1, 2]);
+}
+
+#[test]
+fn slices() {
+    dreck!(owner, arena);
+
+    let blob = arena.add_slice_copy(b"config");
+    let bytes: &[u8] = arena.get_static(blob);
+    assert_eq!(bytes, b"config");
+    assert_eq!(blob.borrow(&owner), bytes);
+}
+
+#[test]
+fn rooted_pointers() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let str = root!(&arena, guard, arena.add_str("rooted"));
+    arena.collect_full(&owner);
+
+    let value = arena.get_static(str);
+    assert_eq!(value, "rooted");
+}
@@ -0,0 +1,35 @@
+use std::pin::pin;
+
+use dreck::*;
+
+const LARGE: usize = 100_000;
+
+#[test]
+fn gray_stacks_shrink() {
+    dreck!(owner, arena);
+
+    {
+        let large = (0..LARGE)
+            .map(|x| arena.add(Some(arena.add(x))))
+            .collect::<Vec<_>>();
+        let large = arena.add(large);
+        let guard = pin!(RootGuard::new());
+        let _large = root!(&arena, guard, large);
+
+        arena.collect_full(&owner);
+        // The cycle needed the memory so it is retained.
+        assert!(arena.stats().gray_capacity >= LARGE);
+    }
+
+    let small = (0..10usize).map(|x| arena.add(x)).collect::<Vec<_>>();
+    let small = arena.add(small);
+    let guard = pin!(RootGuard::new());
+    let small = root!(&arena, guard, small);
+
+    for _ in 0..3 {
+        arena.collect_full(&owner);
+    }
+    assert!(arena.stats().gray_capacity < 1024);
+    assert!(arena.stats().cache_bytes < LARGE);
+    assert_eq!(*small.borrow(&owner)[9].borrow(&owner), 9);
+}
@@ -0,0 +1,94 @@
+use std::{cell::Cell, panic::AssertUnwindSafe, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Dropped(Rc<Cell<usize>>);
+
+impl Drop for Dropped {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Dropped {
+    type Gc<'to> = Dropped;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn limit_exceeded() {
+    dreck!(owner, arena);
+
+    arena.add([0u64; 4]);
+    let limit = arena.stats().total_allocated;
+    arena.set_heap_limit(Some(limit));
+    assert_eq!(arena.unsafe_arena().heap_limit(), Some(limit));
+
+    let err = arena.try_add([1u64; 4]).err().unwrap();
+    assert_eq!(err.error(), Error::LimitExceeded { limit });
+    assert_eq!(err.into_value(), [1u64; 4]);
+    assert_eq!(
+        arena.try_add_str("value").err(),
+        Some(Error::LimitExceeded { limit })
+    );
+    assert_eq!(
+        arena.try_add_from_iter([1u32, 2]).err(),
+        Some(Error::LimitExceeded { limit })
+    );
+    assert_eq!(arena.stats().total_allocated, limit);
+
+    let res = std::panic::catch_unwind(AssertUnwindSafe(|| arena.add(1u32)));
+    assert!(res.is_err());
+
+    arena.set_heap_limit(None);
+    assert_eq!(*arena.try_add(2u32).unwrap().borrow(&owner), 2);
+}
+
+#[test]
+fn collect_frees_room() {
+    dreck!(owner, arena);
+
+    arena.add([0u64; 4]);
+    let limit = arena.stats().total_allocated;
+    arena.set_heap_limit(Some(limit));
+
+    let err = arena.try_add([1u64; 4]).err().unwrap();
+    assert_eq!(err.error(), Error::LimitExceeded { limit });
+
+    // The first object is garbage, so collecting makes room for the value.
+    arena.collect_full(&owner);
+    let value = arena.try_add(err.into_value()).unwrap();
+    let guard = pin!(RootGuard::new());
+    let value = root!(&arena, guard, value);
+    assert_eq!(*value.borrow(&owner), [1u64; 4]);
+}
+
+#[test]
+fn value_is_returned() {
+    dreck!(owner, arena);
+    let dropped = Rc::new(Cell::new(0));
+
+    arena.set_heap_limit(Some(0));
+    let (error, value) = arena
+        .try_add(Dropped(dropped.clone()))
+        .err()
+        .unwrap()
+        .into_parts();
+    assert_eq!(error, Error::LimitExceeded { limit: 0 });
+    assert_eq!(dropped.get(), 0);
+    drop(value);
+    assert_eq!(dropped.get(), 1);
+
+    // The error converts into the error of the other fallible methods.
+    let error: Error = arena.try_add(1u32).err().unwrap().into();
+    assert_eq!(error, Error::LimitExceeded { limit: 0 });
+    arena.collect_full(&owner);
+}
@@ -0,0 +1,37 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn equal_values_share() {
+    dreck!(owner, arena);
+
+    let a = arena.add_interned(&owner, "hello".to_string());
+    let b = arena.add_interned(&owner, "hello".to_string());
+    let c = arena.add_interned(&owner, "world".to_string());
+    let d = arena.add_interned(&owner, 5u32);
+
+    assert_eq!(a.into_gc_box(), b.into_gc_box());
+    assert_ne!(a.into_gc_box(), c.into_gc_box());
+    assert_eq!(*d.borrow(&owner), 5);
+    assert_eq!(arena.interned_count(), 3);
+}
+
+#[test]
+fn dead_values_removed() {
+    dreck!(owner, arena);
+
+    let kept = arena.add_interned(&owner, 1u64);
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, kept);
+    arena.add_interned(&owner, 2u64);
+    assert_eq!(arena.interned_count(), 2);
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.interned_count(), 1);
+
+    let again = arena.add_interned(&owner, 1u64);
+    assert_eq!(again.into_gc_box(), kept.into_gc_box());
+    arena.add_interned(&owner, 2u64);
+    assert_eq!(arena.interned_count(), 2);
+}
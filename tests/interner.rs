@@ -0,0 +1,91 @@
+use std::{collections::HashSet, pin::pin};
+
+use dreck::{sys::Phase, *};
+
+#[test]
+fn same_string_same_symbol() {
+    dreck!(owner, arena);
+    let mut interner = Interner::new();
+
+    let a = interner.intern(&arena, "hello");
+    let b = interner.intern(&arena, &String::from("hello"));
+    let c = interner.intern(&arena, "world");
+    assert!(a.ptr_eq(b));
+    assert!(a == b);
+    assert!(!a.ptr_eq(c));
+    assert_eq!(a.as_str(&owner), "hello");
+    assert_eq!(c.as_str(&owner), "world");
+    assert_eq!(interner.len(), 2);
+
+    let set: HashSet<_> = [a, b, c].into_iter().collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn survives_collection() {
+    dreck!(owner, arena);
+    let mut interner = Interner::new();
+
+    let guard = pin!(RootGuard::new());
+    let symbols = (0..100)
+        .map(|i| interner.intern(&arena, &format!("symbol {i}")))
+        .collect::<Vec<_>>();
+    let symbols = root!(&arena, guard, arena.add(symbols));
+
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    for (i, symbol) in symbols.borrow(&owner).iter().enumerate() {
+        assert_eq!(symbol.as_str(&owner), format!("symbol {i}"));
+        let again = interner.intern(&arena, &format!("symbol {i}"));
+        assert!(again.ptr_eq(*symbol));
+    }
+    assert_eq!(interner.purge(), 0);
+}
+
+#[test]
+fn unreferenced_symbols_are_purged() {
+    dreck!(owner, arena);
+    let mut interner = Interner::new();
+
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(interner.intern(&arena, "kept")));
+    for i in 0..10 {
+        interner.intern(&arena, &format!("garbage {i}"));
+    }
+    assert_eq!(interner.len(), 11);
+
+    arena.collect_full(&owner);
+    assert_eq!(interner.purge(), 10);
+    assert_eq!(interner.len(), 1);
+
+    let again = interner.intern(&arena, "kept");
+    assert!(again.ptr_eq(*kept.borrow(&owner)));
+    let fresh = interner.intern(&arena, "garbage 0");
+    assert_eq!(fresh.as_str(&owner), "garbage 0");
+    assert_eq!(interner.len(), 2);
+}
+
+#[test]
+fn intern_while_sweeping() {
+    dreck!(owner, arena);
+    let mut interner = Interner::new();
+
+    let guard = pin!(RootGuard::new());
+    let list = root!(&arena, guard, arena.add(Vec::<Symbol>::new()));
+    interner.intern(&arena, "garbage");
+    // Step into the sweep phase of a new cycle, where the unreachable symbol isn't freed yet.
+    while arena.stats().phase == Phase::Sweep {
+        unsafe { arena.unsafe_arena().step() };
+    }
+    while arena.stats().phase != Phase::Sweep {
+        unsafe { arena.unsafe_arena().step() };
+    }
+
+    let symbol = interner.intern(&arena, "garbage");
+    list.borrow_mut(&mut owner, &arena).push(symbol);
+    arena.collect_full(&owner);
+
+    assert_eq!(interner.purge(), 0);
+    assert_eq!(list.borrow(&owner)[0].as_str(&owner), "garbage");
+}
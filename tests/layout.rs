@@ -0,0 +1,28 @@
+use std::mem::size_of;
+
+use dreck::{layout::*, sys::GcBox, *};
+
+#[test]
+fn sizes() {
+    assert_eq!(
+        size_of::<GcBox<u64>>(),
+        GC_BOX_HEADER_BYTES + size_of::<u64>()
+    );
+    assert_eq!(size_of::<Gc<u8>>(), GC_PTR_BYTES);
+    assert_eq!(size_of::<Option<Gc<u8>>>(), OPTION_GC_PTR_BYTES);
+    assert_eq!(size_of::<RootGuard>(), ROOT_GUARD_BYTES);
+}
+
+#[test]
+#[cfg(all(
+    target_pointer_width = "64",
+    not(feature = "arena-id"),
+    not(feature = "object-id"),
+    not(feature = "tag-word")
+))]
+fn sizes_64_bit() {
+    assert_eq!(GC_BOX_HEADER_BYTES, 16);
+    assert_eq!(GC_PTR_BYTES, 8);
+    assert_eq!(OPTION_GC_PTR_BYTES, 8);
+    assert_eq!(ROOT_GUARD_BYTES, 32);
+}
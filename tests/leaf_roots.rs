@@ -0,0 +1,52 @@
+use std::pin::{pin, Pin};
+
+use dreck::{
+    sys::{embed::RootList, UnsafeArena, UnsafeRootGuard},
+    *,
+};
+
+const ROOTS: usize = 1000;
+
+#[test]
+fn leaf_roots_not_traced() {
+    dreck!(owner, arena);
+
+    let mut guards: Vec<Pin<Box<RootGuard>>> = Vec::new();
+    for x in 0..ROOTS {
+        let mut guard = Box::pin(RootGuard::new());
+        arena.root(arena.add(x as u64), guard.as_mut());
+        guards.push(guard);
+
+        let mut guard = Box::pin(RootGuard::new());
+        arena.root(arena.add(x.to_string()), guard.as_mut());
+        guards.push(guard);
+    }
+
+    for _ in 0..3 {
+        arena.collect_full(&owner);
+    }
+    assert_eq!(arena.stats().gray_capacity, 0);
+    assert_eq!(arena.root_count(), ROOTS * 2);
+}
+
+#[test]
+fn erased_leaf_pointers_not_traced() {
+    let arena = unsafe { UnsafeArena::new() };
+    let roots = RootList::new();
+    let guard = pin!(UnsafeRootGuard::new());
+
+    unsafe {
+        roots.root(&arena, guard);
+        for x in 0..ROOTS {
+            roots.push(&arena, arena.add(x as u64).cast());
+        }
+        let allocated = arena.stats().total_allocated;
+
+        for _ in 0..3 {
+            arena.collect_full();
+        }
+        assert_eq!(arena.stats().total_allocated, allocated);
+    }
+    // Only the list itself is traced.
+    assert!(arena.stats().gray_capacity < 16);
+}
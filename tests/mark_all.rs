@@ -0,0 +1,118 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+pub struct Counted(Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+unsafe impl<'own> Erasable<'own> for Counted {}
+
+#[test]
+fn abandon_keeps_garbage_until_next_collection() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let live = root!(&arena, guard, arena.add(Counted(drops.clone())));
+    let garbage = arena.add(Counted(drops.clone())).into_gc_box().cast::<u8>();
+
+    let heap = arena.mark_all(&owner);
+    assert!(heap.is_live(live.erase()));
+    let mut dead = Vec::new();
+    heap.for_each_dead(|x| dead.push(x.into_gc_box().cast::<u8>()));
+    assert_eq!(dead, vec![garbage]);
+    heap.abandon();
+
+    assert_eq!(drops.get(), 0);
+    assert_eq!(arena.stats().phase, Phase::Sleep);
+    let mut count = 0;
+    unsafe { arena.unsafe_arena().for_each_object(|_| count += 1) };
+    assert_eq!(count, 2);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(*live.borrow(&owner).0, Cell::new(1));
+}
+
+#[test]
+fn finish_sweep_frees_garbage() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let live = root!(&arena, guard, arena.add(vec![arena.add(1u32)]));
+    arena.add(Counted(drops.clone()));
+    arena.add(Counted(drops.clone()));
+
+    let heap = arena.mark_all(&owner);
+    assert!(heap.is_live(live.erase()));
+    assert!(heap.is_live(live.borrow(heap.owner())[0].erase()));
+    let mut dead = 0;
+    heap.for_each_dead(|x| {
+        assert!(x.type_name().ends_with("Counted"));
+        dead += 1;
+    });
+    assert_eq!(dead, 2);
+    assert_eq!(drops.get(), 0);
+    heap.finish_sweep();
+
+    assert_eq!(drops.get(), 2);
+    assert_eq!(arena.stats().phase, Phase::Sleep);
+}
+
+#[test]
+fn drop_finishes_sweep() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    arena.add(Counted(drops.clone()));
+    {
+        let heap = arena.mark_all(&owner);
+        heap.for_each_dead(|x| assert!(x.type_name().ends_with("Counted")));
+    }
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn allocation_is_forbidden() {
+    let arena = unsafe { sys::UnsafeArena::new() };
+    unsafe {
+        arena.add(1u32);
+        arena.mark_all();
+        assert_eq!(arena.check_allocate(), Err(GcUnavailable::Marked));
+        arena.abandon_sweep();
+        assert_eq!(arena.check_allocate(), Ok(()));
+
+        arena.mark_all();
+        arena.finish_sweep();
+        assert_eq!(arena.check_allocate(), Ok(()));
+    }
+}
+
+#[test]
+#[should_panic(expected = "held fully marked")]
+fn allocation_panics() {
+    let arena = unsafe { sys::UnsafeArena::new() };
+    unsafe {
+        arena.mark_all();
+        arena.add(1u32);
+    }
+}
@@ -0,0 +1,120 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn hits_and_misses() {
+    dreck!(owner, arena);
+    let mut cache = MemoCache::<String, usize>::new(8);
+
+    let guard = pin!(RootGuard::new());
+    let key = root!(&arena, guard, arena.add(String::from("hello")));
+
+    let mut computed = 0;
+    for _ in 0..3 {
+        let value = cache.get_or_insert_with(&mut owner, &arena, key, |owner| {
+            computed += 1;
+            arena.add(key.borrow(owner).len())
+        });
+        assert_eq!(*value.borrow(&owner), 5);
+        arena.collect_full(&owner);
+    }
+    assert_eq!(computed, 1);
+    assert_eq!(
+        cache.stats(),
+        MemoStats {
+            hits: 2,
+            misses: 1,
+            evicted: 0,
+            cleared: 0
+        }
+    );
+}
+
+#[test]
+fn values_rooted() {
+    dreck!(owner, arena);
+    let mut cache = MemoCache::<u32, String>::new(8);
+
+    let guard = pin!(RootGuard::new());
+    let key = root!(&arena, guard, arena.add(1u32));
+    cache.get_or_insert_with(&mut owner, &arena, key, |_| arena.add(String::from("one")));
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    let value = cache.get_or_insert_with(&mut owner, &arena, key, |_| unreachable!());
+    assert_eq!(value.borrow(&owner), "one");
+}
+
+#[test]
+fn capacity_eviction() {
+    dreck!(owner, arena);
+    let mut cache = MemoCache::<u32, u32>::new(2);
+
+    let guard = pin!(RootGuard::new());
+    let keys = root!(
+        &arena,
+        guard,
+        arena.add((0..3u32).map(|x| arena.add(x)).collect::<Vec<_>>())
+    );
+    let keys = keys.borrow(&owner).clone();
+
+    for key in keys.iter().take(2) {
+        cache.get_or_insert_with(&mut owner, &arena, *key, |_| arena.add(0));
+    }
+    // Use the first key so the second is the least recently used.
+    cache.get_or_insert_with(&mut owner, &arena, keys[0], |_| unreachable!());
+    cache.get_or_insert_with(&mut owner, &arena, keys[2], |_| arena.add(0));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.stats().evicted, 1);
+
+    let mut recomputed = false;
+    cache.get_or_insert_with(&mut owner, &arena, keys[1], |_| {
+        recomputed = true;
+        arena.add(0)
+    });
+    assert!(recomputed);
+    cache.get_or_insert_with(&mut owner, &arena, keys[2], |_| unreachable!());
+}
+
+#[test]
+fn key_death_clears_entries() {
+    dreck!(owner, arena);
+    let mut cache = MemoCache::<u32, String>::new(16);
+
+    for x in 0..10u32 {
+        let key = arena.add(x);
+        cache.get_or_insert_with(&mut owner, &arena, key, |_| arena.add(x.to_string()));
+    }
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(10u32));
+    cache.get_or_insert_with(&mut owner, &arena, kept, |_| arena.add(String::from("10")));
+    assert_eq!(cache.len(), 11);
+
+    arena.collect_full(&owner);
+    let before = arena.unsafe_arena().stats().total_allocated;
+    assert_eq!(cache.purge(), 10);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.stats().cleared, 10);
+
+    // The values of the cleared entries are no longer rooted.
+    arena.collect_full(&owner);
+    assert!(arena.unsafe_arena().stats().total_allocated < before);
+    let value = cache.get_or_insert_with(&mut owner, &arena, kept, |_| unreachable!());
+    assert_eq!(value.borrow(&owner), "10");
+}
+
+#[test]
+fn reused_address_is_a_miss() {
+    dreck!(owner, arena);
+    let mut cache = MemoCache::<u32, u32>::new(1024);
+
+    // Freed keys are likely to have their address reused by new keys of the same size.
+    for x in 0..256u32 {
+        let key = arena.add(x);
+        let value = cache.get_or_insert_with(&mut owner, &arena, key, |_| arena.add(x));
+        assert_eq!(*value.borrow(&owner), x);
+        arena.collect_full(&owner);
+    }
+    assert_eq!(cache.stats().hits, 0);
+}
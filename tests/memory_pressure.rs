@@ -0,0 +1,92 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn critical_shrinks_gray_buffers() {
+    dreck!(owner, arena);
+
+    let children = (0..1000).map(|x| arena.add(x)).collect::<Vec<_>>();
+    let parents = (0..1000)
+        .map(|_| arena.add(children.clone()))
+        .collect::<Vec<_>>();
+    let parents = arena.add(parents);
+    let guard = pin!(RootGuard::new());
+    let _parents = root!(&arena, guard, parents);
+
+    arena.collect_full(&owner);
+    let before = arena.stats().cache_bytes;
+    assert!(before > 1000 * std::mem::size_of::<usize>());
+
+    let released = arena.on_memory_pressure(&owner, MemoryPressure::Critical);
+    assert!(released >= before - arena.stats().cache_bytes);
+    assert!(arena.stats().cache_bytes < before);
+    assert_eq!(arena.stats().cache_bytes, 0);
+}
+
+#[test]
+fn critical_shrinks_intern_table() {
+    dreck!(owner, arena);
+
+    for x in 0..1000u32 {
+        arena.add_interned(&owner, x);
+    }
+    assert_eq!(arena.interned_count(), 1000);
+    let before = arena.stats().cache_bytes;
+
+    arena.on_memory_pressure(&owner, MemoryPressure::Critical);
+    assert_eq!(arena.interned_count(), 0);
+    assert!(arena.stats().cache_bytes < before);
+    assert_eq!(arena.stats().cache_bytes, 0);
+}
+
+#[test]
+fn critical_frees_garbage() {
+    dreck!(owner, arena);
+
+    for x in 0..100u32 {
+        arena.add(x);
+    }
+    let allocated = arena.stats().total_allocated;
+    assert!(allocated > 0);
+
+    let released = arena.on_memory_pressure(&owner, MemoryPressure::Critical);
+    assert!(released >= allocated);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
+
+#[test]
+fn normal_finishes_cycle() {
+    dreck!(owner, arena);
+
+    for x in 0..100u32 {
+        arena.add(x);
+    }
+    let allocated = arena.stats().total_allocated;
+    // A new arena starts in the sweep phase, step until it is tracing.
+    while arena.stats().phase != sys::Phase::Trace {
+        unsafe { arena.unsafe_arena().step() };
+    }
+
+    let released = arena.on_memory_pressure(&owner, MemoryPressure::Normal);
+    assert_eq!(arena.stats().phase, sys::Phase::Sleep);
+    assert_eq!(released, allocated);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
+
+#[test]
+fn normal_keeps_caches() {
+    dreck!(owner, arena);
+
+    for x in 0..1000u32 {
+        arena.add_interned(&owner, x);
+    }
+    arena.collect_full(&owner);
+    let before = arena.stats().cache_bytes;
+    assert!(before > 0);
+
+    assert_eq!(arena.on_memory_pressure(&owner, MemoryPressure::Normal), 0);
+    assert_eq!(arena.stats().cache_bytes, before);
+    assert!(arena.shrink_caches() > 0);
+    assert_eq!(arena.stats().cache_bytes, 0);
+}
@@ -0,0 +1,273 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Item(Rc<Cell<usize>>);
+
+impl Drop for Item {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Item {
+    type Gc<'gc> = Item;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct PlayerV1<'gc, 'own> {
+    score: u32,
+    item: Gc<'gc, 'own, Item>,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for PlayerV1<'_, '_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for PlayerV1<'gc, 'own> {
+    type Gc<'to> = PlayerV1<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.item.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for PlayerV1<'gc, 'own> {}
+
+pub struct PlayerV2<'gc, 'own> {
+    score: u32,
+    level: u32,
+    item: Gc<'gc, 'own, Item>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for PlayerV2<'gc, 'own> {
+    type Gc<'to> = PlayerV2<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.item.trace(marker)
+    }
+}
+
+/// A reference to a player of either version.
+pub enum Player<'gc, 'own> {
+    V1(Gc<'gc, 'own, PlayerV1<'gc, 'own>>),
+    V2(Gc<'gc, 'own, PlayerV2<'gc, 'own>>),
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Player<'gc, 'own> {
+    type Gc<'to> = Player<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        match self {
+            Player::V1(x) => x.trace(marker),
+            Player::V2(x) => x.trace(marker),
+        }
+    }
+}
+
+impl<'gc, 'own> Player<'gc, 'own> {
+    fn migrate(&mut self, replacer: Replacer<'gc, 'own, PlayerV2<'_, 'own>>) {
+        if let Player::V1(x) = *self {
+            *self = Player::V2(replacer.get(x).unwrap());
+        }
+    }
+
+    fn unwrap_v2(&self) -> Gc<'gc, 'own, PlayerV2<'gc, 'own>> {
+        match self {
+            Player::V1(_) => panic!("player was not migrated"),
+            Player::V2(x) => *x,
+        }
+    }
+}
+
+pub struct Team<'gc, 'own> {
+    players: Vec<Player<'gc, 'own>>,
+    slots: Vec<Gc<'gc, 'own, Slot<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Team<'gc, 'own> {
+    type Gc<'to> = Team<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.players.trace(marker);
+        self.slots.trace(marker);
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for Team<'gc, 'own> {}
+
+pub struct Slot<'gc, 'own> {
+    player: Option<Player<'gc, 'own>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Slot<'gc, 'own> {
+    type Gc<'to> = Slot<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.player.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for Slot<'gc, 'own> {}
+
+/// Build a team of four players of which every other one is also referenced by a slot.
+fn build<'gc, 'own>(
+    owner: &mut Owner<'own>,
+    arena: &'gc Arena<'own>,
+    player_drops: &Rc<Cell<usize>>,
+    item_drops: &Rc<Cell<usize>>,
+) -> Gc<'gc, 'own, Team<'gc, 'own>> {
+    let team = arena.add(Team {
+        players: Vec::new(),
+        slots: Vec::new(),
+    });
+    for score in 0..4 {
+        let player = arena.add(PlayerV1 {
+            score,
+            item: arena.add(Item(item_drops.clone())),
+            drops: player_drops.clone(),
+        });
+        let team = team.borrow_mut(owner, arena);
+        team.players.push(arena.rebind_to(Player::V1(player)));
+        if score % 2 == 0 {
+            let slot = arena.add(Slot {
+                player: Some(Player::V1(player)),
+            });
+            team.slots.push(arena.rebind_to(slot));
+        }
+    }
+    team
+}
+
+#[test]
+fn migrate_with_inbound_references() {
+    dreck!(owner, arena);
+    let player_drops = Rc::new(Cell::new(0));
+    let item_drops = Rc::new(Cell::new(0));
+
+    let team = build(&mut owner, &arena, &player_drops, &item_drops);
+    let guard = pin!(RootGuard::new());
+    let team = root!(&arena, guard, team);
+
+    // Garbage of the old type is not migrated.
+    arena.add(PlayerV1 {
+        score: 100,
+        item: arena.add(Item(item_drops.clone())),
+        drops: player_drops.clone(),
+    });
+
+    let migration = arena.migrate(&mut owner, |old: &PlayerV1| PlayerV2 {
+        score: old.score,
+        level: old.score * 10,
+        item: old.item,
+    });
+    assert_eq!(migration.len(), 4);
+    assert_eq!(player_drops.get(), 1);
+    assert_eq!(item_drops.get(), 1);
+
+    migration.rewrite::<Team, _>(&mut arena, &mut owner, |team, replacer| {
+        team.players.iter_mut().for_each(|x| x.migrate(replacer))
+    });
+    migration.rewrite::<Slot, _>(&mut arena, &mut owner, |slot, replacer| {
+        slot.player.iter_mut().for_each(|x| x.migrate(replacer))
+    });
+
+    assert_eq!(arena.finish_migration(&owner, migration), 0);
+    assert_eq!(player_drops.get(), 5);
+    assert_eq!(item_drops.get(), 1);
+
+    let team = team.borrow(&owner);
+    for (idx, player) in team.players.iter().enumerate() {
+        let player = player.unwrap_v2().borrow(&owner);
+        assert_eq!(player.score, idx as u32);
+        assert_eq!(player.level, idx as u32 * 10);
+        player.item.borrow(&owner);
+    }
+    // Both containers refer to the same replacement objects.
+    for (idx, slot) in team.slots.iter().enumerate() {
+        let player = slot.borrow(&owner).player.as_ref().unwrap().unwrap_v2();
+        assert_eq!(
+            player.into_gc_box(),
+            team.players[idx * 2].unwrap_v2().into_gc_box()
+        );
+    }
+}
+
+#[test]
+fn unrewritten_references_keep_old_objects() {
+    dreck!(owner, arena);
+    let player_drops = Rc::new(Cell::new(0));
+    let item_drops = Rc::new(Cell::new(0));
+
+    let team = build(&mut owner, &arena, &player_drops, &item_drops);
+    let guard = pin!(RootGuard::new());
+    let team = root!(&arena, guard, team);
+
+    let migration = arena.migrate(&mut owner, |old: &PlayerV1| PlayerV2 {
+        score: old.score,
+        level: 0,
+        item: old.item,
+    });
+    migration.rewrite::<Team, _>(&mut arena, &mut owner, |team, replacer| {
+        team.players.iter_mut().for_each(|x| x.migrate(replacer))
+    });
+
+    // The slots still refer to two old players.
+    assert_eq!(arena.finish_migration(&owner, migration), 2);
+    assert_eq!(player_drops.get(), 2);
+    assert_eq!(item_drops.get(), 0);
+
+    let team = team.borrow(&owner);
+    for slot in team.slots.iter() {
+        match slot.borrow(&owner).player.as_ref().unwrap() {
+            Player::V1(x) => assert_eq!(x.borrow(&owner).score % 2, 0),
+            Player::V2(_) => panic!("slot should not be migrated"),
+        }
+    }
+}
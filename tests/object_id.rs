@@ -0,0 +1,84 @@
+#![cfg(feature = "object-id")]
+
+use std::{collections::HashSet, pin::pin};
+
+use dreck::*;
+
+#[test]
+fn unique() {
+    dreck!(owner, arena);
+
+    let mut seen = HashSet::new();
+    for _ in 0..10 {
+        for x in 0..100u32 {
+            assert!(seen.insert(arena.add(x).object_id()));
+        }
+        assert!(seen.insert(arena.add_slice_copy(&[1u32, 2, 3]).object_id()));
+        assert!(seen.insert(arena.add_str("id").object_id()));
+        // Freed memory is reused by new objects, their ids must still differ.
+        arena.collect_full(&owner);
+    }
+}
+
+#[test]
+fn stable_across_collections() {
+    dreck!(owner, arena);
+
+    let values = (0..100u32).map(|x| arena.add(x)).collect::<Vec<_>>();
+    let values = arena.add(values);
+    let guard = pin!(RootGuard::new());
+    let values = root!(&arena, guard, values);
+
+    let ids = values
+        .borrow(&owner)
+        .iter()
+        .map(|x| x.object_id())
+        .collect::<Vec<_>>();
+    // Ids are assigned in allocation order.
+    assert!(ids.windows(2).all(|x| x[0] < x[1]));
+    assert!(ids[99] < values.object_id());
+
+    for _ in 0..3 {
+        for x in 0..100u32 {
+            arena.add(x);
+        }
+        arena.collect_full(&owner);
+    }
+
+    let after = values
+        .borrow(&owner)
+        .iter()
+        .map(|x| x.erase().object_id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, after);
+}
+
+#[test]
+fn equal_contents() {
+    dreck!(owner, arena);
+
+    let a = arena.add(String::from("same"));
+    let b = arena.add(String::from("same"));
+    assert_eq!(a.borrow(&owner), b.borrow(&owner));
+    assert_ne!(a.object_id(), b.object_id());
+    assert_eq!(a.object_id(), a.object_id());
+    assert_eq!(
+        a.object_id().to_string(),
+        format!("#{}", a.object_id().get())
+    );
+}
+
+#[test]
+fn assigned_before_initialized() {
+    dreck!(owner, arena);
+
+    let mut inside = None;
+    let value = arena.add_cyclic(&mut owner, |ptr: Gc<Option<Gc<u32>>>| {
+        inside = Some(ptr.object_id());
+        None
+    });
+    assert_eq!(inside, Some(value.object_id()));
+
+    let other = arena.add(1u32);
+    assert_ne!(other.object_id(), value.object_id());
+}
@@ -0,0 +1,250 @@
+use std::pin::pin;
+
+use dreck::{containers::GcVec, *};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    pub color: Versioned<u32>,
+    pub width: Versioned<u32>,
+}
+
+unsafe impl<'own> Trace<'own> for Style {
+    type Gc<'gc> = Style;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+pub struct Widget<'gc, 'own> {
+    pub style: Versioned<Style>,
+    pub child: Versioned<Option<Gc<'gc, 'own, u32>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Widget<'gc, 'own> {
+    type Gc<'to> = Widget<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.style.trace(marker);
+        self.child.trace(marker);
+    }
+}
+
+/// Returns how much the epoch of the owner increased while running the function.
+fn bumps<'own, R>(owner: &mut Owner<'own>, f: impl FnOnce(&mut Owner<'own>) -> R) -> (R, u64) {
+    let before = owner.epoch();
+    let res = f(owner);
+    (res, owner.epoch() - before)
+}
+
+#[test]
+fn reads_keep_epoch() {
+    dreck!(owner, arena);
+
+    assert_eq!(owner.epoch(), 0);
+    let value = arena.add(1u32);
+    let cell = arena.add(GcOnceCell::<u32>::new());
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let string = arena.add_string("text");
+    assert_eq!(*value.borrow(&owner), 1);
+    assert!(cell.borrow(&owner).get(&owner).is_none());
+    assert!(vec.as_slice(&owner).is_empty());
+    assert_eq!(string.as_str(&owner), "text");
+    assert_eq!([value].borrow_all(&owner).count(), 1);
+    arena.collect_full(&owner);
+    assert_eq!(owner.epoch(), 0);
+}
+
+#[test]
+fn borrow_mut_family_bumps_once() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(2u32);
+    let (_, n) = bumps(&mut owner, |owner| *a.borrow_mut(owner, &arena) += 1);
+    assert_eq!(n, 1);
+    let (_, n) = bumps(&mut owner, |owner| {
+        *a.try_borrow_mut(owner, &arena).unwrap() += 1
+    });
+    assert_eq!(n, 1);
+    let (_, n) = bumps(&mut owner, |owner| a.set(owner, &arena, 5));
+    assert_eq!(n, 1);
+    let (old, n) = bumps(&mut owner, |owner| a.replace(owner, &arena, 6));
+    assert_eq!((old, n), (5, 1));
+    let (_, n) = bumps(&mut owner, |owner| Gc::swap(a, b, owner, &arena));
+    assert_eq!(n, 1);
+    let (_, n) = bumps(&mut owner, |owner| *a.borrow_mut_untraced(owner) += 1);
+    assert_eq!(n, 1);
+    let (_, n) = bumps(&mut owner, |owner| **a.write(owner, &arena) += 1);
+    assert_eq!(n, 1);
+    assert_eq!(*a.borrow(&owner), 4);
+
+    let items = [a, b];
+    let (_, n) = bumps(&mut owner, |owner| {
+        let mut iter = items.borrow_all_mut(owner, &arena);
+        while let Some(x) = iter.next() {
+            *x += 1;
+        }
+    });
+    assert_eq!(n, 2);
+
+    let c = arena.add(3u32);
+    let (_, n) = bumps(&mut owner, |owner| {
+        let (a, b) = owner.borrow_mut_2(a, b, &arena);
+        std::mem::swap(a, b);
+    });
+    assert_eq!(n, 1);
+    let (_, n) = bumps(&mut owner, |owner| {
+        let (a, b, c) = owner.borrow_mut_3(a, b, c, &arena);
+        *a += *b + *c;
+    });
+    assert_eq!(n, 1);
+}
+
+#[test]
+fn cells_bump_on_change() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(1u32));
+    let (_, n) = bumps(&mut owner, |owner| {
+        *cell.borrow_mut_cell(owner, &arena) += 1
+    });
+    assert_eq!(n, 1);
+    let borrow = cell.borrow_mut_cell(&owner, &arena);
+    let before = owner.epoch();
+    assert!(cell.try_borrow_mut_cell(&owner, &arena).is_err());
+    assert_eq!(owner.epoch(), before);
+    drop(borrow);
+
+    let once = arena.add(GcOnceCell::<u32>::new());
+    let (_, n) = bumps(&mut owner, |owner| {
+        assert_eq!(*once.borrow(owner).get_or_init(owner, &arena, || 1), 1);
+        assert_eq!(*once.borrow(owner).get_or_init(owner, &arena, || 2), 1);
+        assert!(once.borrow(owner).set(owner, &arena, 3).is_err());
+    });
+    assert_eq!(n, 1);
+
+    let sampled = arena.add(Sampled::new(0u32));
+    let (_, n) = bumps(&mut owner, |owner| sampled.borrow(owner).write(owner, 1));
+    assert_eq!(n, 1);
+}
+
+#[test]
+fn containers_bump_on_change() {
+    dreck!(owner, arena);
+
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+    let (_, n) = bumps(&mut owner, |owner| {
+        vec.push(owner, &arena, arena.add(1));
+        vec.insert(owner, &arena, 0, arena.add(0));
+        vec.pop(owner, &arena);
+        vec.remove(owner, &arena, 0);
+    });
+    assert_eq!(n, 4);
+
+    let string = arena.add_string("a");
+    let (_, n) = bumps(&mut owner, |owner| {
+        string.push(owner, 'b');
+        string.push_str(owner, "cd");
+        string.clear(owner);
+    });
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn arena_mutators_bump() {
+    dreck!(owner, arena);
+
+    let value = arena.add(vec![1u32]);
+    let guard = pin!(RootGuard::new());
+    let value = root!(&arena, guard, value);
+    let (_, n) = bumps(&mut owner, |owner| {
+        arena.for_each_mut::<Vec<u32>, _>(owner, |x| x.push(2))
+    });
+    assert_eq!(n, 1);
+
+    let spec = arena.begin_speculation();
+    value.borrow_mut(&mut owner, &spec).push(3);
+    let before = owner.epoch();
+    spec.rollback(&mut owner);
+    assert_eq!(owner.epoch(), before + 1);
+    assert_eq!(*value.borrow(&owner), [1, 2]);
+}
+
+#[test]
+fn versioned_counts_own_changes() {
+    dreck!(owner, arena);
+
+    let widget = arena.add(Widget {
+        style: Versioned::new(Style::default()),
+        child: Versioned::new(None),
+    });
+    let guard = pin!(RootGuard::new());
+    let widget = root!(&arena, guard, widget);
+
+    {
+        let write = widget.write(&mut owner, &arena);
+        let style = project!(write, style).get_mut();
+        project!(style, color).set(0xff0000);
+        project!(write, child).get_mut().set(Some(arena.add(1)));
+    }
+    arena.collect_full(&owner);
+
+    let w = widget.borrow(&owner);
+    assert_eq!(w.style.version(), 1);
+    assert_eq!(w.style.color.version(), 1);
+    assert_eq!(w.style.width.version(), 0);
+    assert_eq!(*w.style.color, 0xff0000);
+    assert_eq!(w.child.version(), 1);
+    assert_eq!(*w.child.unwrap().borrow(&owner), 1);
+
+    // Reading leaves the versions unchanged.
+    let style = w.style.clone();
+    assert_eq!(style.color.version(), 1);
+    assert_eq!(widget.borrow(&owner).style.version(), 1);
+
+    let mut other = Style::default();
+    other.color.set(1);
+    other.color.set(0xff0000);
+    assert_eq!(other.color.version(), 2);
+    // Derived comparisons only look at the values.
+    assert_eq!(other, *style);
+    assert_eq!(
+        format!("{:?}", Versioned::new(3u8)),
+        "Versioned { value: 3, version: 0 }"
+    );
+}
+
+#[test]
+fn versioned_plain_data_projects_directly() {
+    dreck!(owner, arena);
+
+    let style = arena.add(Style::default());
+    {
+        let write = style.write(&mut owner, &arena);
+        project!(write, width).set(2);
+        *project!(write, width).get_mut() += 1;
+        assert_eq!(project!(write, color).replace(4), 0);
+    }
+    let style = style.borrow(&owner);
+    assert_eq!((*style.width, style.width.version()), (3, 2));
+    assert_eq!((*style.color, style.color.version()), (4, 1));
+}
@@ -0,0 +1,291 @@
+//! Every situation in which allocating, rooting or borrowing panics, reached through the fallible
+//! variants instead.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::HashMap,
+    pin::pin,
+    rc::Rc,
+};
+
+use dreck::{sys::GcUnavailable, *};
+
+thread_local! {
+    static FAIL_ALLOC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// An allocator which fails every allocation of the current thread while `FAIL_ALLOC` is set.
+struct FailingAlloc;
+
+unsafe impl GlobalAlloc for FailingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if FAIL_ALLOC.with(|x| x.get()) {
+            std::ptr::null_mut()
+        } else {
+            System.alloc(layout)
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: FailingAlloc = FailingAlloc;
+
+/// Run the function with every allocation failing.
+fn failing_alloc<R>(f: impl FnOnce() -> R) -> R {
+    FAIL_ALLOC.with(|x| x.set(true));
+    let res = f();
+    FAIL_ALLOC.with(|x| x.set(false));
+    res
+}
+
+fn unavailable(reason: GcUnavailable) -> Option<Error> {
+    Some(Error::ArenaUnavailable { reason })
+}
+
+/// A value which tries to use the arena it is allocated in when traced and dropped.
+pub struct UsesArena<'a, 'gc, 'own> {
+    pub arena: &'a Arena<'own>,
+    pub child: Gc<'gc, 'own, u32>,
+    pub results: Rc<Cell<[Option<Error>; 4]>>,
+}
+
+impl UsesArena<'_, '_, '_> {
+    fn errors(&self) -> [Option<Error>; 2] {
+        let guard = pin!(RootGuard::new());
+        [
+            self.arena.try_add_str("value").err(),
+            self.arena.try_root(self.child, guard).err(),
+        ]
+    }
+}
+
+impl Drop for UsesArena<'_, '_, '_> {
+    fn drop(&mut self) {
+        let [a, b, ..] = self.results.get();
+        let [c, d] = self.errors();
+        self.results.set([a, b, c, d]);
+    }
+}
+
+unsafe impl<'a, 'gc, 'own> Trace<'own> for UsesArena<'a, 'gc, 'own> {
+    type Gc<'to> = UsesArena<'a, 'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        let [.., c, d] = self.results.get();
+        let [a, b] = self.errors();
+        self.results.set([a, b, c, d]);
+        marker.mark(self.child);
+    }
+}
+
+#[test]
+fn allocation_failure() {
+    dreck!(owner, arena);
+
+    failing_alloc(|| {
+        assert_eq!(
+            arena.try_add(1u32).err().map(|e| e.error()),
+            Some(Error::AllocationFailed)
+        );
+        assert_eq!(
+            arena.try_add_str("value").err(),
+            Some(Error::AllocationFailed)
+        );
+        assert_eq!(
+            arena.try_add_from_iter([1u32, 2, 3]).err(),
+            Some(Error::AllocationFailed)
+        );
+    });
+
+    // Nothing was allocated, so the arena is still consistent.
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, 0);
+    assert_eq!(arena.try_add_str("value").unwrap().borrow(&owner), "value");
+}
+
+#[test]
+fn while_walking() {
+    dreck!(_owner, arena);
+
+    arena.add(1u32);
+    let mut errors = Vec::new();
+    unsafe {
+        arena.unsafe_arena().for_each_object(|_| {
+            errors.push(arena.try_add(2u32).err().map(|e| e.error()));
+            errors.push(arena.try_add_from_iter([1u32]).err());
+        })
+    };
+    assert_eq!(errors, [unavailable(GcUnavailable::Walking); 2]);
+}
+
+#[test]
+fn while_marked() {
+    dreck!(_owner, arena);
+
+    unsafe { arena.unsafe_arena().mark_all() };
+    assert_eq!(
+        arena.try_add(1u32).err().map(|e| e.error()),
+        unavailable(GcUnavailable::Marked)
+    );
+    assert_eq!(
+        arena.try_add_str("value").err(),
+        unavailable(GcUnavailable::Marked)
+    );
+    unsafe { arena.unsafe_arena().finish_sweep() };
+    assert!(arena.try_add(1u32).is_ok());
+}
+
+#[test]
+fn while_tracing_and_dropping() {
+    dreck!(_owner, arena);
+    let results = Rc::new(Cell::new([None; 4]));
+
+    let value = arena.add(UsesArena {
+        arena: &arena,
+        child: arena.add(1),
+        results: results.clone(),
+    });
+    {
+        let guard = pin!(RootGuard::new());
+        root!(&arena, guard, value);
+        unsafe { arena.unsafe_arena().collect_full() };
+    }
+    unsafe { arena.unsafe_arena().collect_full() };
+
+    assert_eq!(
+        results.get(),
+        [
+            unavailable(GcUnavailable::Tracing),
+            unavailable(GcUnavailable::Tracing),
+            unavailable(GcUnavailable::Dropping),
+            unavailable(GcUnavailable::Dropping),
+        ]
+    );
+}
+
+#[test]
+fn borrow_during_speculation() {
+    dreck!(owner, arena);
+
+    // `HashMap` doesn't support snapshots, `u32` does.
+    let map = arena.add(HashMap::from([(1u32, 2u32)]));
+    let count = arena.add(1u32);
+    let guard = pin!(RootGuard::new());
+    let map = root!(&arena, guard, map);
+    let guard = pin!(RootGuard::new());
+    let count = root!(&arena, guard, count);
+
+    let speculation = arena.begin_speculation();
+    assert_eq!(
+        map.try_borrow_mut(&mut owner, &speculation).err(),
+        Some(Error::SnapshotUnsupported {
+            type_name: std::any::type_name::<HashMap<u32, u32>>()
+        })
+    );
+    *count.try_borrow_mut(&mut owner, &speculation).unwrap() += 1;
+    speculation.rollback(&mut owner);
+
+    assert_eq!(map.borrow(&owner)[&1], 2);
+    assert_eq!(*count.borrow(&owner), 1);
+}
+
+#[test]
+fn borrow_conflict() {
+    dreck!(owner, arena);
+
+    let cell = arena.add(GcRefCell::new(1u32));
+    let borrow = cell.borrow(&owner).borrow();
+    assert_eq!(
+        cell.try_borrow_mut_cell(&owner, &arena).err(),
+        Some(Error::BorrowConflict)
+    );
+    drop(borrow);
+    assert!(cell.try_borrow_mut_cell(&owner, &arena).is_ok());
+}
+
+pub struct Node<'gc, 'own> {
+    pub value: u32,
+    pub next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+    pub cell: Gc<'gc, 'own, GcRefCell<Vec<Gc<'gc, 'own, u32>>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.next.trace(marker);
+        marker.mark(self.cell);
+    }
+}
+
+/// A workload using the infallible methods in every phase of the collector, which must never
+/// reach any of the remaining panics.
+#[test]
+fn normal_operation() {
+    dreck!(owner, arena);
+    arena.set_max_step_work(Some(64));
+
+    let head = arena.add(Node {
+        value: 0,
+        next: None,
+        cell: arena.add(GcRefCell::new(Vec::new())),
+    });
+    let guard = pin!(RootGuard::new());
+    let head = root!(&arena, guard, head);
+
+    for i in 1..2000u32 {
+        let cell = arena.add(GcRefCell::new(vec![arena.add(i)]));
+        let head_mut = head.borrow_mut(&mut owner, &arena);
+        let next = head_mut.next.take();
+        head_mut.next = Some(arena.add(Node {
+            value: i,
+            next,
+            cell,
+        }));
+        let number = arena.add(i);
+        head.borrow(&owner)
+            .cell
+            .borrow_mut_cell(&owner, &arena)
+            .push(number);
+        arena.add_str("text");
+        arena.add_from_iter([i; 4]);
+        if i % 500 == 0 {
+            // Drop the list so the sweep has something to free.
+            head.borrow_mut(&mut owner, &arena).next = None;
+        }
+        arena.collect(&owner);
+    }
+    arena.collect_full(&owner);
+
+    let mut len = 0;
+    let mut cur = head.borrow(&owner).next;
+    while let Some(node) = cur {
+        assert_eq!(
+            *node.borrow(&owner).cell.borrow(&owner).borrow()[0].borrow(&owner),
+            node.borrow(&owner).value
+        );
+        len += 1;
+        cur = node.borrow(&owner).next;
+    }
+    assert_eq!(len, 499);
+    assert_eq!(head.borrow(&owner).cell.borrow(&owner).borrow().len(), 1999);
+}
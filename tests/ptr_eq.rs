@@ -0,0 +1,121 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    pin::pin,
+};
+
+use dreck::{scoped::ScopedArena, *};
+
+#[test]
+fn identity() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(1u32);
+    assert_eq!(a.borrow(&owner), b.borrow(&owner));
+    assert!(!a.ptr_eq(b));
+    assert!(a.ptr_eq(a));
+}
+
+#[test]
+fn rooted() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let guard = pin!(RootGuard::new());
+    let rooted = root!(&arena, guard, a);
+    assert!(rooted.ptr_eq(a));
+
+    let rebound = rebind!(&arena, rooted);
+    assert!(rebound.ptr_eq(rooted));
+    arena.collect_full(&owner);
+    assert_eq!(*rooted.borrow(&owner), 1);
+}
+
+#[test]
+fn scoped() {
+    let mut arena = ScopedArena::new();
+    arena.with(|_, scope| {
+        let a = scope.add(1u32);
+        let b = scope.add(1u32);
+        assert!(!a.ptr_eq(b));
+        assert!(a.ptr_eq(a));
+    });
+}
+
+#[test]
+fn eq_is_identity() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(1u32);
+    assert!(a == a);
+    assert!(a != b);
+    assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+    assert_eq!(a.borrow(&owner), b.borrow(&owner));
+}
+
+#[test]
+fn hash_set() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(1u32);
+    let guard_a = pin!(RootGuard::new());
+    let a = root!(&arena, guard_a, a);
+    let guard_b = pin!(RootGuard::new());
+    let b = root!(&arena, guard_b, b);
+
+    let mut seen = HashSet::new();
+    assert!(seen.insert(a));
+    assert!(seen.insert(b));
+    assert!(!seen.insert(a));
+
+    for _ in 0..1000 {
+        arena.add(0u32);
+    }
+    arena.collect_full(&owner);
+    assert!(seen.contains(&a));
+    assert!(seen.contains(&b));
+    assert_eq!(seen.len(), 2);
+}
+
+#[test]
+fn btree_map() {
+    dreck!(owner, arena);
+
+    let mut guards = (0..8)
+        .map(|_| Box::pin(RootGuard::new()))
+        .collect::<Vec<_>>();
+    let mut names = BTreeMap::new();
+    let mut pointers = Vec::new();
+    for (idx, guard) in guards.iter_mut().enumerate() {
+        let ptr = root!(&arena, guard.as_mut(), arena.add(idx));
+        names.insert(ptr, idx);
+        pointers.push(ptr);
+    }
+
+    arena.collect_full(&owner);
+    for (idx, ptr) in pointers.iter().enumerate() {
+        assert_eq!(names[ptr], idx);
+        assert_eq!(*ptr.borrow(&owner), idx);
+    }
+}
+
+#[test]
+fn scoped_eq() {
+    let mut arena = ScopedArena::new();
+    arena.with(|owner, scope| {
+        let a = scope.add(1u32);
+        let b = scope.add(1u32);
+        assert!(a == a);
+        assert!(a != b);
+
+        let seen = HashSet::from([a]);
+        let order = BTreeMap::from([(a, 0), (b, 1)]);
+        scope.collect_full();
+        assert!(seen.contains(&a));
+        assert!(!seen.contains(&b));
+        assert_eq!(order[&b], 1);
+        assert_eq!(*a.borrow(owner), 1);
+    });
+}
@@ -0,0 +1,34 @@
+use std::{pin::pin, ptr::NonNull};
+
+use dreck::*;
+
+/// A user data slot of a C API.
+struct Slot(Option<NonNull<()>>);
+
+#[test]
+fn round_trip() {
+    dreck!(owner, arena);
+
+    let value = arena.add(3u32);
+    let raw = value.into_raw();
+    assert_eq!(raw, value.into_gc_box().cast());
+    let back: Gc<u32> = unsafe { Gc::from_raw(raw) };
+    assert!(back.ptr_eq(value));
+    assert_eq!(back.into_raw(), raw);
+}
+
+#[test]
+fn across_collection() {
+    dreck!(owner, arena);
+
+    let mut slot = Slot(None);
+    let guard = pin!(RootGuard::new());
+    let value = root!(&arena, guard, arena.add(String::from("dreck")));
+    slot.0 = Some(value.into_raw());
+
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    let value: Gc<String> = unsafe { Gc::from_raw(slot.0.unwrap()) };
+    assert_eq!(value.borrow(&owner), "dreck");
+}
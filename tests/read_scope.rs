@@ -0,0 +1,121 @@
+use std::pin::pin;
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    pub value: u64,
+    pub children: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.children.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> ReadSync<'own> for Node<'gc, 'own> {}
+
+fn tree<'gc, 'own>(
+    arena: &'gc Arena<'own>,
+    depth: u32,
+    next: &mut u64,
+) -> Gc<'gc, 'own, Node<'gc, 'own>> {
+    let children = if depth == 0 {
+        Vec::new()
+    } else {
+        (0..2).map(|_| tree(arena, depth - 1, next)).collect()
+    };
+    *next += 1;
+    arena.add(Node {
+        value: *next,
+        children,
+    })
+}
+
+fn sum<'own>(token: ReadToken<'_, 'own>, node: Gc<'_, 'own, Node<'_, 'own>>) -> u64 {
+    let node = token.borrow(node);
+    node.value + node.children.iter().map(|x| sum(token, *x)).sum::<u64>()
+}
+
+/// Sum the tree, forking a thread for every subtree up to the given depth.
+fn par_sum<'s, 'own>(
+    scope: ReadScope<'s, '_, 'own>,
+    node: SharedGc<'s, 'own, Node<'s, 'own>>,
+    fork: u32,
+) -> u64 {
+    let token = scope.token();
+    if fork == 0 {
+        return sum(token, node.get());
+    }
+    let borrow = token.borrow(node.get());
+    let handles = borrow
+        .children
+        .iter()
+        .map(|x| {
+            let child = token.share(*x);
+            scope.spawn(move |_| par_sum(scope, child, fork - 1))
+        })
+        .collect::<Vec<_>>();
+    borrow.value + handles.into_iter().map(|x| x.join().unwrap()).sum::<u64>()
+}
+
+#[test]
+fn parallel_sum() {
+    dreck!(owner, arena);
+
+    let mut next = 0;
+    let root = tree(&arena, 14, &mut next);
+    let guard = pin!(RootGuard::new());
+    let root = root!(&arena, guard, root);
+    let expected = next * (next + 1) / 2;
+
+    let total = arena.scope_read(&owner, |scope| {
+        let root = scope.token().share(root);
+        par_sum(scope, root, 3)
+    });
+    assert_eq!(total, expected);
+
+    // The heap can be mutated again afterwards.
+    root.borrow_mut(&mut owner, &arena).value = 0;
+    arena.collect_full(&owner);
+    let total = arena.scope_read(&owner, |scope| sum(scope.token(), root));
+    assert_eq!(total, expected - next);
+}
+
+#[test]
+fn strings() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let list = root!(
+        &arena,
+        guard,
+        arena.add(vec![arena.add_str("a"), arena.add_str("bc")])
+    );
+
+    let len = arena.scope_read(&owner, |scope| {
+        let list = scope.token().share(list);
+        let handles = (0..4)
+            .map(|_| {
+                scope.spawn(move |token| {
+                    let list = token.borrow(list.get());
+                    list.iter().map(|x| token.borrow(*x).len()).sum::<usize>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|x| x.join().unwrap())
+            .sum::<usize>()
+    });
+    assert_eq!(len, 12);
+}
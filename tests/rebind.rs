@@ -0,0 +1,45 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn pointer() {
+    dreck!(owner, arena);
+
+    let ptr = {
+        let guard = pin!(RootGuard::new());
+        let ptr = root!(&arena, guard, arena.add(3u32));
+        rebind!(&arena, ptr)
+    };
+    assert_eq!(*ptr.borrow(&owner), 3);
+
+    let str = {
+        let guard = pin!(RootGuard::new());
+        let str = root!(&arena, guard, arena.add_str("unsized"));
+        rebind!(&arena, str)
+    };
+    assert_eq!(str.borrow(&owner), "unsized");
+    assert_eq!(arena.rebind_gc(str).borrow(&owner), "unsized");
+}
+
+#[test]
+fn value() {
+    dreck!(owner, arena);
+
+    let values = {
+        let guard = pin!(RootGuard::new());
+        let list = root!(
+            &arena,
+            guard,
+            arena.add(vec![arena.add(1u32), arena.add(2u32)])
+        );
+        let values = list.borrow(&owner).clone();
+        rebind!(&arena, values)
+    };
+    let values = values.iter().map(|x| *x.borrow(&owner));
+    assert_eq!(values.collect::<Vec<_>>(), [1, 2]);
+
+    let pair = arena.rebind_value((arena.add(3u32), 4u32));
+    assert_eq!(*pair.0.borrow(&owner), 3);
+    assert_eq!(arena.rebind_to(5u32), 5);
+}
@@ -0,0 +1,120 @@
+use std::{cell::Cell, collections::BTreeMap, pin::pin, rc::Rc};
+
+use dreck::*;
+
+/// A fake file descriptor, counting how often it was released.
+pub struct Fd(pub Rc<Cell<usize>>);
+
+impl ResourceKind for Fd {
+    const KIND: &'static str = "fd";
+}
+
+impl Drop for Fd {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+pub struct Buffer(pub u32);
+
+impl ResourceKind for Buffer {
+    const KIND: &'static str = "buffer";
+}
+
+pub struct Handles {
+    pub fd: Resource<Fd>,
+    pub buffer: Resource<Buffer>,
+}
+
+unsafe impl<'own> Trace<'own> for Handles {
+    type Gc<'gc> = Handles;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn ledger() {
+    dreck!(owner, arena);
+    let released = Rc::new(Cell::new(0));
+
+    let handles = arena.add(Handles {
+        fd: Resource::new(&arena, Fd(released.clone())),
+        buffer: Resource::new(&arena, Buffer(3)),
+    });
+    let guard = pin!(RootGuard::new());
+    let handles = root!(&arena, guard, handles);
+    assert_eq!(
+        arena.open_resources(),
+        BTreeMap::from([("buffer", 1), ("fd", 1)])
+    );
+    assert_eq!(handles.borrow(&owner).buffer.0, 3);
+
+    let fd = handles.borrow_mut(&mut owner, &arena).fd.close().unwrap();
+    assert!(handles.borrow(&owner).fd.is_closed());
+    assert!(handles.borrow(&owner).fd.get().is_none());
+    drop(fd);
+    assert_eq!(released.get(), 1);
+    assert!(handles.borrow_mut(&mut owner, &arena).fd.close().is_none());
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.open_resources(), BTreeMap::from([("buffer", 1)]));
+
+    // An unreachable resource is dropped by the sweep.
+    arena.add(Resource::new(&arena, Fd(released.clone())));
+    assert_eq!(arena.open_resources()["fd"], 1);
+    arena.collect_full(&owner);
+    assert_eq!(released.get(), 2);
+    assert_eq!(arena.open_resources(), BTreeMap::from([("buffer", 1)]));
+}
+
+#[test]
+fn audit_closed() {
+    dreck!(owner, arena);
+    arena.set_audit_resources(true);
+
+    let handles = arena.add(Handles {
+        fd: Resource::new(&arena, Fd(Rc::default())),
+        buffer: Resource::new(&arena, Buffer(3)),
+    });
+    let guard = pin!(RootGuard::new());
+    let handles = root!(&arena, guard, handles);
+
+    let handles = handles.borrow_mut(&mut owner, &arena);
+    handles.fd.close();
+    handles.buffer.close();
+}
+
+#[test]
+fn audit_collected() {
+    dreck!(owner, arena);
+    arena.set_audit_resources(true);
+
+    // Resources freed by a collection before the arena is dropped pass the audit.
+    arena.add(Resource::new(&arena, Buffer(1)));
+    arena.collect_full(&owner);
+
+    // As do resources which are not held by an object of the arena.
+    let _buffer = Resource::new(&arena, Buffer(2));
+}
+
+#[test]
+#[should_panic(expected = "dropping the arena dropped 1 resources which were never closed")]
+fn audit_open() {
+    dreck!(owner, arena);
+    arena.set_audit_resources(true);
+
+    let handles = arena.add(Handles {
+        fd: Resource::new(&arena, Fd(Rc::default())),
+        buffer: Resource::new(&arena, Buffer(3)),
+    });
+    let guard = pin!(RootGuard::new());
+    let handles = root!(&arena, guard, handles);
+    handles.borrow_mut(&mut owner, &arena).fd.close();
+}
@@ -0,0 +1,76 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+#[test]
+fn count_follows_guards() {
+    dreck!(owner, arena);
+
+    assert_eq!(arena.root_count(), 0);
+    let a = arena.add(1);
+    let guard_a = pin!(RootGuard::new());
+    let a = root!(&arena, guard_a, a);
+    assert_eq!(arena.root_count(), 1);
+
+    {
+        let b = arena.add(2);
+        let guard_b = pin!(RootGuard::new());
+        let _b = root!(&arena, guard_b, b);
+        let c = arena.add(3);
+        let guard_c = pin!(RootGuard::new());
+        let _c = root!(&arena, guard_c, c);
+        assert_eq!(arena.root_count(), 3);
+        assert_eq!(arena.stats().root_count, 3);
+    }
+
+    assert_eq!(arena.root_count(), 1);
+    arena.collect_full(&owner);
+    assert_eq!(*a.borrow(&owner), 1);
+    assert_eq!(arena.root_count(), 1);
+}
+
+#[test]
+fn limit_hook() {
+    dreck!(_owner, arena);
+
+    let fired = Rc::new(Cell::new(Vec::new()));
+    let fired_clone = fired.clone();
+    arena.set_root_limit(2, move |count| {
+        let mut v = fired_clone.take();
+        v.push(count);
+        fired_clone.set(v);
+    });
+
+    let root_one = |n| {
+        let ptr = arena.add(n);
+        let guard = pin!(RootGuard::new());
+        let _ptr = root!(&arena, guard, ptr);
+        arena.root_count()
+    };
+
+    let a = arena.add(0);
+    let guard_a = pin!(RootGuard::new());
+    let _a = root!(&arena, guard_a, a);
+    let b = arena.add(1);
+    let guard_b = pin!(RootGuard::new());
+    let _b = root!(&arena, guard_b, b);
+    assert_eq!(fired.take(), Vec::<usize>::new());
+
+    assert_eq!(root_one(2), 3);
+    assert_eq!(fired.take(), vec![3]);
+
+    // Crossing the limit again fires again.
+    assert_eq!(root_one(3), 3);
+    assert_eq!(fired.take(), vec![3]);
+}
+
+#[test]
+fn guard_outlives_arena() {
+    let mut guard = pin!(RootGuard::new());
+    {
+        dreck!(_owner, arena);
+        let ptr = arena.add(0);
+        root!(&arena, guard.as_mut(), ptr);
+        assert_eq!(arena.root_count(), 1);
+    }
+}
@@ -0,0 +1,74 @@
+use std::pin::pin;
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    value: u32,
+    next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.next.trace(marker)
+    }
+}
+
+#[test]
+fn set_in_loop() {
+    dreck!(owner, arena);
+
+    arena.add(0u64);
+    let one = arena.stats().total_allocated;
+    arena.collect_full(&owner);
+
+    let mut guard = pin!(RootGuard::new());
+    let mut best = guard.as_mut().set(&arena, arena.add(0u64));
+    for i in [5u64, 3, 9, 1, 12, 4] {
+        let candidate = arena.add(i);
+        if *candidate.borrow(&owner) > *best.borrow(&owner) {
+            best = guard.as_mut().set(&arena, candidate);
+        }
+        arena.collect_full(&owner);
+        // Only the current best is rooted, the previous best and the candidates are freed.
+        assert_eq!(arena.stats().total_allocated, one);
+        assert_eq!(arena.root_count(), 1);
+    }
+    assert_eq!(*best.borrow(&owner), 12);
+}
+
+#[test]
+fn walk_list_with_one_guard() {
+    dreck!(owner, arena);
+
+    let mut head = None;
+    for value in (0..5).rev() {
+        head = Some(arena.add(Node { value, next: head }));
+    }
+
+    let mut guard = pin!(RootGuard::new());
+    let mut cur = root!(&arena, guard.as_mut(), head.unwrap());
+    let mut seen = vec![cur.borrow(&owner).value];
+    let size = arena.stats().total_allocated;
+    while let Some(next) = cur.borrow(&owner).next {
+        // `root!` rebinds the pointer before borrowing the guard, so it can be reached through
+        // the pointer the guard roots.
+        cur = root!(&arena, guard.as_mut(), next);
+        arena.collect_full(&owner);
+        seen.push(cur.borrow(&owner).value);
+        // The nodes before the current one are no longer rooted.
+        assert_eq!(
+            arena.stats().total_allocated,
+            size / 5 * (5 - cur.borrow(&owner).value as usize)
+        );
+    }
+    assert_eq!(seen, [0, 1, 2, 3, 4]);
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "root-provenance")]
+
+use std::pin::pin;
+
+use dreck::{
+    sys::{check_rooted, UnsafeArena, UnsafeRootGuard},
+    *,
+};
+
+#[test]
+fn dropped_root_is_suspect() {
+    let arena = unsafe { UnsafeArena::new() };
+    unsafe {
+        let ptr = arena.add(1u32);
+        {
+            let guard = pin!(UnsafeRootGuard::new());
+            arena.root(guard, ptr);
+        }
+        // Nothing could have been freed without the collector doing any work.
+        assert!(check_rooted(ptr.cast()).is_none());
+
+        arena.step();
+        let suspect = check_rooted(ptr.cast()).expect("pointer should be suspect");
+        assert!(!suspect.freed);
+        assert!(suspect
+            .to_string()
+            .contains("pointer used after its last root"));
+
+        arena.collect_full();
+        assert!(check_rooted(ptr.cast()).unwrap().freed);
+    }
+}
+
+#[test]
+fn live_roots_are_not_suspect() {
+    let arena = unsafe { UnsafeArena::new() };
+    unsafe {
+        let ptr = arena.add(1u32);
+        let outer = pin!(UnsafeRootGuard::new());
+        arena.root(outer, ptr);
+        {
+            let inner = pin!(UnsafeRootGuard::new());
+            arena.root(inner, ptr);
+        }
+        arena.collect_full();
+        assert!(check_rooted(ptr.cast()).is_none());
+
+        let never_rooted = arena.add(2u32);
+        arena.step();
+        assert!(check_rooted(never_rooted.cast()).is_none());
+    }
+}
+
+#[test]
+fn transferred_roots_are_not_suspect() {
+    let arena = unsafe { UnsafeArena::new() };
+    unsafe {
+        let ptr = arena.add(1u32);
+        let mut to = pin!(UnsafeRootGuard::new());
+        {
+            let mut from = pin!(UnsafeRootGuard::new());
+            arena.root(from.as_mut(), ptr);
+            UnsafeRootGuard::transfer(from, to.as_mut());
+        }
+        arena.collect_full();
+        assert!(check_rooted(ptr.cast()).is_none());
+    }
+}
+
+#[test]
+fn rerooting_clears_suspect() {
+    let arena = unsafe { UnsafeArena::new() };
+    unsafe {
+        let ptr = arena.add(1u32);
+        {
+            let guard = pin!(UnsafeRootGuard::new());
+            arena.root(guard, ptr);
+        }
+        arena.step();
+        assert!(check_rooted(ptr.cast()).is_some());
+
+        let guard = pin!(UnsafeRootGuard::new());
+        arena.root(guard, ptr);
+        assert!(check_rooted(ptr.cast()).is_none());
+    }
+}
+
+#[test]
+fn reachable_from_heap_is_not_suspect() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let list = root!(&arena, guard, arena.add(Vec::<Gc<u32>>::new()));
+    {
+        let guard = pin!(RootGuard::new());
+        let item = root!(&arena, guard, arena.add(3u32));
+        list.borrow_mut(&mut owner, &arena).push(item);
+    }
+    arena.collect_full(&owner);
+
+    let item = list.borrow(&owner)[0];
+    assert!(arena.debug_check_rooted(item).is_none());
+    assert_eq!(*item.borrow(&owner), 3);
+}
+
+#[test]
+fn smuggled_pointer_is_suspect() {
+    dreck!(owner, arena);
+
+    let smuggled: Gc<'static, '_, String> = {
+        let guard = pin!(RootGuard::new());
+        let value = root!(&arena, guard, arena.add(String::from("smuggled")));
+        unsafe { std::mem::transmute::<Gc<String>, Gc<'static, '_, String>>(value) }
+    };
+    arena.collect_full(&owner);
+
+    let suspect = arena
+        .debug_check_rooted(smuggled)
+        .expect("pointer should be suspect");
+    assert!(suspect.freed);
+}
+
+#[test]
+fn dropping_arena_forgets_roots() {
+    let ptr = unsafe {
+        let arena = UnsafeArena::new();
+        let ptr = arena.add(1u32);
+        {
+            let guard = pin!(UnsafeRootGuard::new());
+            arena.root(guard, ptr);
+        }
+        arena.step();
+        assert!(check_rooted(ptr.cast()).is_some());
+        ptr
+    };
+    assert!(check_rooted(ptr.cast()).is_none());
+}
@@ -0,0 +1,103 @@
+use std::{cell::Cell, mem, pin::Pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+pub struct Counted(Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+const ROOTS: usize = 10_000;
+
+fn wake<'own>(arena: &Arena<'own>) {
+    while arena.unsafe_arena().phase() != Phase::Wake {
+        unsafe { arena.unsafe_arena().step() };
+    }
+}
+
+#[test]
+fn roots_scanned_incrementally() {
+    dreck!(owner, arena);
+
+    let mut guards = (0..ROOTS)
+        .map(|_| Box::pin(RootGuard::new()))
+        .collect::<Vec<_>>();
+    for (x, guard) in guards.iter_mut().enumerate() {
+        let ptr = arena.add(x);
+        arena.root(ptr, guard.as_mut());
+    }
+
+    let allocated = arena.stats().total_allocated;
+    wake(&arena);
+    let mut steps = 0;
+    while arena.unsafe_arena().phase() == Phase::Wake {
+        let work = unsafe { arena.unsafe_arena().step() };
+        assert!(work <= mem::size_of::<RootGuard>());
+        steps += 1;
+    }
+    assert!(steps > ROOTS);
+
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, allocated);
+}
+
+#[test]
+fn change_roots_while_scanning() {
+    dreck!(owner, arena);
+    let dropped = Rc::new(Cell::new(0));
+
+    let mut guards: Vec<Pin<Box<RootGuard>>> = Vec::new();
+    for _ in 0..ROOTS {
+        let mut guard = Box::pin(RootGuard::new());
+        arena.root(arena.add(Counted(dropped.clone())), guard.as_mut());
+        guards.push(guard);
+    }
+
+    wake(&arena);
+    for _ in 0..ROOTS / 2 {
+        unsafe { arena.unsafe_arena().step() };
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Wake);
+
+    // Drop every other guard, both scanned and unscanned, and add new roots.
+    let mut i = 0;
+    guards.retain(|_| {
+        i += 1;
+        i % 2 == 0
+    });
+    for _ in 0..ROOTS / 2 {
+        let mut guard = Box::pin(RootGuard::new());
+        arena.root(arena.add(Counted(dropped.clone())), guard.as_mut());
+        guards.push(guard);
+    }
+
+    // Finish the current cycle, only unrooted values may be freed.
+    while arena.unsafe_arena().phase() != Phase::Sleep {
+        unsafe { arena.unsafe_arena().step() };
+    }
+    assert!(dropped.get() <= ROOTS / 2);
+
+    arena.collect_full(&owner);
+    assert_eq!(dropped.get(), ROOTS / 2);
+    assert_eq!(arena.root_count(), ROOTS);
+
+    drop(guards);
+    arena.collect_full(&owner);
+    assert_eq!(dropped.get(), ROOTS * 3 / 2);
+}
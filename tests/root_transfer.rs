@@ -0,0 +1,115 @@
+use std::{cell::Cell, pin::pin, pin::Pin, rc::Rc};
+
+use dreck::*;
+
+pub struct Counted {
+    value: u32,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'to> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+fn produce<'r, 'own>(
+    arena: &Arena<'own>,
+    guard: Pin<&'r mut RootGuard>,
+    drops: &Rc<Cell<usize>>,
+) -> Gc<'r, 'own, Counted> {
+    let value = arena.add(Counted {
+        value: 42,
+        drops: drops.clone(),
+    });
+    arena.root(value, guard)
+}
+
+#[test]
+fn reroot_across_function() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut consumer = pin!(RootGuard::new());
+    let value = {
+        let producer = pin!(RootGuard::new());
+        let value = produce(&arena, producer, &drops);
+        assert_eq!(arena.root_count(), 1);
+        arena.reroot(value, consumer.as_mut())
+    };
+    assert_eq!(arena.root_count(), 1);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(value.borrow(&owner).value, 42);
+
+    RootGuard::transfer(consumer.as_mut(), pin!(RootGuard::new()));
+    assert_eq!(arena.root_count(), 0);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn transfer_keeps_rooted() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut consumer = pin!(RootGuard::new());
+    {
+        let mut producer = pin!(RootGuard::new());
+        produce(&arena, producer.as_mut(), &drops);
+        RootGuard::transfer(producer, consumer.as_mut());
+        assert_eq!(arena.root_count(), 1);
+    }
+    assert_eq!(arena.root_count(), 1);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+
+    {
+        let other = pin!(RootGuard::new());
+        let value = arena.add(Counted {
+            value: 1,
+            drops: drops.clone(),
+        });
+        arena.root(value, other);
+        assert_eq!(arena.root_count(), 2);
+    }
+
+    // Transferring into a guard which already roots a pointer unroots that pointer.
+    let mut other = pin!(RootGuard::new());
+    produce(&arena, other.as_mut(), &drops);
+    assert_eq!(arena.root_count(), 2);
+    RootGuard::transfer(other, consumer.as_mut());
+    assert_eq!(arena.root_count(), 1);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn transfer_empty_guard() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let mut guard = pin!(RootGuard::new());
+    produce(&arena, guard.as_mut(), &drops);
+    RootGuard::transfer(pin!(RootGuard::new()), guard);
+    assert_eq!(arena.root_count(), 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+}
@@ -0,0 +1,80 @@
+use std::pin::pin;
+
+use dreck::{sys::Phase, *};
+
+/// Returns the number of bytes allocated for a `u64`.
+fn object_size<'own>(owner: &Owner<'own>, arena: &mut Arena<'own>) -> usize {
+    let before = arena.stats().total_allocated;
+    arena.add(0u64);
+    let size = arena.stats().total_allocated - before;
+    arena.collect_full(owner);
+    size
+}
+
+#[test]
+fn truncate_frees_exactly_truncated() {
+    dreck!(owner, arena);
+    let one = object_size(&owner, &mut arena);
+
+    let mut roots = pin!(RootVec::new());
+    assert!(roots.is_empty());
+    for i in 0..500u64 {
+        let ptr = roots.as_ref().push(&arena, arena.add(i));
+        assert_eq!(*ptr.borrow(&owner), i);
+        if i == 250 {
+            arena.collect_full(&owner);
+        }
+    }
+    assert_eq!(roots.len(), 500);
+    // A single guard roots all the pointers.
+    assert_eq!(arena.root_count(), 1);
+    let size = arena.stats().total_allocated;
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, size);
+
+    roots.as_mut().truncate(200);
+    assert_eq!(roots.len(), 200);
+    arena.collect_full(&owner);
+    assert_eq!(size - arena.stats().total_allocated, 300 * one);
+
+    roots.as_mut().clear();
+    arena.collect_full(&owner);
+    assert_eq!(size - arena.stats().total_allocated, 500 * one);
+}
+
+#[test]
+fn kept_values_survive() {
+    dreck!(owner, arena);
+
+    let mut roots = pin!(RootVec::new());
+    for round in 0..3u32 {
+        let kept: Vec<_> = (0..100)
+            .map(|i| roots.as_ref().push(&arena, arena.add(round * 1000 + i)))
+            .collect();
+        arena.collect_full(&owner);
+        for (i, ptr) in kept.iter().enumerate() {
+            assert_eq!(*ptr.borrow(&owner), round * 1000 + i as u32);
+        }
+        roots.as_mut().clear();
+    }
+}
+
+#[test]
+fn push_while_tracing() {
+    dreck!(owner, arena);
+
+    let roots = pin!(RootVec::new());
+    roots.as_ref().push(&arena, arena.add(0u32));
+    unsafe {
+        arena.unsafe_arena().finish_sweep();
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        // Trace the slots object, so the pointer pushed next must be marked by the push.
+        arena.unsafe_arena().step();
+        assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+    }
+    let ptr = roots.as_ref().push(&arena, arena.add(5u32));
+    arena.collect_full(&owner);
+    assert_eq!(*ptr.borrow(&owner), 5);
+}
@@ -0,0 +1,47 @@
+use std::pin::pin;
+
+use dreck::*;
+
+fn len_of<S: AsRef<str>>(value: S) -> usize {
+    value.as_ref().len()
+}
+
+fn describe<T: std::fmt::Display>(value: T) -> String {
+    format!("<{}>", value)
+}
+
+#[test]
+fn format() {
+    dreck!(owner, arena);
+
+    let name = arena.add(String::from("dreck"));
+    let rooted = name.with(&owner);
+    assert_eq!(format!("{}", rooted), "dreck");
+    assert_eq!(format!("{:?}", rooted), "\"dreck\"");
+    assert_eq!(describe(rooted), "<dreck>");
+}
+
+#[test]
+fn as_ref() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let name = root!(&arena, guard, arena.add(String::from("dreck")));
+    arena.collect_full(&owner);
+
+    assert_eq!(len_of(name.with(&owner)), 5);
+    assert!(name.with(&owner).starts_with("dr"));
+    assert!(name.with(&owner).gc().ptr_eq(name));
+}
+
+#[test]
+fn eq_compares_values() {
+    dreck!(owner, arena);
+
+    let a = arena.add(3u32);
+    let b = arena.add(3u32);
+    let c = arena.add(4u32);
+    assert!(a != b);
+    assert_eq!(a.with(&owner), b.with(&owner));
+    assert_ne!(a.with(&owner), c.with(&owner));
+}
@@ -0,0 +1,97 @@
+use std::{
+    pin::pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use dreck::*;
+
+pub struct Stats<'own> {
+    counters: Sampled<'own, [u64; 4]>,
+}
+
+unsafe impl<'own> Trace<'own> for Stats<'own> {
+    type Gc<'gc> = Stats<'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.counters.trace(marker)
+    }
+}
+
+pub struct Large<'own> {
+    values: Sampled<'own, [u64; 512]>,
+}
+
+unsafe impl<'own> Trace<'own> for Large<'own> {
+    type Gc<'gc> = Large<'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn survives_collection() {
+    dreck!(owner, arena);
+
+    let stats = arena.add(Stats {
+        counters: Sampled::new([0; 4]),
+    });
+    let guard = pin!(RootGuard::new());
+    let stats = root!(&arena, guard, stats);
+
+    stats.borrow(&owner).counters.write(&owner, [1, 2, 3, 4]);
+    arena.collect_full(&owner);
+    assert_eq!(stats.borrow(&owner).counters.sample(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn no_torn_reads() {
+    dreck!(owner, arena);
+
+    let stats = arena.add(Large {
+        values: Sampled::new([0; 512]),
+    });
+    let values = &stats.borrow(&owner).values;
+    let samples = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        let samplers = (0..2)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut last = 0;
+                    while samples.load(Ordering::Relaxed) < 200_000 {
+                        let value = values.sample();
+                        assert!(value.iter().all(|x| *x == value[0]), "torn read");
+                        assert!(value[0] >= last);
+                        last = value[0];
+                        samples.fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Keep writing until the samplers are done, so the reads overlap with writes.
+        let mut i = 0;
+        while !samplers.iter().all(|x| x.is_finished()) {
+            i += 1;
+            values.write(&owner, [i; 512]);
+        }
+
+        for sampler in samplers {
+            sampler.join().unwrap();
+        }
+    });
+}
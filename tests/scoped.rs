@@ -0,0 +1,141 @@
+use std::{
+    cell::Cell,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use dreck::{scoped::*, *};
+
+pub struct Node<'own> {
+    value: usize,
+    children: Vec<scoped::Gc<'own, Node<'own>>>,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Node<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Node<'own> {
+    type Gc<'to> = Node<'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.children.trace(marker)
+    }
+}
+
+#[test]
+fn mutate_during_cycle() {
+    let mut arena = ScopedArena::new();
+    let drops = Rc::new(Cell::new(0));
+
+    arena.with(|owner, scope| {
+        let root = scope.add(Node {
+            value: 0,
+            children: Vec::new(),
+            drops: drops.clone(),
+        });
+        // Enough allocations to run several incremental cycles, the root is traced early in each
+        // cycle so all later additions to it need the write barrier.
+        for value in 1..2000 {
+            let node = scope.add(Node {
+                value,
+                children: Vec::new(),
+                drops: drops.clone(),
+            });
+            root.borrow_mut(owner, scope).children.push(node);
+            scope.collect();
+        }
+        assert_eq!(drops.get(), 0);
+
+        let values = root
+            .borrow(owner)
+            .children
+            .iter()
+            .map(|x| x.borrow(owner).value)
+            .collect::<Vec<_>>();
+        assert_eq!(values, (1..2000).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn allocate_during_cycle() {
+    let mut arena = ScopedArena::new();
+    let drops = Rc::new(Cell::new(0));
+
+    arena.with(|owner, scope| {
+        let nodes = (0..2000)
+            .map(|value| {
+                let node = scope.add(Node {
+                    value,
+                    children: Vec::new(),
+                    drops: drops.clone(),
+                });
+                scope.collect();
+                node
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(drops.get(), 0);
+        assert!(nodes
+            .iter()
+            .enumerate()
+            .all(|(idx, x)| x.borrow(owner).value == idx));
+    });
+}
+
+#[test]
+fn collect_full_frees_garbage() {
+    let mut arena = ScopedArena::new();
+    let drops = Rc::new(Cell::new(0));
+
+    arena.with(|_, scope| {
+        for value in 0..10 {
+            scope.add(Node {
+                value,
+                children: Vec::new(),
+                drops: drops.clone(),
+            });
+        }
+    });
+    assert_eq!(drops.get(), 0);
+
+    arena.with(|_, scope| {
+        scope.collect_full();
+        assert_eq!(drops.get(), 10);
+    });
+}
+
+#[test]
+fn panic_in_scope_removes_roots() {
+    let mut arena = ScopedArena::new();
+    let drops = Rc::new(Cell::new(0));
+
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        arena.with(|_, scope| {
+            for value in 0..10 {
+                scope.add(Node {
+                    value,
+                    children: Vec::new(),
+                    drops: drops.clone(),
+                });
+            }
+            panic!("scope panicked");
+        })
+    }));
+    assert!(res.is_err());
+    assert_eq!(drops.get(), 0);
+
+    arena.with(|_, scope| {
+        scope.collect_full();
+        assert_eq!(drops.get(), 10);
+    });
+}
@@ -0,0 +1,164 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{scoped::*, *};
+
+pub struct Counted(pub u32, pub Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Counted {
+    type Gc<'gc> = Counted;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+unsafe impl<'own> Erasable<'own> for Counted {}
+
+pub struct List<'own>(pub Vec<scoped::Gc<'own, Counted>>);
+
+unsafe impl<'own> Trace<'own> for List<'own> {
+    type Gc<'gc> = List<'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.0.trace(marker)
+    }
+}
+
+unsafe impl<'own> Erasable<'own> for List<'own> {}
+
+#[test]
+fn persist_across_scopes() {
+    let mut arena = ScopedArena::new();
+    let drops = Rc::new(Cell::new(0));
+
+    arena.with(|owner, scope| {
+        let list = scope.add(List(Vec::new()));
+        for x in 0..4 {
+            let value = scope.add(Counted(x, drops.clone()));
+            list.borrow_mut(owner, scope).0.push(value);
+        }
+        scope.persist(list);
+        // Garbage which is not persisted.
+        scope.add(Counted(10, drops.clone()));
+    });
+
+    arena.with(|owner, scope| {
+        scope.collect_full();
+        assert_eq!(drops.get(), 1);
+        assert!(scope.persisted::<Counted>().is_empty());
+        let lists = scope.persisted::<List>();
+        assert_eq!(lists.len(), 1);
+        let values = lists[0].borrow(owner).0.iter().map(|x| x.borrow(owner).0);
+        assert_eq!(values.collect::<Vec<_>>(), [0, 1, 2, 3]);
+    });
+}
+
+#[test]
+fn into_arena() {
+    let mut arena = ScopedArena::new();
+    let drops = Rc::new(Cell::new(0));
+
+    arena.with(|_, scope| {
+        for x in 0..4 {
+            let value = scope.add(Counted(x, drops.clone()));
+            if x % 2 == 0 {
+                scope.persist(value);
+            }
+        }
+    });
+
+    arena.into_arena(|owner, arena, carried| {
+        assert_eq!(carried.len(), 2);
+
+        // Continue with the full API.
+        for x in 4..1000 {
+            arena.add(Counted(x, drops.clone()));
+            arena.collect(owner);
+        }
+        arena.collect_full(owner);
+        assert_eq!(drops.get(), 998);
+
+        let values = carried
+            .iter()
+            .map(|x| x.downcast::<Counted>().unwrap().borrow(owner).0)
+            .collect::<Vec<_>>();
+        assert_eq!(values, [0, 2]);
+    });
+    assert_eq!(drops.get(), 1000);
+}
+
+#[test]
+fn into_scoped() {
+    let drops = Rc::new(Cell::new(0));
+    let mut scoped = {
+        dreck!(owner, arena);
+        let guard = pin!(RootGuard::new());
+        let kept = root!(&arena, guard, arena.add(Counted(0, drops.clone())));
+        arena.add(Counted(1, drops.clone()));
+        arena.into_scoped(owner, &[kept.into()])
+    };
+
+    scoped.with(|owner, scope| {
+        scope.collect_full();
+        assert_eq!(drops.get(), 1);
+        let values = scope.persisted::<Counted>();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].borrow(owner).0, 0);
+    });
+    drop(scoped);
+    assert_eq!(drops.get(), 2);
+}
+
+pub struct Meters(pub u32);
+
+unsafe impl<'own> Trace<'own> for Meters {
+    type Gc<'gc> = Meters;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+unsafe impl<'own> Erasable<'own> for Meters {}
+
+#[test]
+fn persisted_same_layout() {
+    let mut arena = ScopedArena::new();
+
+    arena.with(|_, scope| {
+        scope.persist(scope.add(Meters(3)));
+        scope.persist(scope.add(4u32));
+    });
+
+    arena.with(|owner, scope| {
+        let meters = scope.persisted::<Meters>();
+        assert_eq!(meters.len(), 1);
+        assert_eq!(meters[0].borrow(owner).0, 3);
+        let values = scope.persisted::<u32>();
+        assert_eq!(values.len(), 1);
+        assert_eq!(*values[0].borrow(owner), 4);
+    });
+}
@@ -0,0 +1,44 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use dreck::scoped::ScopedArena;
+
+/// An allocator which counts the number of live bytes.
+struct CountAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountAlloc = CountAlloc;
+
+#[test]
+fn drop_releases_roots() {
+    // Warm up anything the test harness allocates lazily.
+    drop(ScopedArena::new());
+
+    let before = LIVE.load(Ordering::SeqCst);
+    for _ in 0..10 {
+        let mut arena = ScopedArena::new();
+        arena.with(|_, scope| {
+            for i in 0..100u32 {
+                scope.add(i);
+            }
+        });
+        drop(arena);
+    }
+    assert_eq!(LIVE.load(Ordering::SeqCst), before);
+}
@@ -0,0 +1,112 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn resolve() {
+    dreck!(owner, arena);
+    let mut registry = ScriptHandleRegistry::new(16);
+
+    let a = arena.add(String::from("a"));
+    let b = arena.add(2u32);
+    let handle_a = registry.register(&arena, a.erase()).unwrap();
+    let handle_b = registry.register(&arena, b.erase()).unwrap();
+    assert_ne!(handle_a, handle_b);
+    assert_ne!(handle_a, 0);
+    assert_eq!(registry.len(), 2);
+
+    let resolved = registry.resolve(handle_a, &arena).unwrap();
+    assert!(resolved.ptr_eq(a.erase()));
+    let resolved = registry.resolve(handle_b, &arena).unwrap();
+    assert_eq!(*resolved.downcast::<u32>().unwrap().borrow(&owner), 2);
+
+    assert_eq!(
+        registry.resolve(0, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Unknown))
+    );
+    assert_eq!(
+        registry.resolve(handle_b + 1, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Unknown))
+    );
+}
+
+#[test]
+fn stale_after_collect() {
+    dreck!(owner, arena);
+    let mut registry = ScriptHandleRegistry::new(16);
+
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(1u32));
+    let kept = registry.register(&arena, kept.erase()).unwrap();
+    let garbage = registry.register(&arena, arena.add(2u32).erase()).unwrap();
+
+    arena.collect_full(&owner);
+    assert!(registry.resolve(kept, &arena).is_ok());
+    assert_eq!(
+        registry.resolve(garbage, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Collected))
+    );
+
+    assert_eq!(registry.prune(), 1);
+    assert_eq!(registry.len(), 1);
+    assert_eq!(
+        registry.resolve(garbage, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Collected))
+    );
+}
+
+#[test]
+fn stale_after_release() {
+    dreck!(owner, arena);
+    let mut registry = ScriptHandleRegistry::new(16);
+
+    let value = arena.add(1u32);
+    let handle = registry.register(&arena, value.erase()).unwrap();
+    registry.release(handle).unwrap();
+    assert_eq!(
+        registry.resolve(handle, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Released))
+    );
+    assert_eq!(
+        registry.release(handle),
+        Err(Error::StaleHandle(StaleHandle::Released))
+    );
+    assert!(registry.is_empty());
+
+    // The slot is reused with a new generation, the old handle stays stale.
+    let other = arena.add(2u32);
+    let new = registry.register(&arena, other.erase()).unwrap();
+    assert_ne!(new, handle);
+    assert_eq!(new as u32, handle as u32);
+    assert_eq!(
+        registry.resolve(handle, &arena).err(),
+        Some(Error::StaleHandle(StaleHandle::Unknown))
+    );
+    let resolved = registry.resolve(new, &arena).unwrap();
+    assert_eq!(*resolved.downcast::<u32>().unwrap().borrow(&owner), 2);
+}
+
+#[test]
+fn quota() {
+    dreck!(owner, arena);
+    let mut registry = ScriptHandleRegistry::new(2);
+
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(1u32));
+    let first = registry.register(&arena, kept.erase()).unwrap();
+    registry.register(&arena, arena.add(2u32).erase()).unwrap();
+    assert_eq!(
+        registry.register(&arena, kept.erase()),
+        Err(Error::HandleQuotaExceeded { quota: 2 })
+    );
+
+    // Handles of collected objects are pruned to make room.
+    arena.collect_full(&owner);
+    let third = registry.register(&arena, kept.erase()).unwrap();
+    assert_eq!(registry.len(), 2);
+
+    registry.release(first).unwrap();
+    registry.register(&arena, kept.erase()).unwrap();
+    assert!(registry.resolve(third, &arena).is_ok());
+    assert!(registry.register(&arena, kept.erase()).is_err());
+}
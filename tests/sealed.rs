@@ -0,0 +1,96 @@
+use std::pin::pin;
+
+use dreck::*;
+
+#[repr(C)]
+pub struct Node<'gc, 'own> {
+    value: u32,
+    next: Option<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+#[repr(C)]
+pub struct SealedNode<'gc, 'own> {
+    value: u32,
+    next: Option<SealedGc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.next.trace(marker)
+    }
+}
+
+unsafe impl<'gc, 'own> Seal<'own> for Node<'gc, 'own> {
+    type Sealed = SealedNode<'gc, 'own>;
+}
+
+fn sum<'own>(owner: &Owner<'own>, node: SealedGc<'_, 'own, Node<'_, 'own>>) -> u32 {
+    let node = node.borrow(owner);
+    node.value + node.next.map(|next| sum(owner, next)).unwrap_or(0)
+}
+
+#[test]
+fn traverse() {
+    dreck!(owner, arena);
+
+    let mut list = None;
+    for value in 1..=4 {
+        list = Some(arena.add(Node { value, next: list }));
+    }
+    let list = list.unwrap();
+
+    assert_eq!(sum(&owner, list.seal()), 10);
+    assert_eq!(sum(&owner, SealedGc::from(list)), 10);
+}
+
+#[test]
+fn containers() {
+    dreck!(owner, arena);
+
+    let values = [0u32, 1, 2, 3].map(|x| arena.add(x));
+    let values = arena.add(values).seal();
+
+    let sealed: &[SealedGc<u32>; 4] = values.borrow(&owner);
+    let values = sealed.iter().map(|x| *x.borrow(&owner)).collect::<Vec<_>>();
+    assert_eq!(values, [0, 1, 2, 3]);
+
+    let nested = arena.add([None, Some(arena.add(5u32))]).seal();
+    let nested = nested.borrow(&owner)[1].unwrap();
+    assert_eq!(*nested.borrow(&owner), 5);
+}
+
+#[test]
+fn kept_alive() {
+    dreck!(owner, arena);
+
+    let values = (0..4u32).map(|x| arena.add(x).seal()).collect::<Vec<_>>();
+    let values = arena.add(values);
+    let guard = pin!(RootGuard::new());
+    let values = root!(&arena, guard, values);
+
+    let node = arena.add(Node {
+        value: 3,
+        next: None,
+    });
+    let guard = pin!(RootGuard::new());
+    let node = arena.root_sealed(node.seal(), guard);
+
+    arena.collect_full(&owner);
+
+    let values = values
+        .borrow(&owner)
+        .iter()
+        .map(|x| *x.borrow(&owner))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [0, 1, 2, 3]);
+    assert_eq!(node.borrow(&owner).value, 3);
+}
@@ -0,0 +1,510 @@
+//! Generates the compile tests of the soundness matrix.
+//!
+//! Every handle type of the library, a type which grants access to GC objects, must not outlive
+//! what makes it valid. The matrix instantiates a program for every combination of a handle and
+//! a property, each program trying to break the property with the handle:
+//!
+//! - `escape_return`: return the handle from a function as a `'static` value.
+//! - `outer_binding`: store the handle in a binding which outlives the arena.
+//! - `other_arena`: use the handle with a second arena.
+//! - `after_collect`: use the handle after collecting the arena.
+//! - `after_guard_drop`: use the handle after the guard rooting it was dropped.
+//! - `launder_lifetime`: read a GC pointer stored in the object behind the handle with a
+//!   `'static` gc lifetime.
+//!
+//! The programs are written to `tests/compile_fail/matrix`. For every failing program a control
+//! is written to `tests/compile_pass/matrix`, the same program without the violating step, so a
+//! program can't fail to compile because of a broken template. Cells which are sound by design,
+//! like using a scoped pointer after a collection, are written to `tests/compile_pass/matrix`
+//! instead and cells which don't apply to a handle are skipped.
+//!
+//! The generated programs are checked in. This test fails if they are out of date, run it with
+//! `DRECK_MATRIX=overwrite` to regenerate them followed by `TRYBUILD=overwrite` for the
+//! `compile_fail` test to update the expected errors, and review both diffs.
+//!
+//! # Extending the matrix
+//! A new handle type is added with an entry in [`handles`], describing how to create the handle
+//! and how to read through it, after which every property is covered automatically. If a cell is
+//! sound by design, or doesn't apply, add it to [`Handle::expect`] with the reason. A new property
+//! is added with an entry in [`properties`], rendering the violating program and its control from
+//! the snippets of a handle.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+/// How a cell of the matrix is expected to behave.
+#[derive(Clone, Copy)]
+enum Expect {
+    /// The program must not compile.
+    Fail,
+    /// The program is sound by design and must compile.
+    Pass(&'static str),
+    /// The property doesn't apply to the handle.
+    Skip(&'static str),
+}
+
+/// The snippets of a handle type the programs are rendered from.
+///
+/// Snippets create an arena named `arena{id}` with owner `owner{id}`, where `id` is empty for
+/// the first arena of a program and `2` for the second.
+struct Handle {
+    /// The name of the handle used in file names.
+    name: &'static str,
+    /// Opens an arena, the statements following it are part of the arena scope.
+    open: fn(&str) -> String,
+    /// Closes the arena scope, an expression.
+    close: &'static str,
+    /// Statements binding an unrooted handle `h` in the first arena.
+    make: &'static str,
+    /// Statements binding a handle `h` which is rooted by a guard of the current block.
+    make_rooted: Option<&'static str>,
+    /// An `u32` expression reading through the handle, with the given arena.
+    read: fn(&str) -> String,
+    /// A statement collecting the first arena.
+    collect: &'static str,
+    /// Statements binding a handle `h` to an object holding a `Gc<u32>`, an expression reading
+    /// the pointer through the handle and the name of the pointer type it is read as.
+    nested: Option<(&'static str, &'static str, &'static str)>,
+    /// The cells which don't fail to compile.
+    expect: &'static [(&'static str, Expect)],
+}
+
+fn handles() -> Vec<Handle> {
+    vec![
+        Handle {
+            name: "gc",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "let h = arena.add(1u32);",
+            make_rooted: Some("root!(&arena, let h = arena.add(1u32));"),
+            read: |id| format!("*h.borrow(&owner{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "let h = arena.add(arena.add(1u32));",
+                "*h.borrow(&owner)",
+                "Gc",
+            )),
+            expect: &[],
+        },
+        Handle {
+            name: "rooted",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "let h = arena.add(1u32).with(&owner);",
+            make_rooted: Some("root!(&arena, let h = arena.add(1u32));\nlet h = h.with(&owner);"),
+            read: |id| match id {
+                "" => "*h".to_string(),
+                id => format!("*h.gc().borrow(&owner{id})"),
+            },
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "let h = arena.add(arena.add(1u32)).with(&owner);",
+                "*h",
+                "Gc",
+            )),
+            expect: &[],
+        },
+        Handle {
+            name: "scoped_gc",
+            open: |id| format!("ScopedArena::new().with(|owner{id}, arena{id}| {{"),
+            close: "})",
+            make: "let h = arena.add(1u32);",
+            make_rooted: None,
+            read: |id| format!("*h.borrow(owner{id})"),
+            collect: "arena.collect_full();",
+            nested: None,
+            expect: &[
+                (
+                    "after_collect",
+                    Expect::Pass("scoped pointers are rooted until the scope ends"),
+                ),
+                (
+                    "after_guard_drop",
+                    Expect::Skip("scoped pointers are not rooted by guards"),
+                ),
+                (
+                    "launder_lifetime",
+                    Expect::Skip("scoped pointers have no gc lifetime"),
+                ),
+            ],
+        },
+        Handle {
+            name: "read_token",
+            open: |id| {
+                format!(
+                    "dreck!(owner{id}, arena{id});\n\
+                     root!(&arena{id}, let ptr{id} = arena{id}.add(1u32));\n\
+                     arena{id}.scope_read(&owner{id}, |scope{id}| {{"
+                )
+            },
+            close: "})",
+            make: "let h = scope.token();",
+            make_rooted: None,
+            read: |id| format!("*h.borrow(ptr{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: None,
+            expect: &[
+                (
+                    "after_guard_drop",
+                    Expect::Skip("tokens are not rooted by guards"),
+                ),
+                (
+                    "launder_lifetime",
+                    Expect::Skip("tokens only read through pointers created outside of the scope"),
+                ),
+            ],
+        },
+        Handle {
+            name: "sealed_gc",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "let h = arena.add(1u32).seal();",
+            make_rooted: Some(
+                "let guard = pin!(RootGuard::new());\n\
+                 let h = arena.root_sealed(arena.add(1u32).seal(), guard);",
+            ),
+            read: |id| format!("*h.borrow(&owner{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "let h = arena.add(arena.add(1u32)).seal();",
+                "*h.borrow(&owner)",
+                "SealedGc",
+            )),
+            expect: &[],
+        },
+        Handle {
+            name: "root_vec",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "let roots = pin!(RootVec::new());\n\
+                   let h = roots.as_ref().push(&arena, arena.add(1u32));",
+            make_rooted: Some(
+                "let roots = pin!(RootVec::new());\n\
+                 let h = roots.as_ref().push(&arena, arena.add(1u32));",
+            ),
+            read: |id| format!("*h.borrow(&owner{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "let roots = pin!(RootVec::new());\n\
+                 let h = roots.as_ref().push(&arena, arena.add(arena.add(1u32)));",
+                "*h.borrow(&owner)",
+                "Gc",
+            )),
+            expect: &[(
+                "after_collect",
+                Expect::Pass("pointers pushed to a root vector are rooted until it is cleared"),
+            )],
+        },
+        Handle {
+            name: "value_root",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "stack_root!(&arena, let h = vec![arena.add(1u32)]);",
+            make_rooted: Some("stack_root!(&arena, let h = vec![arena.add(1u32)]);"),
+            read: |id| format!("*h[0].borrow(&owner{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "stack_root!(&arena, let h = vec![arena.add(arena.add(1u32))]);",
+                "*h[0].borrow(&owner)",
+                "Gc",
+            )),
+            expect: &[(
+                "after_collect",
+                Expect::Pass("values rooted on the stack are rooted until the guard drops"),
+            )],
+        },
+        Handle {
+            name: "gc_any",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "let h = arena.add(1u32).erase();",
+            make_rooted: Some("root!(&arena, let h = arena.add(1u32));\nlet h = h.erase();"),
+            read: |id| format!("*h.downcast::<u32>().unwrap().borrow(&owner{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "let h = arena.add(arena.add(1u32)).erase();",
+                "*h.downcast::<Gc<u32>>().unwrap().borrow(&owner)",
+                "Gc",
+            )),
+            expect: &[],
+        },
+        Handle {
+            name: "anchored",
+            open: |id| format!("dreck!(owner{id}, arena{id});"),
+            close: "",
+            make: "let h = Anchored::<u32>::new(&arena, arena.add(1u32));",
+            make_rooted: None,
+            read: |id| format!("*h.get(&arena{id}).borrow(&owner{id})"),
+            collect: "arena.collect_full(&owner);",
+            nested: Some((
+                "let h = Anchored::<Gc<u32>>::new(&arena, arena.add(arena.add(1u32)));",
+                "*h.get(&arena).borrow(&owner)",
+                "Gc",
+            )),
+            expect: &[
+                (
+                    "after_collect",
+                    Expect::Pass("anchored pointers are rooted until the handle is dropped"),
+                ),
+                (
+                    "after_guard_drop",
+                    Expect::Skip("anchored pointers are rooted by the handle itself"),
+                ),
+            ],
+        },
+    ]
+}
+
+/// A property, rendering the violating program and its control for a handle, or `None` if the
+/// handle doesn't have the snippets the property needs.
+struct Property {
+    name: &'static str,
+    render: fn(&Handle) -> Option<(String, String)>,
+}
+
+/// Terminates the close snippet of a handle as a statement.
+fn close_stmt(h: &Handle) -> String {
+    if h.close.is_empty() {
+        String::new()
+    } else {
+        format!("{};", h.close)
+    }
+}
+
+fn properties() -> Vec<Property> {
+    vec![
+        Property {
+            name: "escape_return",
+            render: |h| {
+                let program = |ret: &str| {
+                    format!(
+                        "fn escape() -> impl Sized + 'static {{\n{}\n{}\n{ret}\n{}\n}}\n\n\
+                         fn main() {{\n    let _ = escape();\n}}",
+                        (h.open)(""),
+                        h.make,
+                        h.close,
+                    )
+                };
+                Some((program("h"), program(&(h.read)(""))))
+            },
+        },
+        Property {
+            name: "outer_binding",
+            render: |h| {
+                let program = |value: &str| {
+                    format!(
+                        "fn main() {{\nlet mut outer = Vec::new();\n{{\n{}\n{}\nouter.push({value});\n{}\n}}\n\
+                         let _ = outer;\n}}",
+                        (h.open)(""),
+                        h.make,
+                        close_stmt(h),
+                    )
+                };
+                Some((program("h"), program(&(h.read)(""))))
+            },
+        },
+        Property {
+            name: "other_arena",
+            render: |h| {
+                let program = |id: &str| {
+                    format!(
+                        "fn main() {{\n{}\n{}\n{}\nlet _ = {};\n{}\n{}\n}}",
+                        (h.open)(""),
+                        h.make,
+                        (h.open)("2"),
+                        (h.read)(id),
+                        close_stmt(h),
+                        close_stmt(h),
+                    )
+                };
+                Some((program("2"), program("")))
+            },
+        },
+        Property {
+            name: "after_collect",
+            render: |h| {
+                let program = |collect: &str| {
+                    format!(
+                        "fn main() {{\n{}\n{}\n{collect}\nlet _ = {};\n{}\n}}",
+                        (h.open)(""),
+                        h.make,
+                        (h.read)(""),
+                        close_stmt(h),
+                    )
+                };
+                Some((program(h.collect), program("")))
+            },
+        },
+        Property {
+            name: "after_guard_drop",
+            render: |h| {
+                let make = h.make_rooted?;
+                let read = (h.read)("");
+                let program = |body: &str| {
+                    format!(
+                        "fn main() {{\n{}\n{body}\n{}\n}}",
+                        (h.open)(""),
+                        close_stmt(h),
+                    )
+                };
+                Some((
+                    program(&format!("let h = {{\n{make}\nh\n}};\nlet _ = {read};")),
+                    program(&format!("let _ = {{\n{make}\n{read}\n}};")),
+                ))
+            },
+        },
+        Property {
+            name: "launder_lifetime",
+            render: |h| {
+                let (make, read, ty) = h.nested?;
+                let program = |ty: &str| {
+                    format!(
+                        "fn main() {{\n{}\n{make}\nlet inner{ty} = {read};\nlet _ = *inner.borrow(&owner);\n{}\n}}",
+                        (h.open)(""),
+                        close_stmt(h),
+                    )
+                };
+                Some((program(&format!(": {ty}<'static, '_, u32>")), program("")))
+            },
+        },
+    ]
+}
+
+const HEADER: &str = "// Generated by tests/soundness_matrix.rs, do not edit.";
+const FAIL_DIR: &str = "tests/compile_fail/matrix";
+const PASS_DIR: &str = "tests/compile_pass/matrix";
+
+/// Renders a program with its header and indents it.
+fn file(comment: &str, program: &str) -> String {
+    let mut res = format!(
+        "{HEADER}\n// {comment}\n#![allow(unused)]\n\nuse std::pin::pin;\n\nuse dreck::{{scoped::ScopedArena, *}};\n\n"
+    );
+    let mut depth = 0usize;
+    for line in program.lines() {
+        let line = line.trim();
+        if line.is_empty() && depth > 0 {
+            continue;
+        }
+        let lead = usize::from(line.starts_with('}'));
+        depth = depth.saturating_sub(lead);
+        res.push_str(&"    ".repeat(depth));
+        res.push_str(line);
+        res.push('\n');
+        depth =
+            (depth + line.matches('{').count()).saturating_sub(line.matches('}').count() - lead);
+    }
+    res
+}
+
+/// Renders every file of the matrix, keyed by path.
+fn render() -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+    let properties = properties();
+    let mut index = format!(
+        "<!-- Generated by tests/soundness_matrix.rs, do not edit. -->\n# Soundness matrix\n\n| |{}\n|-|{}\n",
+        properties.iter().map(|x| format!(" `{}` |", x.name)).collect::<String>(),
+        "-|".repeat(properties.len()),
+    );
+    for handle in handles() {
+        index.push_str(&format!("| `{}` |", handle.name));
+        for property in &properties {
+            let expect = handle
+                .expect
+                .iter()
+                .find(|(name, _)| *name == property.name)
+                .map_or(Expect::Fail, |(_, expect)| *expect);
+            let Some((program, control)) = (property.render)(&handle) else {
+                let Expect::Skip(reason) = expect else {
+                    panic!(
+                        "the handle `{}` can't render the property `{}`, it should be skipped",
+                        handle.name, property.name
+                    );
+                };
+                index.push_str(&format!(" n/a, {reason} |"));
+                continue;
+            };
+            let name = format!("{}__{}", handle.name, property.name);
+            index.push_str(&match expect {
+                Expect::Fail => " fails |".to_string(),
+                Expect::Pass(reason) => format!(" compiles, {reason} |"),
+                Expect::Skip(reason) => format!(" n/a, {reason} |"),
+            });
+            match expect {
+                Expect::Fail => {
+                    files.insert(
+                        format!("{FAIL_DIR}/{name}.rs"),
+                        file(&format!("`{}` must not compile", property.name), &program),
+                    );
+                    files.insert(
+                        format!("{PASS_DIR}/{name}__control.rs"),
+                        file(
+                            &format!("the control of `{}` must compile", property.name),
+                            &control,
+                        ),
+                    );
+                }
+                Expect::Pass(reason) => {
+                    files.insert(
+                        format!("{PASS_DIR}/{name}.rs"),
+                        file(&format!("sound by design, {reason}"), &program),
+                    );
+                }
+                Expect::Skip(_) => {}
+            }
+        }
+        index.push('\n');
+    }
+    files.insert(format!("{FAIL_DIR}/MATRIX.md"), index);
+    files
+}
+
+/// Returns the generated files on disk, keyed by path.
+fn on_disk(root: &Path) -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+    for dir in [FAIL_DIR, PASS_DIR] {
+        let Ok(entries) = fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|x| x == "rs" || x == "md") {
+                let name = path.file_name().unwrap().to_str().unwrap();
+                files.insert(format!("{dir}/{name}"), fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+    files
+}
+
+#[test]
+fn matrix_is_up_to_date() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let expected = render();
+    let actual = on_disk(root);
+
+    if std::env::var("DRECK_MATRIX").as_deref() == Ok("overwrite") {
+        for path in actual.keys().filter(|x| !expected.contains_key(*x)) {
+            fs::remove_file(root.join(path)).unwrap();
+            let _ = fs::remove_file(root.join(path).with_extension("stderr"));
+        }
+        for (path, contents) in &expected {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        return;
+    }
+
+    let stale: BTreeSet<_> = expected
+        .keys()
+        .chain(actual.keys())
+        .filter(|x| expected.get(*x) != actual.get(*x))
+        .collect();
+    assert!(
+        stale.is_empty(),
+        "the soundness matrix is out of date, run the test with DRECK_MATRIX=overwrite: {stale:?}"
+    );
+}
@@ -0,0 +1,292 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{containers::GcVec, testing::dump_structure, *};
+
+mod common;
+use common::trace_roots;
+
+pub struct Leaf(pub Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+pub struct Node<'gc, 'own> {
+    pub value: u32,
+    pub children: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+    pub leaf: Option<Gc<'gc, 'own, Leaf>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.children.trace(marker);
+        self.leaf.trace(marker);
+    }
+
+    fn fmt_leaf(&self, w: &mut dyn std::fmt::Write) -> Option<std::fmt::Result> {
+        Some(write!(w, "{}", self.value))
+    }
+
+    fn snapshot(&self) -> Option<Self> {
+        Some(Node {
+            value: self.value,
+            children: self.children.clone(),
+            leaf: self.leaf,
+        })
+    }
+}
+
+fn node<'gc, 'own>(
+    arena: &'gc Arena<'own>,
+    value: u32,
+    children: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+) -> Gc<'gc, 'own, Node<'gc, 'own>> {
+    arena.add(Node {
+        value,
+        children,
+        leaf: None,
+    })
+}
+
+#[test]
+fn rollback() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let a = node(&arena, 1, Vec::new());
+    let b = node(&arena, 2, vec![a]);
+    let root = node(&arena, 3, vec![a, b]);
+    let guard = pin!(RootGuard::new());
+    let root = root!(&arena, guard, root);
+    let before = dump_structure(root, &owner);
+
+    let spec = arena.begin_speculation();
+    let a = root.borrow(&owner).children[0];
+    let b = root.borrow(&owner).children[1];
+    a.borrow_mut(&mut owner, &spec).value = 10;
+    // Mutating the same object again logs nothing new.
+    a.borrow_mut(&mut owner, &spec).value = 11;
+    let leaf = spec.add(Leaf(drops.clone()));
+    let c = spec.add(Node {
+        value: 4,
+        children: vec![unsafe { a.rebind() }],
+        leaf: Some(leaf),
+    });
+    // Objects allocated during the speculation are never logged.
+    c.borrow_mut(&mut owner, &spec).value = 5;
+    let b = b.borrow_mut(&mut owner, &spec);
+    b.value = 6;
+    b.children = vec![unsafe { c.rebind() }];
+    root.borrow_mut(&mut owner, &spec).children.reverse();
+    assert_eq!(spec.logged(), 3);
+    assert_ne!(dump_structure(root, &owner), before);
+
+    spec.rollback(&mut owner);
+    assert_eq!(dump_structure(root, &owner), before);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(dump_structure(root, &owner), before);
+}
+
+#[test]
+fn commit() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let guard = pin!(RootGuard::new());
+    let root = root!(&arena, guard, node(&arena, 1, Vec::new()));
+
+    let spec = arena.begin_speculation();
+    let leaf = spec.add(Leaf(drops.clone()));
+    root.borrow_mut(&mut owner, &spec).leaf = Some(leaf);
+    let after = dump_structure(root, &owner);
+    spec.commit();
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(dump_structure(root, &owner), after);
+
+    // Dropping the guard commits as well.
+    let spec = arena.begin_speculation();
+    root.borrow_mut(&mut owner, &spec).leaf = None;
+    drop(spec);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn rollback_after_collection() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+
+    let leaf = arena.add(Leaf(drops.clone()));
+    let child = arena.add(Node {
+        value: 1,
+        children: Vec::new(),
+        leaf: Some(leaf),
+    });
+    let guard = pin!(RootGuard::new());
+    let root = root!(&arena, guard, node(&arena, 2, vec![child]));
+    let before = dump_structure(root, &owner);
+
+    let mut spec = arena.begin_speculation();
+    trace_roots(&spec);
+    // Unlink the only pointer to the child halfway through a cycle.
+    root.borrow_mut(&mut owner, &spec).children.clear();
+    spec.collect_full(&owner);
+    spec.collect_full(&owner);
+    // The undo log keeps the old child alive.
+    assert_eq!(drops.get(), 0);
+
+    spec.rollback(&mut owner);
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(dump_structure(root, &owner), before);
+}
+
+#[test]
+fn rollback_gc_vec() {
+    dreck!(owner, arena);
+
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+    for i in 0..4 {
+        let value = arena.add(i);
+        vec.push(&mut owner, &arena, value);
+    }
+
+    let spec = arena.begin_speculation();
+    vec.pop(&mut owner, &spec);
+    vec.remove(&mut owner, &spec, 0);
+    for i in 10..20 {
+        let value = spec.add(i);
+        vec.push(&mut owner, &spec, value);
+    }
+    vec.as_slice(&owner)[0].set(&mut owner, &spec, 100);
+    spec.rollback(&mut owner);
+
+    arena.collect_full(&owner);
+    let values = vec
+        .as_slice(&owner)
+        .iter()
+        .map(|x| *x.borrow(&owner))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [0, 1, 2, 3]);
+}
+
+#[test]
+fn rollback_gc_vec_grow() {
+    dreck!(owner, arena);
+
+    let vec = arena.add(GcVec::<Gc<u32>>::new());
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, vec);
+    for i in 0..4 {
+        let value = arena.add(i);
+        vec.push(&mut owner, &arena, value);
+    }
+    assert_eq!(vec.borrow(&owner).capacity(), 4);
+
+    // The spine is full, so the push moves the elements into a new spine.
+    let spec = arena.begin_speculation();
+    let value = spec.add(4);
+    vec.push(&mut owner, &spec, value);
+    assert_eq!(vec.borrow(&owner).capacity(), 8);
+    spec.rollback(&mut owner);
+
+    arena.collect_full(&owner);
+    let values = vec
+        .as_slice(&owner)
+        .iter()
+        .map(|x| *x.borrow(&owner))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [0, 1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "the type doesn't support snapshots")]
+fn no_snapshot() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let ptr = root!(&arena, guard, arena.add(Leaf(Rc::new(Cell::new(0)))));
+    let spec = arena.begin_speculation();
+    ptr.borrow_mut(&mut owner, &spec);
+}
+
+#[test]
+fn gc_vec_no_snapshot() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let vec = root!(&arena, guard, arena.add(GcVec::<u32>::new()));
+    vec.push(&mut owner, &arena, 1);
+    assert!(vec.borrow(&owner).snapshot().is_none());
+
+    let spec = arena.begin_speculation();
+    assert!(matches!(
+        vec.try_borrow_mut(&mut owner, &spec),
+        Err(Error::SnapshotUnsupported { .. })
+    ));
+}
+
+#[test]
+fn rollback_none() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let ptr = root!(&arena, guard, arena.add(None::<u32>));
+    let spec = arena.begin_speculation();
+    *ptr.borrow_mut(&mut owner, &spec) = Some(3);
+    spec.rollback(&mut owner);
+    assert_eq!(*ptr.borrow(&owner), None);
+}
+
+#[test]
+fn no_snapshot_some() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let ptr = root!(&arena, guard, arena.add(Some(Leaf(Rc::new(Cell::new(0))))));
+    let spec = arena.begin_speculation();
+    assert!(matches!(
+        ptr.try_borrow_mut(&mut owner, &spec),
+        Err(Error::SnapshotUnsupported { .. })
+    ));
+}
+
+#[test]
+#[should_panic(expected = "the arena is already logging undo entries")]
+fn nested() {
+    dreck!(owner, arena);
+    let _ = &owner;
+
+    let mut spec = arena.begin_speculation();
+    spec.begin_speculation();
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "stable-id")]
+
+use std::{collections::HashSet, pin::pin};
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    edges: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.edges.trace(marker)
+    }
+}
+
+#[test]
+fn ids_survive_collections() {
+    dreck!(owner, arena);
+
+    let values = (0..100u32).map(|x| arena.add(x)).collect::<Vec<_>>();
+    let values = arena.add(values);
+    let guard = pin!(RootGuard::new());
+    let values = root!(&arena, guard, values);
+
+    let ids = values
+        .borrow(&owner)
+        .iter()
+        .map(|x| x.stable_id(&arena))
+        .collect::<Vec<_>>();
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+
+    for _ in 0..3 {
+        for x in 0..100u32 {
+            arena.add(x);
+        }
+        arena.collect_full(&owner);
+    }
+
+    let after = values
+        .borrow(&owner)
+        .iter()
+        .map(|x| x.stable_id(&arena))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, after);
+}
+
+#[test]
+fn ids_not_reused() {
+    dreck!(owner, arena);
+
+    let mut seen = HashSet::new();
+    for _ in 0..10 {
+        for x in 0..100u32 {
+            assert!(seen.insert(arena.add(x).stable_id(&arena)));
+        }
+        // Freed memory is reused by new objects, their ids must still differ.
+        arena.collect_full(&owner);
+    }
+}
+
+fn read_edges(bytes: &[u8]) -> HashSet<(u64, u64)> {
+    assert_eq!(bytes.len() % 16, 0);
+    bytes
+        .chunks(16)
+        .map(|x| {
+            (
+                u64::from_le_bytes(x[..8].try_into().unwrap()),
+                u64::from_le_bytes(x[8..].try_into().unwrap()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn export_edges() {
+    dreck!(owner, arena);
+
+    let a = arena.add(Node { edges: Vec::new() });
+    let b = arena.add(Node { edges: vec![a] });
+    let c = arena.add(Node { edges: vec![a, b] });
+    let guard = pin!(RootGuard::new());
+    let c = root!(&arena, guard, c);
+    arena.collect_full(&owner);
+
+    let b = c.borrow(&owner).edges[1];
+    let a = b.borrow(&owner).edges[0];
+    let (a, b, c) = (
+        a.stable_id(&arena),
+        b.stable_id(&arena),
+        c.stable_id(&arena),
+    );
+
+    let mut bytes = Vec::new();
+    arena.export_edges(&owner, &mut bytes).unwrap();
+    let edges = read_edges(&bytes);
+    assert_eq!(edges, HashSet::from([(b, a), (c, a), (c, b)]));
+}
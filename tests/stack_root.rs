@@ -0,0 +1,104 @@
+use std::pin::pin;
+
+use dreck::{sys::Phase, *};
+
+/// An interpreter frame living on the stack.
+pub struct Frame<'gc, 'own> {
+    function: Gc<'gc, 'own, String>,
+    locals: Vec<Gc<'gc, 'own, u32>>,
+    this: Option<Gc<'gc, 'own, u32>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Frame<'gc, 'own> {
+    type Gc<'to> = Frame<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        marker.mark(self.function);
+        self.locals.trace(marker);
+        self.this.trace(marker);
+    }
+}
+
+fn frame<'gc, 'own>(arena: &'gc Arena<'own>) -> Frame<'gc, 'own> {
+    Frame {
+        function: arena.add(String::from("main")),
+        locals: (0..10).map(|x| arena.add(x)).collect(),
+        this: Some(arena.add(42)),
+    }
+}
+
+#[test]
+fn frame_survives_collect() {
+    dreck!(owner, arena);
+
+    {
+        stack_root!(&arena, let frame = frame(&arena));
+        arena.collect_full(&owner);
+        arena.collect_full(&owner);
+
+        assert_eq!(frame.function.borrow(&owner), "main");
+        for (i, local) in frame.locals.iter().enumerate() {
+            assert_eq!(*local.borrow(&owner), i as u32);
+        }
+        assert_eq!(*frame.this.unwrap().borrow(&owner), 42);
+        assert_eq!(arena.root_count(), 1);
+    }
+
+    // The guard dropped, so the objects of the frame are garbage.
+    assert_eq!(arena.root_count(), 0);
+    arena.collect_full(&owner);
+    assert_eq!(arena.stats().total_allocated, 0);
+}
+
+#[test]
+fn reuse_guard() {
+    dreck!(owner, arena);
+
+    let mut guard = pin!(ValueRootGuard::new());
+    let first = arena.root_value(frame(&arena), guard.as_mut());
+    assert_eq!(*first.this.unwrap().borrow(&owner), 42);
+    arena.collect_full(&owner);
+    let size = arena.stats().total_allocated;
+
+    // Rooting an other value drops the previous one.
+    let second = arena.root_value(
+        Frame {
+            function: arena.add(String::from("other")),
+            locals: Vec::new(),
+            this: None,
+        },
+        guard.as_mut(),
+    );
+    assert_eq!(second.function.borrow(&owner), "other");
+    arena.collect_full(&owner);
+    assert!(arena.stats().total_allocated < size);
+    assert_eq!(second.function.borrow(&owner), "other");
+}
+
+#[test]
+fn root_while_tracing() {
+    dreck!(owner, arena);
+
+    let mut guard = pin!(ValueRootGuard::new());
+    arena.root_value(frame(&arena), guard.as_mut());
+    unsafe {
+        arena.unsafe_arena().finish_sweep();
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        // Trace the value, so the value rooted next must be marked by rooting it.
+        arena.unsafe_arena().step();
+        assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+    }
+    let frame = arena.root_value(frame(&arena), guard.as_mut());
+    arena.collect_full(&owner);
+    assert_eq!(frame.function.borrow(&owner), "main");
+    assert_eq!(*frame.locals[9].borrow(&owner), 9);
+}
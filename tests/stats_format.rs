@@ -0,0 +1,142 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt::{self, Write},
+    pin::pin,
+};
+
+use dreck::{sys::Phase, *};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// An allocator which counts the allocations of the current thread.
+struct CountAlloc;
+
+unsafe impl GlobalAlloc for CountAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|x| x.set(x.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountAlloc = CountAlloc;
+
+/// Returns the number of allocations done by the function.
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(|x| x.get());
+    let res = f();
+    (res, ALLOCATIONS.with(|x| x.get()) - before)
+}
+
+/// A fixed size buffer to format into.
+pub struct Buffer {
+    pub bytes: [u8; 256],
+    pub len: usize,
+}
+
+impl Buffer {
+    fn new() -> Self {
+        Buffer {
+            bytes: [0; 256],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?;
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn gc_stats() {
+    dreck!(owner, arena);
+
+    let value = arena.add(vec![arena.add(1u32)]);
+    let guard = pin!(RootGuard::new());
+    let value = root!(&arena, guard, value);
+    value.borrow_mut(&mut owner, &arena).push(arena.add(2));
+    arena.collect_full(&owner);
+
+    let mut buffer = Buffer::new();
+    let (stats, count) = allocations(|| {
+        let stats = arena.stats();
+        write!(buffer, "{stats}").unwrap();
+        stats
+    });
+    assert_eq!(count, 0);
+    assert_eq!(stats.phase, Phase::Sleep);
+    assert_eq!(
+        buffer.as_str(),
+        format!(
+            "phase: sleep, allocated: {} bytes, roots: 1, cache: {} bytes, gray capacity: {}, \
+             work: {} done 0 deferred, write barriers: 1",
+            stats.total_allocated, stats.cache_bytes, stats.gray_capacity, stats.collect_work
+        )
+    );
+}
+
+#[test]
+fn phase() {
+    let mut buffer = Buffer::new();
+    let ((), count) = allocations(|| {
+        for phase in [Phase::Sleep, Phase::Wake, Phase::Trace, Phase::Sweep] {
+            write!(buffer, "{phase} ").unwrap();
+        }
+    });
+    assert_eq!(count, 0);
+    assert_eq!(buffer.as_str(), "sleep wake trace sweep ");
+}
+
+#[test]
+fn memo_stats() {
+    let stats = MemoStats {
+        hits: 3,
+        misses: 2,
+        evicted: 1,
+        cleared: 0,
+    };
+    let mut buffer = Buffer::new();
+    let ((), count) = allocations(|| write!(buffer, "{stats}").unwrap());
+    assert_eq!(count, 0);
+    assert_eq!(
+        buffer.as_str(),
+        "hits: 3, misses: 2, evicted: 1, cleared: 0"
+    );
+}
+
+#[test]
+fn buffer_too_small() {
+    dreck!(_owner, arena);
+
+    let mut buffer = Buffer {
+        bytes: [0; 256],
+        len: 250,
+    };
+    assert!(write!(buffer, "{}", arena.stats()).is_err());
+}
+
+#[cfg(feature = "defmt")]
+#[test]
+fn defmt_format() {
+    fn assert_format<T: defmt::Format>() {}
+    assert_format::<sys::GcStats>();
+    assert_format::<Phase>();
+    assert_format::<MemoStats>();
+}
@@ -0,0 +1,106 @@
+use std::{pin::pin, time::Duration};
+
+use dreck::{sys::Phase, *};
+
+pub struct Node<'gc, 'own>(pub u32, pub Option<Gc<'gc, 'own, Node<'gc, 'own>>>);
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.1.trace(marker)
+    }
+}
+
+fn list<'gc, 'own>(arena: &'gc Arena<'own>, len: u32) -> Gc<'gc, 'own, Node<'gc, 'own>> {
+    let mut head = arena.add(Node(0, None));
+    for x in 1..len {
+        head = arena.add(Node(x, Some(head)));
+    }
+    head
+}
+
+fn len<'gc, 'own>(head: Gc<'gc, 'own, Node<'gc, 'own>>, owner: &Owner<'own>) -> u32 {
+    let mut len = 0;
+    let mut cur = Some(head);
+    while let Some(x) = cur {
+        len += 1;
+        cur = x.borrow(owner).1;
+    }
+    len
+}
+
+#[test]
+fn max_step_work() {
+    dreck!(owner, arena);
+    // Finish the initial cycle so the list is allocated during a cycle which traces it.
+    arena.collect_full(&owner);
+    arena.set_max_step_work(Some(256));
+
+    let guard = pin!(RootGuard::new());
+    let head = root!(&arena, guard, list(&arena, 2000));
+    // Garbage to sweep.
+    list(&arena, 2000);
+    let node_size = head.allocation_size();
+    assert_ne!(arena.unsafe_arena().phase(), Phase::Sleep);
+
+    let mut calls = 0;
+    while arena.unsafe_arena().phase() != Phase::Sleep {
+        arena.collect(&owner);
+        let stats = arena.stats();
+        assert!(stats.collect_work <= 256 + node_size);
+        if stats.phase != Phase::Sleep {
+            assert!(stats.deferred_work > 0);
+        }
+        calls += 1;
+    }
+    assert!(calls > 4000 * node_size / (256 + node_size));
+    assert_eq!(arena.stats().deferred_work, 0);
+    assert_eq!(arena.stats().total_allocated, 2000 * node_size);
+    assert_eq!(len(head, &owner), 2000);
+}
+
+#[test]
+fn max_step_time() {
+    dreck!(owner, arena);
+    // Finish the initial cycle so the list is allocated during a cycle which traces it.
+    arena.collect_full(&owner);
+    arena.set_max_step_time(Some(Duration::ZERO));
+
+    let guard = pin!(RootGuard::new());
+    let head = root!(&arena, guard, list(&arena, 2000));
+    list(&arena, 2000);
+    let heap_size = arena.stats().total_allocated;
+
+    let mut calls = 0;
+    while arena.unsafe_arena().phase() != Phase::Sleep {
+        arena.collect(&owner);
+        assert!(arena.stats().collect_work < heap_size);
+        calls += 1;
+    }
+    assert!(calls > 1);
+    assert_eq!(len(head, &owner), 2000);
+}
+
+#[test]
+fn unlimited() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let head = root!(&arena, guard, list(&arena, 2000));
+    list(&arena, 2000);
+
+    arena.collect(&owner);
+    assert_eq!(arena.stats().deferred_work, 0);
+    arena.set_max_step_work(Some(256));
+    arena.set_max_step_work(None);
+    arena.collect_full(&owner);
+    assert_eq!(len(head, &owner), 2000);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "tag-word")]
+
+use std::pin::pin;
+
+use dreck::*;
+
+#[test]
+fn survives_collection() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let values = root!(
+        &arena,
+        guard,
+        arena.add((0..10u32).map(|x| arena.add(x)).collect::<Vec<_>>())
+    );
+    for x in values.borrow(&owner).clone() {
+        assert_eq!(x.tag_word(&owner), 0);
+        let tag = 0xdead_0000 + *x.borrow(&owner) as usize;
+        x.set_tag_word(&mut owner, &arena, tag);
+    }
+
+    // Garbage in between so freed memory is reused by new objects.
+    for x in 0..100u32 {
+        arena.add(x);
+    }
+    arena.collect_full(&owner);
+    for x in 0..100u32 {
+        assert_eq!(arena.add(x).tag_word(&owner), 0);
+    }
+    arena.collect_full(&owner);
+
+    for x in values.borrow(&owner).iter() {
+        assert_eq!(x.tag_word(&owner), 0xdead_0000 + *x.borrow(&owner) as usize);
+    }
+}
+
+#[test]
+fn independent() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add_str("b");
+    let c = arena.add_slice_copy(&[1u8, 2, 3]);
+    a.set_tag_word(&mut owner, &arena, 1);
+    b.set_tag_word(&mut owner, &arena, 2);
+    assert_eq!(a.tag_word(&owner), 1);
+    assert_eq!(b.tag_word(&owner), 2);
+    assert_eq!(c.tag_word(&owner), 0);
+    c.set_tag_word(&mut owner, &arena, usize::MAX);
+    a.set_tag_word(&mut owner, &arena, 3);
+    assert_eq!(a.tag_word(&owner), 3);
+    assert_eq!(b.tag_word(&owner), 2);
+    assert_eq!(c.tag_word(&owner), usize::MAX);
+}
+
+#[test]
+fn bumps_epoch() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let epoch = owner.epoch();
+    a.set_tag_word(&mut owner, &arena, 1);
+    assert_ne!(owner.epoch(), epoch);
+}
+
+#[test]
+#[cfg(all(feature = "arena-id", debug_assertions))]
+#[should_panic(expected = "must not hold GC pointers")]
+fn object_address() {
+    dreck!(owner, arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(2u32);
+    let word = b.into_gc_box().as_ptr() as usize;
+    a.set_tag_word(&mut owner, &arena, word);
+}
@@ -0,0 +1,81 @@
+use std::{cell::Cell, cell::RefCell, rc::Rc};
+
+use dreck::*;
+
+pub struct Leaf {
+    value: u32,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'to> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn hooks_run_in_order() {
+    dreck!(owner, arena);
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    for idx in 0..3 {
+        let order = order.clone();
+        arena.on_teardown(Box::new(move |_, _| order.borrow_mut().push(idx)));
+    }
+    assert!(order.borrow().is_empty());
+
+    arena.shutdown(&mut owner);
+    assert_eq!(*order.borrow(), [0, 1, 2]);
+}
+
+#[test]
+fn objects_alive_during_hook() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+    let seen = Rc::new(Cell::new(None));
+
+    let leaf = arena.add(Leaf {
+        value: 7,
+        drops: drops.clone(),
+    });
+    let ptr = leaf.into_gc_box();
+
+    let hook_drops = drops.clone();
+    let hook_seen = seen.clone();
+    arena.on_teardown(Box::new(move |owner, arena| {
+        assert_eq!(hook_drops.get(), 0);
+        let leaf = unsafe { Gc::from_gc_box(ptr) };
+        hook_seen.set(Some(leaf.borrow(owner).value));
+        // Hooks can still use the arena.
+        let other = arena.add(1u32);
+        assert_eq!(*other.borrow(owner), 1);
+    }));
+
+    arena.shutdown(&mut owner);
+    assert_eq!(seen.get(), Some(7));
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn drop_skips_hooks() {
+    let ran = Rc::new(Cell::new(false));
+    {
+        dreck!(_owner, arena);
+        let ran = ran.clone();
+        arena.on_teardown(Box::new(move |_, _| ran.set(true)));
+    }
+    assert!(!ran.get());
+}
@@ -0,0 +1,54 @@
+use std::{collections::HashMap, pin::pin};
+
+use dreck::*;
+
+#[test]
+fn needs_trace() {
+    assert!(!<(u64, Option<u32>)>::needs_trace());
+    assert!(!<[(u8, bool); 4]>::needs_trace());
+    assert!(!<Vec<(u64, [u32; 2])>>::needs_trace());
+    assert!(<(u64, Option<Gc<u32>>)>::needs_trace());
+    assert!(<[Gc<u32>; 2]>::needs_trace());
+    assert!(<(u8, u16, u32, u64, i8, i16, i32, Gc<u32>)>::needs_trace());
+}
+
+#[test]
+fn tuples() {
+    dreck!(owner, arena);
+
+    let values = (0..1000u32)
+        .map(|x| (x as u64, (x % 2 == 0).then(|| arena.add(x))))
+        .collect::<Vec<_>>();
+    let values = arena.add(values);
+    let guard = pin!(RootGuard::new());
+    let values = root!(&arena, guard, values);
+
+    for x in 0..1000u32 {
+        arena.add(x);
+    }
+    arena.collect_full(&owner);
+
+    for (idx, (x, value)) in values.borrow(&owner).iter().enumerate() {
+        assert_eq!(*x, idx as u64);
+        assert_eq!(
+            value.map(|x| *x.borrow(&owner)),
+            (idx % 2 == 0).then_some(idx as u32)
+        );
+    }
+}
+
+#[test]
+fn arrays() {
+    dreck!(owner, arena);
+
+    let values = [arena.add(1u32), arena.add(2), arena.add(3)];
+    let map = HashMap::from([(0u64, values)]);
+    let map = arena.add(map);
+    let guard = pin!(RootGuard::new());
+    let map = root!(&arena, guard, map);
+
+    arena.collect_full(&owner);
+
+    let values = map.borrow(&owner)[&0].map(|x| *x.borrow(&owner));
+    assert_eq!(values, [1, 2, 3]);
+}
@@ -0,0 +1,122 @@
+use std::{
+    cell::Cell,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::pin,
+    rc::Rc,
+};
+
+use dreck::{sys::Phase, *};
+
+pub struct Leaf(Rc<Cell<usize>>);
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+unsafe impl<'own> Trace<'own> for Leaf {
+    type Gc<'gc> = Leaf;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+/// An object which panics while tracing, after marking its first child, when armed.
+pub struct Bomb<'gc, 'own> {
+    armed: Rc<Cell<bool>>,
+    first: Gc<'gc, 'own, Leaf>,
+    second: Gc<'gc, 'own, Leaf>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Bomb<'gc, 'own> {
+    type Gc<'to> = Bomb<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.first.trace(marker);
+        if self.armed.replace(false) {
+            panic!("trace panicked");
+        }
+        self.second.trace(marker);
+    }
+}
+
+#[test]
+fn panic_aborts_cycle() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+    let armed = Rc::new(Cell::new(false));
+
+    let bomb = arena.add(Bomb {
+        armed: armed.clone(),
+        first: arena.add(Leaf(drops.clone())),
+        second: arena.add(Leaf(drops.clone())),
+    });
+    let guard = pin!(RootGuard::new());
+    let bomb = root!(&arena, guard, bomb);
+    arena.add(Leaf(drops.clone()));
+
+    armed.set(true);
+    let res = catch_unwind(AssertUnwindSafe(|| arena.collect_full(&owner)));
+    assert!(res.is_err());
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Wake);
+    assert!(arena.is_usable());
+    assert_eq!(drops.get(), 0);
+
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 1);
+    bomb.borrow(&owner).first.borrow(&owner);
+    bomb.borrow(&owner).second.borrow(&owner);
+
+    // The arena keeps working normally afterwards.
+    let leaf = arena.add(Leaf(drops.clone()));
+    bomb.borrow_mut(&mut owner, &arena).first = leaf;
+    arena.collect_full(&owner);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn panic_during_incremental_cycle() {
+    dreck!(owner, arena);
+    let drops = Rc::new(Cell::new(0));
+    let armed = Rc::new(Cell::new(false));
+
+    let bomb = arena.add(Bomb {
+        armed: armed.clone(),
+        first: arena.add(Leaf(drops.clone())),
+        second: arena.add(Leaf(drops.clone())),
+    });
+    let guard = pin!(RootGuard::new());
+    let bomb = root!(&arena, guard, bomb);
+
+    armed.set(true);
+    let res = catch_unwind(AssertUnwindSafe(|| unsafe {
+        while armed.get() {
+            arena.unsafe_arena().step();
+        }
+    }));
+    assert!(res.is_err());
+
+    // Finish the new cycle step by step.
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+    assert_eq!(drops.get(), 0);
+    assert_eq!(arena.stats().phase, Phase::Sleep);
+    bomb.borrow(&owner).second.borrow(&owner);
+}
@@ -0,0 +1,110 @@
+use std::{cell::Cell, pin::pin};
+
+use dreck::*;
+
+/// A value which calls back into the arena it is allocated in while being traced.
+pub struct Reentrant<'a, 'gc, 'own> {
+    arena: &'a Arena<'own>,
+    child: Option<Gc<'gc, 'own, Option<Gc<'gc, 'own, u32>>>>,
+    allocate: bool,
+    traced: Cell<usize>,
+}
+
+unsafe impl<'a, 'gc, 'own> Trace<'own> for Reentrant<'a, 'gc, 'own> {
+    type Gc<'to> = Reentrant<'a, 'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.traced.set(self.traced.get() + 1);
+        if let Some(child) = self.child {
+            // Both push onto a gray stack while the arena is tracing.
+            marker.mark(child);
+            self.arena.write_barrier(child);
+        }
+        if self.allocate {
+            self.arena.add(0u32);
+        }
+    }
+}
+
+#[test]
+fn mark_while_retracing() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let child = arena.add(Some(arena.add(1u32)));
+    let value = arena.add(Reentrant {
+        arena: &arena,
+        child: Some(child),
+        allocate: false,
+        traced: Cell::new(0),
+    });
+    let value = root!(&arena, guard, value);
+
+    unsafe {
+        // Wake, then trace the root and its child.
+        while value.borrow(&owner).traced.get() == 0 {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+
+        // Mutating the traced root causes it to be traced again from the remembered set, while
+        // the write barrier it calls on its traced child remembers that one as well.
+        value.borrow_mut(&mut owner, &arena).traced.set(1);
+        arena.unsafe_arena().step();
+        assert_eq!(value.borrow(&owner).traced.get(), 2);
+
+        // The arena is borrowed by the value so collecting has to go through the unsafe arena.
+        arena.unsafe_arena().collect_full();
+    }
+
+    let child = value.borrow(&owner).child.unwrap();
+    assert_eq!(*child.borrow(&owner).unwrap().borrow(&owner), 1);
+}
+
+#[test]
+#[should_panic(expected = "cannot allocate or root while the arena is tracing")]
+fn allocate_while_tracing() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let value = arena.add(Reentrant {
+        arena: &arena,
+        child: None,
+        allocate: true,
+        traced: Cell::new(0),
+    });
+    let _value = root!(&arena, guard, value);
+
+    unsafe { arena.unsafe_arena().collect_full() };
+}
+
+#[test]
+fn usable_after_allocate_while_tracing() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let value = arena.add(Reentrant {
+        arena: &arena,
+        child: None,
+        allocate: true,
+        traced: Cell::new(0),
+    });
+    let value = root!(&arena, guard, value);
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+        arena.unsafe_arena().collect_full();
+    }));
+    assert!(res.is_err());
+
+    value.borrow_mut(&mut owner, &arena).allocate = false;
+    unsafe { arena.unsafe_arena().collect_full() };
+    assert!(arena.is_usable());
+    assert_eq!(*arena.add(2u32).borrow(&owner), 2);
+}
@@ -0,0 +1,95 @@
+use std::{cell::RefCell, pin::pin, rc::Rc};
+
+use dreck::{sys::Phase, *};
+
+fn record(
+    arena: &mut Arena,
+    filter: PhaseMask,
+) -> (Rc<RefCell<Vec<Transition>>>, TransitionSubscription) {
+    let transitions = Rc::new(RefCell::new(Vec::new()));
+    let subscription = arena.subscribe_transitions(filter, {
+        let transitions = transitions.clone();
+        move |transition| transitions.borrow_mut().push(transition)
+    });
+    (transitions, subscription)
+}
+
+fn transition(from: Phase, to: Phase, cycle: u64) -> Transition {
+    Transition { from, to, cycle }
+}
+
+#[test]
+fn sequence() {
+    dreck!(owner, arena);
+    let (transitions, _) = record(&mut arena, PhaseMask::ALL);
+
+    let guard = pin!(RootGuard::new());
+    let _value = root!(&arena, guard, arena.add(Some(arena.add(1u32))));
+
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    // The first cycle finishes the sweep the arena starts in.
+    assert_eq!(
+        *transitions.borrow(),
+        [
+            transition(Phase::Sweep, Phase::Sleep, 0),
+            transition(Phase::Wake, Phase::Trace, 1),
+            transition(Phase::Trace, Phase::Sweep, 1),
+            transition(Phase::Sweep, Phase::Sleep, 1),
+            transition(Phase::Wake, Phase::Trace, 2),
+            transition(Phase::Trace, Phase::Sweep, 2),
+            transition(Phase::Sweep, Phase::Sleep, 2),
+        ]
+    );
+}
+
+#[test]
+fn filter() {
+    dreck!(owner, arena);
+    let (transitions, _) = record(&mut arena, PhaseMask::TRACE | PhaseMask::SLEEP);
+
+    arena.collect_full(&owner);
+    arena.collect_full(&owner);
+
+    assert_eq!(
+        *transitions.borrow(),
+        [
+            transition(Phase::Sweep, Phase::Sleep, 0),
+            transition(Phase::Wake, Phase::Trace, 1),
+            transition(Phase::Sweep, Phase::Sleep, 1),
+            transition(Phase::Wake, Phase::Trace, 2),
+            transition(Phase::Sweep, Phase::Sleep, 2),
+        ]
+    );
+}
+
+#[test]
+fn incremental() {
+    dreck!(owner, arena);
+    let (transitions, _) = record(&mut arena, PhaseMask::SLEEP);
+
+    for x in 0..100_000u32 {
+        arena.add(x);
+        arena.collect(&owner);
+    }
+    let transitions = transitions.borrow();
+    assert!(transitions.len() > 2);
+    for (idx, transition) in transitions.iter().enumerate() {
+        assert_eq!(transition.cycle, idx as u64);
+    }
+}
+
+#[test]
+fn unsubscribe() {
+    dreck!(owner, arena);
+    let (transitions, subscription) = record(&mut arena, PhaseMask::ALL);
+
+    arena.collect_full(&owner);
+    let len = transitions.borrow().len();
+    assert!(arena.unsubscribe_transitions(subscription));
+    assert!(!arena.unsubscribe_transitions(subscription));
+
+    arena.collect_full(&owner);
+    assert_eq!(transitions.borrow().len(), len);
+}
@@ -0,0 +1,120 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+/// A value which tries to use the arena it is allocated in when traced and dropped.
+pub struct UsesArena<'a, 'gc, 'own> {
+    arena: &'a Arena<'own>,
+    child: Gc<'gc, 'own, u32>,
+    traced: Rc<Cell<Option<Result<(), Error>>>>,
+    dropped: Rc<Cell<Option<Result<(), Error>>>>,
+}
+
+impl Drop for UsesArena<'_, '_, '_> {
+    fn drop(&mut self) {
+        self.dropped.set(Some(
+            self.arena.try_add(0u32).map(|_| ()).map_err(Error::from),
+        ));
+    }
+}
+
+unsafe impl<'a, 'gc, 'own> Trace<'own> for UsesArena<'a, 'gc, 'own> {
+    type Gc<'to> = UsesArena<'a, 'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        let guard = pin!(RootGuard::new());
+        self.traced
+            .set(Some(self.arena.try_root(self.child, guard).map(|_| ())));
+        marker.mark(self.child);
+    }
+}
+
+#[test]
+fn available() {
+    dreck!(owner, arena);
+
+    let value = arena.try_add(3u32).unwrap();
+    let guard = pin!(RootGuard::new());
+    let value = arena.try_root(value, guard).unwrap();
+    let guard = pin!(RootGuard::new());
+    let value = arena.try_reroot(value, guard).unwrap();
+    arena.collect_full(&owner);
+    assert_eq!(*value.borrow(&owner), 3);
+}
+
+#[test]
+fn unavailable_while_collecting() {
+    dreck!(owner, arena);
+    let traced = Rc::new(Cell::new(None));
+    let dropped = Rc::new(Cell::new(None));
+
+    let value = arena.add(UsesArena {
+        arena: &arena,
+        child: arena.add(1),
+        traced: traced.clone(),
+        dropped: dropped.clone(),
+    });
+    {
+        let guard = pin!(RootGuard::new());
+        root!(&arena, guard, value);
+        unsafe { arena.unsafe_arena().collect_full() };
+        assert_eq!(
+            traced.get(),
+            Some(Err(Error::ArenaUnavailable {
+                reason: GcUnavailable::Tracing
+            }))
+        );
+    }
+
+    unsafe { arena.unsafe_arena().collect_full() };
+    assert_eq!(
+        dropped.get(),
+        Some(Err(Error::ArenaUnavailable {
+            reason: GcUnavailable::Dropping
+        }))
+    );
+    assert!(arena.try_add(0u32).is_ok());
+}
+
+#[test]
+fn unavailable_while_dropping() {
+    let dropped = Rc::new(Cell::new(None));
+    {
+        dreck!(_owner, arena);
+        arena.add(UsesArena {
+            arena: &arena,
+            child: arena.add(1),
+            traced: Rc::new(Cell::new(None)),
+            dropped: dropped.clone(),
+        });
+    }
+    assert_eq!(
+        dropped.get(),
+        Some(Err(Error::ArenaUnavailable {
+            reason: GcUnavailable::Dropping
+        }))
+    );
+}
+
+#[test]
+fn available_during_teardown() {
+    dreck!(owner, arena);
+    let result = Rc::new(Cell::new(None));
+
+    let hook_result = result.clone();
+    arena.on_teardown(Box::new(move |owner, arena| {
+        let value = arena.try_add(2u32).unwrap();
+        let guard = pin!(RootGuard::new());
+        let value = arena.try_root(value, guard).unwrap();
+        hook_result.set(Some(*value.borrow(owner)));
+    }));
+    arena.shutdown(&mut owner);
+    assert_eq!(result.get(), Some(2));
+}
@@ -0,0 +1,87 @@
+use std::{cell::Cell, rc::Rc};
+
+use dreck::*;
+
+/// A value which uses the arena it is allocated in when dropped.
+pub struct UsesArena<'a, 'own> {
+    arena: &'a Arena<'own>,
+    allocate: bool,
+    usable: Rc<Cell<Option<bool>>>,
+}
+
+impl Drop for UsesArena<'_, '_> {
+    fn drop(&mut self) {
+        self.usable.set(Some(self.arena.is_usable()));
+        if self.allocate {
+            self.arena.add(0u32);
+        }
+    }
+}
+
+unsafe impl<'a, 'own> Trace<'own> for UsesArena<'a, 'own> {
+    type Gc<'to> = UsesArena<'a, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn not_usable_while_collecting() {
+    dreck!(owner, arena);
+    let usable = Rc::new(Cell::new(None));
+
+    assert!(arena.is_usable());
+    arena.add(UsesArena {
+        arena: &arena,
+        allocate: false,
+        usable: usable.clone(),
+    });
+    arena.collect_full(&owner);
+    assert_eq!(usable.get(), Some(false));
+    assert!(arena.is_usable());
+}
+
+#[test]
+fn not_usable_while_dropping() {
+    let usable = Rc::new(Cell::new(None));
+    {
+        dreck!(_owner, arena);
+        arena.add(UsesArena {
+            arena: &arena,
+            allocate: false,
+            usable: usable.clone(),
+        });
+    }
+    assert_eq!(usable.get(), Some(false));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "cannot allocate or root while the arena is being dropped")]
+fn allocate_while_collecting() {
+    dreck!(owner, arena);
+    arena.add(UsesArena {
+        arena: &arena,
+        allocate: true,
+        usable: Rc::new(Cell::new(None)),
+    });
+    arena.collect_full(&owner);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "cannot allocate or root while the arena is being dropped")]
+fn allocate_while_dropping() {
+    dreck!(_owner, arena);
+    arena.add(UsesArena {
+        arena: &arena,
+        allocate: true,
+        usable: Rc::new(Cell::new(None)),
+    });
+}
@@ -0,0 +1,81 @@
+use std::pin::pin;
+
+use dreck::{sys::Phase, *};
+
+/// Step until the rooted object is traced.
+fn step_until_traced(arena: &Arena) {
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Trace {
+            arena.unsafe_arena().step();
+        }
+        arena.unsafe_arena().step();
+    }
+    assert_eq!(arena.unsafe_arena().phase(), Phase::Trace);
+}
+
+fn finish_cycle(arena: &Arena) {
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "heap verification failed: object of type `u32`")]
+fn missed_barrier() {
+    dreck!(owner, arena);
+    arena.set_verify(true);
+
+    let parent = arena.add(None::<Gc<u32>>);
+    let guard = pin!(RootGuard::new());
+    let parent = root!(&arena, guard, parent);
+
+    arena.collect_full(&owner);
+    let child = arena.add(3u32);
+    step_until_traced(&arena);
+
+    // The child is unmarked and the parent already traced.
+    unsafe { *parent.borrow_mut_no_barrier(&mut owner) = Some(child) };
+    finish_cycle(&arena);
+}
+
+#[test]
+fn barrier_applied() {
+    dreck!(owner, arena);
+    arena.set_verify(true);
+
+    let parent = arena.add(None::<Gc<u32>>);
+    let guard = pin!(RootGuard::new());
+    let parent = root!(&arena, guard, parent);
+
+    arena.collect_full(&owner);
+    let child = arena.add(3u32);
+    step_until_traced(&arena);
+
+    *parent.borrow_mut(&mut owner, &arena) = Some(child);
+    finish_cycle(&arena);
+
+    assert_eq!(*parent.borrow(&owner).unwrap().borrow(&owner), 3);
+}
+
+#[test]
+fn verify_many_cycles() {
+    dreck!(owner, arena);
+    arena.set_verify(true);
+
+    let guard = pin!(RootGuard::new());
+    let list = root!(&arena, guard, arena.add(Vec::<Gc<u32>>::new()));
+
+    for x in 0..10_000u32 {
+        let value = arena.add(x);
+        let list = list.borrow_mut(&mut owner, &arena);
+        if list.len() == 64 {
+            list.clear();
+        }
+        list.push(value);
+        arena.collect(&owner);
+    }
+    arena.collect_full(&owner);
+    assert_eq!(list.borrow(&owner).len(), 16);
+}
@@ -0,0 +1,124 @@
+use std::{ops::ControlFlow, pin::pin};
+
+use dreck::*;
+
+pub struct Node<'gc, 'own> {
+    id: usize,
+    edges: Vec<Gc<'gc, 'own, Node<'gc, 'own>>>,
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Node<'gc, 'own> {
+    type Gc<'to> = Node<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.edges.trace(marker);
+    }
+}
+
+unsafe impl<'gc, 'own> Erasable<'own> for Node<'gc, 'own> {}
+
+const NODES: usize = 1000;
+
+/// Create a graph with cycles and shared nodes, returning the first node.
+fn graph<'gc, 'own>(
+    owner: &mut Owner<'own>,
+    arena: &'gc Arena<'own>,
+) -> Gc<'gc, 'own, Node<'gc, 'own>> {
+    let nodes: Vec<_> = (0..NODES)
+        .map(|id| {
+            arena.add(Node {
+                id,
+                edges: Vec::new(),
+            })
+        })
+        .collect();
+    for (i, node) in nodes.iter().enumerate() {
+        let edges = vec![nodes[(i + 1) % NODES], nodes[(i * 7) % NODES]];
+        node.borrow_mut(owner, arena).edges = unsafe { edges.rebind() };
+    }
+    nodes[0]
+}
+
+fn record<'b, 'own>(
+    owner: &'b Owner<'own>,
+    seen: &'b mut [usize],
+) -> impl for<'a> FnMut(GcAny<'a, 'own>) -> ControlFlow<()> + 'b {
+    move |x: GcAny<'_, 'own>| {
+        let node = x.downcast::<Node>().unwrap();
+        seen[node.borrow(owner).id] += 1;
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn resumed_across_collections() {
+    dreck!(owner, arena);
+
+    let start = graph(&mut owner, &arena);
+    let guard = pin!(RootGuard::new());
+    let start = root!(&arena, guard, start);
+
+    let mut seen = vec![0; NODES];
+    let mut cursor = arena.walk_subgraph(&owner, start.into(), 400, record(&owner, &mut seen));
+    assert_eq!(cursor.visited(), 400);
+    assert!(!cursor.is_done());
+
+    arena.collect_full(&owner);
+    cursor.resume(&arena, &owner, 400, record(&owner, &mut seen));
+    assert_eq!(cursor.visited(), 800);
+
+    arena.collect_full(&owner);
+    cursor.resume(&arena, &owner, 400, record(&owner, &mut seen));
+    assert_eq!(cursor.visited(), NODES);
+
+    // Stale frontier entries might remain for nodes reached through multiple paths.
+    cursor.resume(&arena, &owner, usize::MAX, record(&owner, &mut seen));
+    assert!(cursor.is_done());
+    assert!(seen.iter().all(|x| *x == 1));
+}
+
+#[test]
+fn frontier_kept_alive() {
+    dreck!(owner, arena);
+
+    let start = graph(&mut owner, &arena);
+    let mut seen = vec![0; NODES];
+    // The graph is not rooted, only the cursor keeps it alive.
+    let mut cursor = arena.walk_subgraph(&owner, start.into(), 10, record(&owner, &mut seen));
+    assert_eq!(cursor.frontier(&arena).len(), 10);
+
+    while !cursor.is_done() {
+        arena.collect_full(&owner);
+        cursor.resume(&arena, &owner, 100, record(&owner, &mut seen));
+    }
+    assert!(seen.iter().all(|x| *x == 1));
+
+    drop(cursor);
+    arena.collect_full(&owner);
+    assert_eq!(arena.unsafe_arena().stats().total_allocated, 0);
+}
+
+#[test]
+fn break_stops_walk() {
+    dreck!(owner, arena);
+
+    let start = graph(&mut owner, &arena);
+    let mut count = 0;
+    let cursor = arena.walk_subgraph(&owner, start.into(), usize::MAX, |_| {
+        count += 1;
+        if count == 5 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(count, 5);
+    assert_eq!(cursor.visited(), 5);
+}
@@ -0,0 +1,130 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::{sys::PhaseMask, *};
+
+/// Returns the values of the observers which are alive, in iteration order.
+fn alive<'own>(
+    owner: &Owner<'own>,
+    arena: &Arena<'own>,
+    list: &mut WeakObserverList<'own, u32>,
+) -> Vec<u32> {
+    let mut values = Vec::new();
+    list.for_each_alive(arena, |x| values.push(*x.borrow(owner)));
+    values
+}
+
+#[test]
+fn dropped_observers_disappear() {
+    dreck!(owner, arena);
+    let mut list = WeakObserverList::new();
+
+    let guard = pin!(RootGuard::new());
+    let kept = root!(
+        &arena,
+        guard,
+        arena.add(vec![arena.add(1u32), arena.add(3)])
+    );
+    let (a, b) = (kept.borrow(&owner)[0], kept.borrow(&owner)[1]);
+    list.subscribe(&arena, a);
+    list.subscribe(&arena, arena.add(2u32));
+    list.subscribe(&arena, b);
+    list.subscribe(&arena, arena.add(4u32));
+    assert_eq!(alive(&owner, &arena, &mut list), [1, 2, 3, 4]);
+
+    arena.collect_full(&owner);
+    assert_eq!(list.len(), 4);
+    // Subscription order is preserved for the living observers.
+    assert_eq!(alive(&owner, &arena, &mut list), [1, 3]);
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.prune(), 0);
+}
+
+#[test]
+fn unsubscribe() {
+    dreck!(owner, arena);
+    let mut list = WeakObserverList::new();
+
+    let guard = pin!(RootGuard::new());
+    let a = root!(&arena, guard, arena.add(1u32));
+    list.subscribe(&arena, a);
+    list.subscribe(&arena, a);
+    assert!(list.unsubscribe(a));
+    assert_eq!(alive(&owner, &arena, &mut list), [1]);
+    assert!(list.unsubscribe(a));
+    assert!(!list.unsubscribe(a));
+    assert!(list.is_empty());
+}
+
+#[test]
+fn prune_after_sweep() {
+    dreck!(owner, arena);
+    let mut list = WeakObserverList::<u32>::new();
+    let pruned = Rc::new(Cell::new(0));
+
+    arena.subscribe_transitions(PhaseMask::SLEEP, {
+        let pruner = list.pruner();
+        let pruned = pruned.clone();
+        move |_| pruned.set(pruned.get() + pruner.prune())
+    });
+
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(1u32));
+    list.subscribe(&arena, kept);
+    for i in 0..10 {
+        list.subscribe(&arena, arena.add(i));
+    }
+    assert_eq!(list.len(), 11);
+
+    arena.collect_full(&owner);
+    assert_eq!(pruned.get(), 10);
+    assert_eq!(list.len(), 1);
+    assert_eq!(alive(&owner, &arena, &mut list), [1]);
+}
+
+pub struct Emitter<'own> {
+    pub listeners: WeakObserverList<'own, u32>,
+}
+
+unsafe impl<'own> Trace<'own> for Emitter<'own> {
+    type Gc<'to> = Emitter<'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn trace(&self, _marker: Marker<'own, '_>) {}
+}
+
+#[test]
+fn gc_field() {
+    dreck!(owner, arena);
+
+    let guard = pin!(RootGuard::new());
+    let emitter = root!(
+        &arena,
+        guard,
+        arena.add(Emitter {
+            listeners: WeakObserverList::new(),
+        })
+    );
+    let guard = pin!(RootGuard::new());
+    let kept = root!(&arena, guard, arena.add(1u32));
+    let garbage = arena.add(2u32);
+    let listeners = &mut emitter.borrow_mut(&mut owner, &arena).listeners;
+    listeners.subscribe(&arena, kept);
+    listeners.subscribe(&arena, garbage);
+
+    // The list doesn't keep its observers alive.
+    arena.collect_full(&owner);
+    let mut values = Vec::new();
+    emitter
+        .borrow_mut(&mut owner, &arena)
+        .listeners
+        .for_each_alive(&arena, |x| values.push(x));
+    assert_eq!(values.len(), 1);
+    assert!(values[0].ptr_eq(kept));
+    assert_eq!(*values[0].borrow(&owner), 1);
+}
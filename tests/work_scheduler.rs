@@ -0,0 +1,137 @@
+use std::{cell::Cell, pin::pin, rc::Rc};
+
+use dreck::*;
+
+#[test]
+fn frames() {
+    dreck!(owner, arena);
+
+    // Idle callbacks scheduled by the arena, run at the end of the next frame.
+    let idle = Rc::new(Cell::new(0usize));
+    let requests = Rc::new(Cell::new(0usize));
+    arena.set_work_scheduler({
+        let idle = idle.clone();
+        let requests = requests.clone();
+        move |request: WorkRequest| {
+            assert!(request.work > 0);
+            requests.set(requests.get() + 1);
+            idle.set(idle.get() + 1);
+        }
+    });
+
+    let guard = pin!(RootGuard::new());
+    let list = root!(&arena, guard, arena.add(Vec::<Gc<u32>>::new()));
+
+    let mut slices = 0;
+    let mut max_allocated = 0;
+    for frame in 0..10_000u32 {
+        // The frame allocates some garbage and keeps a few objects alive.
+        for x in 0..8 {
+            arena.add(x);
+        }
+        let value = arena.add(frame);
+        let list_mut = list.borrow_mut(&mut owner, &arena);
+        if list_mut.len() == 16 {
+            list_mut.clear();
+        }
+        list_mut.push(value);
+
+        // Requests the idle callback if the arena wants to do work.
+        let pending = idle.get();
+        assert!(!arena.pump(&owner));
+        assert!(idle.get() <= pending + 1);
+
+        // Idle time.
+        for _ in 0..idle.replace(0) {
+            assert!(arena.pump(&owner));
+            slices += 1;
+        }
+        max_allocated = max_allocated.max(arena.stats().total_allocated);
+    }
+
+    assert_eq!(requests.get(), slices);
+    assert!(slices > 0);
+    assert!(max_allocated < 64 * 1024);
+    assert!(list.borrow(&owner).len() <= 16);
+}
+
+#[test]
+fn pump_without_request() {
+    dreck!(owner, arena);
+
+    let requests = Rc::new(Cell::new(0usize));
+    arena.set_work_scheduler({
+        let requests = requests.clone();
+        move |_| requests.set(requests.get() + 1)
+    });
+
+    arena.collect_full(&owner);
+    arena.add(1);
+    assert!(!arena.pump(&owner));
+    assert!(!arena.pump(&owner));
+    assert_eq!(requests.get(), 0);
+}
+
+#[test]
+fn requested_once_until_pumped() {
+    dreck!(owner, arena);
+
+    let requests = Rc::new(Cell::new(0usize));
+    arena.set_work_scheduler({
+        let requests = requests.clone();
+        move |_| requests.set(requests.get() + 1)
+    });
+
+    arena.collect_full(&owner);
+    while arena.stats().phase == dreck::sys::Phase::Sleep {
+        arena.add(0u64);
+    }
+    for _ in 0..1000 {
+        arena.add(0u64);
+    }
+    assert_eq!(requests.get(), 0);
+
+    assert!(!arena.pump(&owner));
+    assert_eq!(requests.get(), 1);
+    for _ in 0..1000 {
+        arena.add(0u64);
+    }
+    assert_eq!(requests.get(), 1);
+
+    assert!(arena.pump(&owner));
+    assert_eq!(requests.get(), 1);
+}
+
+#[test]
+fn without_scheduler() {
+    dreck!(owner, arena);
+
+    arena.collect_full(&owner);
+    while arena.stats().phase == dreck::sys::Phase::Sleep {
+        arena.add(0u64);
+    }
+    assert!(arena.pump(&owner));
+    assert!(!arena.pump(&owner));
+}
+
+#[test]
+fn finished_cycle_cancels_request() {
+    dreck!(owner, arena);
+
+    let requests = Rc::new(Cell::new(0usize));
+    arena.set_work_scheduler({
+        let requests = requests.clone();
+        move |_| requests.set(requests.get() + 1)
+    });
+
+    arena.collect_full(&owner);
+    while arena.stats().phase == dreck::sys::Phase::Sleep {
+        arena.add(0u64);
+    }
+    assert!(!arena.pump(&owner));
+    assert_eq!(requests.get(), 1);
+
+    arena.collect_full(&owner);
+    assert!(!arena.pump(&owner));
+    assert_eq!(requests.get(), 1);
+}
@@ -0,0 +1,117 @@
+use std::pin::pin;
+
+use dreck::{sys::Phase, *};
+
+mod common;
+use common::trace_roots;
+
+pub struct Registers<'gc, 'own> {
+    pub acc: Option<Gc<'gc, 'own, u32>>,
+    pub slots: Vec<Option<Gc<'gc, 'own, u32>>>,
+}
+
+pub struct Frame<'gc, 'own> {
+    pub pc: usize,
+    pub regs: Registers<'gc, 'own>,
+    pub pair: (u32, Option<Gc<'gc, 'own, u32>>),
+}
+
+unsafe impl<'gc, 'own> Trace<'own> for Frame<'gc, 'own> {
+    type Gc<'to> = Frame<'to, 'own>;
+
+    fn needs_trace() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn trace(&self, marker: Marker<'own, '_>) {
+        self.regs.acc.trace(marker);
+        self.regs.slots.trace(marker);
+        self.pair.1.trace(marker);
+    }
+}
+
+/// Step the arena until the cycle is finished.
+fn finish_cycle(arena: &Arena) {
+    unsafe {
+        while arena.unsafe_arena().phase() != Phase::Sleep {
+            arena.unsafe_arena().step();
+        }
+    }
+}
+
+/// Move the accumulator into a slot, without access to the arena.
+fn store<'gc, 'own>(regs: &mut Write<Registers<'gc, 'own>>, slot: usize) {
+    let acc = regs.acc;
+    Write::project(project!(regs, slots), |slots| &mut slots[slot]).set(acc);
+}
+
+#[test]
+fn nested_projections_barrier_once() {
+    dreck!(owner, arena);
+
+    let frame = arena.add(Frame {
+        pc: 0,
+        regs: Registers {
+            acc: None,
+            slots: vec![None; 2],
+        },
+        pair: (0, None),
+    });
+    let guard = pin!(RootGuard::new());
+    let frame = root!(&arena, guard, frame);
+    trace_roots(&arena);
+
+    let a = arena.add(1u32);
+    let b = arena.add(2u32);
+    let before = arena.stats().write_barriers;
+    {
+        let write = frame.write(&mut owner, &arena);
+        *project!(write, pc) += 1;
+        *project!(write, pair.0) = 5;
+        project!(write, pair.1).set(Some(b));
+        let regs = project!(write, regs);
+        project!(regs, acc).set(Some(a));
+        store(regs, 1);
+        assert!(project!(write, regs.acc).replace(None).is_some());
+    }
+    assert_eq!(arena.stats().write_barriers, before + 1);
+
+    // The new pointers were only stored after the frame was traced.
+    finish_cycle(&arena);
+    let frame = frame.borrow(&owner);
+    assert_eq!(frame.pc, 1);
+    assert_eq!(frame.pair.0, 5);
+    assert_eq!(*frame.pair.1.unwrap().borrow(&owner), 2);
+    assert!(frame.regs.acc.is_none());
+    assert!(frame.regs.slots[0].is_none());
+    assert_eq!(*frame.regs.slots[1].unwrap().borrow(&owner), 1);
+}
+
+#[test]
+fn borrow_mut_barriers_every_time() {
+    dreck!(owner, arena);
+
+    let frame = arena.add(Frame {
+        pc: 0,
+        regs: Registers {
+            acc: None,
+            slots: Vec::new(),
+        },
+        pair: (0, None),
+    });
+    let before = arena.stats().write_barriers;
+    for _ in 0..3 {
+        frame.borrow_mut(&mut owner, &arena).pc += 1;
+    }
+    assert_eq!(arena.stats().write_barriers, before + 3);
+
+    // Plain data doesn't need tracing, so no barrier is counted.
+    let count = arena.add(0u32);
+    let before = arena.stats().write_barriers;
+    **count.write(&mut owner, &arena) += 1;
+    assert_eq!(arena.stats().write_barriers, before);
+    assert_eq!(*count.borrow(&owner), 1);
+}